@@ -1,21 +1,72 @@
-use litemap::LiteMap;
+use super::map::ParamMap;
+use super::parse;
+use crate::names::{Caseless, ParameterId};
 use std::num::NonZeroUsize;
 
 #[allow(clippy::wildcard_imports)]
 use super::values::*;
 
 ///FIXME — add docs!
-#[derive(Clone, Debug, Default)]
-pub struct Parameters(LiteMap<usize, ParameterValue>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Parameters(
+    pub(super) ParamMap,
+    // The parameters we don't know (`X-` names, and IANA names not in `assets`), in the
+    // order they were set, with their values as written
+    pub(super) Vec<(Caseless<String>, Vec<String>)>,
+);
 
 ///FIXME — add docs!
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum ParameterValue {
     // ParameterValue
 }
 
 // const
 
+// fn parse_known
+
+impl Parameters {
+    /// The parameters we know that are set, in order by name, with their interned names
+    /// (see `others` for the rest)
+    pub fn iter(&self) -> impl Iterator<Item = (ParameterId, &ParameterValue)> {
+        self.0.iter().map(|(n, value)| (ParameterId(n), value))
+    }
+    /// The number of parameters set, counting the ones we don't know
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len() + self.1.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty() && self.1.is_empty()
+    }
+    /// The values of parameter `name` (in any case), one we don't know: an experimental
+    /// (`X-`) parameter, or an IANA one not in `assets`
+    #[must_use]
+    pub fn other(&self, name: &str) -> Option<&[String]> {
+        (self.1.iter()).find(|(other, _)| *other == Caseless(name)).map(|(_, values)| &values[..])
+    }
+    /// Set parameter `name`, one we don't know, replacing any values it has. The parameters
+    /// we know have typed setters; set here, they'd be written twice.
+    pub fn set_other(&mut self, name: impl Into<String>, values: Vec<String>) {
+        let name = Caseless(name.into());
+        match self.1.iter_mut().find(|(other, _)| *other == name) {
+            Some((_, old)) => *old = values,
+            None => self.1.push((name, values)),
+        }
+    }
+    /// Remove parameter `name`, one we don't know, returning its values
+    pub fn remove_other(&mut self, name: &str) -> Option<Vec<String>> {
+        let n = self.1.iter().position(|(other, _)| *other == Caseless(name))?;
+        Some(self.1.remove(n).1)
+    }
+    /// The parameters we don't know, with their names as written, in the order they were set
+    pub fn others(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.1.iter().map(|(name, values)| (name.as_str(), &values[..]))
+    }
+}
+
 #[allow(clippy::missing_panics_doc)] // We should only be `get`ing type that we `set`
 impl Parameters {
     // Parameters
@@ -43,6 +94,33 @@ mod test {
         "SCHEMA", "SENT-BY", "SIZE", "TZID", "VALUE"
         ];
 
+    #[test]
+    fn iteration() {
+        let mut parameters = Parameters::default();
+        assert!(parameters.is_empty());
+        parameters.set_tzid(Tzid::from("Europe/Paris"));
+        parameters.set_cn("Jane".to_string());
+        parameters.set_other("X-A", vec![]);
+        let lookup = crate::names::Lookup::new();
+        let names: Vec<_> = (parameters.iter())
+            .map(|(id, _)| lookup.parameter_name(id).unwrap().to_string())
+            .collect();
+        assert_eq!(names, ["CN", "TZID"]);
+        assert_eq!(parameters.iter().nth(1).map(|(_, value)| value), Some(&ParameterValue::Tzid(Tzid::from("Europe/Paris"))));
+        assert_eq!((parameters.len(), parameters.is_empty()), (3, false));
+    }
+    #[test]
+    fn other_parameters() {
+        let mut parameters = Parameters::default();
+        parameters.set_other("X-Colour", vec!["red".to_string()]);
+        parameters.set_other("X-ROOM", vec!["1".to_string(), "2".to_string()]);
+        parameters.set_other("x-colour", vec!["blue".to_string()]);
+        assert_eq!(parameters.other("X-COLOUR"), Some(&["blue".to_string()][..]));
+        let names: Vec<_> = parameters.others().map(|(name, _)| name).collect();
+        assert_eq!(names, ["X-Colour", "X-ROOM"]);
+        assert_eq!(parameters.remove_other("x-room").map(|values| values.len()), Some(2));
+        assert_eq!(parameters.other("X-ROOM"), None);
+    }
     #[test]
     fn parameter_ids_remain_in_order() {
         let expected: Vec<_> = (ALTREP..=VALUE).collect();
@@ -59,6 +137,15 @@ mod test {
         assert_eq!(NAMES, sorted);
     }
     #[test]
+    fn applicable_properties_are_known_and_sorted() {
+        for properties in APPLIES_TO.into_iter().flatten() {
+            assert!(properties.is_sorted(), "{properties:?}");
+            for name in properties {
+                assert!(crate::property::NAMES.contains(name), "{name}");
+            }
+        }
+    }
+    #[test]
     fn parameter_names_correspond_to_parameter_ids() {
         use crate::names::{Lookup, ParameterId};
         let lookup = Lookup::new();
@@ -0,0 +1,137 @@
+use super::parse::ValueParser;
+use super::values::PropertyValue;
+use crate::names::PropertyId;
+use crate::parameter::Value;
+use litemap::LiteMap;
+
+/// A property we know (one in `assets/properties.kdl`). Properties defined by later RFCs may
+/// become new variants in minor releases, so matches need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum PropertyName {
+    // PropertyName
+}
+
+impl PropertyName {
+    /// The property's name, in uppercase
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        NAMES[self as usize]
+    }
+    /// The property's interned name (every `Lookup` gives known properties the same ids)
+    #[must_use]
+    pub fn id(self) -> PropertyId {
+        PropertyId(self as usize)
+    }
+    /// The property named `name` (in any case), if we know it
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        index_of(&crate::names::uppercase(name)).map(|n| ALL[n])
+    }
+    /// The property with interned name `id`, if we know it
+    #[must_use]
+    pub fn from_id(id: PropertyId) -> Option<Self> {
+        ALL.get(id.0).copied()
+    }
+    /// Whether a component may have more than one of the property
+    #[must_use]
+    pub fn is_multiple(self) -> bool {
+        MULTIPLE[self as usize]
+    }
+}
+
+/// Typed values of the properties we know, by property. Each property may have several
+/// values — one per content line — kept in the order they were added; the getter of a
+/// property a component may have only one of returns the first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Properties(LiteMap<usize, Vec<PropertyValue>>);
+
+// const
+
+impl Properties {
+    #[must_use]
+    pub fn new() -> Self {
+        Properties::default()
+    }
+    /// The values of property `name`, in the order they were added
+    #[must_use]
+    pub fn get(&self, name: PropertyName) -> &[PropertyValue] {
+        self.0.get(&(name as usize)).map_or(&[], Vec::as_slice)
+    }
+    /// The first value of property `name`
+    #[must_use]
+    pub fn first(&self, name: PropertyName) -> Option<&PropertyValue> {
+        self.get(name).first()
+    }
+    /// Add a value of property `name`, after any it already has
+    pub fn add(&mut self, name: PropertyName, value: PropertyValue) {
+        match self.0.get_mut(&(name as usize)) {
+            Some(values) => values.push(value),
+            None => drop(self.0.insert(name as usize, vec![value])),
+        }
+    }
+    /// Replace any values of property `name` with `value`
+    pub fn set(&mut self, name: PropertyName, value: PropertyValue) {
+        self.0.insert(name as usize, vec![value]);
+    }
+    /// Remove property `name`, returning its values
+    pub fn remove(&mut self, name: PropertyName) -> Vec<PropertyValue> {
+        self.0.remove(&(name as usize)).unwrap_or_default()
+    }
+    /// Every value, with its property, in order by property name
+    pub fn iter(&self) -> impl Iterator<Item = (PropertyName, &PropertyValue)> {
+        (self.0.iter()).flat_map(|(n, values)| values.iter().map(move |value| (ALL[*n], value)))
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Properties
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn property_names_are_sorted() {
+        let mut sorted = NAMES;
+        sorted.sort_unstable();
+        assert_eq!(NAMES, sorted);
+    }
+    #[test]
+    fn default_values_are_allowed() {
+        for (n, default) in DEFAULT_VALUES.iter().enumerate() {
+            assert!(ALLOWED_VALUES[n].contains(default), "{}", NAMES[n]);
+        }
+    }
+    #[test]
+    fn property_names_match_ids() {
+        let mut lookup = crate::names::Lookup::new();
+        for (n, name) in ALL.into_iter().enumerate() {
+            assert_eq!(name as usize, n);
+            assert_eq!(PropertyName::from_name(&name.name().to_lowercase()), Some(name));
+            assert_eq!(lookup.property_id(name.name()).unwrap(), name.id());
+            assert_eq!(PropertyName::from_id(name.id()), Some(name));
+        }
+        assert_eq!(PropertyName::from_name("X-WR-CALNAME"), None);
+    }
+    #[test]
+    fn typed_getters() {
+        let mut properties = Properties::new();
+        assert_eq!(properties.summary(), None);
+        properties.set_summary(PropertyValue::from("Lunch".to_string()));
+        properties.set_summary(PropertyValue::from("Dinner".to_string()));
+        properties.add_attendee(PropertyValue::CalAddress("mailto:a@example.com".into()));
+        properties.add_attendee(PropertyValue::CalAddress("mailto:b@example.com".into()));
+        assert_eq!(properties.summary(), Some(&PropertyValue::from("Dinner".to_string())));
+        assert_eq!(properties.attendee().len(), 2);
+        assert!(PropertyName::Attendee.is_multiple() && !PropertyName::Summary.is_multiple());
+        let names: Vec<_> = properties.iter().map(|(name, _)| name.name()).collect();
+        assert_eq!(names, ["ATTENDEE", "ATTENDEE", "SUMMARY"]);
+        assert_eq!(properties.remove(PropertyName::Attendee).len(), 2);
+        assert_eq!(properties.get(PropertyName::Attendee), &[]);
+    }
+}
@@ -0,0 +1,72 @@
+#![allow(clippy::pedantic)]
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use rcal::parameter::Parameters;
+use rcal::preparse::{Prop, cautious_preparse_str};
+
+// Parameters as Google Calendar and Outlook write them, varied over names and zones so the
+// parser doesn't see the same bytes over and over. Compare the representations with
+// `cargo bench --bench parameters` and `cargo bench --bench parameters -F dense-parameters`.
+fn realistic_lines() -> Vec<String> {
+    const ZONES: [&str; 4] = ["America/New_York", "Europe/Paris", "Asia/Tokyo", "UTC"];
+    const NAMES: [&str; 4] = ["Jane Doe", "John Smith", "Ana Lima", "Wei Chen"];
+    let mut lines = Vec::new();
+    for (zone, name) in ZONES.iter().zip(NAMES) {
+        let email = name.to_lowercase().replace(' ', ".") + "@example.com";
+        lines.extend([
+            // Google
+            format!("DTSTART;TZID={zone}:20250303T090000"),
+            format!("DTEND;TZID={zone}:20250303T100000"),
+            format!("ORGANIZER;CN={email}:mailto:{email}"),
+            format!(
+                "ATTENDEE;CUTYPE=INDIVIDUAL;ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;\
+                 RSVP=TRUE;CN={name};X-NUM-GUESTS=0:mailto:{email}"
+            ),
+            format!("ATTACH;FILENAME=notes.pdf;FMTTYPE=application/pdf:https://example.com/{email}"),
+            // Outlook
+            format!("ORGANIZER;CN=\"{name}\":mailto:{email}"),
+            format!("ATTENDEE;ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE;CN={name}:mailto:{email}"),
+            format!("EXDATE;TZID={zone}:20250310T090000,20250317T090000"),
+            format!("DESCRIPTION;LANGUAGE=en-US:Agenda for {name}"),
+            "TRIGGER;RELATED=START:-PT15M".to_string(),
+        ]);
+    }
+    lines
+}
+fn preparsed(lines: &[String]) -> Vec<Prop<'_>> {
+    lines.iter().map(|line| cautious_preparse_str(line).unwrap()).collect()
+}
+fn parse_all(props: &[Prop]) -> Vec<Parameters> {
+    props.iter().map(|prop| Parameters::from_preparsed(prop.parameters()).unwrap()).collect()
+}
+// What a reader of the parsed lines asks for
+fn read_all(parameters: &[Parameters]) -> usize {
+    let mut found = 0;
+    for params in parameters {
+        found += usize::from(params.tzid().is_some())
+            + usize::from(params.partstat().is_some())
+            + usize::from(params.cn().is_some())
+            + usize::from(params.value().is_some())
+            + usize::from(params.rsvp().is_some());
+    }
+    found
+}
+
+pub fn parameters(c: &mut Criterion) {
+    let lines = realistic_lines();
+    let props = preparsed(&lines);
+    let parameters = parse_all(&props);
+    let mut group = c.benchmark_group("Parameters");
+    group.bench_with_input(BenchmarkId::new("Parse", "Realistic"), &props, |b, props| {
+        b.iter(|| parse_all(black_box(props)))
+    });
+    group.bench_with_input(BenchmarkId::new("Get", "Realistic"), &parameters, |b, parameters| {
+        b.iter(|| read_all(black_box(parameters)))
+    });
+    group.bench_function(BenchmarkId::new("Default", "Empty"), |b| {
+        b.iter(|| black_box(Parameters::default()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parameters);
+criterion_main!(benches);
@@ -1,9 +1,14 @@
 #![allow(clippy::pedantic)]
 use bstr::BString;
 use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+#[cfg(feature = "cautious")]
+use rcal::names::Lookup;
+#[cfg(any(feature = "bold", feature = "cautious"))]
 use rcal::preparse;
+use rcal::preparse::{preparse_all_errors, preparse_batch};
 use rcal::unfolded::BufReadContent;
 
+#[cfg(any(feature = "bold", feature = "cautious"))]
 fn discard(stuff: &preparse::Prop) -> u8 {
     if stuff.name.loc == 0 { black_box(b'0') } else { black_box(b'1') }
 }
@@ -29,6 +34,56 @@ fn cautious_preparse_and_discard(lines: &[BString]) -> Vec<u8> {
         unimplemented!("\n\nTry cargo bench --all-features\n")
     }
 }
+#[allow(unused_variables)]
+fn cautious_preparse_str_and_discard(lines: &[String]) -> Vec<u8> {
+    #[cfg(feature = "cautious")]
+    {
+        lines.iter().map(|line| discard(&preparse::cautious_preparse_str(line).unwrap())).collect()
+    }
+    #[cfg(not(feature = "cautious"))]
+    {
+        unimplemented!("\n\nTry cargo bench --all-features\n")
+    }
+}
+// Lines with thousands of parameters or values, with the limits lifted: the time per byte
+// should stay flat as the lines grow
+#[allow(unused_variables)]
+pub fn pathological_lines(c: &mut Criterion) {
+    #[cfg(all(feature = "bold", feature = "cautious"))]
+    {
+        let limits =
+            preparse::PreparseLimits { max_parameters: usize::MAX, max_values: usize::MAX };
+        let mut group = c.benchmark_group("Pathological");
+        for n in [1_000, 4_000, 16_000] {
+            let parameters = format!("X{}:v", ";A=".repeat(n));
+            let values = format!("X;A={}:v", ",".repeat(n));
+            for (name, line) in [("Parameters", &parameters), ("Values", &values)] {
+                group.throughput(criterion::Throughput::Bytes(line.len() as u64));
+                group.bench_with_input(
+                    BenchmarkId::new(format!("Regex {name}"), n),
+                    line,
+                    |b, line| {
+                        b.iter(|| {
+                            preparse::cautious_preparse_with_limits(
+                                black_box(line.as_bytes()),
+                                &limits,
+                            )
+                        })
+                    },
+                );
+                group.bench_with_input(
+                    BenchmarkId::new(format!("Plain {name}"), n),
+                    line,
+                    |b, line| {
+                        b.iter(|| {
+                            preparse::bold_preparse_with_limits(black_box(line.as_bytes()), &limits)
+                        })
+                    },
+                );
+            }
+        }
+    }
+}
 pub fn compare_preparsers(c: &mut Criterion) {
     let mut group = c.benchmark_group("Preparsers");
     group.sample_size(500);
@@ -46,7 +101,108 @@ pub fn compare_preparsers(c: &mut Criterion) {
     group.bench_with_input(BenchmarkId::new("Regex", "Events-Calendar"), &lines, |b, lines| {
         b.iter(|| cautious_preparse_and_discard(black_box(lines)))
     });
+    let strings: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    group.bench_with_input(
+        BenchmarkId::new("Regex str", "Events-Calendar"),
+        &strings,
+        |b, lines| b.iter(|| cautious_preparse_str_and_discard(black_box(lines))),
+    );
+}
+
+// The corpus lines as they are (almost all ASCII, so the value needs no scanning), and with
+// a non-ASCII character appended to each, which sends every line down the slow path
+#[allow(unused_variables)]
+pub fn ascii_fast_path(c: &mut Criterion) {
+    #[cfg(feature = "bold")]
+    {
+        let mut lines: Vec<BString> = Vec::new();
+        for file in std::fs::read_dir("assets/corpus").unwrap() {
+            let input = std::fs::read(file.unwrap().path()).unwrap();
+            let iter = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+            lines.extend(iter.map(|(_, line)| line));
+        }
+        let accented: Vec<BString> =
+            lines.iter().map(|line| [&line[..], "é".as_bytes()].concat().into()).collect();
+        let mut group = c.benchmark_group("ASCII fast path");
+        for (name, lines) in [("Corpus", &lines), ("Corpus with é", &accented)] {
+            let bytes = lines.iter().map(|line| line.len() as u64).sum();
+            group.throughput(criterion::Throughput::Bytes(bytes));
+            group.bench_with_input(BenchmarkId::new("Plain", name), lines, |b, lines| {
+                b.iter(|| bold_preparse_and_discard(black_box(lines)))
+            });
+        }
+    }
+}
+
+// Preparsing the corpus and looking up the ids of its property and parameter names, almost
+// all of which are RFC names
+#[allow(unused_variables)]
+pub fn name_lookup(c: &mut Criterion) {
+    #[cfg(feature = "cautious")]
+    {
+        let mut lines: Vec<BString> = Vec::new();
+        for file in std::fs::read_dir("assets/corpus").unwrap() {
+            let input = std::fs::read(file.unwrap().path()).unwrap();
+            let iter = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+            lines.extend(iter.map(|(_, line)| line));
+        }
+        let mut group = c.benchmark_group("Name lookup");
+        group.bench_with_input(
+            BenchmarkId::new("Preparse and look up", "Corpus"),
+            &lines,
+            |b, lines| {
+                let mut lookup = Lookup::new();
+                b.iter(|| {
+                    for line in black_box(lines) {
+                        let prop = preparse::cautious_preparse(line).unwrap();
+                        black_box(lookup.property_id(prop.name.val()).unwrap());
+                        for param in prop.parameters() {
+                            black_box(lookup.parameter_id(param.name().val()).unwrap());
+                        }
+                    }
+                })
+            },
+        );
+    }
+}
+
+// Scanning the corpus's property names: preparsing line by line, each line a `PartialProp`
+// with its own `Vec`s, against preparsing the whole corpus into a `PropBatch`'s columns
+pub fn batch_preparse(c: &mut Criterion) {
+    let mut lines: Vec<BString> = Vec::new();
+    for file in std::fs::read_dir("assets/corpus").unwrap() {
+        let input = std::fs::read(file.unwrap().path()).unwrap();
+        let iter = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+        lines.extend(iter.map(|(_, line)| line));
+    }
+    let mut group = c.benchmark_group("Batch");
+    let bytes = lines.iter().map(|line| line.len() as u64).sum();
+    group.throughput(criterion::Throughput::Bytes(bytes));
+    group.bench_with_input(BenchmarkId::new("Line by line", "Corpus"), &lines, |b, lines| {
+        b.iter(|| {
+            let props: Vec<_> =
+                black_box(lines).iter().map(|line| preparse_all_errors(line)).collect();
+            props
+                .iter()
+                .filter_map(|(prop, _)| prop.name.as_ref())
+                .map(|name| name.val().len())
+                .sum::<usize>()
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("Batch", "Corpus"), &lines, |b, lines| {
+        b.iter(|| {
+            let batch = preparse_batch(black_box(lines).iter().map(|line| &line[..]));
+            batch.names().map(str::len).sum::<usize>()
+        })
+    });
 }
 
-criterion_group!(benches, compare_preparsers);
+criterion_group!(
+    benches,
+    compare_preparsers,
+    pathological_lines,
+    ascii_fast_path,
+    name_lookup,
+    batch_preparse
+);
 criterion_main!(benches);
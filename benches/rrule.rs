@@ -0,0 +1,71 @@
+#![allow(clippy::pedantic)]
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use jiff::{ToSpan, Zoned};
+use rcal::rrule::{RRule, parse_rrule};
+use winnow::Parser;
+
+// The shapes of rule Google Calendar and Outlook write, each varied over its days, counts and
+// intervals so the parser doesn't see the same bytes over and over
+fn realistic_rules() -> Vec<String> {
+    const DAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+    let mut rules = Vec::new();
+    for (i, day) in DAYS.iter().enumerate() {
+        let n = i + 2;
+        let next = DAYS[(i + 1) % 7];
+        rules.extend([
+            // Google
+            "FREQ=DAILY".to_string(),
+            format!("FREQ=DAILY;COUNT={n}"),
+            format!("FREQ=WEEKLY;BYDAY={day}"),
+            format!("FREQ=WEEKLY;WKST=SU;BYDAY={day},{next}"),
+            format!("FREQ=WEEKLY;WKST=SU;INTERVAL={n};BYDAY={day}"),
+            format!("FREQ=MONTHLY;BYMONTHDAY={n}"),
+            format!("FREQ=MONTHLY;BYDAY={}{day}", i % 4 + 1),
+            format!("FREQ=MONTHLY;BYDAY=-1{day}"),
+            format!("FREQ=YEARLY;UNTIL=2030010{n}T235959Z"),
+            // Outlook
+            format!("FREQ=WEEKLY;UNTIL=2026061{n}T150000Z;INTERVAL=1;BYDAY={day};WKST=SU"),
+            format!("FREQ=DAILY;COUNT={n}0;INTERVAL=1"),
+            "FREQ=WEEKLY;COUNT=52;INTERVAL=1;BYDAY=MO,TU,WE,TH,FR;WKST=SU".to_string(),
+            format!("FREQ=MONTHLY;COUNT=12;BYMONTHDAY={n};INTERVAL=1"),
+            format!("FREQ=MONTHLY;COUNT=12;BYDAY={day};BYSETPOS={};INTERVAL=1", i % 4 + 1),
+            format!("FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;INTERVAL={n}"),
+            format!("FREQ=YEARLY;BYMONTH={n};BYDAY=-1{day};INTERVAL=1"),
+        ]);
+    }
+    rules.into_iter().map(|rule| rule + "\r\n").collect()
+}
+fn parse_all(rules: &[String]) -> Vec<RRule> {
+    rules.iter().map(|rule| parse_rrule.parse(rule.as_bytes()).unwrap()).collect()
+}
+
+pub fn parse(c: &mut Criterion) {
+    let rules = realistic_rules();
+    let mut group = c.benchmark_group("RRULE");
+    let bytes = rules.iter().map(|rule| rule.len() as u64).sum();
+    group.throughput(criterion::Throughput::Bytes(bytes));
+    group.bench_with_input(BenchmarkId::new("Parse", "Realistic"), &rules, |b, rules| {
+        b.iter(|| parse_all(black_box(rules)))
+    });
+}
+
+// The occurrences in the month after `DTSTART`, which is how a calendar view asks
+pub fn expand_month(c: &mut Criterion) {
+    let rules = parse_all(&realistic_rules());
+    let dtstart: Zoned = "2025-03-03T09:30[America/New_York]".parse().unwrap();
+    let end = dtstart.checked_add(1.month()).unwrap();
+    let mut group = c.benchmark_group("RRULE");
+    group.throughput(criterion::Throughput::Elements(rules.len() as u64));
+    group.bench_with_input(BenchmarkId::new("Expand", "One month"), &rules, |b, rules| {
+        b.iter(|| {
+            let mut n = 0;
+            for rule in black_box(rules) {
+                n += rule.iter(&dtstart).take_while(|occurrence| *occurrence < end).count();
+            }
+            n
+        })
+    });
+}
+
+criterion_group!(benches, parse, expand_month);
+criterion_main!(benches);
@@ -0,0 +1,195 @@
+//! # Busy-time statistics
+//! Calendar analytics — how busy was I, on which days, at which hours, doing what? — over a
+//! window of already-expanded event instances.
+use crate::Weekday;
+use crate::event::Event;
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp, Zoned};
+use std::collections::BTreeMap;
+
+/// Busy time within a window
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BusyStats {
+    /// Total busy time. Overlapping events are only counted once.
+    pub total: SignedDuration,
+    /// Busy time on each day of the week, indexed by `Weekday::to_monday_zero_offset`
+    pub by_weekday: [SignedDuration; 7],
+    /// Busy time in each hour of the day
+    pub by_hour: [SignedDuration; 24],
+    /// The time spent in events of each category. An event with several categories counts
+    /// towards each, and overlapping events each count, so these needn't add up to `total`.
+    pub by_category: BTreeMap<String, SignedDuration>,
+}
+
+impl BusyStats {
+    /// Compute statistics for the parts of `instances` that fall in the half-open window
+    /// `[start, end)`. Events overlap when their times do as instants, whatever their time
+    /// zones; floating and all-day events are taken to be in the time zone of `start`, and
+    /// the histograms count hours and days of the week there.
+    #[must_use]
+    pub fn compute<'a>(
+        instances: impl IntoIterator<Item = &'a Event>,
+        (start, end): (Zoned, Zoned),
+    ) -> Self {
+        let zone = start.time_zone().clone();
+        let (start, end) = (start.timestamp(), end.timestamp());
+        let mut stats = BusyStats::default();
+        let mut busy = Vec::new();
+        for event in instances {
+            let (event_start, event_end) = event.zoned_interval(&zone);
+            let clipped_start = event_start.timestamp().max(start);
+            let clipped_end = event_end.timestamp().min(end);
+            if clipped_start >= clipped_end {
+                continue;
+            }
+            let length = clipped_end.duration_since(clipped_start);
+            for category in event.categories() {
+                *stats.by_category.entry(category.clone()).or_default() += length;
+            }
+            busy.push((clipped_start, clipped_end));
+        }
+
+        busy.sort_unstable();
+        let mut merged: Vec<(Timestamp, Timestamp)> = Vec::with_capacity(busy.len());
+        for (start, end) in busy {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        for (start, end) in merged {
+            stats.total += end.duration_since(start);
+            stats.add_to_histograms(start, end, &zone);
+        }
+        stats
+    }
+    // Split `[start, end)` at the hour boundaries of `zone`, adding each piece to the
+    // histograms under its hour and day of the week there
+    fn add_to_histograms(&mut self, start: Timestamp, end: Timestamp, zone: &TimeZone) {
+        let mut start = start.to_zoned(zone.clone());
+        while start.timestamp() < end {
+            let hour = start.with().minute(0).second(0).subsec_nanosecond(0).build();
+            let next_hour = hour.map_or(end, |hour| {
+                (hour.timestamp().checked_add(SignedDuration::from_hours(1))).unwrap_or(end)
+            });
+            let piece_end = next_hour.min(end);
+            let piece = piece_end.duration_since(start.timestamp());
+            self.by_hour[usize::try_from(start.hour()).unwrap_or_default()] += piece;
+            self.by_weekday[usize::from(start.weekday().to_monday_zero_offset().unsigned_abs())] +=
+                piece;
+            start = piece_end.to_zoned(zone.clone());
+        }
+    }
+
+    /// The day of the week with the most busy time, if there's any busy time at all
+    #[must_use]
+    pub fn busiest_weekday(&self) -> Option<Weekday> {
+        let (offset, busy) = busiest(&self.by_weekday)?;
+        if busy.is_zero() { None } else { Weekday::from_monday_zero_offset(offset).ok() }
+    }
+    /// The hour of the day (0 to 23) with the most busy time, if there's any busy time at all
+    #[must_use]
+    pub fn busiest_hour(&self) -> Option<i8> {
+        let (hour, busy) = busiest(&self.by_hour)?;
+        (!busy.is_zero()).then_some(hour)
+    }
+}
+// The (first) index with the largest duration
+fn busiest(histogram: &[SignedDuration]) -> Option<(i8, SignedDuration)> {
+    let mut best: Option<(i8, SignedDuration)> = None;
+    for (index, busy) in (0..).zip(histogram) {
+        if best.is_none_or(|(_, most)| *busy > most) {
+            best = Some((index, *busy));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::EventTime;
+    use jiff::Span;
+    use jiff::civil::{Date, DateTime, date, datetime};
+    use pretty_assertions::assert_eq;
+
+    fn event(start: DateTime, minutes: i64, categories: &[&str]) -> Event {
+        in_time(EventTime::Floating(start), minutes, categories)
+    }
+    fn in_time(start: EventTime, minutes: i64, categories: &[&str]) -> Event {
+        let mut event = Event::new(start);
+        event.set_duration(Span::new().minutes(minutes));
+        event.set_categories(categories.iter().map(|c| (*c).to_string()).collect());
+        event
+    }
+    fn utc(day: Date) -> Zoned {
+        day.at(0, 0, 0, 0).in_tz("UTC").unwrap()
+    }
+    #[test]
+    fn overlapping_events_are_busy_once() {
+        let events = [
+            // Monday 2025-03-03
+            event(datetime(2025, 3, 3, 9, 30, 0, 0), 60, &["WORK"]),
+            event(datetime(2025, 3, 3, 10, 0, 0, 0), 60, &["WORK", "MEETING"]),
+            // Tuesday, partly outside the window
+            event(datetime(2025, 3, 4, 23, 0, 0, 0), 120, &["TRAVEL"]),
+        ];
+        let window = (utc(date(2025, 3, 3)), utc(date(2025, 3, 5)));
+        let stats = BusyStats::compute(&events, window);
+
+        let mins = SignedDuration::from_mins;
+        assert_eq!(stats.total, mins(150));
+        assert_eq!(stats.by_hour[9], mins(30));
+        assert_eq!(stats.by_hour[10], mins(60));
+        assert_eq!(stats.by_hour[23], mins(60));
+        assert_eq!(stats.by_weekday[0], mins(90));
+        assert_eq!(stats.by_weekday[1], mins(60));
+        assert_eq!(stats.by_category["WORK"], mins(120));
+        assert_eq!(stats.by_category["MEETING"], mins(60));
+        assert_eq!(stats.by_category["TRAVEL"], mins(60));
+        assert_eq!(stats.busiest_weekday(), Some(Weekday::Monday));
+        assert_eq!(stats.busiest_hour(), Some(10));
+    }
+    #[test]
+    fn nothing_in_window() {
+        let events = [event(datetime(2025, 3, 3, 9, 30, 0, 0), 60, &[])];
+        let window = (utc(date(2025, 4, 1)), utc(date(2025, 5, 1)));
+        let stats = BusyStats::compute(&events, window);
+        assert_eq!(stats, BusyStats::default());
+        assert_eq!(stats.busiest_weekday(), None);
+        assert_eq!(stats.busiest_hour(), None);
+    }
+    #[test]
+    fn events_in_different_zones() {
+        let zoned = |zone, hour, minute| {
+            EventTime::Zoned(datetime(2025, 3, 3, hour, minute, 0, 0).in_tz(zone).unwrap())
+        };
+        let events = [
+            // 14:00 to 15:00 UTC
+            in_time(zoned("America/New_York", 9, 0), 60, &["NEW YORK"]),
+            // 09:00 to 10:00 UTC, not overlapping the New York meeting
+            in_time(zoned("Europe/London", 9, 0), 60, &["LONDON"]),
+            // 14:30 to 15:30 UTC, half overlapping it
+            in_time(zoned("Europe/London", 14, 30), 60, &["LONDON"]),
+        ];
+        let window = (utc(date(2025, 3, 3)), utc(date(2025, 3, 4)));
+        let stats = BusyStats::compute(&events, window);
+
+        let mins = SignedDuration::from_mins;
+        assert_eq!(stats.total, mins(150));
+        assert_eq!(stats.by_hour[9], mins(60));
+        assert_eq!(stats.by_hour[14], mins(60));
+        assert_eq!(stats.by_hour[15], mins(30));
+        assert_eq!(stats.by_category["NEW YORK"], mins(60));
+        assert_eq!(stats.by_category["LONDON"], mins(120));
+
+        // The histograms are in the window's zone
+        let new_york = |day: Date| day.at(0, 0, 0, 0).in_tz("America/New_York").unwrap();
+        let window = (new_york(date(2025, 3, 3)), new_york(date(2025, 3, 4)));
+        let stats = BusyStats::compute(&events, window);
+        assert_eq!(stats.total, mins(150));
+        assert_eq!(stats.by_hour[4], mins(60));
+        assert_eq!(stats.by_hour[9], mins(60));
+        assert_eq!(stats.by_hour[10], mins(30));
+    }
+}
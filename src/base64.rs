@@ -0,0 +1,164 @@
+// Base64 ([RFC 4648 § 4](https://datatracker.ietf.org/doc/html/rfc4648#section-4)), the
+// encoding RFC 5545 uses for `BINARY` values: the standard alphabet, with padding.
+use std::io;
+use thiserror::Error;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Base64 text that couldn't be decoded: the byte offset of the first problem
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("invalid base64 at byte {0}")]
+pub struct Base64Error(pub usize);
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(char::from(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f]));
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+pub(crate) fn decode(text: &[u8]) -> Result<Vec<u8>, Base64Error> {
+    if !text.len().is_multiple_of(4) {
+        return Err(Base64Error(text.len() - text.len() % 4));
+    }
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    for (c, chunk) in text.chunks(4).enumerate() {
+        let (decoded, len) = decode_chunk(chunk, c * 4)?;
+        if len < 3 && (c + 1) * 4 != text.len() {
+            return Err(Base64Error(c * 4 + len + 1));
+        }
+        bytes.extend_from_slice(&decoded[..len]);
+    }
+    Ok(bytes)
+}
+
+// Decode four bytes of base64 text starting at byte `offset`, returning the bytes and how
+// many of them there are (fewer than three if the chunk ends with padding)
+fn decode_chunk(chunk: &[u8], offset: usize) -> Result<([u8; 3], usize), Base64Error> {
+    let padding = chunk.iter().rev().take_while(|b| **b == b'=').count();
+    if padding > 2 {
+        return Err(Base64Error(offset + 4 - padding));
+    }
+    let mut n = 0u32;
+    for (i, b) in chunk[..4 - padding].iter().enumerate() {
+        let Some(value) = ALPHABET.iter().position(|a| a == b) else {
+            return Err(Base64Error(offset + i));
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let value = value as u32;
+        n |= value << (18 - 6 * i);
+    }
+    let [_, a, b, c] = n.to_be_bytes();
+    Ok(([a, b, c], 3 - padding))
+}
+
+/// Decodes base64 text read from `R` as it's read, for values too large to hold twice, such
+/// as a big `ATTACH`. A read fails with `io::ErrorKind::InvalidData`, wrapping a
+/// `Base64Error`, at the first problem in the text.
+#[derive(Debug)]
+pub struct Base64Reader<R> {
+    text: R,
+    // Bytes of text read so far
+    offset: usize,
+    // Decoded bytes not yet returned
+    decoded: [u8; 3],
+    start: usize,
+    end: usize,
+    // Where the padding was, once a chunk has had some: no text may follow it
+    padding_at: Option<usize>,
+}
+
+impl<R: io::Read> Base64Reader<R> {
+    pub fn new(text: R) -> Self {
+        Base64Reader { text, offset: 0, decoded: [0; 3], start: 0, end: 0, padding_at: None }
+    }
+    // Decode the next chunk into `decoded`, returning `false` at the end of the text
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 4];
+        let mut len = 0;
+        while len < 4 {
+            match self.text.read(&mut chunk[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let invalid = |at| io::Error::new(io::ErrorKind::InvalidData, Base64Error(at));
+        match (len, self.padding_at) {
+            (0, _) => return Ok(false),
+            (_, Some(at)) => return Err(invalid(at)),
+            (1..4, None) => return Err(invalid(self.offset)),
+            _ => {}
+        }
+        let (decoded, n) = decode_chunk(&chunk, self.offset).map_err(|err| invalid(err.0))?;
+        if n < 3 {
+            self.padding_at = Some(self.offset + n + 1);
+        }
+        self.offset += 4;
+        (self.decoded, self.start, self.end) = (decoded, 0, n);
+        Ok(true)
+    }
+}
+
+impl<R: io::Read> io::Read for Base64Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            if self.start == self.end && !self.next_chunk()? {
+                break;
+            }
+            let n = (self.end - self.start).min(buf.len() - written);
+            buf[written..written + n].copy_from_slice(&self.decoded[self.start..self.start + n]);
+            (written, self.start) = (written + n, self.start + n);
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip() {
+        for text in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            let encoded = encode(text.as_bytes());
+            assert_eq!(decode(encoded.as_bytes()).unwrap(), text.as_bytes(), "{encoded}");
+        }
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(b"fo"), "Zm8=");
+        assert_eq!(encode(&[0xfb, 0xff]), "+/8=");
+    }
+    #[test]
+    fn bad_base64() {
+        assert_eq!(decode(b"Zm9vY"), Err(Base64Error(4)));
+        assert_eq!(decode(b"Zm9v!mFy"), Err(Base64Error(4)));
+        assert_eq!(decode(b"Zm8=Zm8="), Err(Base64Error(3)));
+        assert_eq!(decode(b"Z==="), Err(Base64Error(1)));
+    }
+    #[test]
+    fn streaming() {
+        let read = |text: &[u8]| {
+            let mut bytes = Vec::new();
+            io::Read::read_to_end(&mut Base64Reader::new(text), &mut bytes).map(|_| bytes)
+        };
+        for text in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            assert_eq!(read(encode(text.as_bytes()).as_bytes()).unwrap(), text.as_bytes());
+        }
+        for bad in [&b"Zm9vY"[..], b"Zm9v!mFy", b"Zm8=Zm8=", b"Z==="] {
+            let err = read(bad).unwrap_err();
+            let inner = err.get_ref().and_then(|e| e.downcast_ref::<Base64Error>());
+            assert_eq!(inner, decode(bad).err().as_ref(), "{bad:?}");
+        }
+    }
+}
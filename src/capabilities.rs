@@ -0,0 +1,78 @@
+//! # Capabilities
+//! Which optional parts of the crate were compiled in, for plugins and test harnesses that
+//! adapt at runtime rather than through `cfg` attributes of their own. New fields may be added
+//! in minor releases (the struct is `#[non_exhaustive]`), but existing ones keep their meaning.
+
+/// The optional subsystems compiled into this build, from `capabilities()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+#[allow(clippy::struct_excessive_bools)] // One per feature, each independent of the others
+pub struct Capabilities {
+    /// The byte-by-byte preparser, `bold_preparse` (feature `bold`)
+    pub bold: bool,
+    /// The regex-based preparser, `cautious_preparse` (feature `cautious`)
+    pub cautious: bool,
+    /// `Parameters` kept in an array with a slot for each parameter, rather than a small map
+    /// (feature `dense-parameters`)
+    pub dense_parameters: bool,
+    /// `RELATED-TO` and `LINK` relationship graphs, `graph::RelationGraph` (feature `graph`)
+    pub graph: bool,
+    /// Locale-aware week starts and dates from ICU4X, `messages::Localized` (feature `icu`)
+    pub icu: bool,
+    /// `serde::Serialize` implementations, for `Diagnostic` and friends (feature `serde`)
+    pub serde: bool,
+    /// Conversions between `PropertyValue` and the `time` crate's types (feature `time`)
+    pub time: bool,
+}
+
+impl Capabilities {
+    /// The Cargo feature names of the enabled capabilities
+    #[must_use]
+    pub fn features(&self) -> Vec<&'static str> {
+        let Capabilities { bold, cautious, dense_parameters, graph, icu, serde, time } = *self;
+        [
+            ("bold", bold),
+            ("cautious", cautious),
+            ("dense-parameters", dense_parameters),
+            ("graph", graph),
+            ("icu", icu),
+            ("serde", serde),
+            ("time", time),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
+    }
+}
+
+/// The capabilities of this build
+#[must_use]
+pub const fn capabilities() -> Capabilities {
+    Capabilities {
+        bold: cfg!(feature = "bold"),
+        cautious: cfg!(feature = "cautious"),
+        dense_parameters: cfg!(feature = "dense-parameters"),
+        graph: cfg!(feature = "graph"),
+        icu: cfg!(feature = "icu"),
+        serde: cfg!(feature = "serde"),
+        time: cfg!(feature = "time"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_cfg() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.cautious, cfg!(feature = "cautious"));
+        assert_eq!(capabilities.features().contains(&"bold"), cfg!(feature = "bold"));
+        let dense = capabilities.features().contains(&"dense-parameters");
+        assert_eq!(dense, cfg!(feature = "dense-parameters"));
+        assert_eq!(capabilities.features().contains(&"graph"), cfg!(feature = "graph"));
+        assert_eq!(capabilities.features().contains(&"icu"), cfg!(feature = "icu"));
+        assert_eq!(capabilities.features().contains(&"time"), cfg!(feature = "time"));
+    }
+}
@@ -0,0 +1,125 @@
+//! # Error catalog
+//! Every distinct parse or validation error (and warning) has a stable code, like `RCAL0001`,
+//! that won't change between releases: use it for documentation lookup, linter suppression
+//! lists, and programmatic matching. Messages may be reworded; codes won't be reused.
+
+/// A catalog entry: an error code and a one-line summary of what it means
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub code: &'static str,
+    pub summary: &'static str,
+}
+
+macro_rules! catalog {
+    ($($name:ident = $code:literal: $summary:literal,)*) => {
+        $(
+            #[doc = $summary]
+            pub const $name: &str = $code;
+        )*
+        /// All the codes, in order
+        pub const CATALOG: &[Entry] = &[$(Entry { code: $code, summary: $summary }),*];
+    };
+}
+
+catalog! {
+    // Preparse errors
+    UTF8_ERROR = "RCAL0001": "A content line contains an invalid UTF-8 sequence",
+    CONTROL_CHARACTER = "RCAL0002": "A content line contains an ASCII control character other than tab",
+    EMPTY_CONTENT_LINE = "RCAL0003": "A content line is empty",
+    DOUBLE_QUOTE = "RCAL0004": "A double quote appears where it isn't allowed",
+    UNCLOSED_QUOTE = "RCAL0005": "A quoted parameter value has no closing double quote",
+    EMPTY_SEGMENT = "RCAL0006": "A property name, parameter name, or property value is missing",
+    UNTERMINATED_SEGMENT = "RCAL0007": "A name or value is followed by an unexpected character",
+    TOO_MANY = "RCAL0008": "A property has more parameters, or a parameter more values, than the preparse limits allow",
+
+    // RRULE errors
+    RRULE_EXPECTED_EQUAL_SIGN = "RCAL0101": "An RRULE rule part name isn't followed by an equal sign",
+    RRULE_UNKNOWN_RULE_PART = "RCAL0102": "An RRULE has an unrecognized rule part",
+    RRULE_FREQ_REQUIRED = "RCAL0103": "An RRULE has no FREQ rule part",
+    RRULE_REPEATED_RULE_PART = "RCAL0104": "An RRULE has the same rule part more than once",
+    RRULE_BAD_FREQUENCY = "RCAL0105": "FREQ isn't one of SECONDLY through YEARLY",
+    RRULE_BAD_NUMBER = "RCAL0106": "COUNT or INTERVAL isn't an unsigned integer",
+    RRULE_BAD_DAY = "RCAL0107": "A weekday isn't one of SU, MO, TU, WE, TH, FR, or SA",
+    RRULE_BAD_BYDAY_OFFSET = "RCAL0108": "A BYDAY offset is zero or out of range",
+    RRULE_BAD_INDEX_LIST = "RCAL0109": "BYSECOND, BYMINUTE, BYHOUR, or BYMONTH has a value out of range",
+    RRULE_BAD_OFFSET_LIST = "RCAL0110": "BYMONTHDAY, BYYEARDAY, BYWEEKNO, or BYSETPOS has a zero or out-of-range value",
+    RRULE_COMMA_FOR_SEMICOLON = "RCAL0111": "RRULE rule parts are separated by a comma instead of a semicolon",
+    RRULE_BAD_UNTIL = "RCAL0112": "UNTIL isn't in date, date-time, or UTC date-time format",
+    RRULE_NOT_A_TIME = "RCAL0113": "UNTIL is formatted correctly but isn't a real date or time",
+    RRULE_UNTIL_MISMATCH = "RCAL0114": "UNTIL isn't the kind of date or date-time DTSTART requires",
+    RRULE_ZERO_INTERVAL = "RCAL0115": "INTERVAL is zero",
+    RRULE_NOT_AN_RRULE = "RCAL0116": "A property parsed as an RRULE isn't named RRULE",
+    RRULE_UNEXPECTED_PARAMETER = "RCAL0117": "An RRULE property has a parameter RFC 5545 doesn't allow on it",
+    RRULE_OTHER = "RCAL0199": "An RRULE couldn't be parsed",
+
+    // Other errors
+    BAD_NAME = "RCAL0201": "A property or parameter name couldn't be interned",
+    UNKNOWN_TZID = "RCAL0301": "A TZID names neither a VTIMEZONE in the calendar nor a known time zone",
+    PARAMETER_NOT_APPLICABLE = "RCAL0302": "A parameter appears on a property it doesn't apply to",
+    PARAMETER_NOT_MULTI_VALUED = "RCAL0303": "A parameter that may have only one value has several",
+    ENCODING_VALUE_MISMATCH = "RCAL0304": "ENCODING=BASE64 appears without VALUE=BINARY, or the other way around",
+    VALUE_TYPE_NOT_ALLOWED = "RCAL0305": "A VALUE parameter names a type the property can't have",
+    TZID_ON_DATE = "RCAL0306": "A TZID parameter appears on a DATE value",
+    BAD_VALUE = "RCAL0307": "A property value isn't valid for its value type",
+    BAD_PARAMETER_VALUE = "RCAL0308": "A parameter value isn't valid for its parameter",
+    REPEATED_PARAMETER = "RCAL0309": "A property has the same parameter more than once",
+    IO_ERROR = "RCAL0401": "The input couldn't be read",
+    CANCELLED = "RCAL0402": "Parsing was cancelled",
+    OUTSIDE_CALENDAR = "RCAL0501": "A content line appears outside any VCALENDAR",
+    UNTERMINATED_CALENDAR = "RCAL0502": "A VCALENDAR has no END:VCALENDAR",
+    BARE_LINE_FEED = "RCAL0503": "A line ends with a bare line feed instead of CRLF (in strict mode)",
+    OUTSIDE_COMPONENT = "RCAL0504": "A content line appears before a component's BEGIN or after its END",
+    MISMATCHED_END = "RCAL0505": "An END line doesn't match the open component",
+    UNTERMINATED_COMPONENT = "RCAL0506": "A component has no END line",
+    POLICY_REJECTED = "RCAL0507": "A property policy rejected a property",
+    NO_CALENDAR = "RCAL0508": "The input has no VCALENDAR",
+    EVENT_NO_DTSTART = "RCAL0601": "A VEVENT has no DTSTART",
+    EVENT_BAD_VALUE = "RCAL0602": "A VEVENT property has a value that couldn't be parsed",
+    METHOD_NOT_ALLOWED = "RCAL0701": "An iTIP message's METHOD isn't allowed in the event's scheduling state",
+    PARTSTAT_TRANSITION = "RCAL0702": "A REPLY changes an attendee's PARTSTAT in a way iTIP doesn't allow",
+    UNKNOWN_ATTENDEE = "RCAL0703": "A REPLY is from someone who isn't an attendee",
+    CANCELLED_REQUEST = "RCAL0704": "A REQUEST has STATUS:CANCELLED, which only a CANCEL can set",
+    NO_RECURRENCE_ID = "RCAL0705": "An ADD has no RECURRENCE-ID naming the instance it adds",
+    RESOURCE_NO_COMPONENTS = "RCAL0801": "A calendar object resource has no components other than VTIMEZONEs",
+    RESOURCE_MIXED_COMPONENTS = "RCAL0802": "A calendar object resource mixes component types",
+    RESOURCE_NO_UID = "RCAL0803": "A component in a calendar object resource has no UID",
+    RESOURCE_MULTIPLE_UIDS = "RCAL0804": "A calendar object resource has more than one UID",
+    RESOURCE_METHOD = "RCAL0805": "A calendar object resource has a METHOD property",
+    RESOURCE_MISSING_TIMEZONE = "RCAL0806": "A calendar object resource uses a TZID it has no VTIMEZONE for",
+    TIMEZONE_NO_TZID = "RCAL0901": "A VTIMEZONE has no TZID",
+    TIMEZONE_NO_OBSERVANCES = "RCAL0902": "A VTIMEZONE has no STANDARD or DAYLIGHT observances",
+    TIMEZONE_MISSING_PROPERTY = "RCAL0903": "A VTIMEZONE observance has no DTSTART, TZOFFSETFROM, or TZOFFSETTO",
+    TIMEZONE_BAD_VALUE = "RCAL0904": "A VTIMEZONE observance has a DTSTART, TZOFFSETFROM, TZOFFSETTO, RRULE, or RDATE that can't be read",
+    TIMEZONE_SUB_DAILY_RULE = "RCAL0905": "A VTIMEZONE observance has an RRULE that repeats more often than daily",
+
+    // Warnings
+    NORMALIZED_CASE = "RCAL1001": "A name or keyword wasn't in uppercase",
+    CP1252_REPLACED = "RCAL1002": "A Windows-1252 character in UTF-8 text was replaced by its Unicode equivalent",
+    UNKNOWN_RULE_PART_KEPT = "RCAL1003": "An unrecognized RRULE rule part was kept as an extension",
+    ZERO_INTERVAL_REPLACED = "RCAL1004": "An RRULE's INTERVAL=0 was treated as INTERVAL=1",
+    ZERO_COUNT = "RCAL1005": "An RRULE has COUNT=0, so it has no instances",
+    DTSTART_NOT_IN_RULE = "RCAL1006": "DTSTART doesn't match its RRULE, but is an instance anyway",
+    CONFLICTING_TIMEZONES = "RCAL1007": "A calendar defines the same TZID more than once, differently",
+}
+
+/// The catalog entry for `code`, if there is one
+#[must_use]
+pub fn lookup(code: &str) -> Option<&'static Entry> {
+    CATALOG.iter().find(|entry| entry.code == code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn codes_are_well_formed_and_unique() {
+        for (n, entry) in CATALOG.iter().enumerate() {
+            assert!(entry.code.len() == 8 && entry.code.starts_with("RCAL"), "{entry:?}");
+            assert!(entry.code[4..].bytes().all(|b| b.is_ascii_digit()), "{entry:?}");
+            assert!(CATALOG[..n].iter().all(|e| e.code != entry.code), "{entry:?}");
+        }
+        assert_eq!(lookup("RCAL0002").map(|e| e.code), Some(CONTROL_CHARACTER));
+        assert_eq!(lookup("RCAL9999"), None);
+    }
+}
@@ -0,0 +1,269 @@
+//! # Components
+//! An iCalendar object is a tree of components (`VCALENDAR`, `VEVENT`, `VALARM`, …), each with
+//! an ordered list of properties.
+mod calendar;
+pub use calendar::Calendar;
+pub mod event;
+pub mod resource;
+
+use crate::config::RcalConfig;
+use crate::error::CalendarError;
+use crate::names::{Lookup, PropertyId, name_eq, uppercase};
+use crate::policy::{AcceptAll, PolicyAction, PropertyPolicy, truncated_len};
+use crate::preparse::{PreparseLimits, preparse_all_errors, preparse_all_errors_with_limits};
+use bstr::BString;
+use std::str;
+
+/// The first `VCALENDAR` in `r`, read with the default configuration (`RcalConfig::interop`)
+/// — the one call most callers need.
+///
+/// # Errors
+/// See `RcalConfig::parse_calendar`.
+pub fn parse_calendar<R: std::io::BufRead>(r: R) -> Result<Calendar, CalendarError> {
+    RcalConfig::default().parse_calendar(r)
+}
+
+/// A property: its (interned) name, and the unfolded content line it came from
+#[derive(Clone, Debug)]
+pub struct Property {
+    pub id: PropertyId,
+    pub line: String,
+    /// The input line the content line starts on, counting from 1, if it was read from input
+    pub input_line: Option<usize>,
+}
+
+/// Properties are equal if their content lines are, wherever in the input they were read
+impl PartialEq for Property {
+    fn eq(&self, other: &Self) -> bool {
+        (self.id, &self.line) == (other.id, &other.line)
+    }
+}
+
+impl Property {
+    /// The property name, as written
+    #[must_use]
+    pub fn name(&self) -> &str {
+        let end = self.line.find([';', ':']).unwrap_or(self.line.len());
+        &self.line[..end]
+    }
+}
+
+/// A component: its name (like `VEVENT`), its properties in order, and its subcomponents.
+/// The `BEGIN` and `END` lines are implied by `name`, so they aren't stored as properties.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+impl Component {
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Component { name: name.into(), ..Component::default() }
+    }
+
+    /// Parse one component (with its subcomponents) from its content lines, each with the
+    /// input line it starts on — such as an item from `ContentLines::calendars`.
+    ///
+    /// # Errors
+    /// Returns the first preparse error, or an error if the `BEGIN` and `END` lines don't
+    /// nest properly or there's content outside the component.
+    pub fn parse(
+        lines: impl IntoIterator<Item = (usize, BString)>,
+        lookup: &mut Lookup,
+    ) -> Result<Component, CalendarError> {
+        Self::parse_with_policy(lines, lookup, &mut AcceptAll)
+    }
+    /// Like `parse`, but checks each property against `policy`, truncating or rejecting its
+    /// value as the policy decides.
+    ///
+    /// # Errors
+    /// Like `parse`, and also returns `CalendarError::PolicyRejected` if the policy rejects a
+    /// property.
+    pub fn parse_with_policy(
+        lines: impl IntoIterator<Item = (usize, BString)>,
+        lookup: &mut Lookup,
+        policy: &mut dyn PropertyPolicy,
+    ) -> Result<Component, CalendarError> {
+        Self::parse_within(lines, lookup, policy, &PreparseLimits::default())
+    }
+    /// Like `parse`, but preparses each content line within `limits`
+    ///
+    /// # Errors
+    /// Like `parse`; a content line beyond the limits is a preparse error.
+    pub fn parse_with_limits(
+        lines: impl IntoIterator<Item = (usize, BString)>,
+        lookup: &mut Lookup,
+        limits: &PreparseLimits,
+    ) -> Result<Component, CalendarError> {
+        Self::parse_within(lines, lookup, &mut AcceptAll, limits)
+    }
+    fn parse_within(
+        lines: impl IntoIterator<Item = (usize, BString)>,
+        lookup: &mut Lookup,
+        policy: &mut dyn PropertyPolicy,
+        limits: &PreparseLimits,
+    ) -> Result<Component, CalendarError> {
+        // The open components, each with the input line it starts on
+        let mut open: Vec<(usize, Component)> = Vec::new();
+        let mut done = None;
+        for (n, line) in lines {
+            let (prop, errors) = preparse_all_errors_with_limits(&line, limits);
+            if let Some(err) = errors.into_iter().next() {
+                return Err(CalendarError::AtLine(n, err));
+            }
+            let (Some(name), Some(value)) = (prop.name, prop.value) else {
+                unreachable!("a content line without preparse errors has a name and value")
+            };
+            if done.is_some() {
+                return Err(CalendarError::OutsideComponent(n));
+            }
+            if name_eq(name.val, "BEGIN") {
+                open.push((n, Component::new(uppercase(value.val))));
+            } else if name_eq(name.val, "END") {
+                match open.pop() {
+                    Some((_, component)) if name_eq(&component.name, value.val) => {
+                        match open.last_mut() {
+                            Some((_, parent)) => parent.components.push(component),
+                            None => done = Some(component),
+                        }
+                    }
+                    _ => return Err(CalendarError::MismatchedEnd(n)),
+                }
+            } else {
+                let Some((_, component)) = open.last_mut() else {
+                    return Err(CalendarError::OutsideComponent(n));
+                };
+                let id = lookup.property_id(name.val)?;
+                // The preparser found no invalid UTF8, so we never get the default
+                let text = str::from_utf8(&line).unwrap_or_default();
+                let end = match policy.check(id, name.val, value.val) {
+                    PolicyAction::Accept => text.len(),
+                    PolicyAction::Truncate(max_len) => {
+                        value.loc + truncated_len(value.val, max_len)
+                    }
+                    PolicyAction::Reject => {
+                        return Err(CalendarError::PolicyRejected(n, name.val.to_string()));
+                    }
+                };
+                let line = text[..end].to_string();
+                component.properties.push(Property { id, line, input_line: Some(n) });
+            }
+        }
+        match (done, open.first()) {
+            (Some(component), _) => Ok(component),
+            (None, Some((start, _))) => Err(CalendarError::UnterminatedComponent(*start)),
+            (None, None) => Err(CalendarError::UnterminatedComponent(0)),
+        }
+    }
+
+    /// The value of the component's first property named `name` (ignoring case)
+    #[must_use]
+    pub fn value_of(&self, name: &str) -> Option<&str> {
+        (self.properties.iter())
+            .find(|p| name_eq(p.name(), name))
+            .and_then(|p| preparse_all_errors(p.line.as_bytes()).0.value.map(|v| v.val))
+    }
+
+    /// A copy of the component containing only the properties in `keep` — for instance to
+    /// publish a free/busy-only or title-only view of a calendar. Subcomponents are kept, and
+    /// projected in turn.
+    #[must_use]
+    pub fn project(&self, keep: &[PropertyId]) -> Component {
+        self.filtered(&|id| keep.contains(&id))
+    }
+    /// A copy of the component without the properties in `remove`, in it or in any of its
+    /// subcomponents.
+    #[must_use]
+    pub fn strip(&self, remove: &[PropertyId]) -> Component {
+        self.filtered(&|id| !remove.contains(&id))
+    }
+    fn filtered(&self, wanted: &dyn Fn(PropertyId) -> bool) -> Component {
+        Component {
+            name: self.name.clone(),
+            properties: self.properties.iter().filter(|p| wanted(p.id)).cloned().collect(),
+            components: self.components.iter().map(|c| c.filtered(wanted)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::names::Lookup;
+    use pretty_assertions::assert_eq;
+
+    fn property(lookup: &mut Lookup, line: &str) -> Property {
+        let (name, _) = line.split_once([';', ':']).unwrap();
+        Property { id: lookup.property_id(name).unwrap(), line: line.to_string(), input_line: None }
+    }
+    fn calendar(lookup: &mut Lookup, events: &[&[&str]]) -> Component {
+        let mut calendar = Component::new("VCALENDAR");
+        calendar.properties.push(property(lookup, "VERSION:2.0"));
+        for lines in events {
+            let mut event = Component::new("VEVENT");
+            event.properties = lines.iter().map(|line| property(lookup, line)).collect();
+            calendar.components.push(event);
+        }
+        calendar
+    }
+    fn parse(input: &str, policy: &mut dyn PropertyPolicy) -> Result<Component, CalendarError> {
+        use crate::unfolded::BufReadContent;
+        let lines = std::io::Cursor::new(input.as_bytes()).content_lines().map(Result::unwrap);
+        Component::parse_with_policy(lines, &mut Lookup::new(), policy)
+    }
+    #[test]
+    fn parse_nested_components() {
+        let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nbegin:vevent\r\nUID:1\r\n\
+                     END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let calendar = parse(input, &mut AcceptAll).unwrap();
+        assert_eq!(calendar.name, "VCALENDAR");
+        assert_eq!(calendar.properties[0].line, "VERSION:2.0");
+        assert_eq!(calendar.components[0].name, "VEVENT");
+        assert_eq!(calendar.components[0].properties[0].line, "UID:1");
+    }
+    #[test]
+    fn parse_structure_errors() {
+        let code = |input| parse(input, &mut AcceptAll).unwrap_err().code();
+        assert_eq!(code("BEGIN:A\r\nBEGIN:B\r\nEND:A\r\n"), crate::catalog::MISMATCHED_END);
+        assert_eq!(code("BEGIN:A\r\nX:1\r\n"), crate::catalog::UNTERMINATED_COMPONENT);
+        assert_eq!(code("X:1\r\nBEGIN:A\r\nEND:A\r\n"), crate::catalog::OUTSIDE_COMPONENT);
+        assert_eq!(code("BEGIN:A\r\nEND:A\r\nX:1\r\n"), crate::catalog::OUTSIDE_COMPONENT);
+        assert_eq!(code("BEGIN:A\r\nX;:1\r\nEND:A\r\n"), crate::catalog::EMPTY_SEGMENT);
+    }
+    #[test]
+    fn policy_truncates_and_rejects() {
+        let description = format!("DESCRIPTION;LANGUAGE=en:{}", "Lorem ipsum ".repeat(20));
+        let input = format!("BEGIN:VEVENT\r\n{description}\r\nATTACH:data\r\nEND:VEVENT\r\n");
+        let mut quota = |_, name: &str, value: &str| match name {
+            "DESCRIPTION" => PolicyAction::Truncate(11),
+            "ATTACH" if value.len() > 100 => PolicyAction::Reject,
+            _ => PolicyAction::Accept,
+        };
+        let event = parse(&input, &mut quota).unwrap();
+        assert_eq!(event.properties[0].line, "DESCRIPTION;LANGUAGE=en:Lorem ipsum");
+        assert_eq!(event.properties[1].line, "ATTACH:data");
+
+        let input = input.replace("ATTACH:data", &format!("ATTACH:{}", "A".repeat(200)));
+        let err = parse(&input, &mut quota).unwrap_err();
+        assert!(matches!(err, CalendarError::PolicyRejected(3, ref name) if name == "ATTACH"));
+    }
+    #[test]
+    fn free_busy_view() {
+        let mut lookup = Lookup::new();
+        let full = calendar(
+            &mut lookup,
+            &[&["UID:1", "SUMMARY:Dentist", "DTSTART:20250303T090000", "DTEND:20250303T100000"]],
+        );
+        let keep = ["VERSION", "UID", "DTSTART", "DTEND"].map(|n| lookup.property_id(n).unwrap());
+        let expected = calendar(
+            &mut lookup,
+            &[&["UID:1", "DTSTART:20250303T090000", "DTEND:20250303T100000"]],
+        );
+        assert_eq!(full.project(&keep), expected);
+
+        let remove = [lookup.property_id("summary").unwrap()];
+        assert_eq!(full.strip(&remove), expected);
+    }
+}
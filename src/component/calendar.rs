@@ -0,0 +1,212 @@
+//! # Calendars
+//! `Calendar` is a `VCALENDAR` read in one go: its component tree, along with its events and
+//! time zones, typed. `parse_calendar` (or `RcalConfig::parse_calendar`, to choose the
+//! limits and strictness) returns one.
+use super::Component;
+use crate::config::{RcalConfig, Strictness};
+use crate::error::CalendarError;
+use crate::event::Event;
+use crate::names::name_eq;
+use crate::preparse::preparse_all_errors_with_limits;
+use crate::timezone::{TzResolver, VTimeZone};
+use crate::warning::{AtLine, IgnoreWarnings, Warnings};
+
+/// A parsed `VCALENDAR`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calendar {
+    /// The whole component tree, including what isn't typed below
+    pub component: Component,
+    /// The `VEVENT`s, in order, with their `TZID`s resolved (see `resolver`)
+    pub events: Vec<Event>,
+    /// The `VTIMEZONE`s, in order
+    pub timezones: Vec<VTimeZone>,
+}
+
+impl Calendar {
+    /// Type the events and time zones of `component`, a `VCALENDAR`, preparsing their
+    /// properties within `config`'s limits and resolving local times as its DST policy says
+    /// (see `RcalConfig::resolver`). When strict, a `VEVENT` or `VTIMEZONE` we can't
+    /// read is an error; when lenient, it's left out of `events` or `timezones` (but is still
+    /// in `component`).
+    ///
+    /// # Errors
+    /// Returns `CalendarError::Event` or `CalendarError::TimeZone` for the first `VEVENT` or
+    /// `VTIMEZONE` we can't read, when strict.
+    pub fn from_component(
+        component: Component,
+        config: &RcalConfig,
+    ) -> Result<Calendar, CalendarError> {
+        Self::from_component_with(component, config, &mut IgnoreWarnings)
+    }
+    /// Like `from_component`, warning about each `VEVENT` whose `DTSTART` doesn't match its
+    /// `RRULE` (see `RRule::check_dtstart`), with the span of the `RRULE`'s value and the
+    /// input line it's on
+    ///
+    /// # Errors
+    /// As `from_component`.
+    pub fn from_component_with(
+        component: Component,
+        config: &RcalConfig,
+        warnings: &mut dyn Warnings,
+    ) -> Result<Calendar, CalendarError> {
+        let strict = config.strictness() == Strictness::Strict;
+        let of_type = |name| component.components.iter().filter(move |c| name_eq(&c.name, name));
+        let mut timezones = Vec::new();
+        for vtimezone in of_type("VTIMEZONE") {
+            match VTimeZone::from_component(vtimezone) {
+                Ok(zone) => timezones.push(zone),
+                Err(err) if strict => return Err(CalendarError::TimeZone(err)),
+                Err(_) => {}
+            }
+        }
+        let resolver = timezones.iter().cloned().fold(config.resolver(), TzResolver::vtimezone);
+        let mut events = Vec::new();
+        for vevent in of_type("VEVENT") {
+            let mut props = Vec::new();
+            let mut rrules = Vec::new();
+            for property in &vevent.properties {
+                let line = property.line.as_bytes();
+                let preparsed = preparse_all_errors_with_limits(line, config.preparse_limits());
+                let Some(prop) = preparsed.0.into_prop() else { continue };
+                if name_eq(prop.name.val, "RRULE") {
+                    rrules.push((property.input_line, prop.value().clone()));
+                }
+                props.push(prop);
+            }
+            match Event::from_props_with(&props, &resolver) {
+                Ok(event) => {
+                    for (input_line, value) in rrules {
+                        let Ok(rule) =
+                            config.parse_rrule(&mut value.val.as_bytes(), &mut IgnoreWarnings)
+                        else {
+                            continue;
+                        };
+                        let span = value.loc..value.loc + value.val.len();
+                        match input_line {
+                            Some(line) => rule.check_dtstart(
+                                event.dtstart(),
+                                span,
+                                &mut AtLine { line, warnings },
+                            ),
+                            None => rule.check_dtstart(event.dtstart(), span, warnings),
+                        };
+                    }
+                    events.push(event);
+                }
+                Err(err) if strict => return Err(CalendarError::Event(err)),
+                Err(_) => {}
+            }
+        }
+        Ok(Calendar { component, events, timezones })
+    }
+
+    /// The resolver for the calendar's `TZID`s: jiff's time zone database, then its
+    /// `VTIMEZONE`s
+    #[must_use]
+    pub fn resolver(&self) -> TzResolver {
+        self.timezones.iter().cloned().fold(TzResolver::new(), TzResolver::vtimezone)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::PreparseError;
+    use crate::event::{EventError, EventTime};
+    use crate::preparse::PreparseLimits;
+    use crate::timezone::VTimeZoneError;
+    use crate::warning::{Observation, Warning};
+    use pretty_assertions::assert_eq;
+
+    fn input(lines: &[&str]) -> String {
+        lines.join("\r\n") + "\r\n"
+    }
+
+    #[test]
+    fn typed_events_and_timezones() {
+        let input = input(&[
+            "BEGIN:VCALENDAR",
+            "BEGIN:VTIMEZONE",
+            "TZID:Somewhere",
+            "BEGIN:STANDARD",
+            "DTSTART:19700101T000000",
+            "TZOFFSETFROM:+0300",
+            "TZOFFSETTO:+0300",
+            "END:STANDARD",
+            "END:VTIMEZONE",
+            "BEGIN:VEVENT",
+            "UID:a",
+            "DTSTART;TZID=Somewhere:20250303T090000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:b",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]);
+        let calendar = RcalConfig::interop().parse_calendar(input.as_bytes()).unwrap();
+        assert_eq!(calendar.component.components.len(), 3);
+        assert_eq!(calendar.timezones[0].tzid(), "Somewhere");
+        // The second VEVENT has no DTSTART, so it's left out
+        assert_eq!(calendar.events.len(), 1);
+        let EventTime::Zoned(start) = calendar.events[0].dtstart() else {
+            panic!("DTSTART should be zoned");
+        };
+        assert_eq!(start.timestamp().to_string(), "2025-03-03T06:00:00Z");
+        let strict = RcalConfig::strict().parse_calendar(input.as_bytes());
+        assert!(matches!(strict, Err(CalendarError::Event(EventError::NoDtstart))), "{strict:?}");
+    }
+    #[test]
+    fn dtstart_not_in_rule() {
+        let input = input(&[
+            "BEGIN:VCALENDAR",
+            "BEGIN:VEVENT",
+            "UID:a",
+            "DTSTART:20250305T090000Z",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:b",
+            "DTSTART:20250303T090000Z",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]);
+        let mut warnings = Vec::new();
+        let calendar = RcalConfig::interop().parse_calendar_with(input.as_bytes(), &mut warnings);
+        assert_eq!(calendar.unwrap().events.len(), 2);
+        // 2025-03-05 is a Wednesday; the second event starts on a Monday
+        let expected = Warning::new(Observation::DtstartNotInRule, 6..26).at_line(5);
+        assert_eq!(warnings, vec![expected]);
+    }
+    #[test]
+    fn bad_timezones() {
+        let input =
+            input(&["BEGIN:VCALENDAR", "BEGIN:VTIMEZONE", "END:VTIMEZONE", "END:VCALENDAR"]);
+        let calendar = RcalConfig::interop().parse_calendar(input.as_bytes()).unwrap();
+        assert_eq!(calendar.timezones, []);
+        let strict = RcalConfig::strict().parse_calendar(input.as_bytes());
+        let Err(err) = strict else { panic!("a VTIMEZONE without a TZID should be an error") };
+        assert_eq!(err.code(), VTimeZoneError::NoTzid.code());
+    }
+    #[test]
+    fn preparse_limits() {
+        let attendee = format!("ATTENDEE{}:mailto:a@example.com", ";X-A=1".repeat(40));
+        let input = input(&[
+            "BEGIN:VCALENDAR",
+            "BEGIN:VEVENT",
+            "DTSTART:20250303T090000Z",
+            &attendee,
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]);
+        let calendar = RcalConfig::interop().parse_calendar(input.as_bytes()).unwrap();
+        assert_eq!(calendar.events.len(), 1);
+        let config = RcalConfig::interop()
+            .with_preparse_limits(PreparseLimits { max_parameters: 32, max_values: 64 });
+        let err = config.parse_calendar(input.as_bytes()).unwrap_err();
+        let CalendarError::AtLine(4, PreparseError { problem, .. }) = err else {
+            panic!("{err:?} should be a preparse error on line 4");
+        };
+        assert_eq!(problem, crate::error::Problem::TooMany(crate::error::Segment::ParamName));
+    }
+}
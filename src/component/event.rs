@@ -0,0 +1,794 @@
+//! # Events
+//! `DTEND` is exclusive: an event from 10:00 to 11:00 doesn't occupy 11:00, and an all-day
+//! event on March 3 has `DTEND;VALUE=DATE:20250304`. Getting that wrong is the classic
+//! calendar off-by-one, so `Event` answers "which days does this occupy?" itself.
+//!
+//! A recurring event's instances can be overridden: an override is another `VEVENT` with the
+//! same `UID` and a `RECURRENCE-ID` naming the instance it replaces. `Event` keeps its
+//! overrides, so that questions about the series (like `response_summary`) can account for
+//! them, and `instances` gives each instance of the series as an `Instance`, moved where its
+//! override says.
+use crate::catalog;
+use crate::expansion::Instance;
+use crate::names::uppercase;
+use crate::parameter::{CalAddress, Parameters, PartStat, Tzid};
+use crate::preparse::Prop;
+use crate::property::{
+    ValueError, email_address, parameter, parse_date, parse_date_time_with, parse_duration_span,
+    parse_time, split_text_list, unescape_text,
+};
+use crate::timezone::{ResolvedZone, TzResolver, VTimeZone};
+use jiff::civil::{Date, DateTime, Time};
+use jiff::tz::TimeZone;
+use jiff::{Span, SpanFieldwise, Zoned};
+use thiserror::Error;
+
+/// The value of a `DTSTART` or `DTEND` property: an all-day `DATE`, a floating `DATE-TIME`,
+/// or a `DATE-TIME` in a particular time zone (including UTC)
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventTime {
+    Date(Date),
+    Floating(DateTime),
+    Zoned(Zoned),
+}
+
+/// The time zone an event's times are in, as `Event::timezone` finds it
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventTimeZone {
+    /// `DTSTART` is in a time zone: the one its `TZID` names, or (for an event built with a
+    /// zoned `DTSTART` rather than parsed) its own zone, which may have no name
+    Zoned { tzid: Option<Tzid>, zone: TimeZone },
+    /// `DTSTART` is in a zone only the calendar's `VTIMEZONE` for its `TZID` defines. jiff
+    /// can't carry the `VTIMEZONE`'s rules, so the event's times are each in the fixed offset
+    /// in effect then.
+    Defined { tzid: Tzid, zone: VTimeZone },
+    /// `DTSTART` has a `TZID` that names no zone we could find, so the event's times are
+    /// floating
+    Unresolved { tzid: Tzid },
+    /// `DTSTART` is in UTC (written with a `Z`, or with a `TZID` naming UTC)
+    Utc,
+    /// `DTSTART` is floating, or a `DATE`: the event happens at the same local time wherever
+    /// you are
+    Floating,
+}
+
+/// The value of a `VEVENT`'s `STATUS` property
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventStatus {
+    Tentative,
+    Confirmed,
+    Cancelled,
+}
+
+/// Why `Event::from_props` couldn't build an event
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum EventError {
+    #[error("the event has no DTSTART")]
+    NoDtstart,
+    /// The property at `index` has a value we couldn't parse (or, for `DTSTART` and
+    /// friends, a `TZID` naming no known time zone)
+    #[error("the {name} value at index {index} isn't valid")]
+    BadValue { index: usize, name: String },
+}
+impl EventError {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            EventError::NoDtstart => catalog::EVENT_NO_DTSTART,
+            EventError::BadValue { .. } => catalog::EVENT_BAD_VALUE,
+        }
+    }
+}
+
+/// An `ATTENDEE` property: the attendee's address and the property's parameters
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Attendee {
+    pub address: CalAddress,
+    pub parameters: Parameters,
+}
+
+impl Attendee {
+    #[must_use]
+    pub fn new(address: impl Into<CalAddress>) -> Self {
+        Attendee { address: address.into(), parameters: Parameters::default() }
+    }
+    /// The attendee's email address (see `property::email_address`)
+    #[must_use]
+    pub fn email(&self) -> Option<&str> {
+        email_address(&self.address)
+    }
+    /// The attendee's participation status: the `PARTSTAT` parameter, which defaults to
+    /// `NEEDS-ACTION`
+    #[must_use]
+    pub fn partstat(&self) -> &PartStat {
+        static NEEDS_ACTION: PartStat = PartStat::NeedsAction(None);
+        self.parameters.partstat().unwrap_or(&NEEDS_ACTION)
+    }
+}
+
+/// A `VEVENT`: its start and end, and what we need to answer questions about it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    dtstart: EventTime,
+    // `DTSTART`'s `TZID` parameter, if it had one, and the `VTIMEZONE` it resolved to, if it
+    // resolved to one
+    tzid: Option<Tzid>,
+    vtimezone: Option<VTimeZone>,
+    dtend: Option<EventTime>,
+    duration: Option<SpanFieldwise>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    uid: Option<String>,
+    sequence: Option<u32>,
+    status: Option<EventStatus>,
+    categories: Vec<String>,
+    attendees: Vec<Attendee>,
+    recurrence_id: Option<EventTime>,
+    // In order by `RECURRENCE-ID`, each with one
+    overrides: Vec<Event>,
+}
+
+impl Event {
+    #[must_use]
+    pub fn new(dtstart: EventTime) -> Self {
+        Event {
+            dtstart,
+            tzid: None,
+            vtimezone: None,
+            dtend: None,
+            duration: None,
+            summary: None,
+            description: None,
+            location: None,
+            uid: None,
+            sequence: None,
+            status: None,
+            categories: Vec::new(),
+            attendees: Vec::new(),
+            recurrence_id: None,
+            overrides: Vec::new(),
+        }
+    }
+    /// The event described by `props`, the preparsed properties of a `VEVENT` (without its
+    /// `BEGIN` and `END` lines or its subcomponents). Properties we don't handle are ignored,
+    /// and of those we do, the last of each wins, except that every `CATEGORIES` and
+    /// `ATTENDEE` counts.
+    ///
+    /// A `TZID` that names no zone we can find leaves the times it qualifies floating (see
+    /// `EventTimeZone::Unresolved`).
+    ///
+    /// # Errors
+    /// Returns an error if there's no `DTSTART`, or a property we handle has a value we can't
+    /// parse.
+    pub fn from_props(props: &[Prop]) -> Result<Event, EventError> {
+        Event::from_props_with(props, &TzResolver::new())
+    }
+    /// Like `from_props`, with `resolver` finding the zones `TZID`s name
+    ///
+    /// # Errors
+    /// As `from_props`.
+    pub fn from_props_with(props: &[Prop], resolver: &TzResolver) -> Result<Event, EventError> {
+        let is = |prop: &Prop, name: &str| prop.name.val().eq_ignore_ascii_case(name);
+        let Some((index, dtstart)) = props.iter().enumerate().rfind(|(_, p)| is(p, "DTSTART"))
+        else {
+            return Err(EventError::NoDtstart);
+        };
+        let start = event_time(dtstart, resolver).ok_or_else(|| bad(index, dtstart))?;
+        let mut event = Event::new(start);
+        if !matches!(event.dtstart, EventTime::Date(_)) {
+            event.tzid = parameter(dtstart, "TZID").map(Tzid::from);
+        }
+        if let (Some(tzid), EventTime::Zoned(_)) = (&event.tzid, &event.dtstart)
+            && let Some(ResolvedZone::VTimeZone(zone)) = resolver.resolve(tzid.as_str())
+        {
+            event.vtimezone = Some(zone);
+        }
+        for (index, prop) in props.iter().enumerate() {
+            event.set_from(prop, resolver).ok_or_else(|| bad(index, prop))?;
+        }
+        Ok(event)
+    }
+    // Set the property `prop` (if it's one we handle), or return `None` if its value is bad
+    fn set_from(&mut self, prop: &Prop, resolver: &TzResolver) -> Option<()> {
+        let value = prop.value().val();
+        match uppercase(prop.name.val()).as_ref() {
+            "DTEND" => self.set_dtend(event_time(prop, resolver)?),
+            "DURATION" => self.set_duration(parse_duration_span(value).ok()?),
+            "SUMMARY" => self.set_summary(unescape_text(value)),
+            "DESCRIPTION" => self.set_description(unescape_text(value)),
+            "LOCATION" => self.set_location(unescape_text(value)),
+            "UID" => self.set_uid(value.to_string()),
+            "SEQUENCE" => self.set_sequence(value.parse().ok()?),
+            "STATUS" => self.set_status(match value.to_ascii_uppercase().as_str() {
+                "TENTATIVE" => EventStatus::Tentative,
+                "CONFIRMED" => EventStatus::Confirmed,
+                "CANCELLED" => EventStatus::Cancelled,
+                _ => return None,
+            }),
+            "CATEGORIES" => self.categories.extend(split_text_list(value)),
+            "ATTENDEE" => self.attendees.push(attendee(prop)),
+            "RECURRENCE-ID" => self.recurrence_id = Some(event_time(prop, resolver)?),
+            _ => {}
+        }
+        Some(())
+    }
+
+    /// Get the `DTSTART` property
+    #[must_use]
+    pub fn dtstart(&self) -> &EventTime {
+        &self.dtstart
+    }
+    /// The time zone of the event's times: `DTSTART`'s, which RFC 5545 has `DTEND` and
+    /// `RECURRENCE-ID` share. This is the one place display code should ask.
+    #[must_use]
+    pub fn timezone(&self) -> EventTimeZone {
+        match (&self.dtstart, &self.tzid, &self.vtimezone) {
+            (EventTime::Floating(_), Some(tzid), _) => {
+                EventTimeZone::Unresolved { tzid: tzid.clone() }
+            }
+            (EventTime::Date(_) | EventTime::Floating(_), _, _) => EventTimeZone::Floating,
+            (EventTime::Zoned(_), Some(tzid), Some(zone)) => {
+                EventTimeZone::Defined { tzid: tzid.clone(), zone: zone.clone() }
+            }
+            (EventTime::Zoned(zoned), _, _) if zoned.time_zone() == &TimeZone::UTC => {
+                EventTimeZone::Utc
+            }
+            (EventTime::Zoned(zoned), _, _) => {
+                let tzid =
+                    (self.tzid.clone()).or_else(|| zoned.time_zone().iana_name().map(Tzid::from));
+                EventTimeZone::Zoned { tzid, zone: zoned.time_zone().clone() }
+            }
+        }
+    }
+    /// Get the `DTEND` property
+    #[must_use]
+    pub fn dtend(&self) -> Option<&EventTime> {
+        self.dtend.as_ref()
+    }
+    /// Set the `DTEND` property. An event can't have both `DTEND` and `DURATION`, so this
+    /// removes any `DURATION`.
+    pub fn set_dtend(&mut self, dtend: EventTime) {
+        self.dtend = Some(dtend);
+        self.duration = None;
+    }
+    /// Get the `DURATION` property: its days nominal, and its hours, minutes and seconds
+    /// exact (see `parse_duration_span`)
+    #[must_use]
+    pub fn duration(&self) -> Option<Span> {
+        self.duration.map(|duration| duration.0)
+    }
+    /// Set the `DURATION` property, removing any `DTEND`.
+    pub fn set_duration(&mut self, duration: Span) {
+        self.duration = Some(duration.fieldwise());
+        self.dtend = None;
+    }
+    /// Get the `SUMMARY` property
+    #[must_use]
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+    /// Set the `SUMMARY` property
+    pub fn set_summary(&mut self, summary: String) {
+        self.summary = Some(summary);
+    }
+    /// Get the `DESCRIPTION` property
+    #[must_use]
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    /// Set the `DESCRIPTION` property
+    pub fn set_description(&mut self, description: String) {
+        self.description = Some(description);
+    }
+    /// Get the `LOCATION` property
+    #[must_use]
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+    /// Set the `LOCATION` property
+    pub fn set_location(&mut self, location: String) {
+        self.location = Some(location);
+    }
+    /// Get the `UID` property
+    #[must_use]
+    pub fn uid(&self) -> Option<&str> {
+        self.uid.as_deref()
+    }
+    /// Set the `UID` property
+    pub fn set_uid(&mut self, uid: String) {
+        self.uid = Some(uid);
+    }
+    /// Get the `SEQUENCE` property. (An event without one has sequence number 0.)
+    #[must_use]
+    pub fn sequence(&self) -> Option<u32> {
+        self.sequence
+    }
+    /// Set the `SEQUENCE` property
+    pub fn set_sequence(&mut self, sequence: u32) {
+        self.sequence = Some(sequence);
+    }
+    /// Get the `STATUS` property
+    #[must_use]
+    pub fn status(&self) -> Option<EventStatus> {
+        self.status
+    }
+    /// Set the `STATUS` property
+    pub fn set_status(&mut self, status: EventStatus) {
+        self.status = Some(status);
+    }
+    /// Get the `CATEGORIES` property
+    #[must_use]
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+    /// Set the `CATEGORIES` property
+    pub fn set_categories(&mut self, categories: Vec<String>) {
+        self.categories = categories;
+    }
+    /// Get the `ATTENDEE` properties
+    #[must_use]
+    pub fn attendees(&self) -> &[Attendee] {
+        &self.attendees
+    }
+    /// Set the `ATTENDEE` properties
+    pub fn set_attendees(&mut self, attendees: Vec<Attendee>) {
+        self.attendees = attendees;
+    }
+    /// Get the `RECURRENCE-ID` property, which only overrides have
+    #[must_use]
+    pub fn recurrence_id(&self) -> Option<&EventTime> {
+        self.recurrence_id.as_ref()
+    }
+    /// The overrides of the event's instances, in order by `RECURRENCE-ID`
+    #[must_use]
+    pub fn overrides(&self) -> &[Event] {
+        &self.overrides
+    }
+    /// Override the instance at `recurrence_id` with `instance` (setting its `RECURRENCE-ID`),
+    /// replacing any existing override of that instance
+    pub fn set_override(&mut self, recurrence_id: EventTime, mut instance: Event) {
+        let key = sort_key(Some(&recurrence_id));
+        instance.recurrence_id = Some(recurrence_id);
+        let found =
+            self.overrides.binary_search_by_key(&key, |o| sort_key(o.recurrence_id.as_ref()));
+        match found {
+            Ok(n) => self.overrides[n] = instance,
+            Err(n) => self.overrides.insert(n, instance),
+        }
+    }
+
+    /// How the attendees have responded: a summary for the event itself, which covers every
+    /// instance that isn't overridden, then one for each override, in order. An override
+    /// with attendees of its own is summarized with those, and one with none with the
+    /// event's.
+    #[must_use]
+    pub fn response_summary(&self) -> Vec<ResponseSummary> {
+        let summary = |recurrence_id: Option<&EventTime>, attendees: &[Attendee]| {
+            let mut summary = ResponseSummary {
+                recurrence_id: recurrence_id.cloned(),
+                ..ResponseSummary::default()
+            };
+            for attendee in attendees {
+                match attendee.partstat() {
+                    PartStat::Accepted => summary.accepted += 1,
+                    PartStat::Declined => summary.declined += 1,
+                    PartStat::Tentative => summary.tentative += 1,
+                    PartStat::Delegated => summary.delegated += 1,
+                    PartStat::NeedsAction(_) => {
+                        summary.needs_action += 1;
+                        summary.non_responders.push(attendee.address.clone());
+                    }
+                    PartStat::Completed | PartStat::InProcess => summary.other += 1,
+                }
+            }
+            summary
+        };
+        let overrides = self.overrides.iter().map(|instance| {
+            let attendees =
+                if instance.attendees.is_empty() { &self.attendees } else { &instance.attendees };
+            summary(instance.recurrence_id.as_ref(), attendees)
+        });
+        std::iter::once(summary(None, &self.attendees)).chain(overrides).collect()
+    }
+
+    /// The half-open interval `[start, end)` the event occupies, as civil date-times in the
+    /// start's time zone. Following RFC 5545 § 3.6.1, an all-day event with neither `DTEND`
+    /// nor `DURATION` lasts one day, and a timed event with neither is instantaneous. A
+    /// `DURATION` is added to a zoned start with its days nominal and its hours exact.
+    #[must_use]
+    pub fn interval(&self) -> (DateTime, DateTime) {
+        let start = match &self.dtstart {
+            EventTime::Date(date) => midnight(*date),
+            EventTime::Floating(dt) => *dt,
+            EventTime::Zoned(zoned) => zoned.datetime(),
+        };
+        let end = match (&self.dtend, self.duration(), &self.dtstart) {
+            (Some(dtend), _, _) => self.civil_in_start_zone(dtend),
+            // Days are nominal, so an event lasting `P1D` from 09:00 ends at 09:00 even when
+            // the day is 23 or 25 hours long
+            (None, Some(duration), EventTime::Zoned(zoned)) => {
+                (zoned.checked_add(duration)).map_or(DateTime::MAX, |end| end.datetime())
+            }
+            (None, Some(duration), _) => start.checked_add(duration).unwrap_or(DateTime::MAX),
+            (None, None, _) => match self.dtstart {
+                EventTime::Date(_) => one_day_after(start),
+                _ => start,
+            },
+        };
+        (start, end.max(start))
+    }
+    fn civil_in_start_zone(&self, time: &EventTime) -> DateTime {
+        match (time, &self.dtstart) {
+            (EventTime::Date(date), _) => midnight(*date),
+            (EventTime::Floating(dt), _) => *dt,
+            (EventTime::Zoned(end), EventTime::Zoned(start)) => {
+                end.with_time_zone(start.time_zone().clone()).datetime()
+            }
+            (EventTime::Zoned(end), _) => end.datetime(),
+        }
+    }
+
+    /// Does the event occupy any part of `date`? An instantaneous event occupies the date it
+    /// happens on; otherwise the event's end is exclusive, so an event ending at midnight
+    /// doesn't occupy the following day.
+    #[must_use]
+    pub fn occupies(&self, date: Date) -> bool {
+        let (start, end) = self.interval();
+        if start == end {
+            return start.date() == date;
+        }
+        let day_start = midnight(date);
+        start < one_day_after(day_start) && day_start < end
+    }
+
+    /// The dates the event occupies, in order
+    #[must_use]
+    pub fn days(&self) -> Vec<Date> {
+        let (start, end) = self.interval();
+        let mut days = vec![start.date()];
+        while let Ok(next) = days[days.len() - 1].tomorrow() {
+            if midnight(next) >= end {
+                break;
+            }
+            days.push(next);
+        }
+        days
+    }
+
+    /// The event's instances, one for each of `starts` — the instances of its recurrence set,
+    /// as `RcalConfig::expand` gives them — with its `UID`, and in the order of `starts`. Each
+    /// lasts as long as the event does (see `interval`), unless an override with its
+    /// `RECURRENCE-ID` moves it, in which case it has the override's start and end.
+    ///
+    /// Pass `&mut` an `Instances` to check its `limit_reached` afterwards.
+    pub fn instances<'a>(
+        &'a self,
+        starts: impl Iterator<Item = Zoned> + 'a,
+    ) -> impl Iterator<Item = Instance> + 'a {
+        let uid = self.uid.clone().unwrap_or_default();
+        starts.map(move |recurrence_id| {
+            let found = (self.overrides.iter())
+                .find(|o| o.recurrence_id.as_ref().is_some_and(|id| is_at(id, &recurrence_id)));
+            let (start, end) = match found {
+                Some(instance) => instance.zoned_interval(recurrence_id.time_zone()),
+                None => self.zoned_interval_from(recurrence_id.clone()),
+            };
+            Instance { uid: uid.clone(), recurrence_id, start, end }
+        })
+    }
+    /// The event's start and end as zoned times, like [`Event::interval`] but with floating
+    /// and all-day times taken to be in `zone`
+    #[must_use]
+    pub fn zoned_interval(&self, zone: &TimeZone) -> (Zoned, Zoned) {
+        let start = match &self.dtstart {
+            EventTime::Zoned(zoned) => zoned.clone(),
+            _ => (self.interval().0.to_zoned(zone.clone())).unwrap_or_else(|_| Zoned::default()),
+        };
+        self.zoned_interval_from(start)
+    }
+    // An instance of the event starting at `start`, as long as the event: its `DURATION`,
+    // the exact time from a zoned `DTSTART` to a zoned `DTEND` (RFC 5545 § 3.8.5.3), or
+    // otherwise the calendar time between them
+    fn zoned_interval_from(&self, start: Zoned) -> (Zoned, Zoned) {
+        let length = match (&self.dtstart, &self.dtend, self.duration()) {
+            (_, None, Some(duration)) => duration,
+            (EventTime::Zoned(from), Some(EventTime::Zoned(to)), _) => {
+                Span::try_from(to.duration_since(from)).unwrap_or_default()
+            }
+            _ => {
+                let (from, to) = self.interval();
+                from.until(to).unwrap_or_default()
+            }
+        };
+        let end = start.checked_add(length).unwrap_or_else(|_| start.clone());
+        (start, end)
+    }
+}
+
+fn bad(index: usize, prop: &Prop) -> EventError {
+    EventError::BadValue { index, name: prop.name.val().to_ascii_uppercase() }
+}
+
+// The value of a `DTSTART`, `DTEND`, or `RECURRENCE-ID` property, taking a value that's just
+// a date as a `DATE` even without `VALUE=DATE`, as some producers forget it, and one with a
+// `TZID` we can't resolve as floating
+fn event_time(prop: &Prop, resolver: &TzResolver) -> Option<EventTime> {
+    let value = prop.value().val();
+    if value.len() == 8 {
+        return parse_date(value).ok().map(EventTime::Date);
+    }
+    match parse_date_time_with(prop, resolver) {
+        Err(ValueError::UnknownTzid(_)) => {
+            let (date, time) = value.split_once('T')?;
+            let local = parse_date(date).ok()?.to_datetime(parse_time(time).ok()?);
+            Some(EventTime::Floating(local))
+        }
+        time => time.ok(),
+    }
+}
+
+// An `ATTENDEE` property, with the parameters we use
+fn attendee(prop: &Prop) -> Attendee {
+    let mut attendee = Attendee::new(prop.value().val());
+    for param in prop.parameters() {
+        let Some(value) = param.values().first().map(|value| value.val()) else { continue };
+        if param.name().val().eq_ignore_ascii_case("PARTSTAT") {
+            attendee.parameters.set_partstat(PartStat::from(value));
+        } else if param.name().val().eq_ignore_ascii_case("CN") {
+            attendee.parameters.set_cn(value.to_string());
+        }
+    }
+    attendee
+}
+
+/// How the attendees of an event, or of one overridden instance, have responded (see
+/// `Event::response_summary`)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseSummary {
+    /// The overridden instance's `RECURRENCE-ID`, or `None` for the event itself
+    pub recurrence_id: Option<EventTime>,
+    pub accepted: usize,
+    pub declined: usize,
+    pub tentative: usize,
+    pub delegated: usize,
+    /// Attendees who haven't responded (`PARTSTAT=NEEDS-ACTION`, the default, or a status
+    /// we don't recognize)
+    pub needs_action: usize,
+    /// Attendees with a status meant for to-dos (`COMPLETED` or `IN-PROCESS`)
+    pub other: usize,
+    /// The addresses of the attendees who haven't responded, in order
+    pub non_responders: Vec<CalAddress>,
+}
+
+// Overrides are ordered by when the instance they override starts
+fn sort_key(recurrence_id: Option<&EventTime>) -> Option<DateTime> {
+    recurrence_id.map(|id| match id {
+        EventTime::Date(date) => midnight(*date),
+        EventTime::Floating(dt) => *dt,
+        EventTime::Zoned(zoned) => zoned.timestamp().to_zoned(TimeZone::UTC).datetime(),
+    })
+}
+
+// Whether `recurrence_id` names the instance starting at `start` (floating and all-day times
+// by their civil date-time, which the recurrence set keeps)
+fn is_at(recurrence_id: &EventTime, start: &Zoned) -> bool {
+    match recurrence_id {
+        EventTime::Date(date) => midnight(*date) == start.datetime(),
+        EventTime::Floating(dt) => *dt == start.datetime(),
+        EventTime::Zoned(zoned) => zoned.timestamp() == start.timestamp(),
+    }
+}
+
+fn midnight(date: Date) -> DateTime {
+    date.to_datetime(Time::midnight())
+}
+fn one_day_after(dt: DateTime) -> DateTime {
+    dt.checked_add(Span::new().days(1)).unwrap_or(DateTime::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::recurrence::RecurrenceSet;
+    use crate::rrule::parse_rrule;
+    use jiff::civil::{date, datetime};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn all_day_event_ends_the_day_before_dtend() {
+        let mut event = Event::new(EventTime::Date(date(2025, 3, 3)));
+        event.set_dtend(EventTime::Date(date(2025, 3, 4)));
+        assert!(!event.occupies(date(2025, 3, 2)));
+        assert!(event.occupies(date(2025, 3, 3)));
+        assert!(!event.occupies(date(2025, 3, 4)));
+        assert_eq!(event.days(), vec![date(2025, 3, 3)]);
+
+        event.set_dtend(EventTime::Date(date(2025, 3, 6)));
+        assert_eq!(event.days(), vec![date(2025, 3, 3), date(2025, 3, 4), date(2025, 3, 5)]);
+    }
+    #[test]
+    fn all_day_event_defaults_to_one_day() {
+        let event = Event::new(EventTime::Date(date(2024, 2, 29)));
+        assert_eq!(event.days(), vec![date(2024, 2, 29)]);
+        assert!(!event.occupies(date(2024, 3, 1)));
+    }
+    #[test]
+    fn timed_event_ending_at_midnight() {
+        let mut event = Event::new(EventTime::Floating(datetime(2025, 3, 3, 22, 0, 0, 0)));
+        event.set_dtend(EventTime::Floating(datetime(2025, 3, 4, 0, 0, 0, 0)));
+        assert!(event.occupies(date(2025, 3, 3)));
+        assert!(!event.occupies(date(2025, 3, 4)));
+        assert_eq!(event.days(), vec![date(2025, 3, 3)]);
+
+        event.set_duration(Span::new().hours(3));
+        assert_eq!(event.days(), vec![date(2025, 3, 3), date(2025, 3, 4)]);
+    }
+    #[test]
+    fn instantaneous_event() {
+        let event = Event::new(EventTime::Floating(datetime(2025, 3, 4, 0, 0, 0, 0)));
+        assert!(event.occupies(date(2025, 3, 4)));
+        assert!(!event.occupies(date(2025, 3, 3)));
+        assert_eq!(event.days(), vec![date(2025, 3, 4)]);
+    }
+    #[test]
+    #[cfg(feature = "cautious")]
+    fn from_props() {
+        use crate::preparse::cautious_preparse;
+        let lines = [
+            "UID:standup-1@example.com",
+            "DTSTART;TZID=America/New_York:20250303T090000",
+            "DURATION:PT15M",
+            "SUMMARY:Standup\\; bring coffee\\, notes",
+            "DESCRIPTION:Line one\\nLine two",
+            "SEQUENCE:2",
+            "STATUS:confirmed",
+            "CATEGORIES:Work,Team\\, core",
+            "CATEGORIES:Daily",
+            "ATTENDEE;CN=Jane;PARTSTAT=ACCEPTED:mailto:jane@example.com",
+            "X-UNKNOWN:whatever",
+        ];
+        let props: Vec<_> =
+            lines.iter().map(|l| cautious_preparse(l.as_bytes()).unwrap()).collect();
+        let event = Event::from_props(&props).unwrap();
+        let start = datetime(2025, 3, 3, 9, 0, 0, 0).in_tz("America/New_York").unwrap();
+        assert_eq!(event.dtstart(), &EventTime::Zoned(start));
+        assert_eq!(
+            event.duration().map(Span::fieldwise),
+            Some(Span::new().minutes(15).fieldwise())
+        );
+        assert_eq!(event.summary(), Some("Standup; bring coffee, notes"));
+        assert_eq!(event.description(), Some("Line one\nLine two"));
+        assert_eq!(event.uid(), Some("standup-1@example.com"));
+        assert_eq!((event.sequence(), event.status()), (Some(2), Some(EventStatus::Confirmed)));
+        assert_eq!(event.categories(), ["Work", "Team, core", "Daily"]);
+        assert_eq!(event.attendees()[0].partstat(), &PartStat::Accepted);
+        assert_eq!(event.attendees()[0].parameters.cn().map(String::as_str), Some("Jane"));
+        assert_eq!(event.attendees()[0].email(), Some("jane@example.com"));
+
+        let error = |lines: &[&str]| {
+            let props: Vec<_> =
+                lines.iter().map(|l| cautious_preparse(l.as_bytes()).unwrap()).collect();
+            Event::from_props(&props).unwrap_err()
+        };
+        assert_eq!(error(&["SUMMARY:No start"]), EventError::NoDtstart);
+        let bad = |index, name: &str| EventError::BadValue { index, name: name.to_string() };
+        assert_eq!(error(&["DTSTART:20250303", "duration:1H"]), bad(1, "DURATION"));
+        assert_eq!(error(&["DTSTART:20250303T090000Z", "SEQUENCE:-1"]), bad(1, "SEQUENCE"));
+    }
+    #[test]
+    #[cfg(feature = "cautious")]
+    fn timezones() {
+        use crate::preparse::cautious_preparse;
+        let timezone = |dtstart: &str| {
+            let props = [cautious_preparse(dtstart.as_bytes()).unwrap()];
+            Event::from_props(&props).unwrap().timezone()
+        };
+        let zone = TimeZone::get("Europe/Paris").unwrap();
+        let tzid = Some(Tzid::from("Europe/Paris"));
+        let paris = EventTimeZone::Zoned { tzid: tzid.clone(), zone: zone.clone() };
+        assert_eq!(timezone("DTSTART;TZID=Europe/Paris:20250303T090000"), paris);
+        assert_eq!(timezone("DTSTART:20250303T090000Z"), EventTimeZone::Utc);
+        assert_eq!(timezone("DTSTART;TZID=UTC:20250303T090000"), EventTimeZone::Utc);
+        assert_eq!(timezone("DTSTART:20250303T090000"), EventTimeZone::Floating);
+        assert_eq!(timezone("DTSTART;VALUE=DATE:20250303"), EventTimeZone::Floating);
+        let unresolved = EventTimeZone::Unresolved { tzid: Tzid::from("Nowhere") };
+        assert_eq!(timezone("DTSTART;TZID=Nowhere:20250303T090000"), unresolved);
+        let built = datetime(2025, 3, 3, 9, 0, 0, 0).to_zoned(zone.clone()).unwrap();
+        let event = Event::new(EventTime::Zoned(built));
+        assert_eq!(event.timezone(), EventTimeZone::Zoned { tzid, zone: zone.clone() });
+        // A Windows zone name, through the application's mapping
+        let resolver = TzResolver::new().fallback(move |_| Some(zone.clone()));
+        let props = [cautious_preparse(b"DTSTART;TZID=Romance Standard Time:20250303T090000")];
+        let event = Event::from_props_with(&props.map(Result::unwrap), &resolver).unwrap();
+        let tzid = Some(Tzid::from("Romance Standard Time"));
+        let zone = TimeZone::get("Europe/Paris").unwrap();
+        assert_eq!(event.timezone(), EventTimeZone::Zoned { tzid, zone });
+    }
+    #[test]
+    fn responses_with_overrides() {
+        let attendee = |address: &str, partstat: Option<PartStat>| {
+            let mut attendee = Attendee::new(address);
+            if let Some(partstat) = partstat {
+                attendee.parameters.set_partstat(partstat);
+            }
+            attendee
+        };
+        let at = |day| EventTime::Floating(datetime(2025, 3, day, 9, 0, 0, 0));
+        let mut standup = Event::new(at(3));
+        standup.set_attendees(vec![
+            attendee("mailto:a@example.com", Some(PartStat::Accepted)),
+            attendee("mailto:b@example.com", None),
+            attendee("mailto:c@example.com", Some(PartStat::Tentative)),
+        ]);
+        let mut moved = Event::new(at(11));
+        moved.set_attendees(vec![
+            attendee("mailto:a@example.com", Some(PartStat::Declined)),
+            attendee("mailto:b@example.com", Some(PartStat::Accepted)),
+        ]);
+        standup.set_override(at(10), moved);
+        standup.set_override(at(5), Event::new(at(5)));
+        let summaries = standup.response_summary();
+        let counts: Vec<_> = (summaries.iter())
+            .map(|s| (s.recurrence_id.clone(), s.accepted, s.declined, s.tentative, s.needs_action))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(None, 1, 0, 1, 1), (Some(at(5)), 1, 0, 1, 1), (Some(at(10)), 1, 1, 0, 0)]
+        );
+        assert_eq!(summaries[0].non_responders, vec!["mailto:b@example.com".to_string()]);
+        assert!(summaries[2].non_responders.is_empty());
+    }
+    #[test]
+    fn zoned_end_is_converted_to_start_zone() {
+        let start = datetime(2025, 3, 3, 23, 0, 0, 0).in_tz("America/New_York").unwrap();
+        let end = datetime(2025, 3, 4, 5, 30, 0, 0).in_tz("Europe/London").unwrap();
+        let mut event = Event::new(EventTime::Zoned(start));
+        event.set_dtend(EventTime::Zoned(end));
+        assert_eq!(event.interval().1, datetime(2025, 3, 4, 0, 30, 0, 0));
+        assert_eq!(event.days(), vec![date(2025, 3, 3), date(2025, 3, 4)]);
+    }
+    #[test]
+    fn durations_across_dst() {
+        // Summer time starts in Paris at 02:00 on March 30, 2025
+        let start = datetime(2025, 3, 29, 9, 0, 0, 0).in_tz("Europe/Paris").unwrap();
+        let mut event = Event::new(EventTime::Zoned(start));
+        event.set_duration(Span::new().days(1));
+        assert_eq!(event.interval().1, datetime(2025, 3, 30, 9, 0, 0, 0));
+        event.set_duration(Span::new().hours(24));
+        assert_eq!(event.interval().1, datetime(2025, 3, 30, 10, 0, 0, 0));
+    }
+    #[test]
+    fn instances_with_overrides() {
+        let paris = |day, hour, minute| {
+            EventTime::Zoned(
+                datetime(2025, 3, day, hour, minute, 0, 0).in_tz("Europe/Paris").unwrap(),
+            )
+        };
+        let mut event = Event::new(paris(24, 9, 0));
+        event.set_dtend(paris(24, 10, 0));
+        event.set_uid("standup".to_string());
+        let mut moved = Event::new(paris(31, 11, 0));
+        moved.set_duration(Span::new().minutes(90));
+        event.set_override(paris(31, 9, 0), moved);
+
+        let mut set = RecurrenceSet::new(event.dtstart());
+        set.add_rrule(parse_rrule(&mut &b"FREQ=WEEKLY;COUNT=2\r\n"[..]).unwrap());
+        let instances: Vec<_> = event.instances(set.iter()).collect();
+        let times: Vec<_> = (instances.iter())
+            .map(|i| (i.start.datetime().to_string(), i.end.datetime().to_string()))
+            .collect();
+        // The second instance is after the change to summer time, and moved
+        assert_eq!(
+            times,
+            [
+                ("2025-03-24T09:00:00".to_string(), "2025-03-24T10:00:00".to_string()),
+                ("2025-03-31T11:00:00".to_string(), "2025-03-31T12:30:00".to_string()),
+            ]
+        );
+        assert_eq!(instances[1].recurrence_id.datetime(), datetime(2025, 3, 31, 9, 0, 0, 0));
+        assert!(instances.iter().all(|i| i.uid == "standup"));
+        assert!(instances.is_sorted());
+    }
+}
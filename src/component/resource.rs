@@ -0,0 +1,346 @@
+//! # Calendar object resources
+//! `CalDAV` ([RFC 4791 § 4.1](https://datatracker.ietf.org/doc/html/rfc4791#section-4.1)) stores
+//! each event, to-do or journal entry as its own *calendar object resource*: a `VCALENDAR`
+//! whose components, apart from `VTIMEZONE`s, are all the same type and share one `UID`. More
+//! than one component means a recurring item and its overrides. The resource has no `METHOD`,
+//! and includes a `VTIMEZONE` for each `TZID` it uses.
+//!
+//! `check_resource` reports how a `VCALENDAR` breaks those rules, and `split_resources`
+//! partitions any `VCALENDAR` — an export, or a subscribed feed — into resources that don't.
+//! `find_duplicate_uids` finds the `UID`s that several calendars disagree about, as when
+//! importing overlapping feeds into one store.
+use super::{Component, Property};
+use crate::catalog;
+use crate::names::name_eq;
+use crate::preparse::preparse_all_errors;
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+/// How a `VCALENDAR` isn't a valid calendar object resource
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ResourceProblem {
+    #[error("the calendar has no components other than VTIMEZONEs")]
+    NoComponents,
+    #[error("the calendar mixes {first} and {other} components")]
+    MixedComponents { first: String, other: String },
+    /// A component (of the given type) has no `UID`
+    #[error("a {0} has no UID")]
+    NoUid(String),
+    #[error("the calendar has more than one UID: {first} and {other}")]
+    MultipleUids { first: String, other: String },
+    #[error("the calendar has a METHOD property")]
+    Method,
+    #[error("no VTIMEZONE has TZID {0}")]
+    MissingTimezone(String),
+}
+impl ResourceProblem {
+    /// The problem's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            ResourceProblem::NoComponents => catalog::RESOURCE_NO_COMPONENTS,
+            ResourceProblem::MixedComponents { .. } => catalog::RESOURCE_MIXED_COMPONENTS,
+            ResourceProblem::NoUid(_) => catalog::RESOURCE_NO_UID,
+            ResourceProblem::MultipleUids { .. } => catalog::RESOURCE_MULTIPLE_UIDS,
+            ResourceProblem::Method => catalog::RESOURCE_METHOD,
+            ResourceProblem::MissingTimezone(_) => catalog::RESOURCE_MISSING_TIMEZONE,
+        }
+    }
+}
+
+/// The ways `calendar` isn't a valid calendar object resource, in the order we find them:
+/// each problem with its components, in order, then a `METHOD`, then any missing time zones
+#[must_use]
+pub fn check_resource(calendar: &Component) -> Vec<ResourceProblem> {
+    let mut problems = Vec::new();
+    let mut items = calendar.components.iter().filter(|c| !is_timezone(c));
+    let Some(first) = items.next() else {
+        return vec![ResourceProblem::NoComponents];
+    };
+    let uid = first.value_of("UID");
+    if uid.is_none() {
+        problems.push(ResourceProblem::NoUid(first.name.clone()));
+    }
+    for item in items {
+        if item.name != first.name {
+            let (first, other) = (first.name.clone(), item.name.clone());
+            problems.push(ResourceProblem::MixedComponents { first, other });
+        }
+        match (uid, item.value_of("UID")) {
+            (_, None) => problems.push(ResourceProblem::NoUid(item.name.clone())),
+            (Some(first), Some(other)) if first != other => {
+                let (first, other) = (first.to_string(), other.to_string());
+                problems.push(ResourceProblem::MultipleUids { first, other });
+            }
+            _ => {}
+        }
+    }
+    if calendar.properties.iter().any(|p| p.name().eq_ignore_ascii_case("METHOD")) {
+        problems.push(ResourceProblem::Method);
+    }
+    let defined: BTreeSet<&str> = timezones(calendar).map(|(tzid, _)| tzid).collect();
+    let items = calendar.components.iter().filter(|c| !is_timezone(c));
+    for tzid in tzids(items) {
+        if !defined.contains(tzid) {
+            problems.push(ResourceProblem::MissingTimezone(tzid.to_string()));
+        }
+    }
+    problems
+}
+
+/// Partition `calendar` into calendar object resources: one for each component type and
+/// `UID`, in the order they first appear, holding each component of that type with that
+/// `UID`. Each resource has the calendar's properties except `METHOD`, and the `VTIMEZONE`s
+/// its components use. A component without a `UID` gets a resource of its own, which
+/// `check_resource` will still flag: only the server storing it can assign one.
+#[must_use]
+pub fn split_resources(calendar: &Component) -> Vec<Component> {
+    // Each group's component type and UID, if it has one, and its components
+    let mut groups: Vec<(Option<Key>, Vec<&Component>)> = Vec::new();
+    for item in calendar.components.iter().filter(|c| !is_timezone(c)) {
+        let key = item.value_of("UID").map(|uid| (item.name.as_str(), uid));
+        match groups.iter_mut().find(|(k, _)| key.is_some() && *k == key) {
+            Some((_, items)) => items.push(item),
+            None => groups.push((key, vec![item])),
+        }
+    }
+    let properties: Vec<Property> = (calendar.properties.iter())
+        .filter(|p| !p.name().eq_ignore_ascii_case("METHOD"))
+        .cloned()
+        .collect();
+    (groups.into_iter())
+        .map(|(_, items)| {
+            let used: BTreeSet<&str> = tzids(items.iter().copied()).collect();
+            let zones = (timezones(calendar).filter(|(tzid, _)| used.contains(tzid)))
+                .map(|(_, zone)| zone.clone());
+            Component {
+                name: calendar.name.clone(),
+                properties: properties.clone(),
+                components: zones.chain(items.into_iter().cloned()).collect(),
+            }
+        })
+        .collect()
+}
+
+/// A `UID` that more than one calendar has, with different content
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateUid {
+    pub uid: String,
+    /// The calendars with the `UID` (as indexes into those searched), grouped by content:
+    /// the calendars in a group agree with each other, and disagree with every other group.
+    /// Groups are in the order of their first calendar.
+    pub versions: Vec<Vec<usize>>,
+}
+
+/// The `UID`s that more than one of `calendars` (resources, or whole feeds) has, where the
+/// calendars don't all agree on the `UID`'s content, in the order they first appear. A
+/// calendar's content for a `UID` is each component with it, with its subcomponents, in any
+/// order; a `DTSTAMP` only says when the calendar was written, so doesn't count. A `UID` the
+/// calendars all agree on is no trouble to import, so isn't reported.
+#[must_use]
+pub fn find_duplicate_uids<'a>(
+    calendars: impl IntoIterator<Item = &'a Component>,
+) -> Vec<DuplicateUid> {
+    // Each UID with its versions, and each UID's index in `found`
+    let mut found: Vec<(&str, Versions)> = Vec::new();
+    let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+    for (n, calendar) in calendars.into_iter().enumerate() {
+        let mut contents: Vec<(&str, Content)> = Vec::new();
+        for item in calendar.components.iter().filter(|c| !is_timezone(c)) {
+            let Some(uid) = item.value_of("UID") else { continue };
+            let mut lines = Vec::new();
+            content(item, &mut lines);
+            match contents.iter_mut().find(|(u, _)| *u == uid) {
+                Some((_, content)) => content.push(lines),
+                None => contents.push((uid, vec![lines])),
+            }
+        }
+        for (uid, mut content) in contents {
+            content.sort_unstable();
+            let at = *index.entry(uid).or_insert_with(|| {
+                found.push((uid, Vec::new()));
+                found.len() - 1
+            });
+            let versions = &mut found[at].1;
+            match versions.iter_mut().find(|(c, _)| *c == content) {
+                Some((_, calendars)) => calendars.push(n),
+                None => versions.push((content, vec![n])),
+            }
+        }
+    }
+    (found.into_iter())
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(uid, versions)| DuplicateUid {
+            uid: uid.to_string(),
+            versions: versions.into_iter().map(|(_, calendars)| calendars).collect(),
+        })
+        .collect()
+}
+
+// The component's content lines, with its subcomponents' and without `DTSTAMP`s
+fn content(component: &Component, lines: &mut Vec<String>) {
+    lines.push(format!("BEGIN:{}", component.name));
+    (component.properties.iter())
+        .filter(|p| !name_eq(p.name(), "DTSTAMP"))
+        .for_each(|p| lines.push(p.line.clone()));
+    component.components.iter().for_each(|c| content(c, lines));
+    lines.push(format!("END:{}", component.name));
+}
+
+type Key<'a> = (&'a str, &'a str);
+// A calendar's content for a `UID`: each component's content lines
+type Content = Vec<Vec<String>>;
+// A `UID`'s contents, each with the calendars that have it
+type Versions = Vec<(Content, Vec<usize>)>;
+
+fn is_timezone(component: &Component) -> bool {
+    component.name.eq_ignore_ascii_case("VTIMEZONE")
+}
+// The calendar's `VTIMEZONE`s, each with its `TZID`
+fn timezones(calendar: &Component) -> impl Iterator<Item = (&str, &Component)> {
+    (calendar.components.iter().filter(|c| is_timezone(c)))
+        .filter_map(|zone| Some((zone.value_of("TZID")?, zone)))
+}
+// The distinct `TZID` parameter values in `components` and their subcomponents, in order
+fn tzids<'a>(components: impl Iterator<Item = &'a Component>) -> impl Iterator<Item = &'a str> {
+    fn collect<'a>(component: &'a Component, tzids: &mut Vec<&'a str>) {
+        for property in &component.properties {
+            let (prop, _) = preparse_all_errors(property.line.as_bytes());
+            let tzid =
+                prop.parameters.into_iter().find(|p| p.name.val.eq_ignore_ascii_case("TZID"));
+            if let Some(value) = tzid.and_then(|p| p.values.first().map(|v| v.val))
+                && !tzids.contains(&value)
+            {
+                tzids.push(value);
+            }
+        }
+        component.components.iter().for_each(|c| collect(c, tzids));
+    }
+    let mut tzids = Vec::new();
+    components.for_each(|c| collect(c, &mut tzids));
+    tzids.into_iter()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::names::Lookup;
+    use crate::unfolded::BufReadContent;
+    use pretty_assertions::assert_eq;
+
+    fn parse(lines: &[&str]) -> Component {
+        let input = lines.join("\r\n") + "\r\n";
+        let lines = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+        Component::parse(lines, &mut Lookup::new()).unwrap()
+    }
+    fn feed() -> Component {
+        parse(&[
+            "BEGIN:VCALENDAR",
+            "VERSION:2.0",
+            "METHOD:PUBLISH",
+            "BEGIN:VTIMEZONE",
+            "TZID:Europe/Paris",
+            "END:VTIMEZONE",
+            "BEGIN:VEVENT",
+            "UID:standup",
+            "DTSTART;TZID=Europe/Paris:20250303T090000",
+            "RRULE:FREQ=DAILY",
+            "END:VEVENT",
+            "BEGIN:VTODO",
+            "UID:report",
+            "END:VTODO",
+            "BEGIN:VEVENT",
+            "UID:standup",
+            "RECURRENCE-ID;TZID=Europe/Paris:20250304T090000",
+            "DTSTART;TZID=Europe/Paris:20250304T100000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "DTSTART;TZID=America/New_York:20250304T100000",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ])
+    }
+
+    #[test]
+    fn problems() {
+        let codes: Vec<_> = check_resource(&feed()).iter().map(ResourceProblem::code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                catalog::RESOURCE_MIXED_COMPONENTS,
+                catalog::RESOURCE_MULTIPLE_UIDS,
+                catalog::RESOURCE_NO_UID,
+                catalog::RESOURCE_METHOD,
+                catalog::RESOURCE_MISSING_TIMEZONE,
+            ]
+        );
+        let timezone_only =
+            parse(&["BEGIN:VCALENDAR", "BEGIN:VTIMEZONE", "END:VTIMEZONE", "END:VCALENDAR"]);
+        assert_eq!(check_resource(&timezone_only), vec![ResourceProblem::NoComponents]);
+    }
+    #[test]
+    fn split() {
+        let resources = split_resources(&feed());
+        fn summary(resource: &Component) -> (usize, Vec<&str>) {
+            let names = resource.components.iter().map(|c| c.name.as_str()).collect();
+            (resource.properties.len(), names)
+        }
+        let summaries: Vec<_> = resources.iter().map(summary).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                (1, vec!["VTIMEZONE", "VEVENT", "VEVENT"]),
+                (1, vec!["VTODO"]),
+                (1, vec!["VEVENT"]),
+            ]
+        );
+        assert_eq!(check_resource(&resources[0]), vec![]);
+        assert_eq!(check_resource(&resources[1]), vec![]);
+        assert_eq!(
+            check_resource(&resources[2]),
+            vec![
+                ResourceProblem::NoUid("VEVENT".to_string()),
+                ResourceProblem::MissingTimezone("America/New_York".to_string())
+            ]
+        );
+    }
+    #[test]
+    fn duplicate_uids() {
+        let event = |uid: &str, stamp: &str, summary: &str| {
+            parse(&[
+                "BEGIN:VCALENDAR",
+                "BEGIN:VEVENT",
+                &format!("UID:{uid}"),
+                &format!("DTSTAMP:{stamp}"),
+                &format!("SUMMARY:{summary}"),
+                "END:VEVENT",
+                "END:VCALENDAR",
+            ])
+        };
+        let calendars = [
+            event("a", "20250101T000000Z", "Lunch"),
+            event("b", "20250101T000000Z", "Standup"),
+            // The same as the first, written later
+            event("a", "20250102T000000Z", "Lunch"),
+            event("a", "20250101T000000Z", "Dinner"),
+            event("b", "20250101T000000Z", "Standup"),
+            event("standup", "20250101T000000Z", "Standup"),
+            feed(),
+            // The feed's standup and its override, in the other order
+            {
+                let mut resource = split_resources(&feed()).swap_remove(0);
+                resource.components.swap(1, 2);
+                resource
+            },
+        ];
+        let duplicates = find_duplicate_uids(&calendars);
+        assert_eq!(
+            duplicates,
+            vec![
+                DuplicateUid { uid: "a".to_string(), versions: vec![vec![0, 2], vec![3]] },
+                DuplicateUid { uid: "standup".to_string(), versions: vec![vec![5], vec![6, 7]] },
+            ]
+        );
+        assert_eq!(find_duplicate_uids(&calendars[..3]), vec![]);
+    }
+}
@@ -0,0 +1,407 @@
+//! # Configuration
+//! `RcalConfig` gathers the choices a caller makes about parsing — how strict to be, how much
+//! one content line or one expansion may cost, which producer quirks to put up with, and how
+//! to turn local times into instants — in one value that's built once and handed to each
+//! entry point. The presets cover the usual cases:
+//!  * `strict()`, for validators and test suites, which holds input to RFC 5545;
+//!  * `interop()` (the default), for everyday clients, which accepts what real-world
+//!    producers write; and
+//!  * `ingest_untrusted()`, for servers taking calendars from anyone, which is as forgiving
+//!    as `interop()` about quirks but bounds the work any one input can cause.
+use crate::component::{Calendar, Component};
+use crate::error::CalendarError;
+use crate::event::EventTime;
+use crate::expansion::ExpansionLimits;
+use crate::names::Lookup;
+use crate::preparse::PreparseLimits;
+use crate::recurrence::{Instances, RecurrenceSet};
+use crate::rrule::{RRule, parse_rrule_lenient, parse_rrule_with};
+use crate::rrule_error::ModalResult;
+use crate::sanitize::sanitize_cp1252;
+use crate::timezone::TzResolver;
+use crate::unfolded::{BufReadContent, ContentLines};
+use crate::warning::{AtLine, IgnoreWarnings, Warnings};
+use bstr::BString;
+use jiff::Zoned;
+use jiff::civil::Time;
+use jiff::tz::{Disambiguation, TimeZone};
+use std::borrow::Cow;
+use std::io;
+
+/// How closely values must follow RFC 5545
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strictness {
+    /// Reject what RFC 5545 forbids (such as `INTERVAL=0`, or an RRULE part we don't know)
+    Strict,
+    /// Accept it where the intent is clear, with a warning
+    Lenient,
+}
+
+/// Which producer quirks to put up with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quirks {
+    /// None: a content line ending with a bare line feed rather than CRLF is an error
+    Rfc,
+    /// The common ones, such as bare line feeds, and Windows-1252 smart quotes in UTF-8 text
+    Interop,
+}
+
+/// How to resolve a local time that a DST transition skips (a gap) or repeats (a fold)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DstPolicy {
+    /// In a gap, the time as many hours later as the gap is long; in a fold, the earlier
+    /// time — as RFC 5545 says
+    Compatible,
+    /// The time before a gap, or the earlier time in a fold
+    Earlier,
+    /// The time after a gap, or the later time in a fold
+    Later,
+    /// Neither: it's an error
+    Reject,
+}
+impl DstPolicy {
+    /// The matching jiff disambiguation
+    #[must_use]
+    pub fn disambiguation(self) -> Disambiguation {
+        match self {
+            DstPolicy::Compatible => Disambiguation::Compatible,
+            DstPolicy::Earlier => Disambiguation::Earlier,
+            DstPolicy::Later => Disambiguation::Later,
+            DstPolicy::Reject => Disambiguation::Reject,
+        }
+    }
+}
+
+/// How to treat a floating date-time (one with neither `TZID` nor a `Z` suffix) when an
+/// instant is needed
+#[derive(Clone, Debug, PartialEq)]
+pub enum FloatingPolicy {
+    /// As UTC, which keeps its civil date-time unchanged (as `RecurrenceSet` does)
+    Utc,
+    /// In the given time zone — typically the user's own
+    Zone(TimeZone),
+}
+
+/// The settings every entry point takes; see the module docs for the presets
+#[derive(Clone, Debug, PartialEq)]
+pub struct RcalConfig {
+    strictness: Strictness,
+    preparse_limits: PreparseLimits,
+    expansion_limits: ExpansionLimits,
+    quirks: Quirks,
+    dst: DstPolicy,
+    floating: FloatingPolicy,
+}
+
+impl RcalConfig {
+    /// Hold input to RFC 5545: strict values and CRLF line endings, and a local time that
+    /// doesn't exist or happens twice is an error rather than a guess
+    #[must_use]
+    pub fn strict() -> Self {
+        RcalConfig {
+            strictness: Strictness::Strict,
+            quirks: Quirks::Rfc,
+            dst: DstPolicy::Reject,
+            ..RcalConfig::interop()
+        }
+    }
+    /// Accept what real-world producers write, resolving local times as jiff's `compatible`
+    /// disambiguation (and RFC 5545) do
+    #[must_use]
+    pub fn interop() -> Self {
+        RcalConfig {
+            strictness: Strictness::Lenient,
+            preparse_limits: PreparseLimits::default(),
+            expansion_limits: ExpansionLimits::batch(),
+            quirks: Quirks::Interop,
+            dst: DstPolicy::Compatible,
+            floating: FloatingPolicy::Utc,
+        }
+    }
+    /// Like `interop`, but with tight limits, for input from anyone: a few dozen parameters
+    /// and values per content line, and interactive expansion limits
+    #[must_use]
+    pub fn ingest_untrusted() -> Self {
+        RcalConfig {
+            preparse_limits: PreparseLimits { max_parameters: 32, max_values: 64 },
+            expansion_limits: ExpansionLimits::interactive(),
+            ..RcalConfig::interop()
+        }
+    }
+
+    #[must_use]
+    pub fn with_strictness(self, strictness: Strictness) -> Self {
+        RcalConfig { strictness, ..self }
+    }
+    #[must_use]
+    pub fn with_preparse_limits(self, preparse_limits: PreparseLimits) -> Self {
+        RcalConfig { preparse_limits, ..self }
+    }
+    #[must_use]
+    pub fn with_expansion_limits(self, expansion_limits: ExpansionLimits) -> Self {
+        RcalConfig { expansion_limits, ..self }
+    }
+    #[must_use]
+    pub fn with_quirks(self, quirks: Quirks) -> Self {
+        RcalConfig { quirks, ..self }
+    }
+    #[must_use]
+    pub fn with_dst(self, dst: DstPolicy) -> Self {
+        RcalConfig { dst, ..self }
+    }
+    #[must_use]
+    pub fn with_floating(self, floating: FloatingPolicy) -> Self {
+        RcalConfig { floating, ..self }
+    }
+
+    #[must_use]
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+    #[must_use]
+    pub fn preparse_limits(&self) -> &PreparseLimits {
+        &self.preparse_limits
+    }
+    #[must_use]
+    pub fn expansion_limits(&self) -> &ExpansionLimits {
+        &self.expansion_limits
+    }
+    #[must_use]
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+    #[must_use]
+    pub fn dst(&self) -> DstPolicy {
+        self.dst
+    }
+    #[must_use]
+    pub fn floating(&self) -> &FloatingPolicy {
+        &self.floating
+    }
+
+    /// The content lines of `r`, requiring CRLF line endings unless the quirks allow bare
+    /// line feeds
+    pub fn content_lines<R: io::BufRead>(&self, r: R) -> ContentLines<R> {
+        match self.quirks {
+            Quirks::Rfc => r.content_lines().strict_crlf(),
+            Quirks::Interop => r.content_lines(),
+        }
+    }
+    /// The first `VCALENDAR` in `r`, each content line preparsed within the limits, with its
+    /// events and time zones typed as the strictness says (see `Calendar::from_component`).
+    /// Use `content_lines(r).calendars()` to read every calendar in a stream.
+    ///
+    /// # Errors
+    /// Returns an error if reading fails, the input has no `VCALENDAR`, the calendar can't be
+    /// parsed (see `Component::parse_with_limits`), or, when strict, one of its events or
+    /// time zones can't be read.
+    pub fn parse_calendar<R: io::BufRead>(&self, r: R) -> Result<Calendar, CalendarError> {
+        self.parse_calendar_with(r, &mut IgnoreWarnings)
+    }
+    /// Like `parse_calendar`, reporting warnings, each with the input line it's on: about a
+    /// `DTSTART` that doesn't match its `RRULE` (see `Calendar::from_component_with`) and,
+    /// when the quirks allow, about Windows-1252 characters in otherwise UTF-8 lines, which
+    /// are replaced first (see `sanitize_cp1252`).
+    ///
+    /// # Errors
+    /// As `parse_calendar`.
+    pub fn parse_calendar_with<R: io::BufRead>(
+        &self,
+        r: R,
+        warnings: &mut dyn Warnings,
+    ) -> Result<Calendar, CalendarError> {
+        let lines = self.content_lines(r).calendars().next().ok_or(CalendarError::NoCalendar)??;
+        let lines = lines.into_iter().map(|(n, line)| match self.quirks {
+            Quirks::Rfc => (n, line),
+            Quirks::Interop => {
+                let sanitized = match sanitize_cp1252(&line, &mut AtLine { line: n, warnings }) {
+                    Cow::Owned(sanitized) => Some(sanitized),
+                    Cow::Borrowed(_) => None,
+                };
+                (n, sanitized.map_or(line, BString::from))
+            }
+        });
+        let component =
+            Component::parse_with_limits(lines, &mut Lookup::new(), &self.preparse_limits)?;
+        Calendar::from_component_with(component, self, warnings)
+    }
+    /// Preparse a content line within the configured limits
+    #[cfg(feature = "cautious")]
+    pub fn preparse<'a>(
+        &self,
+        line: &'a [u8],
+    ) -> Result<crate::preparse::Prop<'a>, crate::error::PreparseError> {
+        crate::preparse::cautious_preparse_with_limits(line, &self.preparse_limits)
+    }
+    /// A `TZID` resolver that reads a local time a DST transition skips or repeats as the
+    /// DST policy says
+    #[must_use]
+    pub fn resolver(&self) -> TzResolver {
+        TzResolver::new().disambiguation(self.dst.disambiguation())
+    }
+    /// The recurrence set starting at `dtstart`. A floating `dtstart` is put in the zone the
+    /// floating policy gives, if any (see `resolve`), and so are its instances.
+    ///
+    /// # Errors
+    /// As `resolve`.
+    pub fn recurrence_set(&self, dtstart: &EventTime) -> Result<RecurrenceSet, jiff::Error> {
+        match (dtstart, &self.floating) {
+            (EventTime::Floating(_), FloatingPolicy::Zone(_)) => {
+                Ok(RecurrenceSet::new(&EventTime::Zoned(self.resolve(dtstart)?)))
+            }
+            _ => Ok(RecurrenceSet::new(dtstart)),
+        }
+    }
+    /// The instances of `set`, within the expansion limits
+    #[must_use]
+    pub fn expand(&self, set: &RecurrenceSet) -> Instances {
+        set.iter().with_limits(self.expansion_limits)
+    }
+    /// Parse an RRULE value with `parse_rrule_with` or, when lenient, `parse_rrule_lenient`
+    pub fn parse_rrule(
+        &self,
+        input: &mut &[u8],
+        warnings: &mut dyn Warnings,
+    ) -> ModalResult<RRule> {
+        match self.strictness {
+            Strictness::Strict => parse_rrule_with(input, warnings),
+            Strictness::Lenient => parse_rrule_lenient(input, warnings),
+        }
+    }
+    /// The instant `time` starts: a floating time as the floating policy says, and a local
+    /// time a DST transition skips or repeats as the DST policy says. A date starts at
+    /// midnight, floating.
+    ///
+    /// # Errors
+    /// Returns an error when the DST policy is `DstPolicy::Reject` and the local time
+    /// is ambiguous, or the time is out of jiff's range.
+    pub fn resolve(&self, time: &EventTime) -> Result<Zoned, jiff::Error> {
+        let dt = match time {
+            EventTime::Zoned(zoned) => return Ok(zoned.clone()),
+            EventTime::Date(date) => date.to_datetime(Time::midnight()),
+            EventTime::Floating(dt) => *dt,
+        };
+        let zone = match &self.floating {
+            FloatingPolicy::Utc => TimeZone::UTC,
+            FloatingPolicy::Zone(zone) => zone.clone(),
+        };
+        zone.to_ambiguous_zoned(dt).disambiguate(self.dst.disambiguation())
+    }
+}
+
+impl Default for RcalConfig {
+    fn default() -> Self {
+        RcalConfig::interop()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::expansion::LimitReached;
+    use crate::warning::{Observation, Warning};
+    use jiff::civil::datetime;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn presets() {
+        let (strict, interop) = (RcalConfig::strict(), RcalConfig::interop());
+        let untrusted = RcalConfig::ingest_untrusted();
+        assert_eq!(RcalConfig::default(), interop);
+        assert_eq!((strict.strictness(), strict.quirks()), (Strictness::Strict, Quirks::Rfc));
+        assert_eq!(untrusted.quirks(), Quirks::Interop);
+        assert!(untrusted.preparse_limits().max_values < interop.preparse_limits().max_values);
+        assert_eq!(untrusted.expansion_limits(), &ExpansionLimits::interactive());
+        let tweaked = RcalConfig::strict().with_quirks(Quirks::Interop);
+        assert_eq!((tweaked.strictness(), tweaked.quirks()), (Strictness::Strict, Quirks::Interop));
+    }
+    #[test]
+    fn bare_line_feeds() {
+        let input = b"BEGIN:VCALENDAR\nEND:VCALENDAR\r\n";
+        let lines = |config: RcalConfig| -> Vec<_> { config.content_lines(&input[..]).collect() };
+        let interop = lines(RcalConfig::interop());
+        assert!(matches!(interop[..], [Ok((1, _)), Ok((2, _))]), "{interop:?}");
+        let strict = lines(RcalConfig::strict());
+        assert!(matches!(strict[0], Err(CalendarError::BareLineFeed(1))), "{strict:?}");
+    }
+    #[test]
+    fn parsing_calendars() {
+        let input = b"BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:a\nEND:VEVENT\nEND:VCALENDAR\n";
+        let calendar = RcalConfig::interop().parse_calendar(&input[..]).unwrap();
+        assert_eq!(calendar.component.components[0].value_of("UID"), Some("a"));
+        let strict = RcalConfig::strict().parse_calendar(&input[..]);
+        assert!(matches!(strict, Err(CalendarError::BareLineFeed(1))), "{strict:?}");
+        let empty = RcalConfig::interop().parse_calendar(&b"\r\n"[..]);
+        assert!(matches!(empty, Err(CalendarError::NoCalendar)), "{empty:?}");
+    }
+    #[test]
+    fn windows_1252() {
+        let input =
+            b"BEGIN:VCALENDAR\r\nX-NOTE:\x93hi\x94\r\nX-OTHER:a\r\n  \x96 b\r\nEND:VCALENDAR\r\n";
+        let mut warnings = Vec::new();
+        let calendar = RcalConfig::interop().parse_calendar_with(&input[..], &mut warnings);
+        let calendar = calendar.unwrap().component;
+        assert_eq!(calendar.value_of("X-NOTE"), Some("\u{201C}hi\u{201D}"));
+        assert_eq!(calendar.value_of("X-OTHER"), Some("a \u{2013} b"));
+        let replaced = |line, span| Warning::new(Observation::Cp1252Replaced, span).at_line(line);
+        assert_eq!(warnings, vec![replaced(2, 7..8), replaced(2, 10..11), replaced(3, 10..11)]);
+        assert!(RcalConfig::strict().parse_calendar(&input[..]).is_err());
+    }
+    #[test]
+    fn rrule_strictness() {
+        let parse = |config: RcalConfig| {
+            config.parse_rrule(&mut &b"FREQ=DAILY;INTERVAL=0\r\n"[..], &mut IgnoreWarnings)
+        };
+        assert!(parse(RcalConfig::strict()).is_err());
+        assert_eq!(parse(RcalConfig::interop()).unwrap().interval_or_default().get(), 1);
+    }
+    #[test]
+    #[cfg(feature = "cautious")]
+    fn untrusted_limits() {
+        let line = format!("X;A={}:v", "a,".repeat(100));
+        assert!(RcalConfig::interop().preparse(line.as_bytes()).is_ok());
+        assert!(RcalConfig::ingest_untrusted().preparse(line.as_bytes()).is_err());
+    }
+    #[test]
+    fn resolving_local_times() {
+        let new_york = TimeZone::get("America/New_York").unwrap();
+        let config = RcalConfig::interop().with_floating(FloatingPolicy::Zone(new_york));
+        // 02:30 doesn't exist in New York on 2025-03-09
+        let gap = EventTime::Floating(datetime(2025, 3, 9, 2, 30, 0, 0));
+        assert_eq!(config.resolve(&gap).unwrap().datetime(), datetime(2025, 3, 9, 3, 30, 0, 0));
+        let config = config.with_dst(DstPolicy::Reject);
+        assert!(config.resolve(&gap).is_err());
+        let noon = EventTime::Floating(datetime(2025, 3, 9, 12, 0, 0, 0));
+        assert_eq!(RcalConfig::strict().resolve(&noon).unwrap().time_zone(), &TimeZone::UTC);
+    }
+    #[test]
+    fn expanding() {
+        let new_york = TimeZone::get("America/New_York").unwrap();
+        let limits = ExpansionLimits { max_instances: 3, ..ExpansionLimits::interactive() };
+        let config = (RcalConfig::interop().with_expansion_limits(limits))
+            .with_floating(FloatingPolicy::Zone(new_york.clone()));
+        let start = EventTime::Floating(datetime(2025, 3, 8, 9, 0, 0, 0));
+        let mut set = config.recurrence_set(&start).unwrap();
+        set.add_rrule(
+            config.parse_rrule(&mut &b"FREQ=DAILY\r\n"[..], &mut IgnoreWarnings).unwrap(),
+        );
+        let mut instances = config.expand(&set);
+        let found: Vec<_> = instances.by_ref().collect();
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|zoned| zoned.time_zone() == &new_york));
+        // The clocks go forward on March 9, but the instances stay at 09:00
+        assert!(found.iter().all(|zoned| zoned.hour() == 9));
+        assert_eq!(instances.limit_reached(), Some(LimitReached::Instances));
+        // Without a zone for floating times, the set stays floating (in UTC)
+        let set = RcalConfig::interop().recurrence_set(&start).unwrap();
+        assert_eq!(set.zone(), &TimeZone::UTC);
+    }
+    #[test]
+    fn resolving_tzids() {
+        let resolver = RcalConfig::strict().resolver();
+        let gap = datetime(2025, 3, 9, 2, 30, 0, 0);
+        assert!(resolver.to_zoned("America/New_York", gap).unwrap().is_err());
+        let resolver = RcalConfig::interop().resolver();
+        assert!(resolver.to_zoned("America/New_York", gap).unwrap().is_ok());
+    }
+}
@@ -0,0 +1,202 @@
+//! # Date sets
+//! The date-times of `RDATE` and `EXDATE` properties, as sets. An `EXDATE` excludes an instance
+//! when it names the same *instant*, however it's written — so `EXDATE:20250303T140000Z`
+//! excludes the instance at `DTSTART;TZID=Europe/Paris:20250303T150000`. `DateSet` compares
+//! elements that way, so unions, differences, and deduplication get that right.
+use crate::property::{ConversionError, PropertyValue};
+use crate::timezone::TzResolver;
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+use std::collections::BTreeMap;
+
+/// A set of date-times, compared as instants. Iteration is in time order; when equal
+/// instants are inserted in different time zones, the set keeps the first.
+#[derive(Clone, Debug, Default)]
+pub struct DateSet(BTreeMap<Timestamp, Zoned>);
+
+impl DateSet {
+    #[must_use]
+    pub fn new() -> Self {
+        DateSet::default()
+    }
+    /// The date-times of an `RDATE` or `EXDATE` value. Floating date-times and dates (taken
+    /// as midnight) are in `zone`, which should be the zone of the event's `DTSTART`.
+    ///
+    /// # Errors
+    /// Returns an error if `value` isn't a `DATE` or `DATE-TIME` value, if its `TZID` isn't a
+    /// known time zone, or if a date-time can't be represented in its zone.
+    pub fn from_value(value: &PropertyValue, zone: &TimeZone) -> Result<Self, ConversionError> {
+        DateSet::from_value_with(value, zone, &TzResolver::new())
+    }
+    /// Like `from_value`, with `resolver` finding the zone a `TZID` the value kept names
+    ///
+    /// # Errors
+    /// As `from_value`.
+    pub fn from_value_with(
+        value: &PropertyValue,
+        zone: &TimeZone,
+        resolver: &TzResolver,
+    ) -> Result<Self, ConversionError> {
+        let in_zone = |dt: &jiff::civil::DateTime, zone: &TimeZone| {
+            dt.to_zoned(zone.clone()).map_err(|_| ConversionError::OutOfRange)
+        };
+        let mut set = DateSet::new();
+        match value {
+            PropertyValue::Date(dates) => {
+                for date in dates {
+                    set.insert(in_zone(&date.to_datetime(jiff::civil::Time::midnight()), zone)?);
+                }
+            }
+            PropertyValue::DateTime(values)
+            | PropertyValue::DateTimeList { tzid: None, values } => {
+                for dt in values {
+                    set.insert(in_zone(dt, zone)?);
+                }
+            }
+            PropertyValue::DateTimeUtc(values) => {
+                set.extend(values.iter().map(|ts| ts.to_zoned(TimeZone::UTC)));
+            }
+            PropertyValue::DateTimeZoned(values) => set.extend(values.iter().cloned()),
+            PropertyValue::DateTimeList { tzid: Some(tzid), values } => {
+                for dt in values {
+                    let zoned = resolver.to_zoned(tzid.as_str(), *dt);
+                    let zoned = zoned.ok_or(ConversionError::UnknownTimeZone)?;
+                    set.insert(zoned.map_err(|_| ConversionError::OutOfRange)?);
+                }
+            }
+            other => return Err(other.wrong_type("DATE-TIME")),
+        }
+        Ok(set)
+    }
+
+    /// Add `time`, returning `false` if the set already had that instant
+    pub fn insert(&mut self, time: Zoned) -> bool {
+        match self.0.entry(time.timestamp()) {
+            std::collections::btree_map::Entry::Occupied(_) => false,
+            std::collections::btree_map::Entry::Vacant(entry) => {
+                entry.insert(time);
+                true
+            }
+        }
+    }
+    /// Remove the element at the same instant as `time`, returning whether there was one
+    pub fn remove(&mut self, time: &Zoned) -> bool {
+        self.0.remove(&time.timestamp()).is_some()
+    }
+    #[must_use]
+    pub fn contains(&self, time: &Zoned) -> bool {
+        self.0.contains_key(&time.timestamp())
+    }
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// The elements, in time order
+    pub fn iter(&self) -> impl Iterator<Item = &Zoned> {
+        self.0.values()
+    }
+
+    /// The elements in either set. Where both have an instant, `self`'s element is kept.
+    #[must_use]
+    pub fn union(&self, other: &DateSet) -> DateSet {
+        let mut union = self.clone();
+        union.extend(other.iter().cloned());
+        union
+    }
+    /// The elements of `self` at instants `other` doesn't have — the instances left after
+    /// applying `other` as exceptions
+    #[must_use]
+    pub fn difference(&self, other: &DateSet) -> DateSet {
+        let kept = self.0.iter().filter(|(ts, _)| !other.0.contains_key(ts));
+        DateSet(kept.map(|(ts, time)| (*ts, time.clone())).collect())
+    }
+    /// The elements of `self` at instants `other` also has
+    #[must_use]
+    pub fn intersection(&self, other: &DateSet) -> DateSet {
+        let kept = self.0.iter().filter(|(ts, _)| other.0.contains_key(ts));
+        DateSet(kept.map(|(ts, time)| (*ts, time.clone())).collect())
+    }
+}
+
+/// Sets are equal when they have the same instants, whatever zones they're in
+impl PartialEq for DateSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.keys().eq(other.0.keys())
+    }
+}
+impl Eq for DateSet {}
+
+impl Extend<Zoned> for DateSet {
+    fn extend<I: IntoIterator<Item = Zoned>>(&mut self, times: I) {
+        for time in times {
+            self.insert(time);
+        }
+    }
+}
+impl FromIterator<Zoned> for DateSet {
+    fn from_iter<I: IntoIterator<Item = Zoned>>(times: I) -> Self {
+        let mut set = DateSet::new();
+        set.extend(times);
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameter::Tzid;
+    use crate::property::Values;
+    use jiff::civil::datetime;
+    use pretty_assertions::assert_eq;
+
+    fn at(hour: i8, zone: &str) -> Zoned {
+        datetime(2025, 3, 3, hour, 0, 0, 0).in_tz(zone).unwrap()
+    }
+
+    #[test]
+    fn equivalent_representations_are_deduplicated() {
+        let set: DateSet = [at(14, "UTC"), at(15, "Europe/Paris"), at(9, "America/New_York")]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.iter().next().unwrap().time_zone(), &TimeZone::UTC);
+        assert!(set.contains(&at(15, "Europe/Paris")));
+    }
+    #[test]
+    fn set_algebra() {
+        let rdates: DateSet = [at(9, "UTC"), at(10, "UTC"), at(11, "UTC")].into_iter().collect();
+        let exdates: DateSet = [at(11, "Europe/Paris"), at(12, "UTC")].into_iter().collect();
+        let hours = |set: &DateSet| set.iter().map(|t| t.hour()).collect::<Vec<_>>();
+        assert_eq!(hours(&rdates.difference(&exdates)), vec![9, 11]);
+        assert_eq!(hours(&rdates.intersection(&exdates)), vec![10]);
+        assert_eq!(hours(&rdates.union(&exdates)), vec![9, 10, 11, 12]);
+    }
+    #[test]
+    fn from_value() {
+        let paris = TimeZone::get("Europe/Paris").unwrap();
+        let value = PropertyValue::DateTimeList {
+            tzid: Some(Tzid::from("America/New_York")),
+            values: Values::from_vec(vec![datetime(2025, 3, 3, 9, 0, 0, 0)]).unwrap(),
+        };
+        let exdates = DateSet::from_value(&value, &paris).unwrap();
+        assert!(exdates.contains(&at(14, "UTC")));
+
+        let floating = PropertyValue::from(datetime(2025, 3, 3, 15, 0, 0, 0));
+        assert_eq!(DateSet::from_value(&floating, &paris).unwrap(), exdates);
+
+        let unknown = PropertyValue::DateTimeList {
+            tzid: Some(Tzid::from("Mars/Olympus_Mons")),
+            values: Values::new(datetime(2025, 3, 3, 9, 0, 0, 0)),
+        };
+        assert_eq!(DateSet::from_value(&unknown, &paris), Err(ConversionError::UnknownTimeZone));
+        let resolver = TzResolver::new().fallback(|_| Some(TimeZone::UTC));
+        let olympus = DateSet::from_value_with(&unknown, &paris, &resolver).unwrap();
+        assert!(olympus.contains(&at(9, "UTC")));
+        let text = PropertyValue::from("x".to_string());
+        assert!(DateSet::from_value(&text, &paris).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! # Diagnostics
+//! A uniform, serializable form for everything we report about a calendar — preparse errors
+//! and parsing warnings — so services can pipe validation output into their own tooling. With
+//! the `serde` feature, `Diagnostic` implements `Serialize`.
+use crate::error::{PreparseError, Problem};
+use crate::messages::{English, Messages};
+use crate::warning::Warning;
+use std::ops::Range;
+
+/// How serious a `Diagnostic` is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// The content couldn't be parsed
+    Error,
+    /// The content was parsed, but something about it was odd
+    Warning,
+}
+
+/// One problem or observation, located in the input
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnostic {
+    /// The stable code for the kind of diagnostic (see `catalog`)
+    pub code: &'static str,
+    pub message: String,
+    /// Byte offsets into the (unfolded) content line
+    pub span: Range<usize>,
+    /// The input line the content line starts on, counting from 1
+    pub line: usize,
+    /// The byte offset of the start of `span` in the content line, counting from 1
+    pub column: usize,
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    /// The diagnostic for `err`, found in the content line starting on input line `line`
+    #[must_use]
+    pub fn from_preparse_error(err: &PreparseError, line: usize) -> Self {
+        Self::from_preparse_error_with(err, line, &English)
+    }
+    /// Like `from_preparse_error`, but with the message worded by `messages`
+    #[must_use]
+    pub fn from_preparse_error_with(
+        err: &PreparseError,
+        line: usize,
+        messages: &dyn Messages,
+    ) -> Self {
+        use Problem::*;
+        let start = err.valid_up_to;
+        let len = match err.problem {
+            Utf8Error(_, Some(len)) => usize::from(len),
+            ControlCharacter(_) => 1,
+            _ => 0,
+        };
+        Diagnostic {
+            code: err.code(),
+            message: messages.preparse_error(err),
+            span: start..start + len,
+            line,
+            column: start + 1,
+            severity: Severity::Error,
+        }
+    }
+    /// The diagnostic for `warning`, found in the content line starting on input line `line`
+    #[must_use]
+    pub fn from_warning(warning: &Warning, line: usize) -> Self {
+        Diagnostic {
+            code: warning.observation.code(),
+            message: warning.observation.to_string(),
+            span: warning.span.clone(),
+            line,
+            column: warning.span.start + 1,
+            severity: Severity::Warning,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Segment;
+    use crate::warning::Observation;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn from_preparse_error() {
+        let problem = Problem::ControlCharacter(Segment::PropertyValue);
+        let err = PreparseError { problem, valid_up_to: 8 };
+        let diagnostic = Diagnostic::from_preparse_error(&err, 12);
+        assert_eq!(
+            diagnostic,
+            Diagnostic {
+                code: "RCAL0002",
+                message: "invalid control character in property value at index 8".to_string(),
+                span: 8..9,
+                line: 12,
+                column: 9,
+                severity: Severity::Error,
+            }
+        );
+    }
+    #[test]
+    fn from_warning() {
+        let warning = Warning::new(Observation::NormalizedCase, 5..11);
+        let diagnostic = Diagnostic::from_warning(&warning, 3);
+        assert_eq!((diagnostic.span, diagnostic.column), (5..11, 6));
+        assert_eq!(diagnostic.severity, Severity::Warning);
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_to_json() {
+        let warning = Warning::new(Observation::NormalizedCase, 5..11);
+        let json = serde_json::to_value(Diagnostic::from_warning(&warning, 3)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "RCAL1001",
+                "message": "accepted a name or keyword not in uppercase",
+                "span": { "start": 5, "end": 11 },
+                "line": 3,
+                "column": 6,
+                "severity": "warning",
+            })
+        );
+    }
+}
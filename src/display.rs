@@ -0,0 +1,239 @@
+//! # Display helpers
+//! Shortening property values for logs and fixed-width columns. Truncation never splits a
+//! UTF-8 sequence, a backslash escape like `\n` or `\,`, or a character from the combining
+//! marks, variation selectors, and zero-width joiners that follow it — so an accented letter
+//! or a multi-codepoint emoji is kept or dropped as a whole.
+//!
+//! `display_text` picks the text to show for a property like `DESCRIPTION`, asking an
+//! `AltRepResolver` for the content its `ALTREP` parameter points to.
+use crate::component::Component;
+use crate::names::name_eq;
+use crate::preparse::preparse_all_errors;
+use crate::property::unescape_text;
+use std::borrow::Cow;
+
+/// The marker appended to truncated text
+pub const ELLIPSIS: char = '…';
+
+/// Shorten `text` to at most `max_cols` terminal columns, ending it with `…` if anything was
+/// cut. Wide (East Asian and emoji) characters take two columns.
+#[must_use]
+pub fn truncate_display(text: &str, max_cols: usize) -> Cow<'_, str> {
+    if display_width(text) <= max_cols {
+        return Cow::Borrowed(text);
+    }
+    let Some(room) = max_cols.checked_sub(1) else {
+        return Cow::Borrowed("");
+    };
+    let (mut end, mut cols) = (0, 0);
+    for cluster in Clusters(text) {
+        cols += cluster_width(cluster);
+        if cols > room {
+            break;
+        }
+        end += cluster.len();
+    }
+    Cow::Owned(format!("{}{ELLIPSIS}", &text[..end]))
+}
+
+/// The number of terminal columns `text` takes
+#[must_use]
+pub fn display_width(text: &str) -> usize {
+    Clusters(text).map(cluster_width).sum()
+}
+
+// Splits text into the units truncation keeps or drops whole: a backslash escape, or a
+// character with any zero-width characters (and characters joined by a ZWJ) that follow it
+struct Clusters<'a>(&'a str);
+
+impl<'a> Iterator for Clusters<'a> {
+    type Item = &'a str;
+    fn next(&mut self) -> Option<&'a str> {
+        let mut chars = self.0.char_indices();
+        let (_, first) = chars.next()?;
+        let mut end = self.0.len();
+        let mut joined = first == '\\';
+        for (i, c) in chars {
+            if !(joined || is_extender(c)) {
+                end = i;
+                break;
+            }
+            joined = c == '\u{200D}';
+        }
+        let (cluster, rest) = self.0.split_at(end);
+        self.0 = rest;
+        Some(cluster)
+    }
+}
+
+// A cluster is as wide as its first character; an escape shows as two characters
+fn cluster_width(cluster: &str) -> usize {
+    match cluster.chars().next() {
+        Some('\\') => cluster.chars().count(),
+        Some(c) => char_width(c),
+        None => 0,
+    }
+}
+
+fn is_extender(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'      // combining diacritical marks
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200D}'                 // zero-width joiner
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}'    // variation selectors
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{1F3FB}'..='\u{1F3FF}'  // emoji skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}'  // emoji tag sequences
+    )
+}
+
+fn char_width(c: char) -> usize {
+    match c {
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{303E}'
+        | '\u{3041}'..='\u{33FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F900}'..='\u{1F9FF}'
+        | '\u{20000}'..='\u{3FFFD}' => 2,
+        _ if c.is_control() || is_extender(c) => 0,
+        _ => 1,
+    }
+}
+
+/// Supplies the content an `ALTREP` parameter's URI points to — say, by fetching the HTML
+/// alternative of a `DESCRIPTION`, or finding a `cid:` attachment in a MIME message. The
+/// library never dereferences URIs itself. Closures taking a `&str` URI are resolvers.
+pub trait AltRepResolver {
+    /// The content at `uri`, or `None` if it isn't available
+    fn resolve(&mut self, uri: &str) -> Option<String>;
+}
+impl<F: FnMut(&str) -> Option<String>> AltRepResolver for F {
+    fn resolve(&mut self, uri: &str) -> Option<String> {
+        self(uri)
+    }
+}
+
+/// The text to show for a property, and where it came from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisplayText {
+    /// The content of an `ALTREP` URI, as the resolver supplied it
+    Alternate { uri: String, content: String },
+    /// The property's own value, unescaped
+    Inline(String),
+}
+impl DisplayText {
+    #[must_use]
+    pub fn text(&self) -> &str {
+        match self {
+            DisplayText::Alternate { content, .. } => content,
+            DisplayText::Inline(text) => text,
+        }
+    }
+}
+
+/// The text to show for `component`'s property `name` (like `DESCRIPTION`): the content of
+/// the first `ALTREP` that `resolver` resolves, trying the properties named `name` in order,
+/// and otherwise the first such property's own value. It's `None` if the component has no
+/// such property; properties that don't preparse are skipped.
+#[must_use]
+pub fn display_text(
+    component: &Component,
+    name: &str,
+    mut resolver: Option<&mut dyn AltRepResolver>,
+) -> Option<DisplayText> {
+    let mut inline = None;
+    for property in component.properties.iter().filter(|p| name_eq(p.name(), name)) {
+        let (prop, errors) = preparse_all_errors(property.line.as_bytes());
+        let Some(value) = prop.value.filter(|_| errors.is_empty()) else {
+            continue;
+        };
+        let altrep = (prop.parameters.iter())
+            .find(|param| name_eq(param.name().val(), "ALTREP"))
+            .and_then(|param| param.values().first());
+        if let (Some(uri), Some(resolver)) = (altrep, resolver.as_deref_mut())
+            && let Some(content) = resolver.resolve(uri.val())
+        {
+            return Some(DisplayText::Alternate { uri: uri.val().to_string(), content });
+        }
+        inline.get_or_insert_with(|| DisplayText::Inline(unescape_text(value.val())));
+    }
+    inline
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn altrep_resolution() {
+        use crate::component::Property;
+        use crate::names::Lookup;
+        let mut lookup = Lookup::new();
+        let mut event = Component::new("VEVENT");
+        for line in [
+            "SUMMARY:Lunch",
+            r#"DESCRIPTION;ALTREP="cid:part1@example.com":Soup\, then salad"#,
+            r#"DESCRIPTION;LANGUAGE=fr;ALTREP="https://example.com/fr.html":Soupe"#,
+        ] {
+            let id = lookup.property_id(&line[..line.find([';', ':']).unwrap()]).unwrap();
+            event.properties.push(Property { id, line: line.to_string(), input_line: None });
+        }
+        let inline = DisplayText::Inline("Soup, then salad".to_string());
+        assert_eq!(display_text(&event, "description", None), Some(inline.clone()));
+        let mut asked = Vec::new();
+        let mut web_only = |uri: &str| {
+            asked.push(uri.to_string());
+            uri.starts_with("https:").then(|| "<p>Soupe</p>".to_string())
+        };
+        let resolved = display_text(&event, "DESCRIPTION", Some(&mut web_only)).unwrap();
+        assert_eq!(resolved.text(), "<p>Soupe</p>");
+        assert_eq!(asked, ["cid:part1@example.com", "https://example.com/fr.html"]);
+        let mut nothing = |_: &str| None;
+        assert_eq!(display_text(&event, "DESCRIPTION", Some(&mut nothing)), Some(inline));
+        assert_eq!(display_text(&event, "LOCATION", None), None);
+    }
+    #[test]
+    fn short_text_is_borrowed() {
+        assert!(matches!(truncate_display("Standup", 7), Cow::Borrowed("Standup")));
+        assert_eq!(truncate_display("Standup", 0), "");
+    }
+    #[test]
+    fn ascii() {
+        assert_eq!(truncate_display("Weekly planning", 8), "Weekly …");
+        assert_eq!(truncate_display("Weekly planning", 1), "…");
+    }
+    #[test]
+    fn escapes_are_not_split() {
+        assert_eq!(truncate_display(r"Lunch\, then nap", 8), "Lunch\\,…");
+        assert_eq!(truncate_display(r"Lunch\, then nap", 7), "Lunch…");
+        assert_eq!(display_width(r"a\nb"), 4);
+    }
+    #[test]
+    fn combining_marks_stay_with_their_base() {
+        let text = "Cafe\u{301} meeting";
+        assert_eq!(display_width(text), 12);
+        assert_eq!(truncate_display(text, 5), "Cafe\u{301}…");
+        assert_eq!(truncate_display(text, 4), "Caf…");
+    }
+    #[test]
+    fn wide_characters_and_emoji() {
+        assert_eq!(truncate_display("会議の予定", 6), "会議…");
+        assert_eq!(truncate_display("会議の予定", 5), "会議…");
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let text = format!("{family}{family} picnic");
+        assert_eq!(display_width(&text), 11);
+        assert_eq!(truncate_display(&text, 4), format!("{family}…"));
+        assert_eq!(truncate_display("👍🏽👍🏽 ok", 3), "👍🏽…");
+    }
+}
@@ -1,3 +1,7 @@
+use crate::catalog;
+use crate::event::EventError;
+use crate::timezone::VTimeZoneError;
+use crate::unfolded::Progress;
 use std::fmt;
 use std::io;
 use thiserror::Error;
@@ -9,6 +13,51 @@ pub enum CalendarError {
     AtLine(usize, PreparseError),
     #[error(transparent)]
     Name(#[from] NameError),
+    #[error("Parsing cancelled after {} input lines", .0.lines_read)]
+    Cancelled(Progress),
+    #[error("The content line starting at input line {0} is outside any VCALENDAR")]
+    OutsideCalendar(usize),
+    #[error("The VCALENDAR starting at input line {0} has no END:VCALENDAR")]
+    UnterminatedCalendar(usize),
+    #[error("Input line {0} ends with a bare line feed instead of CRLF")]
+    BareLineFeed(usize),
+    #[error("The content line starting at input line {0} is outside the component")]
+    OutsideComponent(usize),
+    #[error("The END at input line {0} doesn't match the open component")]
+    MismatchedEnd(usize),
+    #[error("The component starting at input line {0} has no END")]
+    UnterminatedComponent(usize),
+    #[error("The {1} property at input line {0} was rejected by the property policy")]
+    PolicyRejected(usize, String),
+    #[error("The input has no VCALENDAR")]
+    NoCalendar,
+    #[error("A VEVENT can't be read: {0}")]
+    Event(EventError),
+    #[error("A VTIMEZONE can't be read: {0}")]
+    TimeZone(VTimeZoneError),
+}
+
+impl CalendarError {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            CalendarError::Io(_) => catalog::IO_ERROR,
+            CalendarError::AtLine(_, err) => err.code(),
+            CalendarError::Name(err) => err.code(),
+            CalendarError::Cancelled(_) => catalog::CANCELLED,
+            CalendarError::OutsideCalendar(_) => catalog::OUTSIDE_CALENDAR,
+            CalendarError::UnterminatedCalendar(_) => catalog::UNTERMINATED_CALENDAR,
+            CalendarError::BareLineFeed(_) => catalog::BARE_LINE_FEED,
+            CalendarError::OutsideComponent(_) => catalog::OUTSIDE_COMPONENT,
+            CalendarError::MismatchedEnd(_) => catalog::MISMATCHED_END,
+            CalendarError::UnterminatedComponent(_) => catalog::UNTERMINATED_COMPONENT,
+            CalendarError::PolicyRejected(..) => catalog::POLICY_REJECTED,
+            CalendarError::NoCalendar => catalog::NO_CALENDAR,
+            CalendarError::Event(err) => err.code(),
+            CalendarError::TimeZone(err) => err.code(),
+        }
+    }
 }
 
 pub type NameResult<T> = Result<T, NameError>;
@@ -16,8 +65,17 @@ pub type NameResult<T> = Result<T, NameError>;
 #[derive(Error, Debug)]
 #[error("{0}")]
 pub struct NameError(pub String);
+impl NameError {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        catalog::BAD_NAME
+    }
+}
 
+/// The part of a content line a problem was found in
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Segment {
     PropertyName,
     PropertyValue,
@@ -33,24 +91,75 @@ impl fmt::Display for Segment {
             ParamName => "parameter name",
             ParamValue => "parameter value",
         };
-        write!(f, "{display}",)
+        write!(f, "{display}")
     }
 }
+/// What's wrong with a content line. Every problem found within a segment of the line carries
+/// that `Segment`. New problems may be added in minor releases, so matches need a wildcard arm.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Problem {
-    Utf8Error(Option<u8>),
-    ControlCharacter,
+    /// An invalid UTF8 sequence, with its length (`None` if the line ends mid-sequence)
+    Utf8Error(Segment, Option<u8>),
+    ControlCharacter(Segment),
     EmptyContentLine,
     DoubleQuote(Segment),
     UnclosedQuote(Segment),
     Empty(Segment),
     Unterminated(Segment),
+    /// More parameters (in `ParamName`) or parameter values (in `ParamValue`) than the
+    /// `PreparseLimits` allow
+    TooMany(Segment),
 }
 #[derive(Clone, Debug, Error, PartialEq)]
 pub struct PreparseError {
     pub(crate) problem: Problem,
     pub(crate) valid_up_to: usize,
 }
+impl Problem {
+    /// The segment of the content line the problem was found in, if any
+    #[must_use]
+    pub fn segment(&self) -> Option<Segment> {
+        use Problem::*;
+        match *self {
+            EmptyContentLine => None,
+            Utf8Error(segment, _)
+            | ControlCharacter(segment)
+            | DoubleQuote(segment)
+            | UnclosedQuote(segment)
+            | Empty(segment)
+            | Unterminated(segment)
+            | TooMany(segment) => Some(segment),
+        }
+    }
+    /// The problem's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        use Problem::*;
+        match self {
+            Utf8Error(..) => catalog::UTF8_ERROR,
+            ControlCharacter(_) => catalog::CONTROL_CHARACTER,
+            EmptyContentLine => catalog::EMPTY_CONTENT_LINE,
+            DoubleQuote(_) => catalog::DOUBLE_QUOTE,
+            UnclosedQuote(_) => catalog::UNCLOSED_QUOTE,
+            Empty(_) => catalog::EMPTY_SEGMENT,
+            Unterminated(_) => catalog::UNTERMINATED_SEGMENT,
+            TooMany(_) => catalog::TOO_MANY,
+        }
+    }
+}
+impl PreparseError {
+    /// The segment of the content line the error was found in, if any
+    #[must_use]
+    pub fn segment(&self) -> Option<Segment> {
+        self.problem.segment()
+    }
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        self.problem.code()
+    }
+}
 pub(crate) const EMPTY_CONTENT_LINE: PreparseError =
     PreparseError { problem: Problem::EmptyContentLine, valid_up_to: 0 };
 
@@ -60,17 +169,21 @@ impl fmt::Display for PreparseError {
         use Segment::*;
         let valid_up_to = self.valid_up_to;
         match self.problem {
-            ControlCharacter => {
-                write!(f, "invalid control character at index {valid_up_to}")
+            ControlCharacter(segment) => {
+                write!(f, "invalid control character in {segment} at index {valid_up_to}")
             }
-            Utf8Error(error_len) => {
+            Utf8Error(segment, error_len) => {
                 if let Some(error_len) = error_len {
                     write!(
                         f,
-                        "invalid utf-8 sequence of {error_len} bytes from index {valid_up_to}"
+                        "invalid utf-8 sequence of {error_len} bytes in {segment} from index \
+                        {valid_up_to}"
                     )
                 } else {
-                    write!(f, "incomplete utf-8 byte sequence from index {valid_up_to}")
+                    write!(
+                        f,
+                        "incomplete utf-8 byte sequence in {segment} from index {valid_up_to}"
+                    )
                 }
             }
             EmptyContentLine => write!(f, "content line is empty"),
@@ -114,6 +227,16 @@ impl fmt::Display for PreparseError {
                     have separate error messages"
                 ),
             },
+            TooMany(segment) => match segment {
+                ParamName => {
+                    write!(f, "too many parameters: limit exceeded at index {valid_up_to}")
+                }
+                ParamValue => write!(
+                    f,
+                    "too many values for one parameter: limit exceeded at index {valid_up_to}"
+                ),
+                _ => write!(f, "too many {segment}s: limit exceeded at index {valid_up_to}"),
+            },
         }
     }
 }
@@ -126,8 +249,9 @@ mod test {
         use Problem::*;
         use Segment::*;
         let problems = [
-            Utf8Error(None),
-            ControlCharacter,
+            Utf8Error(PropertyName, None),
+            Utf8Error(ParamValue, Some(2)),
+            ControlCharacter(ParamName),
             EmptyContentLine,
             DoubleQuote(ParamValue),
             UnclosedQuote(ParamValue),
@@ -149,4 +273,14 @@ mod test {
             }
         }
     }
+    #[test]
+    fn test_segment() {
+        use Problem::*;
+        use Segment::*;
+        let err = |problem| PreparseError { problem, valid_up_to: 0 };
+        assert_eq!(err(EmptyContentLine).segment(), None);
+        assert_eq!(err(ControlCharacter(ParamName)).segment(), Some(ParamName));
+        assert_eq!(err(Utf8Error(PropertyValue, None)).segment(), Some(PropertyValue));
+        assert_eq!(err(Unterminated(ParamValue)).segment(), Some(ParamValue));
+    }
 }
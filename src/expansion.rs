@@ -0,0 +1,243 @@
+//! # Expansion
+//! The output of expanding recurring events, and limits on the work expansion does.
+//!
+//! Expanding a recurrence rule can produce an unbounded number of instances (`FREQ=SECONDLY`
+//! with no `COUNT` or `UNTIL`), or grind through millions of candidate date-times to find
+//! very few (`FREQ=SECONDLY;BYMONTH=2;BYMONTHDAY=29`). `ExpansionLimits` bounds all three
+//! costs; the presets are sensible starting points, so callers needn't tune every knob.
+use jiff::civil::DateTime;
+use jiff::{Span, ToSpan, Zoned};
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicU64};
+use thiserror::Error;
+
+/// One occurrence of an event, as `Event::instances` gives them. Instances are ordered by start time,
+/// then by `uid` — so sorting or merging the output of several expansions always gives the
+/// same order — and then by end time and recurrence id. Times are compared as instants, so
+/// the same moment in two time zones compares equal.
+#[derive(Clone, Debug)]
+pub struct Instance {
+    pub uid: String,
+    /// The `RECURRENCE-ID` identifying this instance: where it starts in the recurrence set,
+    /// before any override moves it
+    pub recurrence_id: Zoned,
+    pub start: Zoned,
+    pub end: Zoned,
+}
+impl Ord for Instance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.start.timestamp().cmp(&other.start.timestamp()))
+            .then_with(|| self.uid.cmp(&other.uid))
+            .then_with(|| self.end.timestamp().cmp(&other.end.timestamp()))
+            .then_with(|| self.recurrence_id.timestamp().cmp(&other.recurrence_id.timestamp()))
+    }
+}
+impl PartialOrd for Instance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Instance {}
+
+/// Bounds on a single expansion: pass them to `Occurrences::with_limits` or
+/// `Instances::with_limits`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExpansionLimits {
+    /// The most instances one expansion returns
+    pub max_instances: usize,
+    /// How many years past `DTSTART` an expansion may look
+    pub max_span_years: i16,
+    /// The most candidate date-times one expansion may generate and test against the rule's
+    /// `BYxxx` parts — a proxy for compute time that doesn't depend on the machine
+    pub max_candidates: u64,
+}
+
+/// Which of the `ExpansionLimits` an expansion ran into
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum LimitReached {
+    #[error("Expansion reached the maximum number of instances")]
+    Instances,
+    #[error("Expansion reached the maximum number of years past DTSTART")]
+    Span,
+    #[error("Expansion reached the maximum number of candidate date-times")]
+    Candidates,
+}
+
+impl ExpansionLimits {
+    /// For expansions done while someone waits, like rendering a calendar view: a few
+    /// thousand instances, at most ten years out, and quick to give up on pathological rules.
+    #[must_use]
+    pub const fn interactive() -> Self {
+        ExpansionLimits { max_instances: 5_000, max_span_years: 10, max_candidates: 100_000 }
+    }
+    /// For background work, like indexing or exporting: far more generous, but still
+    /// finite.
+    #[must_use]
+    pub const fn batch() -> Self {
+        ExpansionLimits {
+            max_instances: 1_000_000,
+            max_span_years: 200,
+            max_candidates: 50_000_000,
+        }
+    }
+
+    /// The (exclusive) end of the window an expansion starting at `dtstart` may look in
+    #[must_use]
+    pub fn span_end(&self, dtstart: DateTime) -> DateTime {
+        let span: Span = i64::from(self.max_span_years).years();
+        dtstart.checked_add(span).unwrap_or(DateTime::MAX)
+    }
+
+    /// Check an expansion that started at `dtstart`, has produced `instances` instances,
+    /// has tested `candidates` candidates, and is about to consider `next`.
+    ///
+    /// # Errors
+    /// Returns the first limit (in the order instances, span, candidates) that has been
+    /// reached.
+    pub fn check(
+        &self,
+        dtstart: DateTime,
+        instances: usize,
+        candidates: u64,
+        next: DateTime,
+    ) -> Result<(), LimitReached> {
+        if instances >= self.max_instances {
+            Err(LimitReached::Instances)
+        } else if next >= self.span_end(dtstart) {
+            Err(LimitReached::Span)
+        } else if candidates >= self.max_candidates {
+            Err(LimitReached::Candidates)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for ExpansionLimits {
+    fn default() -> Self {
+        ExpansionLimits::interactive()
+    }
+}
+
+/// How far an expansion has gotten, passed to its `should_continue` callback (see
+/// `Occurrences::should_continue` and `Instances::should_continue`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExpansionProgress {
+    /// The candidate date-times tested so far — for a recurrence set, by all its rules
+    pub candidates: u64,
+    /// The occurrences (or, for a recurrence set, the instances) returned so far
+    pub occurrences: u64,
+}
+
+// A `should_continue` callback, shared by the iterators of one expansion (a recurrence set's
+// and its rules') along with the progress they've made between them
+#[derive(Clone)]
+pub(crate) struct Hook {
+    should_continue: Arc<dyn Fn(&ExpansionProgress) -> bool + Send + Sync>,
+    candidates: Arc<AtomicU64>,
+    occurrences: Arc<AtomicU64>,
+    // Whether this iterator's occurrences are the expansion's (a set's rules' aren't)
+    counts_occurrences: bool,
+}
+
+impl Hook {
+    pub(crate) fn new(
+        should_continue: impl Fn(&ExpansionProgress) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Hook {
+            should_continue: Arc::new(should_continue),
+            candidates: Arc::default(),
+            occurrences: Arc::default(),
+            counts_occurrences: true,
+        }
+    }
+    // The hook for a part of this expansion, whose occurrences aren't the expansion's
+    pub(crate) fn part(&self) -> Self {
+        Hook { counts_occurrences: false, ..self.clone() }
+    }
+    pub(crate) fn add_candidates(&self, candidates: u64) {
+        self.candidates.fetch_add(candidates, atomic::Ordering::Relaxed);
+    }
+    pub(crate) fn add_occurrence(&self) {
+        if self.counts_occurrences {
+            self.occurrences.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+    pub(crate) fn should_continue(&self) -> bool {
+        (self.should_continue)(&ExpansionProgress {
+            candidates: self.candidates.load(atomic::Ordering::Relaxed),
+            occurrences: self.occurrences.load(atomic::Ordering::Relaxed),
+        })
+    }
+}
+
+impl fmt::Debug for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hook")
+            .field("candidates", &self.candidates)
+            .field("occurrences", &self.occurrences)
+            .field("counts_occurrences", &self.counts_occurrences)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use jiff::civil::datetime;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn instances_are_ordered_by_start_then_uid() {
+        let at = |hour, tz: &str| datetime(2025, 3, 3, hour, 0, 0, 0).in_tz(tz).unwrap();
+        let instance = |uid: &str, start: Zoned| Instance {
+            uid: uid.to_string(),
+            recurrence_id: start.clone(),
+            end: start.clone(),
+            start,
+        };
+        let mut instances = [
+            instance("b", at(9, "UTC")),
+            instance("a", at(10, "UTC")),
+            instance("a", at(4, "America/New_York")),
+            instance("c", at(8, "UTC")),
+        ];
+        instances.sort();
+        let uids: Vec<_> = instances.iter().map(|i| i.uid.as_str()).collect();
+        assert_eq!(uids, vec!["c", "a", "b", "a"]);
+        // 04:00 in New York is 09:00 UTC
+        assert_eq!(instance("a", at(4, "America/New_York")), instance("a", at(9, "UTC")));
+    }
+    #[test]
+    fn presets() {
+        let (interactive, batch) = (ExpansionLimits::interactive(), ExpansionLimits::batch());
+        assert_eq!(ExpansionLimits::default(), interactive);
+        assert!(interactive.max_instances < batch.max_instances);
+        assert!(interactive.max_span_years < batch.max_span_years);
+        assert!(interactive.max_candidates < batch.max_candidates);
+    }
+    #[test]
+    fn check() {
+        let limits = ExpansionLimits::interactive();
+        let dtstart = datetime(2024, 2, 29, 9, 0, 0, 0);
+        assert_eq!(limits.span_end(dtstart), datetime(2034, 2, 28, 9, 0, 0, 0));
+        let next = datetime(2030, 1, 1, 0, 0, 0, 0);
+        assert_eq!(limits.check(dtstart, 10, 10, next), Ok(()));
+        assert_eq!(limits.check(dtstart, 5_000, 10, next), Err(LimitReached::Instances));
+        let too_late = datetime(2034, 2, 28, 9, 0, 0, 0);
+        assert_eq!(limits.check(dtstart, 10, 10, too_late), Err(LimitReached::Span));
+        assert_eq!(limits.check(dtstart, 10, 100_000, next), Err(LimitReached::Candidates));
+    }
+    #[test]
+    fn span_end_saturates() {
+        let limits = ExpansionLimits::batch();
+        assert_eq!(limits.span_end(datetime(9900, 1, 1, 0, 0, 0, 0)), DateTime::MAX);
+    }
+}
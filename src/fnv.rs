@@ -0,0 +1,10 @@
+// FNV-1a: a tiny hash whose output never changes between versions or platforms, for places
+// where we need the same input to always produce the same result.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
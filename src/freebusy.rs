@@ -0,0 +1,111 @@
+//! # Free/busy time
+//! Combining several attendees' busy time (from `FREEBUSY` properties, which RFC 5545 requires
+//! to be in UTC) to find windows when everyone is free — the core of "find a meeting time".
+use jiff::{SignedDuration, Timestamp};
+
+/// Busy periods, as half-open `[start, end)` intervals, kept sorted and non-overlapping
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FreeBusy {
+    busy: Vec<(Timestamp, Timestamp)>,
+}
+
+impl FreeBusy {
+    /// Busy time made up of `periods`, which may overlap and be in any order. Empty periods
+    /// are ignored.
+    #[must_use]
+    pub fn new(periods: impl IntoIterator<Item = (Timestamp, Timestamp)>) -> Self {
+        let mut periods: Vec<_> = periods.into_iter().filter(|(start, end)| start < end).collect();
+        periods.sort_unstable();
+        let mut busy: Vec<(Timestamp, Timestamp)> = Vec::with_capacity(periods.len());
+        for (start, end) in periods {
+            match busy.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => busy.push((start, end)),
+            }
+        }
+        FreeBusy { busy }
+    }
+    /// The busy periods, sorted, with overlapping and adjacent periods merged
+    #[must_use]
+    pub fn busy(&self) -> &[(Timestamp, Timestamp)] {
+        &self.busy
+    }
+
+    /// The combined busy time of everyone in `all`: busy whenever anyone is busy
+    #[must_use]
+    pub fn merge_all<'a>(all: impl IntoIterator<Item = &'a FreeBusy>) -> Self {
+        FreeBusy::new(all.into_iter().flat_map(|fb| fb.busy.iter().copied()))
+    }
+
+    /// The free windows within `[start, end)` lasting at least `min_duration`, in order
+    #[must_use]
+    pub fn free_windows(
+        &self,
+        (start, end): (Timestamp, Timestamp),
+        min_duration: SignedDuration,
+    ) -> Vec<(Timestamp, Timestamp)> {
+        let mut windows = Vec::new();
+        let mut free_from = start;
+        let busy_until_end = self.busy.iter().map(|(s, e)| (*s, *e)).chain([(end, end)]);
+        for (busy_start, busy_end) in busy_until_end {
+            let free_until = busy_start.min(end);
+            if free_until > free_from && free_until.duration_since(free_from) >= min_duration {
+                windows.push((free_from, free_until));
+            }
+            free_from = free_from.max(busy_end);
+            if free_from >= end {
+                break;
+            }
+        }
+        windows
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn at(hour: i64, minute: i64) -> Timestamp {
+        Timestamp::from_second(1_741_000_000 / 86_400 * 86_400 + hour * 3600 + minute * 60).unwrap()
+    }
+    #[test]
+    fn new_merges_and_sorts() {
+        let fb =
+            FreeBusy::new([(at(13, 0), at(14, 0)), (at(9, 0), at(10, 0)), (at(9, 30), at(11, 0))]);
+        assert_eq!(fb.busy(), [(at(9, 0), at(11, 0)), (at(13, 0), at(14, 0))]);
+        assert_eq!(FreeBusy::new([(at(9, 0), at(9, 0))]), FreeBusy::default());
+    }
+    #[test]
+    fn common_free_windows() {
+        let alice = FreeBusy::new([(at(9, 0), at(10, 0)), (at(14, 0), at(15, 0))]);
+        let bob = FreeBusy::new([(at(10, 0), at(10, 30)), (at(12, 0), at(13, 0))]);
+        let carol = FreeBusy::new([(at(16, 30), at(18, 0))]);
+        let everyone = FreeBusy::merge_all([&alice, &bob, &carol]);
+        let day = (at(8, 0), at(17, 0));
+
+        assert_eq!(
+            everyone.free_windows(day, SignedDuration::from_mins(60)),
+            vec![
+                (at(8, 0), at(9, 0)),
+                (at(10, 30), at(12, 0)),
+                (at(13, 0), at(14, 0)),
+                (at(15, 0), at(16, 30))
+            ]
+        );
+        assert_eq!(
+            everyone.free_windows(day, SignedDuration::from_mins(90)),
+            vec![(at(10, 30), at(12, 0)), (at(15, 0), at(16, 30))]
+        );
+        assert_eq!(everyone.free_windows(day, SignedDuration::from_mins(91)), vec![]);
+    }
+    #[test]
+    fn busy_time_outside_the_window() {
+        let fb = FreeBusy::new([(at(6, 0), at(9, 0)), (at(16, 0), at(20, 0))]);
+        assert_eq!(
+            fb.free_windows((at(8, 0), at(17, 0)), SignedDuration::ZERO),
+            vec![(at(9, 0), at(16, 0))]
+        );
+        assert_eq!(fb.free_windows((at(7, 0), at(8, 0)), SignedDuration::ZERO), vec![]);
+    }
+}
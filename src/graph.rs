@@ -0,0 +1,304 @@
+//! # Relationship graphs
+//! The components of a calendar that have a `UID`, as the nodes of a graph whose edges are
+//! their `RELATED-TO` properties
+//! ([RFC 5545 § 3.8.4.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.5)) and
+//! their `LINK` properties with `VALUE=UID`
+//! ([RFC 9253 § 8.2](https://datatracker.ietf.org/doc/html/rfc9253#section-8.2)) — for
+//! consumers, like project-management tools, that treat `VTODO`s as a tree.
+//!
+//! Edges point from parent to child, whichever end the property was written on: a
+//! `RELATED-TO;RELTYPE=PARENT` names the component's parent, and `RELTYPE=CHILD` its child.
+//! Only those parent/child edges take part in cycle detection and topological ordering.
+use crate::component::Component;
+use crate::names::name_eq;
+use crate::preparse::{PartialProp, preparse_all_errors};
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+/// What an edge says about its two components
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Relation {
+    /// The edge goes from parent to child
+    Parent,
+    /// The two components are siblings (the edge goes from the one with the property)
+    Sibling,
+    /// A `RELTYPE` other than `PARENT`, `CHILD` or `SIBLING` (in uppercase), such as RFC
+    /// 9253's `DEPENDS-ON`
+    Other(String),
+    /// A `LINK` property, with its `LINKREL`
+    Link(String),
+}
+
+/// An edge between the nodes with indexes `from` and `to`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub relation: Relation,
+}
+
+/// A set of parent/child edges that loop back on themselves: the node indexes around the
+/// loop, in order, starting with the smallest
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("The parent/child relationships form a cycle")]
+pub struct Cycle(pub Vec<usize>);
+
+/// The graph of a calendar's components; see the module docs
+#[derive(Clone, Debug)]
+pub struct RelationGraph<'c> {
+    nodes: Vec<(&'c Component, String)>,
+    index: FxHashMap<String, usize>,
+    edges: Vec<Edge>,
+    // Each node's children and parents, without duplicates, in the order of their edges
+    children: Vec<Vec<usize>>,
+    parents: Vec<Vec<usize>>,
+    // Relationships whose other end isn't in the calendar: the node, and the UID it names
+    unresolved: Vec<(usize, String)>,
+}
+
+impl<'c> RelationGraph<'c> {
+    /// The graph of `calendar` and its subcomponents, at any depth. When two components have
+    /// the same `UID` (say, a recurring event and its overrides), the first is the node.
+    #[must_use]
+    pub fn new(calendar: &'c Component) -> Self {
+        let mut graph = RelationGraph {
+            nodes: Vec::new(),
+            index: FxHashMap::default(),
+            edges: Vec::new(),
+            children: Vec::new(),
+            parents: Vec::new(),
+            unresolved: Vec::new(),
+        };
+        graph.add_nodes(calendar);
+        graph.children = vec![Vec::new(); graph.len()];
+        graph.parents = vec![Vec::new(); graph.len()];
+        for from in 0..graph.nodes.len() {
+            for line in &graph.nodes[from].0.properties {
+                let (prop, errors) = preparse_all_errors(line.line.as_bytes());
+                if errors.is_empty()
+                    && let Some((uid, relation, forward)) = relation(&prop)
+                {
+                    match graph.index.get(uid) {
+                        Some(&to) if forward => graph.add_edge(Edge { from, to, relation }),
+                        Some(&to) => graph.add_edge(Edge { from: to, to: from, relation }),
+                        None => graph.unresolved.push((from, uid.to_string())),
+                    }
+                }
+            }
+        }
+        graph
+    }
+    fn add_edge(&mut self, edge: Edge) {
+        if edge.relation == Relation::Parent && !self.children[edge.from].contains(&edge.to) {
+            self.children[edge.from].push(edge.to);
+            self.parents[edge.to].push(edge.from);
+        }
+        self.edges.push(edge);
+    }
+    fn add_nodes(&mut self, component: &'c Component) {
+        if let Some(uid) = component.value_of("UID")
+            && !self.index.contains_key(uid)
+        {
+            self.index.insert(uid.to_string(), self.nodes.len());
+            self.nodes.push((component, uid.to_string()));
+        }
+        for child in &component.components {
+            self.add_nodes(child);
+        }
+    }
+
+    /// The number of nodes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// The component of node `n`
+    #[must_use]
+    pub fn component(&self, n: usize) -> &'c Component {
+        self.nodes[n].0
+    }
+    /// The `UID` of node `n`
+    #[must_use]
+    pub fn uid(&self, n: usize) -> &str {
+        &self.nodes[n].1
+    }
+    /// The node whose component has `UID` `uid`
+    #[must_use]
+    pub fn node(&self, uid: &str) -> Option<usize> {
+        self.index.get(uid).copied()
+    }
+    /// Every edge, in the order the properties appear
+    #[must_use]
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+    /// The relationships naming a `UID` that no component in the calendar has
+    #[must_use]
+    pub fn unresolved(&self) -> &[(usize, String)] {
+        &self.unresolved
+    }
+    /// The parents of node `n`, without duplicates
+    #[must_use]
+    pub fn parents(&self, n: usize) -> &[usize] {
+        &self.parents[n]
+    }
+    /// The children of node `n`, without duplicates
+    #[must_use]
+    pub fn children(&self, n: usize) -> &[usize] {
+        &self.children[n]
+    }
+
+    /// A cycle of parent/child edges, if there is one
+    #[must_use]
+    pub fn find_cycle(&self) -> Option<Cycle> {
+        // Depth-first search, coloring nodes unvisited (0), on the current path (1), or done
+        let children = &self.children;
+        let mut color = vec![0u8; self.len()];
+        for root in 0..self.len() {
+            if color[root] != 0 {
+                continue;
+            }
+            // The current path, each node with the index of the next child to visit
+            let mut path = vec![(root, 0)];
+            color[root] = 1;
+            while let Some((n, next)) = path.last_mut() {
+                let Some(&child) = children[*n].get(*next) else {
+                    color[*n] = 2;
+                    path.pop();
+                    continue;
+                };
+                *next += 1;
+                match color[child] {
+                    0 => {
+                        color[child] = 1;
+                        path.push((child, 0));
+                    }
+                    1 => {
+                        let start = path.iter().position(|(n, _)| *n == child).unwrap_or(0);
+                        let mut cycle: Vec<_> = path[start..].iter().map(|(n, _)| *n).collect();
+                        let smallest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+                        cycle.rotate_left(smallest);
+                        return Some(Cycle(cycle));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+    /// Every node, with each parent before its children, and otherwise earlier nodes first.
+    ///
+    /// # Errors
+    /// Returns a `Cycle` if there is no such order.
+    pub fn topological_order(&self) -> Result<Vec<usize>, Cycle> {
+        let children = &self.children;
+        let mut parents: Vec<_> = self.parents.iter().map(Vec::len).collect();
+        let mut order = Vec::with_capacity(self.len());
+        let mut ready: std::collections::BTreeSet<_> =
+            (0..self.len()).filter(|&n| parents[n] == 0).collect();
+        while let Some(n) = ready.pop_first() {
+            order.push(n);
+            for &child in &children[n] {
+                parents[child] -= 1;
+                if parents[child] == 0 {
+                    ready.insert(child);
+                }
+            }
+        }
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            Err(self.find_cycle().unwrap_or(Cycle(Vec::new())))
+        }
+    }
+}
+
+// The UID a property relates its component to, the relation, and whether the edge goes from
+// the component to that UID (`false` for `RELTYPE=PARENT`, which names the parent)
+fn relation<'a>(prop: &PartialProp<'a>) -> Option<(&'a str, Relation, bool)> {
+    let name = prop.name.as_ref()?.val();
+    let uid = prop.value.as_ref()?.val();
+    let parameter = |wanted: &str| {
+        (prop.parameters.iter())
+            .find(|param| name_eq(param.name().val(), wanted))
+            .and_then(|param| param.values().first())
+            .map(|value| value.val().to_ascii_uppercase())
+    };
+    if name_eq(name, "RELATED-TO") {
+        let (relation, forward) = match parameter("RELTYPE").as_deref() {
+            None | Some("PARENT") => (Relation::Parent, false),
+            Some("CHILD") => (Relation::Parent, true),
+            Some("SIBLING") => (Relation::Sibling, true),
+            Some(other) => (Relation::Other(other.to_string()), true),
+        };
+        Some((uid, relation, forward))
+    } else if name_eq(name, "LINK") && parameter("VALUE").as_deref() == Some("UID") {
+        Some((uid, Relation::Link(parameter("LINKREL").unwrap_or_default()), true))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::names::Lookup;
+    use crate::unfolded::BufReadContent;
+    use pretty_assertions::assert_eq;
+
+    fn calendar(todos: &[&str]) -> Component {
+        let todos: Vec<_> = (todos.iter())
+            .map(|todo| format!("BEGIN:VTODO\r\n{}\r\nEND:VTODO\r\n", todo.replace('|', "\r\n")))
+            .collect();
+        let input = format!("BEGIN:VCALENDAR\r\n{}END:VCALENDAR\r\n", todos.concat());
+        let lines = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+        Component::parse(lines, &mut Lookup::new()).unwrap()
+    }
+
+    #[test]
+    fn parents_and_children() {
+        let calendar = calendar(&[
+            "UID:build|RELATED-TO;RELTYPE=CHILD:walls",
+            "UID:walls|RELATED-TO;RELTYPE=SIBLING:roof",
+            "UID:roof|RELATED-TO:build|RELATED-TO;RELTYPE=DEPENDS-ON:walls",
+            "UID:paint|LINK;VALUE=UID;LINKREL=related:walls|RELATED-TO:house",
+        ]);
+        let graph = RelationGraph::new(&calendar);
+        let n = |uid| graph.node(uid).unwrap();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.uid(n("roof")), "roof");
+        assert_eq!(graph.children(n("build")), vec![n("walls"), n("roof")]);
+        assert_eq!(graph.parents(n("roof")), vec![n("build")]);
+        assert!(graph.edges().contains(&Edge {
+            from: n("roof"),
+            to: n("walls"),
+            relation: Relation::Other("DEPENDS-ON".to_string())
+        }));
+        assert!(graph.edges().contains(&Edge {
+            from: n("paint"),
+            to: n("walls"),
+            relation: Relation::Link("RELATED".to_string())
+        }));
+        assert_eq!(graph.unresolved(), &[(n("paint"), "house".to_string())]);
+        assert_eq!(graph.find_cycle(), None);
+        assert_eq!(graph.topological_order(), Ok(vec![0, 1, 2, 3]));
+    }
+    #[test]
+    fn parents_come_first() {
+        let calendar = calendar(&["UID:c|RELATED-TO:b", "UID:b|RELATED-TO:a", "UID:a"]);
+        let graph = RelationGraph::new(&calendar);
+        assert_eq!(graph.topological_order(), Ok(vec![2, 1, 0]));
+    }
+    #[test]
+    fn cycles() {
+        let calendar =
+            calendar(&["UID:x", "UID:a|RELATED-TO:c", "UID:b|RELATED-TO:a", "UID:c|RELATED-TO:b"]);
+        let graph = RelationGraph::new(&calendar);
+        assert_eq!(graph.find_cycle(), Some(Cycle(vec![1, 2, 3])));
+        assert_eq!(graph.topological_order(), Err(Cycle(vec![1, 2, 3])));
+    }
+}
@@ -4,17 +4,46 @@
 )]
 
 pub mod property;
+pub mod recurrence;
+pub mod redact;
 pub mod rrule;
 pub mod rrule_error;
+pub mod sanitize;
+pub mod scheduling;
+pub mod timezone;
+pub mod validate;
 pub use jiff::civil::Weekday;
 pub use property::PropertyValue;
+pub mod analytics;
+mod base64;
+pub mod capabilities;
+pub use capabilities::{Capabilities, capabilities};
+pub mod catalog;
+pub mod component;
+pub use component::parse_calendar;
+pub mod config;
+pub mod dateset;
+pub mod diagnostic;
+pub mod display;
 pub mod error;
+pub use component::event;
+pub mod expansion;
+mod fnv;
+pub mod freebusy;
+#[cfg(feature = "graph")]
+pub mod graph;
+pub mod messages;
 pub(crate) use error::{NameError, NameResult};
 pub mod names;
 pub mod parameter;
+pub mod policy;
+pub mod prelude;
 pub mod preparse;
 #[cfg(feature = "bold")]
-pub use preparse::bold_preparse;
+pub use preparse::{bold_preparse, bold_preparse_str};
 #[cfg(feature = "cautious")]
-pub use preparse::cautious_preparse;
+pub use preparse::{cautious_preparse, cautious_preparse_str};
+pub mod subscription;
 pub mod unfolded;
+pub mod warning;
+pub mod writer;
@@ -0,0 +1,183 @@
+//! # Messages
+//! All the text we show people — error messages and descriptions of recurrence rules — goes
+//! through the `Messages` trait, so apps can ship translations without forking. Every method
+//! has a default English implementation; a translation overrides the methods it needs, and
+//! can use the errors' stable codes (see `catalog`) as keys.
+//!
+//! With the `icu` feature, `locale_wkst` gives the day a locale starts its week on, and
+//! `Localized` writes the dates in descriptions as the locale does, using ICU4X.
+use crate::Weekday;
+use crate::error::PreparseError;
+use crate::rrule::Frequency;
+use crate::rrule_error::RRuleError;
+
+#[cfg(feature = "icu")]
+mod icu;
+#[cfg(feature = "icu")]
+pub use icu::{LocaleDates, Localized, locale_wkst};
+
+/// The wording of errors and descriptions. The defaults are English.
+pub trait Messages {
+    /// A preparse error
+    fn preparse_error(&self, err: &PreparseError) -> String {
+        err.to_string()
+    }
+    /// An RRULE parse error
+    fn rrule_error(&self, err: &RRuleError) -> String {
+        err.context().join("; ")
+    }
+
+    /// How often a rule repeats: "every day", "every 2 weeks"
+    fn every(&self, interval: u32, freq: Frequency) -> String {
+        let unit = match freq {
+            Frequency::Secondly => "second",
+            Frequency::Minutely => "minute",
+            Frequency::Hourly => "hour",
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        };
+        match interval {
+            1 => format!("every {unit}"),
+            n => format!("every {n} {unit}s"),
+        }
+    }
+    /// The name of a day of the week
+    fn weekday(&self, day: Weekday) -> String {
+        format!("{day:?}")
+    }
+    /// A day of the week within the month or year: "the first Monday", "the last Friday"
+    fn nth_weekday(&self, n: i8, day: Weekday) -> String {
+        format!("the {} {}", ordinal(n), self.weekday(day))
+    }
+    /// Monday through Friday, as one of the days a rule repeats on
+    fn weekdays(&self) -> String {
+        "weekdays".to_string()
+    }
+    /// Saturday and Sunday, as one of the days a rule repeats on
+    fn weekend(&self) -> String {
+        "weekends".to_string()
+    }
+    /// The days (from `weekday`, `nth_weekday`, `weekdays` or `weekend`) a rule repeats on:
+    /// "on Monday and Friday"
+    fn on_days(&self, days: &[String]) -> String {
+        format!("on {}", self.list(days))
+    }
+    /// The days of the month a rule repeats on: "on the 1st and last day of the month"
+    fn on_month_days(&self, days: &[i8]) -> String {
+        let days: Vec<_> = days.iter().map(|n| ordinal(*n)).collect();
+        format!("on the {} day of the month", self.list(&days))
+    }
+    /// The name of a month, from 1 (January) to 12 (December)
+    fn month(&self, month: u8) -> String {
+        const MONTHS: [&str; 12] = [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ];
+        match MONTHS.get(usize::from(month).wrapping_sub(1)) {
+            Some(name) => (*name).to_string(),
+            None => format!("month {month}"),
+        }
+    }
+    /// The months (from `month`) a rule repeats in: "in March and September"
+    fn in_months(&self, months: &[String]) -> String {
+        format!("in {}", self.list(months))
+    }
+    /// A `COUNT` limit: "once", "4 times"
+    fn times(&self, count: u32) -> String {
+        match count {
+            1 => "once".to_string(),
+            n => format!("{n} times"),
+        }
+    }
+    /// An `UNTIL` limit, given the `UNTIL` value in ISO 8601 format
+    fn until(&self, until: &str) -> String {
+        format!("until {until}")
+    }
+    /// A list: "A", "A and B", "A, B and C"
+    fn list(&self, items: &[String]) -> String {
+        match items {
+            [] => String::new(),
+            [item] => item.clone(),
+            [init @ .., last] => format!("{} and {last}", init.join(", ")),
+        }
+    }
+    /// A whole description: the phrases saying when the rule repeats, then its limit (from
+    /// `times` or `until`), if any
+    fn description(&self, phrases: &[String], limit: Option<String>) -> String {
+        let phrases = phrases.join(" ");
+        match limit {
+            Some(limit) => format!("{phrases}, {limit}"),
+            None => phrases,
+        }
+    }
+}
+
+/// The default English `Messages`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct English;
+impl Messages for English {}
+
+// "first", "second", ..., "6th", ..., "last", "second to last", ...
+fn ordinal(n: i8) -> String {
+    const WORDS: [&str; 5] = ["first", "second", "third", "fourth", "fifth"];
+    match n {
+        -1 => "last".to_string(),
+        _ if n < 0 => format!("{} to last", ordinal(n.saturating_neg())),
+        1..=5 => WORDS[usize::from(n.unsigned_abs()) - 1].to_string(),
+        _ => {
+            let suffix = match (n % 10, n % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            format!("{n}{suffix}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn ordinals() {
+        let words: Vec<_> = [1, 2, 5, 6, 11, 21, 22, 23, -1, -2].map(ordinal).to_vec();
+        assert_eq!(
+            words,
+            vec![
+                "first",
+                "second",
+                "fifth",
+                "6th",
+                "11th",
+                "21st",
+                "22nd",
+                "23rd",
+                "last",
+                "second to last"
+            ]
+        );
+    }
+    #[test]
+    fn lists() {
+        let items = |items: &[&str]| items.iter().map(|s| (*s).to_string()).collect::<Vec<_>>();
+        assert_eq!(English.list(&items(&[])), "");
+        assert_eq!(English.list(&items(&["A"])), "A");
+        assert_eq!(English.list(&items(&["A", "B", "C"])), "A, B and C");
+    }
+}
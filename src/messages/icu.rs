@@ -0,0 +1,175 @@
+// Locale-aware helpers from ICU4X (feature `icu`), for internationalized UIs: the day a locale
+// starts its week on, as a default `WKST` for the rules its users write, and `Localized`, which
+// writes the dates in descriptions of recurrence rules as a locale does.
+use super::{English, Messages};
+use crate::Weekday;
+use crate::error::PreparseError;
+use crate::rrule::Frequency;
+use crate::rrule_error::RRuleError;
+use icu_calendar::week::WeekInformation;
+use icu_datetime::fieldsets::YMD;
+use icu_datetime::{DateTimeFormatter, DateTimeFormatterLoadError};
+use icu_locale_core::Locale;
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, civil};
+
+/// The day `locale` starts its week on (respecting a `-u-fw-` extension, as in
+/// `en-US-u-fw-mon`), for the `WKST` of rules its users write. That's Monday, RFC 5545's
+/// default, if ICU4X has no data for the locale.
+#[must_use]
+pub fn locale_wkst(locale: &Locale) -> Weekday {
+    use icu_calendar::types::Weekday as Icu;
+    let Ok(week) = WeekInformation::try_new(locale.into()) else {
+        return Weekday::Monday;
+    };
+    match week.first_weekday {
+        Icu::Monday => Weekday::Monday,
+        Icu::Tuesday => Weekday::Tuesday,
+        Icu::Wednesday => Weekday::Wednesday,
+        Icu::Thursday => Weekday::Thursday,
+        Icu::Friday => Weekday::Friday,
+        Icu::Saturday => Weekday::Saturday,
+        Icu::Sunday => Weekday::Sunday,
+    }
+}
+
+/// Writes dates, such as the dates of a rule's occurrences, as a locale does (in its own
+/// calendar system, which needn't be the Gregorian one)
+#[derive(Debug)]
+pub struct LocaleDates {
+    formatter: DateTimeFormatter<YMD>,
+}
+
+impl LocaleDates {
+    /// Dates as `locale` writes them, at medium length ("Mar 3, 2025" in `en-US`)
+    ///
+    /// # Errors
+    /// Returns an error if ICU4X has no date formats for the locale.
+    pub fn try_new(locale: &Locale) -> Result<Self, DateTimeFormatterLoadError> {
+        let formatter = DateTimeFormatter::try_new(locale.into(), YMD::medium())?;
+        Ok(LocaleDates { formatter })
+    }
+    /// `date`, as the locale writes it
+    #[must_use]
+    pub fn format(&self, date: civil::Date) -> String {
+        let month = u8::try_from(date.month()).unwrap_or_default();
+        let day = u8::try_from(date.day()).unwrap_or_default();
+        // Every jiff date is in ICU4X's range
+        match icu_calendar::Date::try_new_iso(i32::from(date.year()), month, day) {
+            Ok(date) => self.formatter.format(&date).to_string(),
+            Err(_) => date.to_string(),
+        }
+    }
+}
+
+/// `Messages` that write a rule's `UNTIL` date as a locale does, and everything else as
+/// `messages` do — say, a translation into the locale's language:
+/// `rule.describe_with(&Localized::new(dates, French))`.
+#[derive(Debug)]
+pub struct Localized<M = English> {
+    dates: LocaleDates,
+    messages: M,
+}
+
+impl<M: Messages> Localized<M> {
+    #[must_use]
+    pub fn new(dates: LocaleDates, messages: M) -> Self {
+        Localized { dates, messages }
+    }
+}
+
+impl<M: Messages> Messages for Localized<M> {
+    fn preparse_error(&self, err: &PreparseError) -> String {
+        self.messages.preparse_error(err)
+    }
+    fn rrule_error(&self, err: &RRuleError) -> String {
+        self.messages.rrule_error(err)
+    }
+    fn every(&self, interval: u32, freq: Frequency) -> String {
+        self.messages.every(interval, freq)
+    }
+    fn weekday(&self, day: Weekday) -> String {
+        self.messages.weekday(day)
+    }
+    fn nth_weekday(&self, n: i8, day: Weekday) -> String {
+        self.messages.nth_weekday(n, day)
+    }
+    fn weekdays(&self) -> String {
+        self.messages.weekdays()
+    }
+    fn weekend(&self) -> String {
+        self.messages.weekend()
+    }
+    fn on_days(&self, days: &[String]) -> String {
+        self.messages.on_days(days)
+    }
+    fn on_month_days(&self, days: &[i8]) -> String {
+        self.messages.on_month_days(days)
+    }
+    fn month(&self, month: u8) -> String {
+        self.messages.month(month)
+    }
+    fn in_months(&self, months: &[String]) -> String {
+        self.messages.in_months(months)
+    }
+    fn times(&self, count: u32) -> String {
+        self.messages.times(count)
+    }
+    /// The `UNTIL` date as the locale writes it (in UTC, for an `UNTIL` in UTC)
+    fn until(&self, until: &str) -> String {
+        let date = (until.parse::<Timestamp>())
+            .map(|instant| instant.to_zoned(TimeZone::UTC).date())
+            .or_else(|_| until.parse::<civil::Date>());
+        match date {
+            Ok(date) => self.messages.until(&self.dates.format(date)),
+            Err(_) => self.messages.until(until),
+        }
+    }
+    fn list(&self, items: &[String]) -> String {
+        self.messages.list(items)
+    }
+    fn description(&self, phrases: &[String], limit: Option<String>) -> String {
+        self.messages.description(phrases, limit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rrule::parse_rrule;
+    use pretty_assertions::assert_eq;
+
+    fn locale(name: &str) -> Locale {
+        Locale::try_from_str(name).unwrap()
+    }
+
+    #[test]
+    fn week_starts() {
+        assert_eq!(locale_wkst(&locale("en-US")), Weekday::Sunday);
+        assert_eq!(locale_wkst(&locale("en-GB")), Weekday::Monday);
+        assert_eq!(locale_wkst(&locale("fr-FR")), Weekday::Monday);
+        assert_eq!(locale_wkst(&locale("ar-EG")), Weekday::Saturday);
+        assert_eq!(locale_wkst(&locale("en-US-u-fw-mon")), Weekday::Monday);
+    }
+    #[test]
+    fn dates() {
+        let date = civil::date(2025, 3, 3);
+        let format = |name: &str| LocaleDates::try_new(&locale(name)).unwrap().format(date);
+        assert_eq!(format("en-US"), "Mar 3, 2025");
+        assert_eq!(format("de-DE"), "03.03.2025");
+        assert_eq!(format("ja-JP"), "2025/03/03");
+    }
+    #[test]
+    fn descriptions() {
+        let describe = |name: &str, rule: &str| {
+            let rule = parse_rrule(&mut rule.as_bytes()).unwrap();
+            let dates = LocaleDates::try_new(&locale(name)).unwrap();
+            rule.describe_with(&Localized::new(dates, English))
+        };
+        let rule = "FREQ=WEEKLY;BYDAY=MO;UNTIL=20250331T235959Z\r\n";
+        assert_eq!(describe("en-US", rule), "every week on Monday, until Mar 31, 2025");
+        assert_eq!(describe("en-GB", rule), "every week on Monday, until 31 Mar 2025");
+        let rule = "FREQ=DAILY;UNTIL=20250401\r\n";
+        assert_eq!(describe("de-DE", rule), "every day, until 01.04.2025");
+    }
+}
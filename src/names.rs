@@ -12,12 +12,19 @@ macro_rules! err {
     };
 }
 /// `Lookup` is a string interner for parameter and property names. It starts out
-#[derive(Default, Debug)]
+/// knowing the RFC names, which are found by a generated `match` without hashing; only other
+/// names go through the interner.
+#[derive(Debug)]
 #[allow(dead_code)] // for now!
 pub struct Lookup {
     parms: NameIds,
     props: NameIds,
 }
+impl Default for Lookup {
+    fn default() -> Self {
+        Lookup::new()
+    }
+}
 impl Lookup {
     #[must_use]
     pub fn new() -> Self {
@@ -32,6 +39,9 @@ impl Lookup {
     }
     #[inline]
     pub fn parameter_id(&mut self, name: &str) -> NameResult<ParameterId> {
+        if let Some(id) = crate::parameter::index_of(name) {
+            return Ok(ParameterId(id));
+        }
         self.parms.id(name).map(ParameterId)
     }
     #[inline]
@@ -41,21 +51,24 @@ impl Lookup {
     }
     #[inline]
     pub fn known_property(&mut self, name: &'static str) -> NameResult<PropertyId> {
-        self.parms.known_id(name).map(PropertyId)
+        self.props.known_id(name).map(PropertyId)
     }
     #[inline]
     pub fn property_id(&mut self, name: &str) -> NameResult<PropertyId> {
-        self.parms.id(name).map(PropertyId)
+        if let Some(id) = crate::property::index_of(name) {
+            return Ok(PropertyId(id));
+        }
+        self.props.id(name).map(PropertyId)
     }
     #[inline]
     #[must_use]
     pub fn property_name(&self, id: PropertyId) -> Option<&Key> {
-        self.parms.name(id.0)
+        self.props.name(id.0)
     }
 }
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ParameterId(pub(crate) usize);
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PropertyId(pub(crate) usize);
 
 type Key = Cow<'static, str>;
@@ -121,6 +134,73 @@ fn well_formed(nym: &str) -> WellFormed {
     ok
 }
 
+/// Whether two names are the same: RFC 5545 names (of properties, parameters, components,
+/// and enumerated values) ignore ASCII case
+#[must_use]
+pub fn name_eq(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+/// `name` in uppercase, copied only if it has lowercase letters
+#[must_use]
+pub fn uppercase(name: &str) -> Cow<'_, str> {
+    if name.bytes().any(|b| b.is_ascii_lowercase()) {
+        Cow::Owned(name.to_ascii_uppercase())
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+pub(crate) fn uppercase_bytes(name: &[u8]) -> Cow<'_, [u8]> {
+    if name.iter().any(u8::is_ascii_lowercase) {
+        Cow::Owned(name.to_ascii_uppercase())
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// A name as written, which compares, orders, and hashes ignoring ASCII case — for maps
+/// keyed by name that keep the case the name came in
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Caseless<S>(pub S);
+impl<S: AsRef<str>> Caseless<S> {
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
+    fn folded(&self) -> impl Iterator<Item = u8> + '_ {
+        self.as_str().bytes().map(|b| b.to_ascii_uppercase())
+    }
+}
+impl<A: AsRef<str>, B: AsRef<str>> PartialEq<Caseless<B>> for Caseless<A> {
+    fn eq(&self, other: &Caseless<B>) -> bool {
+        name_eq(self.as_str(), other.as_str())
+    }
+}
+impl<S: AsRef<str>> Eq for Caseless<S> {}
+impl<S: AsRef<str>> PartialOrd for Caseless<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<S: AsRef<str>> Ord for Caseless<S> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.folded().cmp(other.folded())
+    }
+}
+impl<S: AsRef<str>> std::hash::Hash for Caseless<S> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for b in self.folded() {
+            state.write_u8(b);
+        }
+        state.write_u8(0xff);
+    }
+}
+impl<S: AsRef<str>> std::fmt::Display for Caseless<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -149,6 +229,49 @@ mod test {
         assert_eq!(names.name(id).unwrap(), "FOO");
     }
     #[test]
+    fn caseless_names() {
+        use std::collections::HashSet;
+        assert!(name_eq("Dtstart", "DTSTART"));
+        assert!(matches!(uppercase("DTSTART"), Cow::Borrowed("DTSTART")));
+        assert_eq!(uppercase("x-Wr-calname"), "X-WR-CALNAME");
+        let names: HashSet<_> = ["Summary", "SUMMARY", "summary", "UID"].map(Caseless).into();
+        assert_eq!(names.len(), 2);
+        assert_eq!(Caseless("vEvent"), Caseless(String::from("VEVENT")));
+        assert_eq!(Caseless("vEvent").to_string(), "vEvent");
+        assert!(Caseless("alarm") < Caseless("BEGIN"));
+    }
+    #[test]
+    fn known_names_match_the_interner() {
+        let mut lookup = Lookup::new();
+        for (n, name) in crate::property::NAMES.iter().enumerate() {
+            assert_eq!(crate::property::index_of(name), Some(n));
+            assert_eq!(lookup.props.id(name).unwrap(), n);
+            assert_eq!(lookup.property_id(&name.to_ascii_lowercase()).unwrap().0, n);
+        }
+        for (n, name) in crate::parameter::NAMES.iter().enumerate() {
+            assert_eq!(crate::parameter::index_of(name), Some(n));
+            assert_eq!(lookup.parms.id(name).unwrap(), n);
+            assert_eq!(lookup.parameter_id(&name.to_ascii_lowercase()).unwrap().0, n);
+        }
+        assert_eq!(crate::property::index_of("DTSTARTS"), None);
+        assert_eq!(crate::property::index_of(""), None);
+        assert_eq!(crate::parameter::index_of("X-TZID"), None);
+    }
+    #[test]
+    fn properties_and_parameters_are_interned_apart() {
+        let mut lookup = Lookup::new();
+        let parameter = lookup.parameter_id("X-SHARED").unwrap();
+        let property = lookup.property_id("X-OTHER").unwrap();
+        assert_eq!(lookup.parameter_name(parameter).unwrap(), "X-SHARED");
+        assert_eq!(lookup.property_name(property).unwrap(), "X-OTHER");
+        // Interning a property leaves the parameter names as they were
+        assert_eq!(lookup.parameter_name(ParameterId(property.0)), None);
+        assert_eq!(
+            lookup.known_property("SUMMARY").unwrap(),
+            lookup.property_id("summary").unwrap()
+        );
+    }
+    #[test]
     fn fresh_invalid() {
         let mut names = empty();
         let orig = names.clone();
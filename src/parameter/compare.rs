@@ -0,0 +1,123 @@
+// Equivalence of parameter values, for diffing and tests. RFC 5545 § 3.2 says "Property
+// parameter values that are not in quoted-strings are case-insensitive", and a value means the
+// same thing whether or not it's quoted — so `TZID=America/New_York` and
+// `TZID="America/New_York"` are equivalent, as are `ROLE=x-chair` and `ROLE=X-CHAIR`.
+use super::{CUType, Display, FBType, Feature, ParameterValue, PartStat, RelType, Role};
+use super::{ScheduleAgent, ScheduleForceSend, Value};
+use crate::names::{Caseless, name_eq};
+
+// Strip one pair of surrounding double quotes, if present
+fn unquoted(s: &str) -> &str {
+    match s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => s,
+    }
+}
+fn same_text(a: &str, b: &str) -> bool {
+    unquoted(a) == unquoted(b)
+}
+fn same_keyword(a: &str, b: &str) -> bool {
+    name_eq(unquoted(a), unquoted(b))
+}
+fn same_optional_keyword(a: Option<&String>, b: Option<&String>) -> bool {
+    a.map(|a| Caseless(unquoted(a))) == b.map(|b| Caseless(unquoted(b)))
+}
+
+// For enums whose "unrecognized value" variant remembers the value as written, compare that
+// value case-insensitively; otherwise the variants must match.
+macro_rules! keyword_enum_equivalent {
+    ($a:expr, $b:expr, $typ:ident :: $variant:ident (Option)) => {
+        match ($a, $b) {
+            ($typ::$variant(a), $typ::$variant(b)) => same_optional_keyword(a.as_ref(), b.as_ref()),
+            (a, b) => a == b,
+        }
+    };
+    ($a:expr, $b:expr, $typ:ident :: $variant:ident) => {
+        match ($a, $b) {
+            ($typ::$variant(a), $typ::$variant(b)) => same_keyword(a, b),
+            (a, b) => a == b,
+        }
+    };
+}
+
+impl ParameterValue {
+    /// Are `self` and `other` the same value, ignoring surrounding double quotes on text
+    /// values, and ignoring case in keyword values (like `ROLE` and `PARTSTAT`)?
+    #[must_use]
+    pub fn equivalent(&self, other: &ParameterValue) -> bool {
+        use ParameterValue as P;
+        match (self, other) {
+            (P::FmtType(a), P::FmtType(b)) | (P::Language(a), P::Language(b)) => same_keyword(a, b),
+            (P::ParamText(a), P::ParamText(b))
+            | (P::SentBy(a), P::SentBy(b))
+            | (P::Text(a), P::Text(b))
+            | (P::Uri(a), P::Uri(b)) => same_text(a, b),
+            (P::Tzid(a), P::Tzid(b)) => same_text(a.as_str(), b.as_str()),
+            (P::UriList(a), P::UriList(b)) | (P::ScheduleStatus(a), P::ScheduleStatus(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_text(a, b))
+            }
+            (P::CUType(a), P::CUType(b)) => keyword_enum_equivalent!(a, b, CUType::Unknown(Option)),
+            (P::Display(a), P::Display(b)) => {
+                a.len() == b.len()
+                    && (a.iter().zip(b))
+                        .all(|(a, b)| keyword_enum_equivalent!(a, b, Display::Badge(Option)))
+            }
+            (P::FBType(a), P::FBType(b)) => keyword_enum_equivalent!(a, b, FBType::Busy(Option)),
+            (P::Feature(a), P::Feature(b)) => {
+                a.len() == b.len()
+                    && (a.iter().zip(b))
+                        .all(|(a, b)| keyword_enum_equivalent!(a, b, Feature::Other))
+            }
+            (P::PartStat(a), P::PartStat(b)) => {
+                keyword_enum_equivalent!(a, b, PartStat::NeedsAction(Option))
+            }
+            (P::RelType(a), P::RelType(b)) => {
+                keyword_enum_equivalent!(a, b, RelType::Parent(Option))
+            }
+            (P::Role(a), P::Role(b)) => {
+                keyword_enum_equivalent!(a, b, Role::ReqParticipant(Option))
+            }
+            (P::ScheduleAgent(a), P::ScheduleAgent(b)) => {
+                keyword_enum_equivalent!(a, b, ScheduleAgent::None(Option))
+            }
+            (P::ScheduleForceSend(a), P::ScheduleForceSend(b)) => {
+                keyword_enum_equivalent!(a, b, ScheduleForceSend::Unknown(Option))
+            }
+            (P::Value(a), P::Value(b)) => keyword_enum_equivalent!(a, b, Value::Other),
+            (a, b) => a == b,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(s: &str) -> ParameterValue {
+        ParameterValue::Tzid(s.into())
+    }
+    #[test]
+    fn quotes_are_ignored() {
+        assert!(text("America/New_York").equivalent(&text(r#""America/New_York""#)));
+        assert!(text(r#""America/New_York""#).equivalent(&text("America/New_York")));
+        assert!(!text("America/New_York").equivalent(&text("america/new_york")));
+        assert!(!text(r#""America/New_York"#).equivalent(&text("America/New_York")));
+    }
+    #[test]
+    fn keywords_ignore_case() {
+        let role = |s: &str| ParameterValue::Role(Role::ReqParticipant(Some(s.to_string())));
+        assert!(role("x-Speaker").equivalent(&role("X-SPEAKER")));
+        assert!(!role("X-SPEAKER").equivalent(&role("X-LISTENER")));
+        assert!(!role("X-SPEAKER").equivalent(&ParameterValue::Role(Role::Chair)));
+
+        let partstat = |s: &str| ParameterValue::PartStat(PartStat::NeedsAction(Some(s.into())));
+        assert!(partstat("x-maybe").equivalent(&partstat("\"X-MAYBE\"")));
+        let accepted = ParameterValue::PartStat(PartStat::Accepted);
+        assert!(accepted.equivalent(&accepted.clone()));
+        assert!(!accepted.equivalent(&partstat("ACCEPTED")));
+    }
+    #[test]
+    fn different_variants_differ() {
+        assert!(!text("A").equivalent(&ParameterValue::Text("A".to_string())));
+    }
+}
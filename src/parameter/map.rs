@@ -0,0 +1,73 @@
+// Where `Parameters` keeps the parameters we know, by their index in `NAMES`. Most content
+// lines have a handful of parameters at most, which a `LiteMap` holds in a small sorted `Vec`.
+// With the `dense-parameters` feature, an array with a slot for each parameter holds them
+// instead: lookups index rather than search, and nothing is allocated, but every `Parameters`
+// is much bigger. `benches/parameters.rs` compares the two.
+use super::ParameterValue;
+
+#[cfg(not(feature = "dense-parameters"))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct ParamMap(litemap::LiteMap<usize, ParameterValue>);
+
+#[cfg(not(feature = "dense-parameters"))]
+impl ParamMap {
+    pub(super) fn get(&self, n: usize) -> Option<&ParameterValue> {
+        self.0.get(&n)
+    }
+    pub(super) fn contains_key(&self, n: usize) -> bool {
+        self.0.contains_key(&n)
+    }
+    pub(super) fn insert(&mut self, n: usize, value: ParameterValue) -> Option<ParameterValue> {
+        self.0.insert(n, value)
+    }
+    pub(super) fn remove(&mut self, n: usize) -> Option<ParameterValue> {
+        self.0.remove(&n)
+    }
+    // The parameters that are set, in order by index
+    pub(super) fn iter(&self) -> impl Iterator<Item = (usize, &ParameterValue)> {
+        self.0.iter().map(|(n, value)| (*n, value))
+    }
+    pub(super) fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(feature = "dense-parameters")]
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct ParamMap([Option<ParameterValue>; super::NAMES.len()]);
+
+#[cfg(feature = "dense-parameters")]
+impl Default for ParamMap {
+    fn default() -> Self {
+        ParamMap([const { None }; super::NAMES.len()])
+    }
+}
+
+#[cfg(feature = "dense-parameters")]
+impl ParamMap {
+    pub(super) fn get(&self, n: usize) -> Option<&ParameterValue> {
+        self.0[n].as_ref()
+    }
+    pub(super) fn contains_key(&self, n: usize) -> bool {
+        self.0[n].is_some()
+    }
+    pub(super) fn insert(&mut self, n: usize, value: ParameterValue) -> Option<ParameterValue> {
+        self.0[n].replace(value)
+    }
+    pub(super) fn remove(&mut self, n: usize) -> Option<ParameterValue> {
+        self.0[n].take()
+    }
+    // The parameters that are set, in order by index
+    pub(super) fn iter(&self) -> impl Iterator<Item = (usize, &ParameterValue)> {
+        (self.0.iter().enumerate()).filter_map(|(n, value)| Some((n, value.as_ref()?)))
+    }
+    pub(super) fn len(&self) -> usize {
+        self.0.iter().filter(|value| value.is_some()).count()
+    }
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.iter().all(Option::is_none)
+    }
+}
@@ -1,5 +1,55 @@
+mod compare;
+mod map;
 mod parameter_value;
+mod parse;
 mod values;
-pub(crate) use parameter_value::NAMES;
+mod write;
+use crate::names::{ParameterId, PropertyId};
 pub use parameter_value::*;
+pub(crate) use parameter_value::{NAMES, index_of};
+pub use parse::ParamError;
 pub use values::*;
+
+impl ParameterId {
+    /// Whether the parameter may appear on property `property`. Parameters and properties we
+    /// don't know (`X-` names, and IANA names not in `assets`) may go together freely.
+    #[must_use]
+    pub fn applies_to(self, property: PropertyId) -> bool {
+        match (APPLIES_TO.get(self.0), crate::property::NAMES.get(property.0)) {
+            (Some(Some(properties)), Some(name)) => properties.binary_search(name).is_ok(),
+            _ => true,
+        }
+    }
+    /// Whether the parameter may have more than one (comma-separated) value. Parameters we
+    /// don't know may.
+    #[must_use]
+    pub fn is_multi_valued(self) -> bool {
+        MULTI_VALUED.get(self.0).is_none_or(|multi| *multi)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::names::Lookup;
+
+    #[test]
+    fn applicability() {
+        let mut lookup = Lookup::new();
+        let mut param = |name| lookup.parameter_id(name).unwrap();
+        let (partstat, value, x_param) = (param("PARTSTAT"), param("VALUE"), param("X-FOO"));
+        let mut prop = |name| lookup.property_id(name).unwrap();
+        let (attendee, summary, x_prop) = (prop("ATTENDEE"), prop("SUMMARY"), prop("X-BAR"));
+        assert!(partstat.applies_to(attendee));
+        assert!(!partstat.applies_to(summary));
+        assert!(partstat.applies_to(x_prop));
+        assert!(value.applies_to(summary));
+        assert!(x_param.applies_to(summary));
+        // STRUCTURED-DATA can be BINARY, so it can have an ENCODING (RFC 9073 § 6.6)
+        let encoding = lookup.parameter_id("ENCODING").unwrap();
+        assert!(encoding.applies_to(lookup.property_id("STRUCTURED-DATA").unwrap()));
+        assert!(!encoding.applies_to(summary));
+        assert!(!partstat.is_multi_valued());
+        assert!(lookup.parameter_id("MEMBER").unwrap().is_multi_valued());
+        assert!(x_param.is_multi_valued());
+    }
+}
@@ -1,23 +1,31 @@
-use litemap::LiteMap;
+use super::map::ParamMap;
+use super::parse;
+use crate::names::{Caseless, ParameterId};
 use std::num::NonZeroUsize;
 
 #[allow(clippy::wildcard_imports)]
 use super::values::*;
 
 ///FIXME — add docs!
-#[derive(Clone, Debug, Default)]
-pub struct Parameters(LiteMap<usize, ParameterValue>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Parameters(
+    pub(super) ParamMap,
+    // The parameters we don't know (`X-` names, and IANA names not in `assets`), in the
+    // order they were set, with their values as written
+    pub(super) Vec<(Caseless<String>, Vec<String>)>,
+);
 
 ///FIXME — add docs!
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum ParameterValue {
     Boolean(bool),
     CUType(CUType),
-    Display(Display),
+    Display(Vec<Display>),
     Duration(SignedDuration),
-    Encoding(Base64),
+    Encoding(Encoding),
     FBType(FBType),
-    Feature(Feature),
+    Feature(Vec<Feature>),
     FmtType(FmtType),
     Language(Language),
     Order(NonZeroUsize),
@@ -33,7 +41,7 @@ pub enum ParameterValue {
     SentBy(CalAddress),
     Size(u64),
     Text(String),
-    Tzid(String),
+    Tzid(Tzid),
     Uri(UriString),
     UriList(Vec<UriString>),
     Value(Value),
@@ -111,13 +119,225 @@ pub(crate) const NAMES: [&str; 35] = [
     "TZID",
     "VALUE",
 ];
+/// The index in `NAMES` of `name`, if it's there (names are all uppercase)
+pub(crate) fn index_of(name: &str) -> Option<usize> {
+    let candidates: &[usize] = match (name.len(), *name.as_bytes().first()?) {
+        (2, b'C') => &[1],      // CN
+        (3, b'D') => &[6],      // DIR
+        (3, b'G') => &[14],     // GAP
+        (4, b'R') => &[25, 26], // ROLE RSVP
+        (4, b'S') => &[32],     // SIZE
+        (4, b'T') => &[33],     // TZID
+        (5, b'E') => &[8],      // EMAIL
+        (5, b'L') => &[15],     // LABEL
+        (5, b'O') => &[20],     // ORDER
+        (5, b'R') => &[22],     // RANGE
+        (5, b'V') => &[34],     // VALUE
+        (6, b'A') => &[0],      // ALTREP
+        (6, b'C') => &[2],      // CUTYPE
+        (6, b'F') => &[10],     // FBTYPE
+        (6, b'M') => &[19],     // MEMBER
+        (6, b'S') => &[30],     // SCHEMA
+        (7, b'D') => &[5, 7],   // DERIVED DISPLAY
+        (7, b'F') => &[11, 13], // FEATURE FMTTYPE
+        (7, b'L') => &[17],     // LINKREL
+        (7, b'R') => &[23, 24], // RELATED RELTYPE
+        (7, b'S') => &[31],     // SENT-BY
+        (8, b'E') => &[9],      // ENCODING
+        (8, b'F') => &[12],     // FILENAME
+        (8, b'L') => &[16],     // LANGUAGE
+        (8, b'P') => &[21],     // PARTSTAT
+        (10, b'M') => &[18],    // MANAGED-ID
+        (12, b'D') => &[4],     // DELEGATED-TO
+        (14, b'D') => &[3],     // DELEGATED-FROM
+        (14, b'S') => &[27],    // SCHEDULE-AGENT
+        (15, b'S') => &[29],    // SCHEDULE-STATUS
+        (19, b'S') => &[28],    // SCHEDULE-FORCE-SEND
+        _ => return None,
+    };
+    candidates.iter().copied().find(|&n| NAMES[n] == name)
+}
+/// The properties each parameter may appear on, or `None` if any property may have it
+pub(crate) const APPLIES_TO: [Option<&[&str]>; 35] = [
+    Some(&[
+        "COMMENT",
+        "CONTACT",
+        "DESCRIPTION",
+        "LOCATION",
+        "RESOURCES",
+        "STYLED-DESCRIPTION",
+        "SUMMARY",
+    ]), // "ALTREP"
+    Some(&["ATTENDEE", "ORGANIZER"]),              // "CN"
+    Some(&["ATTENDEE"]),                           // "CUTYPE"
+    Some(&["ATTENDEE"]),                           // "DELEGATED-FROM"
+    Some(&["ATTENDEE"]),                           // "DELEGATED-TO"
+    Some(&["STYLED-DESCRIPTION"]),                 // "DERIVED"
+    Some(&["ATTENDEE", "ORGANIZER"]),              // "DIR"
+    Some(&["IMAGE"]),                              // "DISPLAY"
+    Some(&["ATTENDEE", "ORGANIZER"]),              // "EMAIL"
+    Some(&["ATTACH", "IMAGE", "STRUCTURED-DATA"]), // "ENCODING"
+    Some(&["FREEBUSY"]),                           // "FBTYPE"
+    Some(&["CONFERENCE"]),                         // "FEATURE"
+    Some(&["ATTACH"]),                             // "FILENAME"
+    Some(&["ATTACH", "IMAGE", "LINK", "STRUCTURED-DATA", "STYLED-DESCRIPTION"]), // "FMTTYPE"
+    Some(&["LINK", "RELATED-TO"]),                 // "GAP"
+    Some(&["CONFERENCE", "LINK"]),                 // "LABEL"
+    Some(&[
+        "ATTENDEE",
+        "CATEGORIES",
+        "COMMENT",
+        "CONFERENCE",
+        "CONTACT",
+        "DESCRIPTION",
+        "LINK",
+        "LOCATION",
+        "NAME",
+        "ORGANIZER",
+        "REQUEST-STATUS",
+        "RESOURCES",
+        "STYLED-DESCRIPTION",
+        "SUMMARY",
+        "TZNAME",
+    ]), // "LANGUAGE"
+    Some(&["LINK"]),                               // "LINKREL"
+    Some(&["ATTACH"]),                             // "MANAGED-ID"
+    Some(&["ATTENDEE"]),                           // "MEMBER"
+    None,                                          // "ORDER"
+    Some(&["ATTENDEE"]),                           // "PARTSTAT"
+    Some(&["RECURRENCE-ID"]),                      // "RANGE"
+    Some(&["TRIGGER"]),                            // "RELATED"
+    Some(&["RELATED-TO"]),                         // "RELTYPE"
+    Some(&["ATTENDEE"]),                           // "ROLE"
+    Some(&["ATTENDEE"]),                           // "RSVP"
+    Some(&["ATTENDEE", "ORGANIZER"]),              // "SCHEDULE-AGENT"
+    Some(&["ATTENDEE", "ORGANIZER"]),              // "SCHEDULE-FORCE-SEND"
+    Some(&["ATTENDEE", "ORGANIZER"]),              // "SCHEDULE-STATUS"
+    Some(&["STRUCTURED-DATA"]),                    // "SCHEMA"
+    Some(&["ATTENDEE", "ORGANIZER"]),              // "SENT-BY"
+    Some(&["ATTACH"]),                             // "SIZE"
+    Some(&["DTEND", "DTSTART", "DUE", "EXDATE", "RDATE", "RECURRENCE-ID"]), // "TZID"
+    None,                                          // "VALUE"
+];
+/// Whether each parameter may have more than one value
+pub(crate) const MULTI_VALUED: [bool; 35] = [
+    false, // "ALTREP"
+    false, // "CN"
+    false, // "CUTYPE"
+    true,  // "DELEGATED-FROM"
+    true,  // "DELEGATED-TO"
+    false, // "DERIVED"
+    false, // "DIR"
+    true,  // "DISPLAY"
+    false, // "EMAIL"
+    false, // "ENCODING"
+    false, // "FBTYPE"
+    true,  // "FEATURE"
+    false, // "FILENAME"
+    false, // "FMTTYPE"
+    false, // "GAP"
+    false, // "LABEL"
+    false, // "LANGUAGE"
+    false, // "LINKREL"
+    false, // "MANAGED-ID"
+    true,  // "MEMBER"
+    false, // "ORDER"
+    false, // "PARTSTAT"
+    false, // "RANGE"
+    false, // "RELATED"
+    false, // "RELTYPE"
+    false, // "ROLE"
+    false, // "RSVP"
+    false, // "SCHEDULE-AGENT"
+    false, // "SCHEDULE-FORCE-SEND"
+    true,  // "SCHEDULE-STATUS"
+    false, // "SCHEMA"
+    false, // "SENT-BY"
+    false, // "SIZE"
+    false, // "TZID"
+    false, // "VALUE"
+];
+
+/// The value of the parameter at index `n` in `NAMES`, parsed from its `values` as written, or `None` if they aren't valid
+pub(super) fn parse_known(n: usize, values: &[&str]) -> Option<ParameterValue> {
+    Some(match n {
+        DERIVED | RSVP => ParameterValue::Boolean(parse::boolean(values)?),
+        CUTYPE => ParameterValue::CUType(parse::one(values).into()),
+        DISPLAY => ParameterValue::Display(parse::all(values)),
+        GAP => ParameterValue::Duration(parse::duration(values)?),
+        ENCODING => ParameterValue::Encoding(parse::encoding(values)?),
+        FBTYPE => ParameterValue::FBType(parse::one(values).into()),
+        FEATURE => ParameterValue::Feature(parse::all(values)),
+        FMTTYPE => ParameterValue::FmtType(parse::one(values).into()),
+        LANGUAGE => ParameterValue::Language(parse::one(values).into()),
+        ORDER => ParameterValue::Order(parse::order(values)?),
+        FILENAME | MANAGED_ID => ParameterValue::ParamText(parse::one(values).into()),
+        PARTSTAT => ParameterValue::PartStat(parse::one(values).into()),
+        RANGE => ParameterValue::Range(parse::range(values)?),
+        RELTYPE => ParameterValue::RelType(parse::one(values).into()),
+        RELATED => ParameterValue::Related(parse::related(values)?),
+        ROLE => ParameterValue::Role(parse::one(values).into()),
+        SCHEDULE_AGENT => ParameterValue::ScheduleAgent(parse::one(values).into()),
+        SCHEDULE_FORCE_SEND => ParameterValue::ScheduleForceSend(parse::one(values).into()),
+        SCHEDULE_STATUS => ParameterValue::ScheduleStatus(parse::all(values)),
+        SENT_BY => ParameterValue::SentBy(parse::one(values).into()),
+        SIZE => ParameterValue::Size(parse::size(values)?),
+        CN | EMAIL | LABEL => ParameterValue::Text(parse::one(values).into()),
+        TZID => ParameterValue::Tzid(parse::one(values).into()),
+        ALTREP | DIR | LINKREL | SCHEMA => ParameterValue::Uri(parse::one(values).into()),
+        DELEGATED_FROM | DELEGATED_TO | MEMBER => ParameterValue::UriList(parse::all(values)),
+        VALUE => ParameterValue::Value(parse::one(values).into()),
+        _ => return None,
+    })
+}
+
+impl Parameters {
+    /// The parameters we know that are set, in order by name, with their interned names
+    /// (see `others` for the rest)
+    pub fn iter(&self) -> impl Iterator<Item = (ParameterId, &ParameterValue)> {
+        self.0.iter().map(|(n, value)| (ParameterId(n), value))
+    }
+    /// The number of parameters set, counting the ones we don't know
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len() + self.1.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty() && self.1.is_empty()
+    }
+    /// The values of parameter `name` (in any case), one we don't know: an experimental
+    /// (`X-`) parameter, or an IANA one not in `assets`
+    #[must_use]
+    pub fn other(&self, name: &str) -> Option<&[String]> {
+        (self.1.iter()).find(|(other, _)| *other == Caseless(name)).map(|(_, values)| &values[..])
+    }
+    /// Set parameter `name`, one we don't know, replacing any values it has. The parameters
+    /// we know have typed setters; set here, they'd be written twice.
+    pub fn set_other(&mut self, name: impl Into<String>, values: Vec<String>) {
+        let name = Caseless(name.into());
+        match self.1.iter_mut().find(|(other, _)| *other == name) {
+            Some((_, old)) => *old = values,
+            None => self.1.push((name, values)),
+        }
+    }
+    /// Remove parameter `name`, one we don't know, returning its values
+    pub fn remove_other(&mut self, name: &str) -> Option<Vec<String>> {
+        let n = self.1.iter().position(|(other, _)| *other == Caseless(name))?;
+        Some(self.1.remove(n).1)
+    }
+    /// The parameters we don't know, with their names as written, in the order they were set
+    pub fn others(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.1.iter().map(|(name, values)| (name.as_str(), &values[..]))
+    }
+}
 
 #[allow(clippy::missing_panics_doc)] // We should only be `get`ing type that we `set`
 impl Parameters {
     /// Get the `ALTREP` parameter ([RFC 5545, § 3.2.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.1)).
     #[must_use]
     pub fn altrep(&self) -> Option<&UriString> {
-        match self.0.get(&ALTREP) {
+        match self.0.get(ALTREP) {
             None => None,
             Some(ParameterValue::Uri(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "ALTREP"),
@@ -132,7 +352,7 @@ impl Parameters {
     /// Get the `CN` parameter ([RFC 5545, § 3.2.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.2)).
     #[must_use]
     pub fn cn(&self) -> Option<&String> {
-        match self.0.get(&CN) {
+        match self.0.get(CN) {
             None => None,
             Some(ParameterValue::Text(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "CN"),
@@ -147,7 +367,7 @@ impl Parameters {
     /// Get the `CUTYPE` parameter ([RFC 5545, § 3.2.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.3)).
     #[must_use]
     pub fn cutype(&self) -> Option<&CUType> {
-        match self.0.get(&CUTYPE) {
+        match self.0.get(CUTYPE) {
             None => None,
             Some(ParameterValue::CUType(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "CUTYPE"),
@@ -162,7 +382,7 @@ impl Parameters {
     /// Get the `DELEGATED_FROM` parameter ([RFC 5545, § 3.2.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.4)).
     #[must_use]
     pub fn delegated_from(&self) -> Option<&Vec<UriString>> {
-        match self.0.get(&DELEGATED_FROM) {
+        match self.0.get(DELEGATED_FROM) {
             None => None,
             Some(ParameterValue::UriList(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "DELEGATED-FROM"),
@@ -177,7 +397,7 @@ impl Parameters {
     /// Get the `DELEGATED_TO` parameter ([RFC 5545, § 3.2.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.5)).
     #[must_use]
     pub fn delegated_to(&self) -> Option<&Vec<UriString>> {
-        match self.0.get(&DELEGATED_TO) {
+        match self.0.get(DELEGATED_TO) {
             None => None,
             Some(ParameterValue::UriList(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "DELEGATED-TO"),
@@ -192,7 +412,7 @@ impl Parameters {
     /// Get the `DERIVED` parameter ([RFC 9073, § 5.3](https://datatracker.ietf.org/doc/html/rfc9073#section-5.3)).
     #[must_use]
     pub fn derived(&self) -> Option<bool> {
-        match self.0.get(&DERIVED) {
+        match self.0.get(DERIVED) {
             None => None,
             Some(ParameterValue::Boolean(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "DERIVED"),
@@ -207,7 +427,7 @@ impl Parameters {
     /// Get the `DIR` parameter ([RFC 5545, § 3.2.6](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.6)).
     #[must_use]
     pub fn dir(&self) -> Option<&UriString> {
-        match self.0.get(&DIR) {
+        match self.0.get(DIR) {
             None => None,
             Some(ParameterValue::Uri(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "DIR"),
@@ -221,8 +441,8 @@ impl Parameters {
 
     /// Get the `DISPLAY` parameter ([RFC 7986, § 6.1](https://datatracker.ietf.org/doc/html/rfc7986#section-6.1)).
     #[must_use]
-    pub fn display(&self) -> Option<&Display> {
-        match self.0.get(&DISPLAY) {
+    pub fn display(&self) -> Option<&Vec<Display>> {
+        match self.0.get(DISPLAY) {
             None => None,
             Some(ParameterValue::Display(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "DISPLAY"),
@@ -230,14 +450,14 @@ impl Parameters {
     }
 
     /// Set the `DISPLAY` parameter ([RFC 7986, § 6.1](https://datatracker.ietf.org/doc/html/rfc7986#section-6.1)).
-    pub fn set_display(&mut self, value: Display) {
+    pub fn set_display(&mut self, value: Vec<Display>) {
         self.0.insert(DISPLAY, ParameterValue::Display(value));
     }
 
     /// Get the `EMAIL` parameter ([RFC 7986, § 6.2](https://datatracker.ietf.org/doc/html/rfc7986#section-6.2)).
     #[must_use]
     pub fn email(&self) -> Option<&String> {
-        match self.0.get(&EMAIL) {
+        match self.0.get(EMAIL) {
             None => None,
             Some(ParameterValue::Text(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "EMAIL"),
@@ -250,12 +470,11 @@ impl Parameters {
     }
 
     /// Get the `ENCODING` parameter ([RFC 5545, § 3.2.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.7)).
-    /// RFC 5545 gives values of `8BIT` or `BASE64` but the effect of an `8BIT` value
-    /// is the same as having no `ENCODING` parameter so we use the single-valued
-    /// `Base64` type.
+    /// An `8BIT` value means the same as no `ENCODING` parameter, but we keep it, so that
+    /// the parameter is written back as it was read.
     #[must_use]
-    pub fn encoding(&self) -> Option<Base64> {
-        match self.0.get(&ENCODING) {
+    pub fn encoding(&self) -> Option<Encoding> {
+        match self.0.get(ENCODING) {
             None => None,
             Some(ParameterValue::Encoding(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "ENCODING"),
@@ -263,17 +482,14 @@ impl Parameters {
     }
 
     /// Set the `ENCODING` parameter ([RFC 5545, § 3.2.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.7)).
-    pub fn set_encoding(&mut self, value: Option<Base64>) {
-        match value {
-            None => self.0.remove(&ENCODING),
-            Some(v) => self.0.insert(ENCODING, ParameterValue::Encoding(v)),
-        };
+    pub fn set_encoding(&mut self, value: Encoding) {
+        self.0.insert(ENCODING, ParameterValue::Encoding(value));
     }
 
     /// Get the `FBTYPE` parameter ([RFC 5545, § 3.2.9](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.9)).
     #[must_use]
     pub fn fbtype(&self) -> Option<&FBType> {
-        match self.0.get(&FBTYPE) {
+        match self.0.get(FBTYPE) {
             None => None,
             Some(ParameterValue::FBType(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "FBTYPE"),
@@ -287,8 +503,8 @@ impl Parameters {
 
     /// Get the `FEATURE` parameter ([RFC 7986, § 6.3](https://datatracker.ietf.org/doc/html/rfc7986#section-6.3)).
     #[must_use]
-    pub fn feature(&self) -> Option<&Feature> {
-        match self.0.get(&FEATURE) {
+    pub fn feature(&self) -> Option<&Vec<Feature>> {
+        match self.0.get(FEATURE) {
             None => None,
             Some(ParameterValue::Feature(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "FEATURE"),
@@ -296,14 +512,14 @@ impl Parameters {
     }
 
     /// Set the `FEATURE` parameter ([RFC 7986, § 6.3](https://datatracker.ietf.org/doc/html/rfc7986#section-6.3)).
-    pub fn set_feature(&mut self, value: Feature) {
+    pub fn set_feature(&mut self, value: Vec<Feature>) {
         self.0.insert(FEATURE, ParameterValue::Feature(value));
     }
 
     /// Get the `FILENAME` parameter ([RFC 8607, § 4.2](https://datatracker.ietf.org/doc/html/rfc8607#section-4.2)).
     #[must_use]
     pub fn filename(&self) -> Option<&ParamText> {
-        match self.0.get(&FILENAME) {
+        match self.0.get(FILENAME) {
             None => None,
             Some(ParameterValue::ParamText(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "FILENAME"),
@@ -318,7 +534,7 @@ impl Parameters {
     /// Get the `FMTTYPE` parameter ([RFC 5545, § 3.2.8](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.8)).
     #[must_use]
     pub fn fmttype(&self) -> Option<&FmtType> {
-        match self.0.get(&FMTTYPE) {
+        match self.0.get(FMTTYPE) {
             None => None,
             Some(ParameterValue::FmtType(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "FMTTYPE"),
@@ -333,7 +549,7 @@ impl Parameters {
     /// Get the `GAP` parameter ([RFC 9253, § 6.2](https://datatracker.ietf.org/doc/html/rfc9253#section-6.2)).
     #[must_use]
     pub fn gap(&self) -> Option<SignedDuration> {
-        match self.0.get(&GAP) {
+        match self.0.get(GAP) {
             None => None,
             Some(ParameterValue::Duration(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "GAP"),
@@ -348,7 +564,7 @@ impl Parameters {
     /// Get the `LABEL` parameter ([RFC 7986, § 6.4](https://datatracker.ietf.org/doc/html/rfc7986#section-6.4)).
     #[must_use]
     pub fn label(&self) -> Option<&String> {
-        match self.0.get(&LABEL) {
+        match self.0.get(LABEL) {
             None => None,
             Some(ParameterValue::Text(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "LABEL"),
@@ -363,7 +579,7 @@ impl Parameters {
     /// Get the `LANGUAGE` parameter ([RFC 5545, § 3.2.10](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.10)).
     #[must_use]
     pub fn language(&self) -> Option<&Language> {
-        match self.0.get(&LANGUAGE) {
+        match self.0.get(LANGUAGE) {
             None => None,
             Some(ParameterValue::Language(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "LANGUAGE"),
@@ -378,7 +594,7 @@ impl Parameters {
     /// Get the `LINKREL` parameter ([RFC 9253, § 6.1](https://datatracker.ietf.org/doc/html/rfc9253#section-6.1)).
     #[must_use]
     pub fn linkrel(&self) -> Option<&UriString> {
-        match self.0.get(&LINKREL) {
+        match self.0.get(LINKREL) {
             None => None,
             Some(ParameterValue::Uri(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "LINKREL"),
@@ -393,7 +609,7 @@ impl Parameters {
     /// Get the `MANAGED_ID` parameter ([RFC 8607, § 4.3](https://datatracker.ietf.org/doc/html/rfc8607#section-4.3)).
     #[must_use]
     pub fn managed_id(&self) -> Option<&ParamText> {
-        match self.0.get(&MANAGED_ID) {
+        match self.0.get(MANAGED_ID) {
             None => None,
             Some(ParameterValue::ParamText(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "MANAGED-ID"),
@@ -408,7 +624,7 @@ impl Parameters {
     /// Get the `MEMBER` parameter ([RFC 5545, § 3.2.11](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.11)).
     #[must_use]
     pub fn member(&self) -> Option<&Vec<UriString>> {
-        match self.0.get(&MEMBER) {
+        match self.0.get(MEMBER) {
             None => None,
             Some(ParameterValue::UriList(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "MEMBER"),
@@ -423,7 +639,7 @@ impl Parameters {
     /// Get the `ORDER` parameter ([RFC 9073, § 5.1](https://datatracker.ietf.org/doc/html/rfc9073#section-5.1)).
     #[must_use]
     pub fn order(&self) -> Option<NonZeroUsize> {
-        match self.0.get(&ORDER) {
+        match self.0.get(ORDER) {
             None => None,
             Some(ParameterValue::Order(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "ORDER"),
@@ -438,7 +654,7 @@ impl Parameters {
     /// Get the `PARTSTAT` parameter ([RFC 5545, § 3.2.12](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.12)).
     #[must_use]
     pub fn partstat(&self) -> Option<&PartStat> {
-        match self.0.get(&PARTSTAT) {
+        match self.0.get(PARTSTAT) {
             None => None,
             Some(ParameterValue::PartStat(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "PARTSTAT"),
@@ -455,7 +671,7 @@ impl Parameters {
     /// so we have another single-valued type
     #[must_use]
     pub fn range(&self) -> Option<ThisAndFuture> {
-        match self.0.get(&RANGE) {
+        match self.0.get(RANGE) {
             None => None,
             Some(ParameterValue::Range(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "RANGE"),
@@ -465,7 +681,7 @@ impl Parameters {
     /// Set the `RANGE` parameter ([RFC 5545, § 3.2.13](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.13)).
     pub fn set_range(&mut self, value: Option<ThisAndFuture>) {
         match value {
-            None => self.0.remove(&RANGE),
+            None => self.0.remove(RANGE),
             Some(v) => self.0.insert(RANGE, ParameterValue::Range(v)),
         };
     }
@@ -473,7 +689,7 @@ impl Parameters {
     /// Get the `RELATED` parameter ([RFC 5545, § 3.2.14](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.14)).
     #[must_use]
     pub fn related(&self) -> Option<Related> {
-        match self.0.get(&RELATED) {
+        match self.0.get(RELATED) {
             None => None,
             Some(ParameterValue::Related(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "RELATED"),
@@ -487,23 +703,23 @@ impl Parameters {
 
     /// Get the `RELTYPE` parameter ([RFC 5545, § 3.2.15](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.15)).
     #[must_use]
-    pub fn reltype(&self) -> Option<Related> {
-        match self.0.get(&RELTYPE) {
+    pub fn reltype(&self) -> Option<&RelType> {
+        match self.0.get(RELTYPE) {
             None => None,
-            Some(ParameterValue::Related(value)) => Some(*value),
+            Some(ParameterValue::RelType(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "RELTYPE"),
         }
     }
 
     /// Set the `RELTYPE` parameter ([RFC 5545, § 3.2.15](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.15)).
-    pub fn set_reltype(&mut self, value: Related) {
-        self.0.insert(RELTYPE, ParameterValue::Related(value));
+    pub fn set_reltype(&mut self, value: RelType) {
+        self.0.insert(RELTYPE, ParameterValue::RelType(value));
     }
 
     /// Get the `ROLE` parameter ([RFC 5545, § 3.2.16](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.16)).
     #[must_use]
     pub fn role(&self) -> Option<&Role> {
-        match self.0.get(&ROLE) {
+        match self.0.get(ROLE) {
             None => None,
             Some(ParameterValue::Role(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "ROLE"),
@@ -518,7 +734,7 @@ impl Parameters {
     /// Get the `RSVP` parameter ([RFC 5545, § 3.2.17](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.17)).
     #[must_use]
     pub fn rsvp(&self) -> Option<bool> {
-        match self.0.get(&RSVP) {
+        match self.0.get(RSVP) {
             None => None,
             Some(ParameterValue::Boolean(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "RSVP"),
@@ -533,7 +749,7 @@ impl Parameters {
     /// Get the `SCHEDULE_AGENT` parameter ([RFC 6638, § 7.1](https://datatracker.ietf.org/doc/html/rfc6638#section-7.1)).
     #[must_use]
     pub fn schedule_agent(&self) -> Option<&ScheduleAgent> {
-        match self.0.get(&SCHEDULE_AGENT) {
+        match self.0.get(SCHEDULE_AGENT) {
             None => None,
             Some(ParameterValue::ScheduleAgent(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "SCHEDULE-AGENT"),
@@ -548,7 +764,7 @@ impl Parameters {
     /// Get the `SCHEDULE_FORCE_SEND` parameter ([RFC 6638, § 7.2](https://datatracker.ietf.org/doc/html/rfc6638#section-7.2)).
     #[must_use]
     pub fn schedule_force_send(&self) -> Option<&ScheduleForceSend> {
-        match self.0.get(&SCHEDULE_FORCE_SEND) {
+        match self.0.get(SCHEDULE_FORCE_SEND) {
             None => None,
             Some(ParameterValue::ScheduleForceSend(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "SCHEDULE-FORCE-SEND"),
@@ -563,7 +779,7 @@ impl Parameters {
     /// Get the `SCHEDULE_STATUS` parameter ([RFC 6638, § 7.3](https://datatracker.ietf.org/doc/html/rfc6638#section-7.3)).
     #[must_use]
     pub fn schedule_status(&self) -> Option<&ScheduleStatus> {
-        match self.0.get(&SCHEDULE_STATUS) {
+        match self.0.get(SCHEDULE_STATUS) {
             None => None,
             Some(ParameterValue::ScheduleStatus(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "SCHEDULE-STATUS"),
@@ -578,7 +794,7 @@ impl Parameters {
     /// Get the `SCHEMA` parameter ([RFC 9073, § 5.2](https://datatracker.ietf.org/doc/html/rfc9073#section-5.2)).
     #[must_use]
     pub fn schema(&self) -> Option<&UriString> {
-        match self.0.get(&SCHEMA) {
+        match self.0.get(SCHEMA) {
             None => None,
             Some(ParameterValue::Uri(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "SCHEMA"),
@@ -593,7 +809,7 @@ impl Parameters {
     /// Get the `SENT_BY` parameter ([RFC 5545, § 3.2.18](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.18)).
     #[must_use]
     pub fn sent_by(&self) -> Option<&CalAddress> {
-        match self.0.get(&SENT_BY) {
+        match self.0.get(SENT_BY) {
             None => None,
             Some(ParameterValue::SentBy(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "SENT-BY"),
@@ -608,7 +824,7 @@ impl Parameters {
     /// Get the `SIZE` parameter ([RFC 8607, § 4.1](https://datatracker.ietf.org/doc/html/rfc8607#section-4.1)).
     #[must_use]
     pub fn size(&self) -> Option<u64> {
-        match self.0.get(&SIZE) {
+        match self.0.get(SIZE) {
             None => None,
             Some(ParameterValue::Size(value)) => Some(*value),
             _ => panic!("Unexpected type for {}", "SIZE"),
@@ -622,8 +838,8 @@ impl Parameters {
 
     /// Get the `TZID` parameter ([RFC 5545, § 3.2.19](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.19)).
     #[must_use]
-    pub fn tzid(&self) -> Option<&String> {
-        match self.0.get(&TZID) {
+    pub fn tzid(&self) -> Option<&Tzid> {
+        match self.0.get(TZID) {
             None => None,
             Some(ParameterValue::Tzid(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "TZID"),
@@ -631,14 +847,14 @@ impl Parameters {
     }
 
     /// Set the `TZID` parameter ([RFC 5545, § 3.2.19](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.19)).
-    pub fn set_tzid(&mut self, value: String) {
+    pub fn set_tzid(&mut self, value: Tzid) {
         self.0.insert(TZID, ParameterValue::Tzid(value));
     }
 
     /// Get the `VALUE` parameter ([RFC 5545, § 3.2.20](https://datatracker.ietf.org/doc/html/rfc5545#section-3.2.20)).
     #[must_use]
     pub fn value(&self) -> Option<&Value> {
-        match self.0.get(&VALUE) {
+        match self.0.get(VALUE) {
             None => None,
             Some(ParameterValue::Value(value)) => Some(value),
             _ => panic!("Unexpected type for {}", "VALUE"),
@@ -673,6 +889,36 @@ mod test {
         "SCHEMA", "SENT-BY", "SIZE", "TZID", "VALUE"
         ];
 
+    #[test]
+    fn iteration() {
+        let mut parameters = Parameters::default();
+        assert!(parameters.is_empty());
+        parameters.set_tzid(Tzid::from("Europe/Paris"));
+        parameters.set_cn("Jane".to_string());
+        parameters.set_other("X-A", vec![]);
+        let lookup = crate::names::Lookup::new();
+        let names: Vec<_> = (parameters.iter())
+            .map(|(id, _)| lookup.parameter_name(id).unwrap().to_string())
+            .collect();
+        assert_eq!(names, ["CN", "TZID"]);
+        assert_eq!(
+            parameters.iter().nth(1).map(|(_, value)| value),
+            Some(&ParameterValue::Tzid(Tzid::from("Europe/Paris")))
+        );
+        assert_eq!((parameters.len(), parameters.is_empty()), (3, false));
+    }
+    #[test]
+    fn other_parameters() {
+        let mut parameters = Parameters::default();
+        parameters.set_other("X-Colour", vec!["red".to_string()]);
+        parameters.set_other("X-ROOM", vec!["1".to_string(), "2".to_string()]);
+        parameters.set_other("x-colour", vec!["blue".to_string()]);
+        assert_eq!(parameters.other("X-COLOUR"), Some(&["blue".to_string()][..]));
+        let names: Vec<_> = parameters.others().map(|(name, _)| name).collect();
+        assert_eq!(names, ["X-Colour", "X-ROOM"]);
+        assert_eq!(parameters.remove_other("x-room").map(|values| values.len()), Some(2));
+        assert_eq!(parameters.other("X-ROOM"), None);
+    }
     #[test]
     fn parameter_ids_remain_in_order() {
         let expected: Vec<_> = (ALTREP..=VALUE).collect();
@@ -689,6 +935,15 @@ mod test {
         assert_eq!(NAMES, sorted);
     }
     #[test]
+    fn applicable_properties_are_known_and_sorted() {
+        for properties in APPLIES_TO.into_iter().flatten() {
+            assert!(properties.is_sorted(), "{properties:?}");
+            for name in properties {
+                assert!(crate::property::NAMES.contains(name), "{name}");
+            }
+        }
+    }
+    #[test]
     fn parameter_names_correspond_to_parameter_ids() {
         use crate::names::{Lookup, ParameterId};
         let lookup = Lookup::new();
@@ -0,0 +1,171 @@
+// Typed parameters from the raw names and values a preparser finds
+use super::parameter_value::parse_known;
+use super::{Encoding, NAMES, Parameters, Related, ThisAndFuture, index_of};
+use crate::catalog;
+use crate::names::{ParameterId, uppercase};
+use crate::preparse::Param;
+use crate::property::parse_duration;
+use jiff::SignedDuration;
+use std::num::NonZeroUsize;
+use thiserror::Error;
+
+/// Why a property's parameters couldn't be made into `Parameters`
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ParamError {
+    #[error("{value:?} isn't a valid value of the {name} parameter")]
+    Malformed { name: &'static str, value: String },
+    #[error("the {0} parameter may have only one value")]
+    NotMultiValued(&'static str),
+    #[error("the {0} parameter appears more than once")]
+    Repeated(String),
+}
+impl ParamError {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParamError::Malformed { .. } => catalog::BAD_PARAMETER_VALUE,
+            ParamError::NotMultiValued(_) => catalog::PARAMETER_NOT_MULTI_VALUED,
+            ParamError::Repeated(_) => catalog::REPEATED_PARAMETER,
+        }
+    }
+}
+
+impl Parameters {
+    /// The parameters `params` (as a preparser found them), each value of a parameter we know
+    /// parsed into its type. Names are matched ignoring case; parameters we don't know (`X-`
+    /// names, and IANA names not in `assets`) keep their values as written (see `other`).
+    ///
+    /// # Errors
+    /// Returns a `ParamError` if a value isn't valid for its parameter, a parameter that may
+    /// have only one value has several, or a parameter appears twice.
+    pub fn from_preparsed(params: &[Param]) -> Result<Parameters, ParamError> {
+        let mut parameters = Parameters::default();
+        for param in params {
+            let values: Vec<_> = param.values().iter().map(|value| value.val()).collect();
+            let Some(n) = index_of(&uppercase(param.name().val())) else {
+                let name = param.name().val();
+                if parameters.other(name).is_some() {
+                    return Err(ParamError::Repeated(name.to_string()));
+                }
+                parameters.set_other(name, values.iter().map(ToString::to_string).collect());
+                continue;
+            };
+            let name = NAMES[n];
+            if parameters.0.contains_key(n) {
+                return Err(ParamError::Repeated(name.to_string()));
+            }
+            if values.len() > 1 && !ParameterId(n).is_multi_valued() {
+                return Err(ParamError::NotMultiValued(name));
+            }
+            let typed = parse_known(n, &values)
+                .ok_or_else(|| ParamError::Malformed { name, value: values.join(",") })?;
+            parameters.0.insert(n, typed);
+        }
+        Ok(parameters)
+    }
+}
+
+// The parsers `parse_known` dispatches to. Each takes the parameter's values as written (just
+// one, unless the parameter is multi-valued); the types named after their variant may reject
+// them, and the others are `From<&str>`.
+
+// The value of a single-valued parameter
+pub(super) fn one<'a>(values: &[&'a str]) -> &'a str {
+    values.first().copied().unwrap_or_default()
+}
+// Each value of a multi-valued parameter
+pub(super) fn all<'a, T: From<&'a str>>(values: &[&'a str]) -> Vec<T> {
+    values.iter().map(|&value| value.into()).collect()
+}
+pub(super) fn boolean(values: &[&str]) -> Option<bool> {
+    match uppercase(one(values)).as_ref() {
+        "TRUE" => Some(true),
+        "FALSE" => Some(false),
+        _ => None,
+    }
+}
+pub(super) fn duration(values: &[&str]) -> Option<SignedDuration> {
+    parse_duration(one(values)).ok()
+}
+pub(super) fn encoding(values: &[&str]) -> Option<Encoding> {
+    match uppercase(one(values)).as_ref() {
+        "8BIT" => Some(Encoding::EightBit),
+        "BASE64" => Some(Encoding::Base64),
+        _ => None,
+    }
+}
+pub(super) fn order(values: &[&str]) -> Option<NonZeroUsize> {
+    one(values).parse().ok()
+}
+pub(super) fn range(values: &[&str]) -> Option<ThisAndFuture> {
+    (uppercase(one(values)) == "THISANDFUTURE").then_some(ThisAndFuture())
+}
+pub(super) fn related(values: &[&str]) -> Option<Related> {
+    match uppercase(one(values)).as_ref() {
+        "START" => Some(Related::Start),
+        "END" => Some(Related::End),
+        _ => None,
+    }
+}
+pub(super) fn size(values: &[&str]) -> Option<u64> {
+    let value = one(values);
+    value.bytes().all(|b| b.is_ascii_digit()).then(|| value.parse().ok())?
+}
+
+#[cfg(all(test, feature = "cautious"))]
+mod test {
+    use super::*;
+    use crate::parameter::{Display, Feature, PartStat, RelType, Role};
+    use crate::preparse::cautious_preparse_str;
+    use pretty_assertions::assert_eq;
+
+    fn parameters(line: &str) -> Result<Parameters, ParamError> {
+        Parameters::from_preparsed(cautious_preparse_str(line).unwrap().parameters())
+    }
+
+    #[test]
+    fn typed_values() {
+        let line = "ATTENDEE;partstat=accepted;ROLE=X-SPEAKER;RSVP=true;CN=\"Doe, Jane\";\
+                    MEMBER=\"mailto:a@example.com\",\"mailto:b@example.com\";X-FOO=bar;\
+                    ORDER=2;ENCODING=8BIT:mailto:jane@example.com";
+        let params = parameters(line).unwrap();
+        assert_eq!(params.partstat(), Some(&PartStat::Accepted));
+        assert_eq!(params.role(), Some(&Role::ReqParticipant(Some("X-SPEAKER".into()))));
+        assert_eq!(params.rsvp(), Some(true));
+        assert_eq!(params.cn().map(String::as_str), Some("Doe, Jane"));
+        assert_eq!(params.member().map(Vec::len), Some(2));
+        assert_eq!(params.order(), NonZeroUsize::new(2));
+        assert_eq!(params.encoding(), Some(Encoding::EightBit));
+        assert_eq!(params.other("x-foo"), Some(&["bar".to_string()][..]));
+        let params = parameters("RELATED-TO;RELTYPE=child;GAP=PT1H:x").unwrap();
+        assert_eq!(params.reltype(), Some(&RelType::Child));
+        assert_eq!(params.gap(), Some(SignedDuration::from_hours(1)));
+    }
+    #[test]
+    fn multiple_values() {
+        let params =
+            parameters("IMAGE;DISPLAY=badge,THUMBNAIL;VALUE=URI:https://a.example").unwrap();
+        assert_eq!(params.display(), Some(&vec![Display::Badge(None), Display::Thumbnail]));
+        let params = parameters("CONFERENCE;FEATURE=AUDIO,X-RAISE-HAND:https://a.example").unwrap();
+        let features = vec![Feature::Audio, Feature::Other("X-RAISE-HAND".into())];
+        assert_eq!(params.feature(), Some(&features));
+        let mut written = String::new();
+        params.write_to(&mut written).unwrap();
+        assert_eq!(written, ";FEATURE=AUDIO,X-RAISE-HAND");
+    }
+    #[test]
+    fn errors() {
+        let malformed = |name, value: &str| ParamError::Malformed { name, value: value.into() };
+        assert_eq!(parameters("X;RSVP=yes:x"), Err(malformed("RSVP", "yes")));
+        assert_eq!(parameters("X;ORDER=0:x"), Err(malformed("ORDER", "0")));
+        assert_eq!(parameters("X;SIZE=+5:x"), Err(malformed("SIZE", "+5")));
+        assert_eq!(parameters("X;RANGE=THISANDPRIOR:x"), Err(malformed("RANGE", "THISANDPRIOR")));
+        assert_eq!(parameters("X;CN=a,b:x"), Err(ParamError::NotMultiValued("CN")));
+        let repeated = parameters("X;TZID=A;tzid=B:x").unwrap_err();
+        assert_eq!(repeated, ParamError::Repeated("TZID".to_string()));
+        assert_eq!(repeated.code(), catalog::REPEATED_PARAMETER);
+        let repeated = ParamError::Repeated("X-FOO".to_string());
+        assert_eq!(parameters("X;X-Foo=a;X-FOO=b:x"), Err(repeated));
+    }
+}
@@ -1,9 +1,26 @@
+use crate::names::uppercase;
 pub use jiff::SignedDuration;
+use std::fmt;
 
-#[derive(Clone, Copy, Debug)]
-pub struct Base64();
+/// The value of an `ENCODING` parameter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// `8BIT`: the value is text, as if there were no `ENCODING`
+    EightBit,
+    /// `BASE64`: the value is binary, in base64
+    Base64,
+}
+impl fmt::Display for Encoding {
+    /// The `ENCODING` value
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::EightBit => "8BIT",
+            Encoding::Base64 => "BASE64",
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CUType {
     Individual,
     Group,
@@ -11,24 +28,101 @@ pub enum CUType {
     Room,
     Unknown(Option<String>),
 }
+impl From<&str> for CUType {
+    /// The type named by a `CUTYPE` value (ignoring case); one we don't recognize is
+    /// `UNKNOWN`, as RFC 5545 says, along with the value as written
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "INDIVIDUAL" => CUType::Individual,
+            "GROUP" => CUType::Group,
+            "RESOURCE" => CUType::Resource,
+            "ROOM" => CUType::Room,
+            "UNKNOWN" => CUType::Unknown(None),
+            _ => CUType::Unknown(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for CUType {
+    /// The `CUTYPE` value: the name as written, for a type we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CUType::Individual => "INDIVIDUAL",
+            CUType::Group => "GROUP",
+            CUType::Resource => "RESOURCE",
+            CUType::Room => "ROOM",
+            CUType::Unknown(None) => "UNKNOWN",
+            CUType::Unknown(Some(name)) => name,
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Display {
     Badge(Option<String>),
     Graphic,
     Fullsize,
     Thumbnail,
 }
+impl From<&str> for Display {
+    /// The display named by a `DISPLAY` value (ignoring case); one we don't recognize is
+    /// `BADGE`, as RFC 7986 says, along with the value as written
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "BADGE" => Display::Badge(None),
+            "GRAPHIC" => Display::Graphic,
+            "FULLSIZE" => Display::Fullsize,
+            "THUMBNAIL" => Display::Thumbnail,
+            _ => Display::Badge(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for Display {
+    /// The `DISPLAY` value: the name as written, for a display we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Display::Badge(None) => "BADGE",
+            Display::Badge(Some(name)) => name,
+            Display::Graphic => "GRAPHIC",
+            Display::Fullsize => "FULLSIZE",
+            Display::Thumbnail => "THUMBNAIL",
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum FBType {
     Free,
     Busy(Option<String>),
     BusyUnavailable,
     BusyTentative,
 }
+impl From<&str> for FBType {
+    /// The free/busy type named by an `FBTYPE` value (ignoring case); one we don't recognize
+    /// is `BUSY`, as RFC 5545 says, along with the value as written
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "FREE" => FBType::Free,
+            "BUSY" => FBType::Busy(None),
+            "BUSY-UNAVAILABLE" => FBType::BusyUnavailable,
+            "BUSY-TENTATIVE" => FBType::BusyTentative,
+            _ => FBType::Busy(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for FBType {
+    /// The `FBTYPE` value: the name as written, for a type we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FBType::Free => "FREE",
+            FBType::Busy(None) => "BUSY",
+            FBType::Busy(Some(name)) => name,
+            FBType::BusyUnavailable => "BUSY-UNAVAILABLE",
+            FBType::BusyTentative => "BUSY-TENTATIVE",
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Feature {
     Audio,
     Chat,
@@ -39,8 +133,38 @@ pub enum Feature {
     Video,
     Other(String),
 }
+impl From<&str> for Feature {
+    /// The feature named by a `FEATURE` value (ignoring case)
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "AUDIO" => Feature::Audio,
+            "CHAT" => Feature::Chat,
+            "FEED" => Feature::Feed,
+            "MODERATOR" => Feature::Moderator,
+            "PHONE" => Feature::Phone,
+            "SCREEN" => Feature::Screen,
+            "VIDEO" => Feature::Video,
+            _ => Feature::Other(name.to_string()),
+        }
+    }
+}
+impl fmt::Display for Feature {
+    /// The `FEATURE` value
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Feature::Audio => "AUDIO",
+            Feature::Chat => "CHAT",
+            Feature::Feed => "FEED",
+            Feature::Moderator => "MODERATOR",
+            Feature::Phone => "PHONE",
+            Feature::Screen => "SCREEN",
+            Feature::Video => "VIDEO",
+            Feature::Other(name) => name,
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PartStat {
     NeedsAction(Option<String>),
     Accepted,
@@ -50,45 +174,178 @@ pub enum PartStat {
     Completed,
     InProcess,
 }
-#[derive(Clone, Copy, Debug)]
+impl From<&str> for PartStat {
+    /// The status named by a `PARTSTAT` parameter's value (ignoring case). RFC 5545 says to
+    /// treat a status we don't recognize as `NEEDS-ACTION`, so that's what it becomes, along
+    /// with the value as written.
+    fn from(name: &str) -> Self {
+        match name.to_ascii_uppercase().as_str() {
+            "NEEDS-ACTION" => PartStat::NeedsAction(None),
+            "ACCEPTED" => PartStat::Accepted,
+            "DECLINED" => PartStat::Declined,
+            "TENTATIVE" => PartStat::Tentative,
+            "DELEGATED" => PartStat::Delegated,
+            "COMPLETED" => PartStat::Completed,
+            "IN-PROCESS" => PartStat::InProcess,
+            _ => PartStat::NeedsAction(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for PartStat {
+    /// The `PARTSTAT` value: the name as written, for a status we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PartStat::NeedsAction(None) => "NEEDS-ACTION",
+            PartStat::NeedsAction(Some(name)) => name,
+            PartStat::Accepted => "ACCEPTED",
+            PartStat::Declined => "DECLINED",
+            PartStat::Tentative => "TENTATIVE",
+            PartStat::Delegated => "DELEGATED",
+            PartStat::Completed => "COMPLETED",
+            PartStat::InProcess => "IN-PROCESS",
+        })
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Related {
     Start,
     End,
 }
+impl fmt::Display for Related {
+    /// The `RELATED` value
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Related::Start => "START",
+            Related::End => "END",
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RelType {
     Parent(Option<String>),
     Child,
     Sibling,
 }
+impl From<&str> for RelType {
+    /// The relationship named by a `RELTYPE` value (ignoring case); one we don't recognize is
+    /// `PARENT`, as RFC 5545 says, along with the value as written
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "PARENT" => RelType::Parent(None),
+            "CHILD" => RelType::Child,
+            "SIBLING" => RelType::Sibling,
+            _ => RelType::Parent(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for RelType {
+    /// The `RELTYPE` value: the name as written, for a relationship we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RelType::Parent(None) => "PARENT",
+            RelType::Parent(Some(name)) => name,
+            RelType::Child => "CHILD",
+            RelType::Sibling => "SIBLING",
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Role {
     Chair,
     ReqParticipant(Option<String>),
     OptParticipant,
     NonParticipant,
 }
+impl From<&str> for Role {
+    /// The role named by a `ROLE` value (ignoring case); one we don't recognize is
+    /// `REQ-PARTICIPANT`, as RFC 5545 says, along with the value as written
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "CHAIR" => Role::Chair,
+            "REQ-PARTICIPANT" => Role::ReqParticipant(None),
+            "OPT-PARTICIPANT" => Role::OptParticipant,
+            "NON-PARTICIPANT" => Role::NonParticipant,
+            _ => Role::ReqParticipant(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for Role {
+    /// The `ROLE` value: the name as written, for a role we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Chair => "CHAIR",
+            Role::ReqParticipant(None) => "REQ-PARTICIPANT",
+            Role::ReqParticipant(Some(name)) => name,
+            Role::OptParticipant => "OPT-PARTICIPANT",
+            Role::NonParticipant => "NON-PARTICIPANT",
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ScheduleAgent {
     Server,
     Client,
     None(Option<String>),
 }
+impl From<&str> for ScheduleAgent {
+    /// The agent named by a `SCHEDULE-AGENT` value (ignoring case); one we don't recognize
+    /// is kept as written
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "SERVER" => ScheduleAgent::Server,
+            "CLIENT" => ScheduleAgent::Client,
+            "NONE" => ScheduleAgent::None(None),
+            _ => ScheduleAgent::None(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for ScheduleAgent {
+    /// The `SCHEDULE-AGENT` value: the name as written, for an agent we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScheduleAgent::Server => "SERVER",
+            ScheduleAgent::Client => "CLIENT",
+            ScheduleAgent::None(None) => "NONE",
+            ScheduleAgent::None(Some(name)) => name,
+        })
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ScheduleForceSend {
     Request,
     Reply,
     Unknown(Option<String>),
 }
+impl From<&str> for ScheduleForceSend {
+    /// The request named by a `SCHEDULE-FORCE-SEND` value (ignoring case)
+    fn from(name: &str) -> Self {
+        match uppercase(name).as_ref() {
+            "REQUEST" => ScheduleForceSend::Request,
+            "REPLY" => ScheduleForceSend::Reply,
+            _ => ScheduleForceSend::Unknown(Some(name.to_string())),
+        }
+    }
+}
+impl fmt::Display for ScheduleForceSend {
+    /// The `SCHEDULE-FORCE-SEND` value: the name as written, for one we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScheduleForceSend::Request => "REQUEST",
+            ScheduleForceSend::Reply => "REPLY",
+            ScheduleForceSend::Unknown(None) => "UNKNOWN",
+            ScheduleForceSend::Unknown(Some(name)) => name,
+        })
+    }
+}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ThisAndFuture();
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Value {
     Binary,
     Boolean,
@@ -108,6 +365,54 @@ pub enum Value {
     XmlReference,
     Other(String),
 }
+impl From<&str> for Value {
+    /// The value type named by a `VALUE` parameter's value (ignoring case)
+    fn from(name: &str) -> Self {
+        match name.to_ascii_uppercase().as_str() {
+            "BINARY" => Value::Binary,
+            "BOOLEAN" => Value::Boolean,
+            "CAL-ADDRESS" => Value::CalAddress,
+            "DATE" => Value::Date,
+            "DATE-TIME" => Value::DateTime,
+            "DURATION" => Value::Duration,
+            "FLOAT" => Value::Float,
+            "INTEGER" => Value::Integer,
+            "PERIOD" => Value::Period,
+            "RECUR" => Value::Recur,
+            "TEXT" => Value::Text,
+            "TIME" => Value::Time,
+            "UID" => Value::Uid,
+            "URI" => Value::Uri,
+            "UTC-OFFSET" => Value::UtcOffset,
+            "XML-REFERENCE" => Value::XmlReference,
+            _ => Value::Other(name.to_string()),
+        }
+    }
+}
+impl fmt::Display for Value {
+    /// The `VALUE` value: the name as written, for a type we didn't recognize
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Value::Binary => "BINARY",
+            Value::Boolean => "BOOLEAN",
+            Value::CalAddress => "CAL-ADDRESS",
+            Value::Date => "DATE",
+            Value::DateTime => "DATE-TIME",
+            Value::Duration => "DURATION",
+            Value::Float => "FLOAT",
+            Value::Integer => "INTEGER",
+            Value::Period => "PERIOD",
+            Value::Recur => "RECUR",
+            Value::Text => "TEXT",
+            Value::Time => "TIME",
+            Value::Uid => "UID",
+            Value::Uri => "URI",
+            Value::UtcOffset => "UTC-OFFSET",
+            Value::XmlReference => "XML-REFERENCE",
+            Value::Other(name) => name,
+        })
+    }
+}
 
 pub type UriString = String; // FIXME: this type can't contain CONTROL, DQUOTE, ";", ":", ","
 pub type ParamText = String; // FIXME: this type can't contain CONTROL, DQUOTE, ";", ":", ","
@@ -115,3 +420,44 @@ pub type FmtType = String; // FIXME: must be a media type the media type [RFC428
 pub type Language = String; // FIXME: must be as defined in [RFC5646].
 pub type ScheduleStatus = Vec<String>; // FIXME: must be at least one dot-separated pair or triplet of integers, like "3.1" or "3.1.1"
 pub type CalAddress = String; // FIXME: must be mailto: uri
+
+/// A time zone identifier: the value of a `TZID` parameter or property. Whether it names a
+/// known zone is checked separately (see `timezone::validate_tzids`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tzid(String);
+impl Tzid {
+    #[must_use]
+    pub fn new(tzid: impl Into<String>) -> Self {
+        Tzid(tzid.into())
+    }
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    /// Is this a globally unique identifier — one starting with a solidus (`/`), which
+    /// RFC 5545 § 3.2.19 reserves for ids in a globally defined time zone registry?
+    #[must_use]
+    pub fn is_custom(&self) -> bool {
+        self.0.starts_with('/')
+    }
+}
+impl From<String> for Tzid {
+    fn from(tzid: String) -> Self {
+        Tzid(tzid)
+    }
+}
+impl From<&str> for Tzid {
+    fn from(tzid: &str) -> Self {
+        Tzid(tzid.to_string())
+    }
+}
+impl AsRef<str> for Tzid {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+impl fmt::Display for Tzid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
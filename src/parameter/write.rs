@@ -0,0 +1,114 @@
+// Writing parameters back in content-line syntax
+use super::{NAMES, ParameterValue, Parameters};
+use crate::property::format_duration;
+use crate::writer::quote_value_of;
+use std::borrow::Cow;
+use std::fmt;
+
+impl Parameters {
+    /// Write each parameter as `;NAME=value`, separating multiple values with commas and
+    /// quoting values as `ContentWriter::write_property` does. The parameters we know come
+    /// first, in order by name, then the others in the order they were set.
+    ///
+    /// # Errors
+    /// Returns an error if `w` does, or if a value contains a double quote or a control
+    /// character, which no parameter value can represent.
+    pub fn write_to(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        let known = self.iter().map(|(id, value)| (NAMES[id.0], texts(value)));
+        let others = (self.1.iter())
+            .map(|(name, values)| (name.as_str(), values.iter().map(Cow::from).collect()));
+        for (name, values) in known.chain(others) {
+            write!(w, ";{name}=")?;
+            for (n, value) in values.iter().enumerate() {
+                if n > 0 {
+                    w.write_char(',')?;
+                }
+                w.write_str(&quote_value_of(name, value).ok_or(fmt::Error)?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The value's text, as a list of the values to write
+fn texts(value: &ParameterValue) -> Vec<Cow<'_, str>> {
+    use ParameterValue as P;
+    let text = match value {
+        P::Boolean(b) => Cow::from(if *b { "TRUE" } else { "FALSE" }),
+        P::CUType(cutype) => cutype.to_string().into(),
+        P::Duration(duration) => format_duration(*duration).into(),
+        P::Encoding(encoding) => encoding.to_string().into(),
+        P::FBType(fbtype) => fbtype.to_string().into(),
+        P::FmtType(text)
+        | P::Language(text)
+        | P::ParamText(text)
+        | P::SentBy(text)
+        | P::Text(text)
+        | P::Uri(text) => text.into(),
+        P::Order(order) => order.to_string().into(),
+        P::PartStat(partstat) => partstat.to_string().into(),
+        P::Range(_) => "THISANDFUTURE".into(),
+        P::RelType(reltype) => reltype.to_string().into(),
+        P::Related(related) => related.to_string().into(),
+        P::Role(role) => role.to_string().into(),
+        P::ScheduleAgent(agent) => agent.to_string().into(),
+        P::ScheduleForceSend(send) => send.to_string().into(),
+        P::Size(size) => size.to_string().into(),
+        P::Tzid(tzid) => tzid.as_str().into(),
+        P::Value(value) => value.to_string().into(),
+        P::ScheduleStatus(texts) | P::UriList(texts) => {
+            return texts.iter().map(Cow::from).collect();
+        }
+        P::Display(displays) => return displays.iter().map(|d| d.to_string().into()).collect(),
+        P::Feature(features) => return features.iter().map(|f| f.to_string().into()).collect(),
+    };
+    vec![text]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameter::{PartStat, Role};
+    use pretty_assertions::assert_eq;
+
+    fn written(parameters: &Parameters) -> Result<String, fmt::Error> {
+        let mut text = String::new();
+        parameters.write_to(&mut text).map(|()| text)
+    }
+
+    #[test]
+    fn content_line_syntax() {
+        let mut parameters = Parameters::default();
+        parameters.set_partstat(PartStat::NeedsAction(Some("x-maybe".to_string())));
+        parameters.set_role(Role::Chair);
+        parameters.set_cn("Doe, Jane".to_string());
+        parameters.set_rsvp(true);
+        parameters.set_member(vec!["mailto:a@example.com".into(), "mailto:b@example.com".into()]);
+        parameters.set_gap(jiff::SignedDuration::from_mins(-90));
+        parameters.set_other("X-Room", vec!["12".to_string(), "B:4".to_string()]);
+        assert_eq!(
+            written(&parameters).unwrap(),
+            ";CN=\"Doe, Jane\";GAP=-PT1H30M;MEMBER=\"mailto:a@example.com\",\
+             \"mailto:b@example.com\";PARTSTAT=x-maybe;ROLE=CHAIR;RSVP=TRUE;X-Room=12,\"B:4\""
+        );
+        parameters.set_cn("Say \"hi\"".to_string());
+        assert_eq!(written(&parameters), Err(fmt::Error));
+        assert_eq!(written(&Parameters::default()).unwrap(), "");
+    }
+    #[cfg(feature = "cautious")]
+    #[test]
+    fn round_trips_through_from_preparsed() {
+        use crate::preparse::cautious_preparse_str;
+        let parse = |line: &str| {
+            Parameters::from_preparsed(cautious_preparse_str(line).unwrap().parameters()).unwrap()
+        };
+        let parameters =
+            parse("ATTACH;ENCODING=BASE64;VALUE=BINARY;FMTTYPE=image/png;SIZE=3;X-A=\"a;b\":AAAA");
+        let line = format!("ATTACH{}:AAAA", written(&parameters).unwrap());
+        assert_eq!(
+            line,
+            "ATTACH;ENCODING=BASE64;FMTTYPE=image/png;SIZE=3;VALUE=BINARY;X-A=\"a;b\":AAAA"
+        );
+        assert_eq!(parse(&line), parameters);
+    }
+}
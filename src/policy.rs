@@ -0,0 +1,66 @@
+//! # Property policies
+//! Per-deployment rules applied to each property as a component is parsed, so a server can
+//! enforce its quotas — say, on the length of `DESCRIPTION` or inline `ATTACH` values —
+//! inside the parse rather than after building the whole tree.
+use crate::names::PropertyId;
+
+/// What to do with a property, as decided by a `PropertyPolicy`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyAction {
+    /// Keep the property as is
+    Accept,
+    /// Keep at most this many bytes of the value. The cut is moved back, if necessary, so it
+    /// doesn't split a UTF-8 sequence or a backslash escape.
+    Truncate(usize),
+    /// Stop parsing, with a `CalendarError::PolicyRejected` error
+    Reject,
+}
+
+/// A hook called with each property's id, name (as written), and value while parsing a
+/// component. Closures taking the same arguments are policies.
+pub trait PropertyPolicy {
+    fn check(&mut self, id: PropertyId, name: &str, value: &str) -> PolicyAction;
+}
+
+impl<F: FnMut(PropertyId, &str, &str) -> PolicyAction> PropertyPolicy for F {
+    fn check(&mut self, id: PropertyId, name: &str, value: &str) -> PolicyAction {
+        self(id, name, value)
+    }
+}
+
+/// The policy that accepts every property
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcceptAll;
+impl PropertyPolicy for AcceptAll {
+    fn check(&mut self, _: PropertyId, _: &str, _: &str) -> PolicyAction {
+        PolicyAction::Accept
+    }
+}
+
+// The length of `value` truncated to at most `max_len` bytes, without splitting a character
+// or leaving half of an escape at the end
+pub(crate) fn truncated_len(value: &str, max_len: usize) -> usize {
+    if value.len() <= max_len {
+        return value.len();
+    }
+    let mut end = max_len;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    let backslashes = value[..end].bytes().rev().take_while(|b| *b == b'\\').count();
+    end - backslashes % 2
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn truncation_points() {
+        assert_eq!(truncated_len("short", 10), 5);
+        assert_eq!(truncated_len("abcdef", 3), 3);
+        assert_eq!(truncated_len("aé", 2), 1);
+        assert_eq!(truncated_len(r"ab\,c", 3), 2);
+        assert_eq!(truncated_len(r"a\\,c", 3), 3);
+    }
+}
@@ -0,0 +1,16 @@
+//! # Prelude
+//! The stable, commonly used surface of the API. `use rcal::prelude::*;` brings it into scope,
+//! and keeps working when the modules that define these items are reorganized — the paths
+//! here are the ones we promise not to move.
+pub use crate::Weekday;
+pub use crate::component::{Calendar, Component, Property, parse_calendar};
+pub use crate::config::RcalConfig;
+pub use crate::diagnostic::{Diagnostic, Severity};
+pub use crate::error::{CalendarError, PreparseError, Problem, Segment};
+pub use crate::event::{Event, EventTime};
+pub use crate::parameter::{ParameterValue, Parameters};
+pub use crate::property::PropertyValue;
+pub use crate::rrule::{Frequency, RRule, parse_rrule};
+pub use crate::rrule_error::RRuleError;
+pub use crate::unfolded::BufReadContent;
+pub use crate::warning::{Warning, Warnings};
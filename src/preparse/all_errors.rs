@@ -0,0 +1,331 @@
+// Preparsing that keeps going after a problem, so linters can report everything wrong with a
+// content line at once. Until the first problem, this follows the same grammar as the other
+// preparsers, so the first error it reports is the one they would report. After a problem:
+//  * in the property name, we skip to the first `;` or `:`,
+//  * in a parameter, we drop that parameter and skip to the next `;` or `:` (finishing the
+//    quoted string first, if we were in one), and
+//  * in the property value, we skip the offending character and keep going.
+// Past the limit on parameters, we skip the rest of them after reporting it once.
+use super::{LocStr, Param, ParamValues, PreparseLimits, Prop};
+use crate::error::{EMPTY_CONTENT_LINE, PreparseError, Problem, Segment};
+use std::str;
+
+/// As much of a content line as `preparse_all_errors` could make sense of. A property name or
+/// value with problems is `None`; a parameter with problems is left out of `parameters`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PartialProp<'a> {
+    pub name: Option<LocStr<'a>>,
+    pub parameters: Vec<Param<'a>>,
+    pub value: Option<LocStr<'a>>,
+}
+
+impl<'a> PartialProp<'a> {
+    /// The content line as a `Prop`, if it has a name and a value (as it does when
+    /// `preparse_all_errors` found no problems)
+    #[must_use]
+    pub fn into_prop(self) -> Option<Prop<'a>> {
+        Some(Prop { name: self.name?, parameters: self.parameters, value: self.value? })
+    }
+}
+
+/// Preparse a content line, continuing after recoverable problems. Returns what could be
+/// parsed, along with every problem found, in order. The first problem (if any) is the one
+/// `cautious_preparse` and `bold_preparse` report; parameters with a problem are skipped, and
+/// each contributes at most one problem.
+#[must_use]
+pub fn preparse_all_errors(v: &[u8]) -> (PartialProp<'_>, Vec<PreparseError>) {
+    preparse_all_errors_with_limits(v, &PreparseLimits::default())
+}
+
+/// Like `preparse_all_errors`, but within `limits`
+#[must_use]
+pub fn preparse_all_errors_with_limits<'a>(
+    v: &'a [u8],
+    limits: &PreparseLimits,
+) -> (PartialProp<'a>, Vec<PreparseError>) {
+    use Problem::*;
+    use Segment::*;
+
+    let mut partial = PartialProp::default();
+    let mut errors = Vec::new();
+    if v.is_empty() {
+        errors.push(EMPTY_CONTENT_LINE);
+        return (partial, errors);
+    }
+    let mut s = Scanner { v, index: 0, in_quote: false, max_values: limits.max_values };
+
+    let name = s.name();
+    if name.val.is_empty() {
+        errors.push(s.problem(Empty(PropertyName)));
+    } else if !(s.at(b';') || s.at(b':')) {
+        errors.push(s.problem(Unterminated(PropertyName)));
+    } else {
+        partial.name = Some(name);
+    }
+    if partial.name.is_none() && !s.skip_parameter() {
+        return (partial, errors);
+    }
+
+    let mut seen = 0;
+    while s.consume(b';') {
+        if seen == limits.max_parameters {
+            errors.push(s.problem(TooMany(ParamName)));
+            while s.skip_parameter() && s.consume(b';') {}
+            if !s.at(b':') {
+                return (partial, errors);
+            }
+            break;
+        }
+        seen += 1;
+        match s.parameter() {
+            Ok(param) => partial.parameters.push(param),
+            Err(err) => {
+                errors.push(err);
+                if !s.skip_parameter() {
+                    return (partial, errors);
+                }
+            }
+        }
+    }
+
+    if s.consume(b':') {
+        let start = s.index;
+        let mut clean = true;
+        while let Err(err) = s.scan(PropertyValue, |_| true) {
+            s.skip_problem(err.problem);
+            errors.push(err);
+            clean = false;
+        }
+        if clean {
+            partial.value = Some(s.loc_str(start));
+        }
+    } else {
+        errors.push(s.problem(Empty(PropertyValue)));
+    }
+    (partial, errors)
+}
+
+struct Scanner<'a> {
+    v: &'a [u8],
+    index: usize,
+    in_quote: bool,
+    max_values: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn at(&self, b: u8) -> bool {
+        self.v.get(self.index) == Some(&b)
+    }
+    fn consume(&mut self, b: u8) -> bool {
+        self.at(b) && {
+            self.index += 1;
+            true
+        }
+    }
+    // Only called on ranges we've scanned, which are valid UTF8, so we never get the default
+    fn loc_str(&self, start: usize) -> LocStr<'a> {
+        LocStr { loc: start, val: str::from_utf8(&self.v[start..self.index]).unwrap_or_default() }
+    }
+    fn error(&self, problem: Problem) -> PreparseError {
+        PreparseError { problem, valid_up_to: self.index }
+    }
+    // A structural problem at the current index — unless the character there is itself a
+    // problem, which takes precedence
+    fn problem(&self, structural: Problem) -> PreparseError {
+        let segment = structural.segment().unwrap_or(Segment::PropertyName);
+        match char_width(self.v, self.index, segment) {
+            Some(Err(problem)) => self.error(problem),
+            _ => self.error(structural),
+        }
+    }
+
+    fn name(&mut self) -> LocStr<'a> {
+        let start = self.index;
+        while self.v.get(self.index).is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'-') {
+            self.index += 1;
+        }
+        self.loc_str(start)
+    }
+    // Advance past (ASCII) characters for which `allowed` is true, and all valid non-ASCII
+    // characters, stopping at a control character or invalid UTF8
+    fn scan(
+        &mut self,
+        segment: Segment,
+        allowed: impl Fn(u8) -> bool,
+    ) -> Result<(), PreparseError> {
+        while let Some(width) = char_width(self.v, self.index, segment) {
+            match width {
+                Ok(1) if !allowed(self.v[self.index]) => break,
+                Ok(width) => self.index += width,
+                Err(problem) => return Err(self.error(problem)),
+            }
+        }
+        Ok(())
+    }
+    fn skip_problem(&mut self, problem: Problem) {
+        self.index = match problem {
+            Problem::Utf8Error(_, Some(len)) => self.index + usize::from(len),
+            Problem::Utf8Error(_, None) => self.v.len(),
+            _ => self.index + 1,
+        };
+    }
+
+    // Called just after the `;` that starts a parameter
+    fn parameter(&mut self) -> Result<Param<'a>, PreparseError> {
+        use Problem::*;
+        use Segment::*;
+
+        let name = self.name();
+        if name.val.is_empty() {
+            return Err(self.problem(Empty(ParamName)));
+        }
+        if !self.consume(b'=') {
+            return Err(self.problem(Unterminated(ParamName)));
+        }
+        let mut values = ParamValues::default();
+        loop {
+            if values.len() == self.max_values {
+                return Err(self.problem(TooMany(ParamValue)));
+            }
+            if self.consume(b'"') {
+                self.in_quote = true;
+                let start = self.index;
+                self.scan(ParamValue, |b| b != b'"')?;
+                if !self.at(b'"') {
+                    return Err(self.error(UnclosedQuote(ParamValue)));
+                }
+                values.push(self.loc_str(start));
+                self.index += 1;
+                self.in_quote = false;
+            } else {
+                let start = self.index;
+                self.scan(ParamValue, |b| !matches!(b, b'"' | b',' | b':' | b';'))?;
+                values.push(self.loc_str(start));
+            }
+            if !self.consume(b',') {
+                break;
+            }
+        }
+        match self.v.get(self.index) {
+            None | Some(b':' | b';') => Ok(Param { name, values }),
+            Some(b'"') => Err(self.error(DoubleQuote(ParamValue))),
+            Some(_) => Err(self.problem(Unterminated(ParamValue))),
+        }
+    }
+    // Skip to the next `;` or `:` after a problem, first finishing the quoted string we were
+    // in, if any. Stray double quotes elsewhere are taken literally. Returns `false` if we
+    // reached the end of the line instead.
+    fn skip_parameter(&mut self) -> bool {
+        if self.in_quote {
+            self.in_quote = false;
+            match self.v[self.index..].iter().position(|b| *b == b'"') {
+                Some(quote) => self.index += quote + 1,
+                None => self.index = self.v.len(),
+            }
+        }
+        match self.v[self.index..].iter().position(|b| matches!(b, b';' | b':')) {
+            Some(delimiter) => {
+                self.index += delimiter;
+                true
+            }
+            None => {
+                self.index = self.v.len();
+                false
+            }
+        }
+    }
+}
+
+// The width of the character at `v[index]` (which is in `segment`), or its problem if it's a
+// control character (other than tab) or invalid UTF8. `None` at the end of `v`.
+fn char_width(v: &[u8], index: usize, segment: Segment) -> Option<Result<usize, Problem>> {
+    let b = *v.get(index)?;
+    Some(match b {
+        b'\t' | b' '..=b'~' => Ok(1),
+        ..=0x7f => Err(Problem::ControlCharacter(segment)),
+        _ => {
+            let window = &v[index..v.len().min(index + 4)];
+            match window.utf8_chunks().next().and_then(|chunk| chunk.valid().chars().next()) {
+                Some(c) => Ok(c.len_utf8()),
+                #[allow(clippy::cast_possible_truncation)]
+                None => Err(Problem::Utf8Error(
+                    segment,
+                    str::from_utf8(window).err().and_then(|e| e.error_len()).map(|len| len as u8),
+                )),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Problem::*;
+    use Segment::*;
+    use pretty_assertions::assert_eq;
+
+    fn problems(text: &[u8]) -> Vec<(Problem, usize)> {
+        let (_, errors) = preparse_all_errors(text);
+        errors.into_iter().map(|err| (err.problem, err.valid_up_to)).collect()
+    }
+    fn names<'a>(partial: &PartialProp<'a>) -> Vec<&'a str> {
+        partial.parameters.iter().map(|param| param.name.val).collect()
+    }
+
+    #[test]
+    fn valid_line_has_no_problems() {
+        let (partial, errors) = preparse_all_errors(br#"DTSTART;TZID="America/New_York":20250303"#);
+        assert_eq!(errors, vec![]);
+        assert_eq!(partial.name.unwrap().val, "DTSTART");
+        assert_eq!(partial.parameters[0].values[0].val, "America/New_York");
+        assert_eq!(partial.value.unwrap().val, "20250303");
+    }
+    #[test]
+    fn bad_parameters_are_skipped() {
+        let text =
+            b"ATTENDEE;=x;ROLE=CHAIR;CN=\"Al\x01ice:\";RSVP=a\"b;PARTSTAT=ACCEPTED:mailto:a@b.c";
+        let (partial, errors) = preparse_all_errors(text);
+        assert_eq!(names(&partial), vec!["ROLE", "PARTSTAT"]);
+        assert_eq!(partial.value.unwrap().val, "mailto:a@b.c");
+        let errors: Vec<_> = errors.into_iter().map(|err| (err.problem, err.valid_up_to)).collect();
+        assert_eq!(
+            errors,
+            vec![
+                (Empty(ParamName), 9),
+                (ControlCharacter(ParamValue), 29),
+                (DoubleQuote(ParamValue), 42)
+            ]
+        );
+    }
+    #[test]
+    fn every_bad_character_in_the_value_is_reported() {
+        assert_eq!(
+            problems(b"SUMMARY:a\x01b\xffc\x7f"),
+            vec![
+                (ControlCharacter(PropertyValue), 9),
+                (Utf8Error(PropertyValue, Some(1)), 11),
+                (ControlCharacter(PropertyValue), 13)
+            ]
+        );
+        let (partial, _) = preparse_all_errors(b"SUMMARY:a\x01b");
+        assert_eq!(partial.name.unwrap().val, "SUMMARY");
+        assert_eq!(partial.value, None);
+    }
+    #[test]
+    fn bad_name() {
+        let (partial, errors) = preparse_all_errors(b"DT START;X=y:20250303");
+        assert_eq!(
+            errors,
+            vec![PreparseError { problem: Unterminated(PropertyName), valid_up_to: 2 }]
+        );
+        assert_eq!(partial.name, None);
+        assert_eq!(names(&partial), vec!["X"]);
+        assert_eq!(partial.value.unwrap().val, "20250303");
+    }
+    #[test]
+    fn no_extra_problem_when_recovery_reaches_the_end() {
+        assert_eq!(problems(br#"X;A="b"#), vec![(UnclosedQuote(ParamValue), 6)]);
+        assert_eq!(problems(b"X;A=b"), vec![(Empty(PropertyValue), 5)]);
+        assert_eq!(problems(b""), vec![(EmptyContentLine, 0)]);
+    }
+}
@@ -0,0 +1,146 @@
+// Preparsing many content lines at once into flat columns, for analytics-style scans that look
+// at one part (say, every property name) of a great many lines. Instead of a `Prop` with its
+// own `Vec`s per line, a `PropBatch` copies the lines into one arena string, and each column
+// is a flat `Vec` of 8-byte `Piece`s: offsets into the arena relative to the start of their
+// line, so that a column of a million names is 8 MB rather than the 16 MB of `Range`s.
+use super::preparse_all_errors;
+use crate::error::{PreparseError, Problem, Segment};
+
+/// The columnar result of `preparse_batch`. Rows are the lines that preparsed cleanly, in
+/// input order; `errors` holds the first problem in each of the others.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PropBatch {
+    arena: String,
+    // The input index of each row, and where its line starts in `arena`
+    lines: Vec<usize>,
+    starts: Vec<usize>,
+    names: Vec<Piece>,
+    values: Vec<Piece>,
+    // Row `i`'s parameters are `param_names[params[i]..params[i + 1]]`
+    params: Vec<usize>,
+    param_names: Vec<Piece>,
+    // Parameter `j`'s values are `param_values[param_value_starts[j]..param_value_starts[j + 1]]`
+    param_values: Vec<Piece>,
+    param_value_starts: Vec<usize>,
+    errors: Vec<(usize, PreparseError)>,
+}
+
+// Part of a line: its offset from the start of the line, and its length
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Piece {
+    offset: u32,
+    len: u32,
+}
+
+/// Preparse `lines` into a `PropBatch`. A line of 4 GiB or more can't be a row, and is
+/// reported as `Problem::TooMany(Segment::PropertyValue)`.
+#[must_use]
+pub fn preparse_batch<'a>(lines: impl IntoIterator<Item = &'a [u8]>) -> PropBatch {
+    let mut batch =
+        PropBatch { params: vec![0], param_value_starts: vec![0], ..PropBatch::default() };
+    for (n, line) in lines.into_iter().enumerate() {
+        if u32::try_from(line.len()).is_err() {
+            let problem = Problem::TooMany(Segment::PropertyValue);
+            batch.errors.push((n, PreparseError { problem, valid_up_to: u32::MAX as usize }));
+            continue;
+        }
+        let (prop, errors) = preparse_all_errors(line);
+        if let Some(err) = errors.into_iter().next() {
+            batch.errors.push((n, err));
+            continue;
+        }
+        let (Some(name), Some(value)) = (prop.name, prop.value) else {
+            unreachable!("a content line without preparse errors has a name and value")
+        };
+        // The line preparsed cleanly, so it's valid UTF8 and we never get the default
+        batch.lines.push(n);
+        batch.starts.push(batch.arena.len());
+        batch.arena.push_str(str::from_utf8(line).unwrap_or_default());
+        // The line is shorter than 4 GiB, so its offsets and lengths fit
+        #[allow(clippy::cast_possible_truncation)]
+        let piece = |loc: usize, val: &str| Piece { offset: loc as u32, len: val.len() as u32 };
+        batch.names.push(piece(name.loc, name.val));
+        batch.values.push(piece(value.loc, value.val));
+        for param in &prop.parameters {
+            batch.param_names.push(piece(param.name.loc, param.name.val));
+            batch.param_values.extend(param.values.iter().map(|v| piece(v.loc, v.val)));
+            batch.param_value_starts.push(batch.param_values.len());
+        }
+        batch.params.push(batch.param_names.len());
+    }
+    batch
+}
+
+impl PropBatch {
+    /// The number of rows (lines that preparsed cleanly)
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+    /// The index, in the input, of row `row`'s line
+    #[must_use]
+    pub fn line(&self, row: usize) -> usize {
+        self.lines[row]
+    }
+    /// The property name column
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.starts.iter().zip(&self.names).map(|(&start, &piece)| self.text(start, piece))
+    }
+    /// The property value column
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.starts.iter().zip(&self.values).map(|(&start, &piece)| self.text(start, piece))
+    }
+    /// Row `row`'s parameters, each a name and its values
+    pub fn parameters(&self, row: usize) -> impl Iterator<Item = (&str, Vec<&str>)> {
+        let start = self.starts[row];
+        (self.params[row]..self.params[row + 1]).map(move |j| {
+            let values =
+                &self.param_values[self.param_value_starts[j]..self.param_value_starts[j + 1]];
+            let values = values.iter().map(|&piece| self.text(start, piece)).collect();
+            (self.text(start, self.param_names[j]), values)
+        })
+    }
+    /// The lines that didn't preparse: each line's input index and its first problem
+    #[must_use]
+    pub fn errors(&self) -> &[(usize, PreparseError)] {
+        &self.errors
+    }
+    // The text of `piece`, of the line starting at `start`
+    fn text(&self, start: usize, piece: Piece) -> &str {
+        let from = start + piece.offset as usize;
+        &self.arena[from..from + piece.len as usize]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::{Problem, Segment};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn columns() {
+        let lines: [&[u8]; 4] = [
+            b"DTSTART;TZID=America/New_York:20250303T090000",
+            b"SUMMARY:Standup",
+            b"BAD LINE",
+            br#"ATTENDEE;ROLE=CHAIR;MEMBER="mailto:a@b.c","mailto:d@e.f":mailto:g@h.i"#,
+        ];
+        let batch = preparse_batch(lines);
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch.names().collect::<Vec<_>>(), vec!["DTSTART", "SUMMARY", "ATTENDEE"]);
+        assert_eq!(batch.values().nth(2), Some("mailto:g@h.i"));
+        assert_eq!(batch.line(2), 3);
+        assert_eq!(
+            batch.parameters(2).collect::<Vec<_>>(),
+            vec![("ROLE", vec!["CHAIR"]), ("MEMBER", vec!["mailto:a@b.c", "mailto:d@e.f"])]
+        );
+        assert_eq!(batch.parameters(1).count(), 0);
+        let problem = Problem::Unterminated(Segment::PropertyName);
+        assert_eq!(batch.errors(), &[(2, PreparseError { problem, valid_up_to: 3 })]);
+    }
+}
@@ -1,22 +1,35 @@
 // RFC 5545 has multiple cases where a "good" ASCII character range has a one-character gap
 #![allow(non_contiguous_range_endpoints)]
-use super::{LocStr, Param, Prop, ToPreparseError, control_character_or};
+use super::{
+    LocStr, Param, ParamValues, PreparseLimits, Prop, control_character_or, first_problem,
+};
 use crate::error::{EMPTY_CONTENT_LINE, PreparseError, Problem, Segment};
 use std::{mem, str};
 pub fn bold_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
+    bold_preparse_with_limits(v, &PreparseLimits::default())
+}
+/// Like `bold_preparse`, with `limits` instead of the default limits
+pub fn bold_preparse_with_limits<'a>(
+    v: &'a [u8],
+    limits: &PreparseLimits,
+) -> Result<Prop<'a>, PreparseError> {
     if v.is_empty() {
         return Err(EMPTY_CONTENT_LINE);
     }
-    match inner_preparse(v) {
+    match inner_preparse(v, limits) {
         Ok(value) => Ok(value),
-        Err(err) => {
-            if let Err(utf8_err) = str::from_utf8(v) {
-                Err(utf8_err.to_preparse_error())
-            } else {
-                Err(control_character_or(err, v))
-            }
-        }
+        Err(err) => Err(first_problem(err, v)),
+    }
+}
+/// Like `bold_preparse`, for a content line that's already known to be valid UTF8. The
+/// byte-by-byte scanner validates UTF8 as it goes, so this only saves revalidating the line
+/// when there's a problem.
+pub fn bold_preparse_str(v: &str) -> Result<Prop<'_>, PreparseError> {
+    if v.is_empty() {
+        return Err(EMPTY_CONTENT_LINE);
     }
+    inner_preparse(v.as_bytes(), &PreparseLimits::default())
+        .map_err(|err| control_character_or(err, v.as_bytes()))
 }
 // Return an error: the input doesn't correspond to the basic grammar in RFC 5545 § 3.1
 macro_rules! rfc_err {
@@ -28,7 +41,7 @@ macro_rules! rfc_err {
 fn finish_parameter<'a>(
     parameters: &mut Vec<Param<'a>>,
     name: &mut LocStr<'a>,
-    values: &mut Vec<LocStr<'a>>,
+    values: &mut ParamValues<'a>,
 ) {
     if !name.val.is_empty() {
         parameters.push(Param { name: mem::take(name), values: mem::take(values) });
@@ -40,7 +53,7 @@ unsafe fn loc_str(v: &[u8], start: usize, index: usize) -> LocStr<'_> {
     debug_assert!(str::from_utf8(&v[start..index]).is_ok());
     LocStr { loc: start, val: unsafe { str::from_utf8_unchecked(v.get_unchecked(start..index)) } }
 }
-pub fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
+pub fn inner_preparse<'a>(v: &'a [u8], limits: &PreparseLimits) -> Result<Prop<'a>, PreparseError> {
     if v.is_empty() {
         return Err(EMPTY_CONTENT_LINE);
     }
@@ -75,12 +88,15 @@ pub fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
     }
 
     let mut param_name = LocStr::default();
-    let mut param_values = Vec::<LocStr>::new();
+    let mut param_values = ParamValues::default();
     let mut parameters = Vec::<Param>::new();
     let property_name = unsafe { loc_str(v, start, index) };
 
     'outer: while index < len && v[index] == b';' {
         finish_parameter(&mut parameters, &mut param_name, &mut param_values);
+        if parameters.len() == limits.max_parameters {
+            return Err(PreparseError { problem: TooMany(ParamName), valid_up_to: index + 1 });
+        }
         (start, index) = (index + 1, rfc5545_name(v, index + 1));
         if index == start {
             rfc_err!(Empty(ParamName), index)
@@ -97,7 +113,7 @@ pub fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
                 rfc_err!(Unterminated(ParamName), index)
             }
         }
-        index = list(v, index, &mut param_values)?;
+        index = list(v, index, &mut param_values, limits.max_values)?;
         if index >= len {
             break;
         }
@@ -110,7 +126,10 @@ pub fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
     }
     if index < len && v[index] == b':' {
         finish_parameter(&mut parameters, &mut param_name, &mut param_values);
-        (start, index) = (index + 1, property_value(v, index + 1)?);
+        // A value that's all printable ASCII is the rest of the line
+        let end =
+            if is_printable_ascii(&v[index + 1..]) { len } else { property_value(v, index + 1)? };
+        (start, index) = (index + 1, end);
         Ok(Prop { name: property_name, parameters, value: unsafe { loc_str(v, start, index) } })
     } else {
         rfc_err!(Empty(PropertyValue), index);
@@ -120,11 +139,15 @@ pub fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
 pub fn list<'a>(
     v: &'a [u8],
     mut index: usize,
-    param_values: &mut Vec<LocStr<'a>>,
+    param_values: &mut ParamValues<'a>,
+    max_values: usize,
 ) -> Result<usize, PreparseError> {
     use Problem::*;
     let len = v.len();
     let mut start = index;
+    if max_values == 0 {
+        rfc_err!(TooMany(Segment::ParamValue), index)
+    }
     while index < len {
         if v[index] == b'"' {
             (start, index) = (index + 1, param_quoted(v, index + 1)?);
@@ -136,7 +159,7 @@ pub fn list<'a>(
                     param_values.push(unsafe { loc_str(v, start, index) });
                     index += 1;
                 }
-                _ => rfc_err!(ControlCharacter, index),
+                _ => rfc_err!(ControlCharacter(Segment::ParamValue), index),
             }
         } else {
             (start, index) = (start, param_text(v, start)?);
@@ -146,6 +169,9 @@ pub fn list<'a>(
             return Ok(index);
         } else {
             (index, start) = (index + 1, index + 1);
+            if param_values.len() == max_values {
+                rfc_err!(TooMany(Segment::ParamValue), index)
+            }
         }
     }
     Ok(index)
@@ -167,7 +193,7 @@ fn param_text(v: &[u8], mut index: usize) -> Result<usize, PreparseError> {
     while index < v.len() {
         match v[index] {
             b'\t' | b' '..b'"' | b'#'..b',' | b'-'..b':' | b'<'..127 => index += 1,
-            128.. => index = handle_non_ascii(v, index)?,
+            128.. => index = handle_non_ascii(v, index, Segment::ParamValue)?,
             _ => break,
         }
     }
@@ -177,7 +203,7 @@ fn param_quoted(v: &[u8], mut index: usize) -> Result<usize, PreparseError> {
     while index < v.len() {
         match v[index] {
             b'\t' | b' '..b'"' | b'#'..127 => index += 1,
-            128.. => index = handle_non_ascii(v, index)?,
+            128.. => index = handle_non_ascii(v, index, Segment::ParamValue)?,
             _ => break,
         }
     }
@@ -187,24 +213,41 @@ fn property_value(v: &[u8], mut index: usize) -> Result<usize, PreparseError> {
     while index < v.len() {
         match v[index] {
             b'\t' | b' '..127 => index += 1,
-            128.. => index = handle_non_ascii(v, index)?,
-            _ => rfc_err!(Problem::ControlCharacter, index),
+            128.. => index = handle_non_ascii(v, index, Segment::PropertyValue)?,
+            _ => rfc_err!(Problem::ControlCharacter(Segment::PropertyValue), index),
         }
     }
     Ok(index)
 }
 
+// Whether every byte of `v` is printable ASCII or a tab, checking eight bytes at a time: no
+// byte may have its high bit set, and a chunk with a byte below space or a DEL (which may
+// just be a tab) is checked byte by byte. (The "has a byte less than n" test is exact when no
+// byte has its high bit set.)
+fn is_printable_ascii(v: &[u8]) -> bool {
+    const ONES: u64 = u64::from_ne_bytes([1; 8]);
+    const HIGH: u64 = ONES << 7;
+    let printable = |b: &u8| matches!(b, b'\t' | b' '..127);
+    let (chunks, rest) = v.as_chunks::<8>();
+    chunks.iter().all(|chunk| {
+        let x = u64::from_ne_bytes(*chunk);
+        let del = x ^ (0x7f * ONES);
+        let suspect = (x.wrapping_sub(0x20 * ONES) & !x) | (del.wrapping_sub(ONES) & !del);
+        x & HIGH == 0 && (suspect & HIGH == 0 || chunk.iter().all(printable))
+    }) && rest.iter().all(printable)
+}
+
 // Modeled after `run_utf8_validation` in
 // lib/rustlib/src/rust/library/core/src/str/validations.rs
 // Panics if `index >= v.len()`
 #[allow(clippy::cast_possible_wrap, clippy::unnested_or_patterns)]
-fn handle_non_ascii(v: &[u8], mut index: usize) -> Result<usize, PreparseError> {
+fn handle_non_ascii(v: &[u8], mut index: usize, segment: Segment) -> Result<usize, PreparseError> {
     let len = v.len();
     while index < len {
         let old_offset = index;
         macro_rules! utf8_err {
             ($error_len: expr) => {
-                rfc_err!(Problem::Utf8Error($error_len), old_offset)
+                rfc_err!(Problem::Utf8Error(segment, $error_len), old_offset)
             };
         }
         macro_rules! next {
@@ -313,3 +356,21 @@ const UTF8_CHAR_WIDTH: &[u8; 256] = &[
 fn utf8_char_width(b: u8) -> usize {
     UTF8_CHAR_WIDTH[b as usize] as usize
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn printable_ascii() {
+        let line = b"DESCRIPTION;LANGUAGE=en:Tabs\tare fine~";
+        assert!(is_printable_ascii(line));
+        for bad in [b'\0', b'\n', 0x1f, 0x7f, 0x80, 0xff] {
+            for at in [0, 7, 8, 30, line.len() - 1] {
+                let mut line = line.to_vec();
+                line[at] = bad;
+                assert!(!is_printable_ascii(&line), "{bad:#x} at {at}");
+            }
+        }
+    }
+}
@@ -1,14 +1,37 @@
 //! Operations related to RFC 5545 validation.
-use crate::error::{PreparseError, Problem};
-use std::str;
+use crate::error::{PreparseError, Problem, Segment};
+use std::{mem, ops, slice, str};
+mod all_errors;
+pub use all_errors::{PartialProp, preparse_all_errors, preparse_all_errors_with_limits};
+mod batch;
+pub use batch::{PropBatch, preparse_batch};
+mod owned;
+pub use owned::{OwnedLocStr, OwnedParam, OwnedProp};
 #[cfg(feature = "cautious")]
 mod with_regex;
 #[cfg(feature = "cautious")]
-pub use with_regex::cautious_preparse;
+pub use with_regex::{cautious_preparse, cautious_preparse_str, cautious_preparse_with_limits};
 #[cfg(feature = "bold")]
 mod byte_by_byte;
 #[cfg(feature = "bold")]
-pub use byte_by_byte::bold_preparse;
+pub use byte_by_byte::{bold_preparse, bold_preparse_str, bold_preparse_with_limits};
+
+/// Bounds on what one content line can make a preparser allocate, so hostile input (say, a
+/// line of thousands of `;A=` parameters) can't force huge allocations. Exceeding a limit is
+/// a `Problem::TooMany` error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreparseLimits {
+    /// The most parameters a property may have
+    pub max_parameters: usize,
+    /// The most values a single parameter may have
+    pub max_values: usize,
+}
+impl Default for PreparseLimits {
+    /// Far more than any real calendar uses
+    fn default() -> Self {
+        PreparseLimits { max_parameters: 256, max_values: 1024 }
+    }
+}
 
 /// A located `str`: a substring of a larger string, along with its location in that string.
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -19,7 +42,48 @@ pub struct LocStr<'a> {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Param<'a> {
     pub(crate) name: LocStr<'a>,
-    pub(crate) values: Vec<LocStr<'a>>,
+    pub(crate) values: ParamValues<'a>,
+}
+/// A parameter's values. Almost every parameter in real calendars has exactly one value, so
+/// a single value is kept inline, and only a multi-valued parameter allocates.
+#[derive(Debug, Default, Clone)]
+pub(crate) enum ParamValues<'a> {
+    #[default]
+    Empty,
+    One(LocStr<'a>),
+    Many(Vec<LocStr<'a>>),
+}
+impl<'a> ParamValues<'a> {
+    pub(crate) fn push(&mut self, value: LocStr<'a>) {
+        *self = match mem::take(self) {
+            ParamValues::Empty => ParamValues::One(value),
+            ParamValues::One(first) => ParamValues::Many(vec![first, value]),
+            ParamValues::Many(mut values) => {
+                values.push(value);
+                ParamValues::Many(values)
+            }
+        }
+    }
+    // Whether the values are on the heap (only `tests` asks)
+    #[cfg(all(test, feature = "cautious", feature = "bold"))]
+    pub(crate) fn spilled(&self) -> bool {
+        matches!(self, ParamValues::Many(_))
+    }
+}
+impl<'a> ops::Deref for ParamValues<'a> {
+    type Target = [LocStr<'a>];
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ParamValues::Empty => &[],
+            ParamValues::One(value) => slice::from_ref(value),
+            ParamValues::Many(values) => values,
+        }
+    }
+}
+impl PartialEq for ParamValues<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
 }
 #[derive(Debug, Clone, PartialEq)]
 pub struct Prop<'a> {
@@ -27,37 +91,106 @@ pub struct Prop<'a> {
     pub(crate) parameters: Vec<Param<'a>>,
     pub(crate) value: LocStr<'a>,
 }
+impl<'a> LocStr<'a> {
+    /// The substring itself
+    #[must_use]
+    pub fn val(&self) -> &'a str {
+        self.val
+    }
+}
+impl<'a> Param<'a> {
+    /// The parameter name, as written
+    #[must_use]
+    pub fn name(&self) -> &LocStr<'a> {
+        &self.name
+    }
+    /// The parameter values, without any surrounding double quotes
+    #[must_use]
+    pub fn values(&self) -> &[LocStr<'a>] {
+        &self.values
+    }
+}
+impl<'a> Prop<'a> {
+    /// The parameters, in the order they appear in the content line
+    #[must_use]
+    pub fn parameters(&self) -> &[Param<'a>] {
+        &self.parameters
+    }
+    /// The property value (everything after the first unquoted colon)
+    #[must_use]
+    pub fn value(&self) -> &LocStr<'a> {
+        &self.value
+    }
+}
 
 // Content lines must be valid UTF8 and contain no ASCII control characters except tabs.
 //
-//`invalid_character_or` ensures that invalid UTF8 is reported even if other errors occur
-// earlier in `v`, and if `v` is valid UTF8, ensures that invalid ASCII control characters are
-//reported even if parsing errors occur earlier.
+// When a content line has more than one problem, both preparsers report the same one:
+//  * the problem at the earliest byte offset wins, and
+//  * at equal offsets, an invalid UTF8 sequence or a control character beats a structural
+//    problem (a missing colon, an unclosed quote, and so on).
+// `first_problem` implements that precedence, given the first structural problem a preparser
+// found (or an invalid UTF8 sequence or control character, if that's what stopped it).
 
 trait ToPreparseError {
-    fn to_preparse_error(&self) -> PreparseError;
+    fn to_preparse_error(&self, segment: Segment) -> PreparseError;
 }
 impl ToPreparseError for str::Utf8Error {
-    fn to_preparse_error(&self) -> PreparseError {
+    fn to_preparse_error(&self, segment: Segment) -> PreparseError {
         #[allow(clippy::cast_possible_truncation)]
         PreparseError {
-            problem: Problem::Utf8Error(self.error_len().map(|len| len as u8)),
+            problem: Problem::Utf8Error(segment, self.error_len().map(|len| len as u8)),
             valid_up_to: self.valid_up_to(),
         }
     }
 }
 
+fn first_problem(err: PreparseError, v: &[u8]) -> PreparseError {
+    match str::from_utf8(v) {
+        Err(utf8_err)
+            if utf8_err.valid_up_to() <= err.valid_up_to
+                && !matches!(err.problem, Problem::Utf8Error(..)) =>
+        {
+            utf8_err.to_preparse_error(segment_at(v, utf8_err.valid_up_to()))
+        }
+        _ => control_character_or(err, v),
+    }
+}
+
 fn control_character_or(err: PreparseError, v: &[u8]) -> PreparseError {
-    if matches!(err.problem, Problem::Utf8Error(_)) || err.valid_up_to == v.len() {
+    if matches!(err.problem, Problem::Utf8Error(..)) || err.valid_up_to == v.len() {
         return err;
     }
     let b = v[err.valid_up_to];
     if b.is_ascii_control() && b != b'\t' {
-        PreparseError { problem: Problem::ControlCharacter, valid_up_to: err.valid_up_to }
+        let problem = Problem::ControlCharacter(segment_at(v, err.valid_up_to));
+        PreparseError { problem, valid_up_to: err.valid_up_to }
     } else {
         err
     }
 }
 
+// The segment that `v[index]` belongs to, assuming `v[..index]` is well formed. A byte where a
+// delimiter was expected belongs to the segment the delimiter would have ended.
+fn segment_at(v: &[u8], index: usize) -> Segment {
+    use Segment::*;
+    let mut segment = PropertyName;
+    let mut in_quote = false;
+    for b in &v[..index] {
+        segment = match (segment, *b) {
+            (PropertyValue, _) => return PropertyValue,
+            (ParamValue, b'"') => {
+                in_quote = !in_quote;
+                ParamValue
+            }
+            (PropertyName | ParamValue, b';') if !in_quote => ParamName,
+            (PropertyName | ParamValue, b':') if !in_quote => PropertyValue,
+            (ParamName, b'=') => ParamValue,
+            (segment, _) => segment,
+        };
+    }
+    segment
+}
+
 #[cfg(test)]
 mod tests;
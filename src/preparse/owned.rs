@@ -0,0 +1,90 @@
+// Preparsed properties that own their text, to keep after the input buffer is gone
+use super::{LocStr, Param, ParamValues, Prop};
+
+/// A `LocStr` that owns its text
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OwnedLocStr {
+    pub loc: usize,
+    pub val: String,
+}
+impl From<&LocStr<'_>> for OwnedLocStr {
+    fn from(s: &LocStr<'_>) -> Self {
+        OwnedLocStr { loc: s.loc, val: s.val.to_string() }
+    }
+}
+impl OwnedLocStr {
+    fn borrow(&self) -> LocStr<'_> {
+        LocStr { loc: self.loc, val: &self.val }
+    }
+}
+
+/// A `Param` that owns its text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedParam {
+    pub name: OwnedLocStr,
+    pub values: Vec<OwnedLocStr>,
+}
+
+/// A `Prop` that owns its text, made by `Prop::into_owned`. Locations are still byte offsets
+/// into the content line the property came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedProp {
+    pub name: OwnedLocStr,
+    pub parameters: Vec<OwnedParam>,
+    pub value: OwnedLocStr,
+}
+
+impl OwnedProp {
+    /// A borrowed view of the property, for functions that take a `Prop`
+    #[must_use]
+    pub fn as_prop(&self) -> Prop<'_> {
+        let parameters = (self.parameters.iter())
+            .map(|param| {
+                let mut values = ParamValues::default();
+                for value in &param.values {
+                    values.push(value.borrow());
+                }
+                Param { name: param.name.borrow(), values }
+            })
+            .collect();
+        Prop { name: self.name.borrow(), parameters, value: self.value.borrow() }
+    }
+}
+
+impl Prop<'_> {
+    /// A copy of the property that doesn't borrow the content line
+    #[must_use]
+    pub fn into_owned(self) -> OwnedProp {
+        OwnedProp::from(&self)
+    }
+}
+impl From<&Prop<'_>> for OwnedProp {
+    fn from(prop: &Prop<'_>) -> Self {
+        let parameters = (prop.parameters.iter())
+            .map(|param| OwnedParam {
+                name: (&param.name).into(),
+                values: param.values.iter().map(OwnedLocStr::from).collect(),
+            })
+            .collect();
+        OwnedProp { name: (&prop.name).into(), parameters, value: (&prop.value).into() }
+    }
+}
+
+#[cfg(all(test, feature = "cautious"))]
+mod test {
+    use crate::preparse::cautious_preparse;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn outlives_the_line() {
+        const LINE: &str = "ATTENDEE;ROLE=CHAIR;MEMBER=\"a\",\"b\":mailto:c@example.com";
+        let owned = {
+            let line = String::from(LINE);
+            cautious_preparse(line.as_bytes()).unwrap().into_owned()
+        };
+        assert_eq!(owned.name.val, "ATTENDEE");
+        assert_eq!(owned.parameters[1].values.len(), 2);
+        assert_eq!((owned.value.loc, owned.value.val.as_str()), (35, "mailto:c@example.com"));
+        assert_eq!(owned.as_prop(), cautious_preparse(LINE.as_bytes()).unwrap());
+    }
+}
@@ -6,7 +6,7 @@ use Segment::*;
 use bstr::{BString, ByteSlice};
 use pretty_assertions::assert_eq;
 
-fn equivalent_from_bytes(text: &[u8]) -> Result<Prop, PreparseError> {
+fn equivalent_from_bytes(text: &[u8]) -> Result<Prop<'_>, PreparseError> {
     let bold = bold_preparse(text);
     let cautious = cautious_preparse(text);
     assert_eq!(
@@ -17,14 +17,31 @@ fn equivalent_from_bytes(text: &[u8]) -> Result<Prop, PreparseError> {
         bold,
         cautious
     );
+    agrees_with_all_errors(text, &bold);
     bold
 }
-fn equivalent(text: &str) -> Result<Prop, PreparseError> {
+fn equivalent(text: &str) -> Result<Prop<'_>, PreparseError> {
     let bold = bold_preparse(text.as_bytes());
     let cautious = cautious_preparse(text.as_bytes());
     assert_eq!(bold, cautious, "bold!=cautious, text: {text}");
+    assert_eq!(bold_preparse_str(text), bold, "bold_preparse_str, text: {text}");
+    assert_eq!(cautious_preparse_str(text), bold, "cautious_preparse_str, text: {text}");
+    agrees_with_all_errors(text.as_bytes(), &bold);
     bold
 }
+// `preparse_all_errors` finds the same first problem, and parses valid lines the same way
+fn agrees_with_all_errors(text: &[u8], expected: &Result<Prop<'_>, PreparseError>) {
+    let (partial, errors) = preparse_all_errors(text);
+    match expected {
+        Ok(prop) => {
+            assert_eq!(errors, vec![], "text: {:?}", text.as_bstr());
+            let parameters = prop.parameters.clone();
+            let (name, value) = (Some(prop.name.clone()), Some(prop.value.clone()));
+            assert_eq!(partial, PartialProp { name, parameters, value });
+        }
+        Err(err) => assert_eq!(errors.first(), Some(err), "text: {:?}", text.as_bstr()),
+    }
+}
 fn err_for(text: &str) -> Problem {
     let err = equivalent(text).unwrap_err();
     err.problem
@@ -76,7 +93,7 @@ fn forbid_dquote_after_ending_dquote() {
 fn forbid_control_character_after_ending_dquote() {
     let mut text = BString::from(r#"A;B="c" ,"d":val"#);
     text[7] = 3;
-    assert_eq!(err_from_bytes(&text), ControlCharacter);
+    assert_eq!(err_from_bytes(&text), ControlCharacter(ParamValue));
 }
 #[test]
 fn property_name_required() {
@@ -98,23 +115,92 @@ fn must_be_utf8_len_2() {
     //let mut bad = BString::from("abc𒀁");
     let len = bad.len();
     bad[len - 1] = b'a';
-    assert_eq!(err_from_bytes(bad.as_slice()), Utf8Error(Some(1)), "text: {:?}", bad);
+    assert_eq!(
+        err_from_bytes(bad.as_slice()),
+        Utf8Error(PropertyValue, Some(1)),
+        "text: {:?}",
+        bad
+    );
 }
 #[test]
 fn must_be_utf8_len_4() {
     let mut bad = BString::from("abc𒀁");
     let len = bad.len();
     bad[len - 2] = b'a';
-    assert_eq!(err_from_bytes(bad.as_slice()), Utf8Error(Some(2)), "text: {:?}", bad);
+    assert_eq!(err_from_bytes(bad.as_slice()), Utf8Error(PropertyName, Some(2)), "text: {:?}", bad);
+}
+#[test]
+fn earliest_problem_wins() {
+    assert_eq!(err_from_bytes(b"Foo;=bar:\xFF"), Empty(ParamName));
+    assert_eq!(err_from_bytes(b"Foo;B=\"x\"y:\xFF"), Unterminated(ParamValue));
+    assert_eq!(err_from_bytes(b"Foo;B=x\x01:\xFF"), ControlCharacter(ParamValue));
+    assert_eq!(err_from_bytes(b"Foo\xFF;=bar"), Utf8Error(PropertyName, Some(1)));
+}
+#[test]
+fn utf8_beats_structural_problem_at_same_offset() {
+    assert_eq!(err_from_bytes(b"Foo\xFF:bar"), Utf8Error(PropertyName, Some(1)));
+    assert_eq!(err_from_bytes(b"Foo;B\xFF=x:bar"), Utf8Error(ParamName, Some(1)));
+    assert_eq!(err_from_bytes(b"Foo;B=\"x\xFF"), Utf8Error(ParamValue, Some(1)));
 }
 
+// This was slow because it was the first call to `cautious_preparse` in its test thread, which
+// compiles the regexes. The line itself is quick, as `cautious_scans_each_byte_once` checks.
 #[test]
 fn fuzz_says_this_is_slow_but_i_dont_know_why() {
     let bad = b"R\xc7F=;6=;A=;B=;A=\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
               \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
               \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
               \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff;A=;4=;A=;A=;B=;A6;";
-    assert_eq!(err_from_bytes(bad.as_slice()), Utf8Error(Some(1)));
+    assert_eq!(err_from_bytes(bad.as_slice()), Utf8Error(PropertyName, Some(1)));
+}
+
+#[test]
+fn too_many_parameters_or_values() {
+    let limits = PreparseLimits { max_parameters: 2, max_values: 3 };
+    let both = |text: &str| {
+        let bold = bold_preparse_with_limits(text.as_bytes(), &limits);
+        assert_eq!(bold, cautious_preparse_with_limits(text.as_bytes(), &limits), "text: {text}");
+        bold.map(|prop| prop.parameters.len()).map_err(|err| (err.problem, err.valid_up_to))
+    };
+    assert_eq!(both("X;A=1;B=2:v"), Ok(2));
+    assert_eq!(both("X;A=1;B=2;C=3:v"), Err((TooMany(ParamName), 10)));
+    assert_eq!(both("X;A=1,2,3:v"), Ok(1));
+    assert_eq!(both("X;A=1,2,3,\"4\":v"), Err((TooMany(ParamValue), 10)));
+    assert_eq!(both("X;A=1,2,3,"), Err((TooMany(ParamValue), 10)));
+    assert_eq!(both("X;A=1;B=2;\x01"), Err((ControlCharacter(ParamName), 10)));
+    let (partial, errors) = preparse_all_errors_with_limits(b"X;A=1;B=2;C=3:v", &limits);
+    assert_eq!(errors.iter().map(|err| err.problem).collect::<Vec<_>>(), [TooMany(ParamName)]);
+    assert_eq!(partial.parameters.len(), 2);
+
+    // The default limits, which `preparse_all_errors` also uses
+    let limits = PreparseLimits::default();
+    let text = format!("X{}:v", ";A=1".repeat(limits.max_parameters + 2));
+    let problem = err_for(&text);
+    assert_eq!(problem, TooMany(ParamName));
+    let (partial, errors) = preparse_all_errors(text.as_bytes());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(partial.parameters.len(), limits.max_parameters);
+    assert_eq!(partial.value.map(|v| v.val), Some("v"));
+    let text = format!("X;A=1{}:v", ",1".repeat(limits.max_values));
+    assert_eq!(err_for(&text), TooMany(ParamValue));
+}
+
+#[test]
+fn cautious_matches_each_byte_once() {
+    use super::with_regex::MATCHED;
+    let limits = PreparseLimits { max_parameters: usize::MAX, max_values: usize::MAX };
+    let n = 5000;
+    for text in [
+        format!("X{}:v", ";A=".repeat(n)),
+        format!("X;A={}:v", ",".repeat(n)),
+        format!("X;A={}\"\":v", "\"a\",".repeat(n)),
+        format!("X{}\x01", ";A=".repeat(n)),
+    ] {
+        MATCHED.with(|matched| matched.set(0));
+        let _ = cautious_preparse_with_limits(text.as_bytes(), &limits);
+        let matched = MATCHED.with(std::cell::Cell::get);
+        assert!(matched <= text.len(), "matched {matched} bytes of {}", text.len());
+    }
 }
 
 // Tests for the result returned
@@ -311,3 +397,22 @@ fn z_semi_z_qqq() {
     eprintln!("{text}");
     compare(text.as_bytes());
 }
+
+// On the corpus, only the rare multi-valued parameter allocates for its values
+#[test]
+fn single_values_stay_inline() {
+    use crate::unfolded::BufReadContent;
+    let (mut parameters, mut spilled) = (0, 0);
+    for file in std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/corpus")).unwrap() {
+        let input = std::fs::read(file.unwrap().path()).unwrap();
+        for (_, line) in std::io::Cursor::new(input).content_lines().map(Result::unwrap) {
+            for param in equivalent_from_bytes(&line).unwrap().parameters {
+                assert_eq!(param.values.spilled(), param.values.len() > 1, "line: {line}");
+                parameters += 1;
+                spilled += usize::from(param.values.spilled());
+            }
+        }
+    }
+    eprintln!("{spilled} of {parameters} parameters allocate for their values");
+    assert!(parameters > 0 && spilled * 10 < parameters);
+}
@@ -1,8 +1,11 @@
 use crate::error::{EMPTY_CONTENT_LINE, PreparseError, Problem, Segment};
-use regex::Regex;
+use regex::{Match, Regex};
 use std::{mem, str, sync::LazyLock};
 
-use super::{LocStr, Param, Prop, ToPreparseError, control_character_or};
+use super::{
+    LocStr, Param, ParamValues, PreparseLimits, Prop, ToPreparseError, control_character_or,
+    first_problem,
+};
 static NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\A[a-zA-Z0-9-]+"#).unwrap());
 static VALUE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\A[^\x00-\x08\x0A-\x1F\x7F]*").unwrap());
@@ -12,25 +15,64 @@ static QUOTED: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"\A"[^\x00-\x08\x0A-\x1F\x7F"]*"#).unwrap());
 
 pub fn cautious_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
+    cautious_preparse_with_limits(v, &PreparseLimits::default())
+}
+/// Like `cautious_preparse`, with `limits` instead of the default limits
+pub fn cautious_preparse_with_limits<'a>(
+    v: &'a [u8],
+    limits: &PreparseLimits,
+) -> Result<Prop<'a>, PreparseError> {
     if v.is_empty() {
         return Err(EMPTY_CONTENT_LINE);
     }
-    match inner_preparse(v) {
-        Ok(value) => Ok(value),
-        Err(err) => Err(control_character_or(err, v)),
+    // If `v` isn't valid UTF8 we parse the valid prefix, so that a structural problem earlier
+    // in the line is reported in preference to the invalid UTF8 sequence.
+    let (valid, utf8_err) = match str::from_utf8(v) {
+        Ok(valid) => (valid, None),
+        Err(utf8_err) => {
+            // `v[..valid_up_to]` is valid UTF8, so we never get the default
+            let valid = str::from_utf8(&v[..utf8_err.valid_up_to()]).unwrap_or_default();
+            (valid, Some(utf8_err))
+        }
+    };
+    match (inner_preparse(valid, limits), utf8_err) {
+        (Ok(value), None) => Ok(value),
+        (Ok(_), Some(utf8_err)) => Err(utf8_err.to_preparse_error(Segment::PropertyValue)),
+        (Err(err), _) => Err(first_problem(err, v)),
+    }
+}
+/// Like `cautious_preparse`, for a content line that's already known to be valid UTF8 — so
+/// we skip validating it again.
+pub fn cautious_preparse_str(v: &str) -> Result<Prop<'_>, PreparseError> {
+    if v.is_empty() {
+        return Err(EMPTY_CONTENT_LINE);
     }
+    inner_preparse(v, &PreparseLimits::default())
+        .map_err(|err| control_character_or(err, v.as_bytes()))
+}
+// Every regex match starts where the previous one ended, so the matches cover each byte of the
+// line at most once. Each regex is anchored and stops at the first byte outside its class, so
+// it examines at most one byte past its match, and preparsing is linear. In tests we total
+// the match lengths to make sure no change starts re-matching (see
+// `preparse::tests::cautious_matches_each_byte_once`).
+#[cfg(test)]
+thread_local! {
+    pub(super) static MATCHED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+#[inline]
+fn find<'h>(re: &Regex, v: &'h str) -> Option<Match<'h>> {
+    let m = re.find(v);
+    #[cfg(test)]
+    MATCHED.with(|matched| matched.set(matched.get() + m.map_or(0, |m| m.end())));
+    m
 }
-fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
+fn inner_preparse<'a>(mut v: &'a str, limits: &PreparseLimits) -> Result<Prop<'a>, PreparseError> {
     use Problem::*;
     use Segment::*;
 
-    let mut v = match str::from_utf8(v) {
-        Ok(v) => v,
-        Err(utf8_err) => return Err(utf8_err.to_preparse_error()),
-    };
     let mut start = 0;
     let mut param_name;
-    let mut param_values = Vec::<LocStr>::new();
+    let mut param_values = ParamValues::default();
     let mut parameters = Vec::<Param>::new();
 
     macro_rules! loc_str {
@@ -68,7 +110,7 @@ fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
         };
     }
 
-    let Some(m) = NAME.find(v) else {
+    let Some(m) = find(&NAME, v) else {
         err!(Empty(PropertyName), start);
     };
     let property_name = loc_str!(m);
@@ -79,14 +121,20 @@ fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
 
     let mut segment = PropertyValue;
     while consume!(b';') {
-        let Some(m) = NAME.find(v) else { err!(Empty(ParamName), start) };
+        if parameters.len() == limits.max_parameters {
+            err!(TooMany(ParamName), start)
+        }
+        let Some(m) = find(&NAME, v) else { err!(Empty(ParamName), start) };
         param_name = loc_str!(m);
         advance_past!(m);
         consume!(b'=' else err!(Unterminated(ParamName), start));
 
         segment = ParamValue;
         loop {
-            if let Some(m) = QUOTED.find(v) {
+            if param_values.len() == limits.max_values {
+                err!(TooMany(ParamValue), start)
+            }
+            if let Some(m) = find(&QUOTED, v) {
                 let quote = m.end();
                 if quote < v.len() && v.as_bytes()[quote] == b'"' {
                     let loc = start + 1;
@@ -97,7 +145,7 @@ fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
                     err!(UnclosedQuote(ParamValue), start + quote)
                 }
             } else {
-                let m = TEXT.find(v).unwrap(); // SAFETY: TEXT matches the empty string
+                let m = find(&TEXT, v).unwrap(); // SAFETY: TEXT matches the empty string
                 param_values.push(loc_str!(m));
                 advance_past!(m);
             }
@@ -108,7 +156,7 @@ fn inner_preparse(v: &[u8]) -> Result<Prop<'_>, PreparseError> {
     }
 
     if consume!(b':') {
-        let m = VALUE.find(v).unwrap(); // SAFETY: VALUE matches the empty string
+        let m = find(&VALUE, v).unwrap(); // SAFETY: VALUE matches the empty string
         if m.end() == v.len() {
             return Ok(Prop { name: property_name, value: loc_str!(m), parameters });
         } else {
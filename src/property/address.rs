@@ -0,0 +1,80 @@
+// Email addresses in `CAL-ADDRESS` values ([RFC 5545 § 3.3.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.3.3)).
+// The value should be a `mailto:` URI, but real calendars also have `MAILTO:`, stray
+// whitespace, a `?subject=` query, angle brackets around the address, and bare addresses with
+// no scheme at all; we take all of those. Other schemes (like `urn:uuid:`) have no address.
+
+/// The email address in a `CAL-ADDRESS` value, like `jane@example.com` for
+/// `mailto:jane@example.com`. The `mailto` scheme may be in any case, and may be missing;
+/// whitespace, angle brackets around the address, and a `?` query after it are ignored. It's
+/// `None` for other schemes, and for anything that isn't `local@domain`.
+#[must_use]
+pub fn email_address(cal_address: &str) -> Option<&str> {
+    let value = cal_address.trim();
+    let address = match value.split_once(':') {
+        Some((scheme, rest)) if scheme.trim().eq_ignore_ascii_case("mailto") => rest.trim(),
+        Some(_) => return None,
+        None => value,
+    };
+    let address = address.split_once('?').map_or(address, |(address, _)| address);
+    let address = match address.rsplit_once('<') {
+        Some((_, bracketed)) => bracketed.strip_suffix('>')?,
+        None => address,
+    };
+    let (local, domain) = address.split_once('@')?;
+    let valid = |part: &str| {
+        !part.is_empty() && !part.contains(|c: char| c.is_whitespace() || "@<>\",;:".contains(c))
+    };
+    (valid(local) && valid(domain)).then_some(address)
+}
+
+/// Whether two `CAL-ADDRESS` values are the same person: their email addresses match,
+/// ignoring case, or (when either has no email address) the values are identical
+#[must_use]
+pub fn same_address(a: &str, b: &str) -> bool {
+    match (email_address(a), email_address(b)) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn email_addresses() {
+        for value in [
+            "mailto:jane@example.com",
+            "MAILTO:jane@example.com",
+            " Mailto: jane@example.com ",
+            "mailto:jane@example.com?subject=Lunch",
+            "mailto:Jane Doe <jane@example.com>",
+            "jane@example.com",
+        ] {
+            assert_eq!(email_address(value), Some("jane@example.com"), "{value}");
+        }
+        for value in [
+            "urn:uuid:f81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+            "https://example.com/jane",
+            "mailto:",
+            "mailto:jane",
+            "mailto:@example.com",
+            "mailto:jane@",
+            "mailto:jane doe@example.com",
+            "mailto:a@b@c",
+            "mailto:Jane <jane@example.com",
+            "",
+        ] {
+            assert_eq!(email_address(value), None, "{value}");
+        }
+    }
+    #[test]
+    fn same_addresses() {
+        assert!(same_address("mailto:Jane@Example.com", "MAILTO:jane@example.com"));
+        assert!(same_address("jane@example.com", "mailto:jane@example.com"));
+        assert!(!same_address("mailto:jane@example.com", "mailto:john@example.com"));
+        assert!(same_address("urn:uuid:1", "urn:uuid:1"));
+        assert!(!same_address("urn:uuid:1", "URN:UUID:1"));
+    }
+}
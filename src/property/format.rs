@@ -0,0 +1,335 @@
+// Writing property values in their RFC 5545 text form (§ 3.3): dates and times in the basic
+// ISO 8601 format, durations like `P1DT2H`, floats in the shortest form that parses back to
+// the same number, and text with its special characters escaped. A value's time zone isn't
+// part of its text; `tzid` gives the `TZID` parameter to write alongside it.
+use super::text::{NewlineEscape, write_escaped};
+use super::values::{Period, PropertyValue, Trigger, Values};
+use crate::parameter::Tzid;
+use jiff::{SignedDuration, Timestamp, tz::TimeZone};
+use std::fmt;
+
+const DATE: &str = "%Y%m%d";
+const DATE_TIME: &str = "%Y%m%dT%H%M%S";
+const UTC_DATE_TIME: &str = "%Y%m%dT%H%M%SZ";
+
+impl PropertyValue {
+    /// The `TZID` parameter the value needs: the zone of zoned date-times (converted, if
+    /// necessary, to the zone of the first), or the `TZID` of a date-time list. Zones without
+    /// an IANA name, and UTC, need none; such values are written in UTC.
+    #[must_use]
+    pub fn tzid(&self) -> Option<Tzid> {
+        match self {
+            PropertyValue::DateTimeZoned(values) => zone_name(values.first().time_zone()),
+            PropertyValue::DateTimeList { tzid, .. } => tzid.clone(),
+            _ => None,
+        }
+    }
+    /// The value formatted for property `name`, with floats rounded to at most
+    /// `max_decimals` decimal places. (`GEO`'s two floats are separated by a semicolon, not
+    /// a comma.)
+    #[must_use]
+    pub fn formatted(&self, name: &str, max_decimals: Option<usize>) -> Formatted<'_> {
+        let float_separator = if name.eq_ignore_ascii_case("GEO") { ";" } else { "," };
+        Formatted { value: self, float_separator, max_decimals, newline: NewlineEscape::default() }
+    }
+}
+
+/// A `PropertyValue` formatted for a particular property (see `PropertyValue::formatted`)
+#[derive(Clone, Copy, Debug)]
+pub struct Formatted<'a> {
+    value: &'a PropertyValue,
+    float_separator: &'static str,
+    max_decimals: Option<usize>,
+    newline: NewlineEscape,
+}
+
+impl Formatted<'_> {
+    /// Escape newlines in `TEXT` values as `newline` says (rather than as `\n`)
+    #[must_use]
+    pub fn newline_escape(self, newline: NewlineEscape) -> Self {
+        Formatted { newline, ..self }
+    }
+}
+
+// The IANA name of `zone`, unless it's UTC
+fn zone_name(zone: &TimeZone) -> Option<Tzid> {
+    zone.iana_name().filter(|name| *name != "UTC").map(Tzid::from)
+}
+
+/// The value as written in a content line, after the colon. Fractions of a second in
+/// durations and offsets can't be represented and are dropped. Floats that aren't finite
+/// can't be represented either, and are written as Rust writes them.
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.formatted("", None))
+    }
+}
+
+impl fmt::Display for Formatted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use PropertyValue::*;
+        match self.value {
+            Binary(binary) => f.write_str(binary.base64()),
+            Boolean(b) => f.write_str(if *b { "TRUE" } else { "FALSE" }),
+            CalAddress(text) | Uri(text) => f.write_str(text),
+            Date(dates) => list(f, dates, |f, date| write!(f, "{}", date.strftime(DATE))),
+            DateTime(dts) => list(f, dts, |f, dt| write!(f, "{}", dt.strftime(DATE_TIME))),
+            DateTimeList { values, .. } => {
+                list(f, values, |f, dt| write!(f, "{}", dt.strftime(DATE_TIME)))
+            }
+            DateTimeUtc(timestamps) => list(f, timestamps, utc),
+            DateTimeZoned(zoned) => match zone_name(zoned.first().time_zone()) {
+                Some(_) => {
+                    let zone = zoned.first().time_zone();
+                    list(f, zoned, |f, z| {
+                        write!(f, "{}", z.with_time_zone(zone.clone()).strftime(DATE_TIME))
+                    })
+                }
+                None => list(f, zoned, |f, z| utc(f, &z.timestamp())),
+            },
+            Duration(durations) => list(f, durations, |f, d| duration(f, *d)),
+            Float(floats) => {
+                for (n, x) in floats.iter().enumerate() {
+                    if n > 0 {
+                        f.write_str(self.float_separator)?;
+                    }
+                    float(f, *x, self.max_decimals)?;
+                }
+                Ok(())
+            }
+            Integer(integers) => list(f, integers, |f, n| write!(f, "{n}")),
+            Period(periods) => list(f, periods, |f, period| write!(f, "{period}")),
+            Recur(rrule) => write!(f, "{rrule}"),
+            Text(texts) => list(f, texts, |f, text| write_escaped(f, text, self.newline)),
+            Time(times) => list(f, times, |f, time| write!(f, "{}", time.strftime("%H%M%S"))),
+            UtcOffset(offset) => utc_offset(f, *offset),
+        }
+    }
+}
+
+/// The `PERIOD` value, in UTC, in the form it was written
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        utc(f, &self.start())?;
+        f.write_str("/")?;
+        match self {
+            Period::Explicit { end, .. } => utc(f, end),
+            Period::Start { duration: d, .. } => duration(f, *d),
+        }
+    }
+}
+
+/// The `TRIGGER` value: its duration, or its UTC date-time
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::Relative { duration: d, .. } => duration(f, *d),
+            Trigger::At(timestamp) => utc(f, timestamp),
+        }
+    }
+}
+
+/// A `DURATION` value: weeks if the duration is a whole number of them, and otherwise days
+/// and a time. Fractions of a second are dropped.
+#[must_use]
+pub fn format_duration(d: SignedDuration) -> String {
+    let mut text = String::new();
+    // Writing to a `String` never fails
+    let _ = duration(&mut text, d);
+    text
+}
+
+/// A `UTC-OFFSET` value, like `+0100` or `-050030`. Fractions of a second are dropped.
+#[must_use]
+pub fn format_utc_offset(offset: SignedDuration) -> String {
+    let mut text = String::new();
+    // Writing to a `String` never fails
+    let _ = utc_offset(&mut text, offset);
+    text
+}
+
+// Write `values` separated by commas
+fn list<T>(
+    f: &mut fmt::Formatter<'_>,
+    values: &Values<T>,
+    write: impl Fn(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    for (n, value) in values.iter().enumerate() {
+        if n > 0 {
+            f.write_str(",")?;
+        }
+        write(f, value)?;
+    }
+    Ok(())
+}
+
+// Rust writes the shortest decimal that parses back to the same `f64`, which never drifts
+// through parse/write cycles. Rounding to `max_decimals` places doesn't drift either: the
+// rounded text parses to the `f64` nearest it, which rounds back to the same text.
+fn float(f: &mut fmt::Formatter<'_>, x: f64, max_decimals: Option<usize>) -> fmt::Result {
+    let shortest = x.to_string();
+    let decimals = shortest.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+    let text = match max_decimals {
+        Some(max) if decimals > max => {
+            let rounded = format!("{x:.max$}");
+            let rounded = if max > 0 { rounded.trim_end_matches('0') } else { &rounded };
+            rounded.trim_end_matches('.').to_string()
+        }
+        _ => shortest,
+    };
+    f.write_str(if text == "-0" { "0" } else { &text })
+}
+
+fn utc(f: &mut fmt::Formatter<'_>, timestamp: &Timestamp) -> fmt::Result {
+    write!(f, "{}", timestamp.strftime(UTC_DATE_TIME))
+}
+
+// RFC 5545 § 3.3.6: weeks if the duration is a whole number of them, and otherwise days and
+// a time. A time with hours and seconds needs the minutes too.
+fn duration(f: &mut impl fmt::Write, duration: SignedDuration) -> fmt::Result {
+    if duration.is_negative() {
+        f.write_str("-")?;
+    }
+    let secs = duration.as_secs().unsigned_abs();
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    let (hours, minutes, seconds) = (rest / 3600, rest / 60 % 60, rest % 60);
+    f.write_str("P")?;
+    if secs == 0 {
+        return f.write_str("T0S");
+    }
+    if rest == 0 && days.is_multiple_of(7) {
+        return write!(f, "{}W", days / 7);
+    }
+    if days > 0 {
+        write!(f, "{days}D")?;
+    }
+    if rest > 0 {
+        f.write_str("T")?;
+    }
+    if hours > 0 {
+        write!(f, "{hours}H")?;
+    }
+    if minutes > 0 || (hours > 0 && seconds > 0) {
+        write!(f, "{minutes}M")?;
+    }
+    if seconds > 0 {
+        write!(f, "{seconds}S")?;
+    }
+    Ok(())
+}
+
+// RFC 5545 § 3.3.14: `+HHMM`, with seconds only if there are any. A zero offset is `+0000`,
+// since `-0000` isn't allowed.
+fn utc_offset(f: &mut impl fmt::Write, offset: SignedDuration) -> fmt::Result {
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    let secs = offset.as_secs().unsigned_abs();
+    write!(f, "{sign}{:02}{:02}", secs / 3600, secs / 60 % 60)?;
+    if !secs.is_multiple_of(60) {
+        write!(f, "{:02}", secs % 60)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::property::Binary;
+    use crate::rrule::parse_rrule;
+    use jiff::civil::{date, datetime, time};
+    use pretty_assertions::assert_eq;
+
+    fn list<T>(values: Vec<T>) -> Values<T> {
+        Values::from_vec(values).unwrap()
+    }
+
+    #[test]
+    fn dates_and_times() {
+        let dt = datetime(2025, 3, 3, 9, 0, 0, 0);
+        let zoned = dt.in_tz("America/New_York").unwrap();
+        let tokyo = datetime(2025, 3, 3, 22, 30, 0, 0).in_tz("Asia/Tokyo").unwrap();
+        let cases = [
+            (PropertyValue::from(date(2025, 3, 3)), "20250303"),
+            (PropertyValue::from(dt), "20250303T090000"),
+            (PropertyValue::from(zoned.timestamp()), "20250303T140000Z"),
+            (
+                PropertyValue::DateTimeZoned(list(vec![zoned.clone(), tokyo])),
+                "20250303T090000,20250303T083000",
+            ),
+            (PropertyValue::from(dt.in_tz("UTC").unwrap()), "20250303T090000Z"),
+            (PropertyValue::from(time(7, 5, 9, 0)), "070509"),
+            (
+                PropertyValue::Period(list(vec![
+                    Period::Explicit {
+                        start: zoned.timestamp(),
+                        end: zoned.timestamp() + SignedDuration::from_hours(1),
+                    },
+                    Period::Start {
+                        start: zoned.timestamp(),
+                        duration: SignedDuration::from_mins(90),
+                    },
+                ])),
+                "20250303T140000Z/20250303T150000Z,20250303T140000Z/PT1H30M",
+            ),
+        ];
+        for (value, text) in cases {
+            assert_eq!(value.to_string(), text);
+        }
+        assert_eq!(PropertyValue::from(zoned).tzid(), Some(Tzid::from("America/New_York")));
+        assert_eq!(PropertyValue::from(dt.in_tz("UTC").unwrap()).tzid(), None);
+        // A list whose TZID wasn't resolved keeps it, and its local times
+        let unresolved = PropertyValue::DateTimeList {
+            tzid: Some(Tzid::from("Nowhere/Special")),
+            values: list(vec![dt, datetime(2025, 3, 10, 9, 0, 0, 0)]),
+        };
+        assert_eq!(unresolved.to_string(), "20250303T090000,20250310T090000");
+        assert_eq!(unresolved.tzid(), Some(Tzid::from("Nowhere/Special")));
+    }
+    #[test]
+    fn durations_and_offsets() {
+        let duration = |secs| PropertyValue::from(SignedDuration::from_secs(secs)).to_string();
+        assert_eq!(duration(0), "PT0S");
+        assert_eq!(duration(14 * 86_400), "P2W");
+        assert_eq!(duration(-(86_400 + 2 * 3600)), "-P1DT2H");
+        assert_eq!(duration(3600 + 5), "PT1H0M5S");
+        assert_eq!(duration(15 * 60), "PT15M");
+        assert_eq!(duration(8 * 86_400), "P8D");
+        assert_eq!(format_duration(SignedDuration::from_mins(-30)), "-PT30M");
+        let offset = |secs| PropertyValue::UtcOffset(SignedDuration::from_secs(secs)).to_string();
+        assert_eq!(offset(-5 * 3600), "-0500");
+        assert_eq!(offset(0), "+0000");
+        assert_eq!(offset(5 * 3600 + 30 * 60 + 15), "+053015");
+    }
+    #[test]
+    fn floats() {
+        let floats = |values: Vec<f64>| PropertyValue::Float(list(values));
+        let geo = floats(vec![37.386_013_4, -122.082_932]);
+        assert_eq!(geo.to_string(), "37.3860134,-122.082932");
+        assert_eq!(geo.formatted("GEO", Some(6)).to_string(), "37.386013;-122.082932");
+        let sum = floats(vec![0.1 + 0.2, -0.0, 1e21, 2.5]);
+        assert_eq!(sum.to_string(), "0.30000000000000004,0,1000000000000000000000,2.5");
+        assert_eq!(sum.formatted("X-SUM", Some(0)).to_string(), "0,0,1000000000000000000000,2");
+        // Parsing and writing again never changes the text
+        for x in [1.0 / 3.0, 37.386_013_5, -0.000_000_4, 123_456.789_012_345] {
+            for max in [None, Some(6)] {
+                let text = floats(vec![x]).formatted("GEO", max).to_string();
+                let again = floats(vec![text.parse().unwrap()]).formatted("GEO", max).to_string();
+                assert_eq!(again, text);
+            }
+        }
+    }
+    #[test]
+    fn other_values() {
+        let text = PropertyValue::Text(list(vec!["Lunch; then, a\\nap\r\n".into(), "ok".into()]));
+        assert_eq!(text.to_string(), r"Lunch\; then\, a\\nap\n,ok");
+        assert_eq!(PropertyValue::from(false).to_string(), "FALSE");
+        assert_eq!(
+            PropertyValue::Float(list(vec![37.386_013, -122.082_932])).to_string(),
+            "37.386013,-122.082932"
+        );
+        assert_eq!(PropertyValue::from(Binary::from_bytes(b"Hi")).to_string(), "SGk=");
+        let rrule = parse_rrule(&mut &b"FREQ=WEEKLY;COUNT=4;BYDAY=MO,WE\r\n"[..]).unwrap();
+        assert_eq!(PropertyValue::from(rrule).to_string(), "FREQ=WEEKLY;COUNT=4;BYDAY=MO,WE");
+        let uri = PropertyValue::Uri("https://example.com/a,b".into());
+        assert_eq!(uri.to_string(), "https://example.com/a,b");
+    }
+}
@@ -1,3 +1,76 @@
+mod address;
+mod format;
+mod parse;
+mod property_table;
+mod text;
+#[cfg(feature = "time")]
+mod time_interop;
 mod values;
-pub use self::values::PropertyValue;
-pub(crate) const NAMES: [&str; 1] = ["CALSCALE"];
+pub use self::address::{email_address, same_address};
+pub use self::format::{Formatted, format_duration, format_utc_offset};
+pub(crate) use self::parse::parameter;
+pub use self::parse::{
+    ValueError, binary_reader, parse_binary, parse_date, parse_date_time, parse_date_time_with,
+    parse_duration, parse_duration_span, parse_float, parse_gap, parse_geo, parse_period,
+    parse_time, parse_trigger, parse_utc_offset, parse_value, parse_value_with,
+};
+pub use self::text::{
+    NewlineEscape, Text, escape_text, escape_text_with, split_text_list, unescape_text,
+};
+pub use self::values::{Binary, ConversionError, Geo, Period, PropertyValue, Trigger, Values};
+pub use crate::base64::{Base64Error, Base64Reader};
+use crate::names::PropertyId;
+use crate::parameter::Value;
+use parse::ValueParser;
+use property_table::{ALLOWED_VALUES, DEFAULT_VALUES, VALUE_PARSERS};
+pub(crate) use property_table::{NAMES, index_of};
+pub use property_table::{Properties, PropertyName};
+
+/// The value type of a known property that has no `VALUE` parameter, or `None` for a property
+/// we don't know (an `X-` or IANA property not in `assets/properties.kdl`)
+#[must_use]
+pub fn default_value_type(id: PropertyId) -> Option<&'static Value> {
+    DEFAULT_VALUES.get(id.0)
+}
+
+// The default value type of the property named `name` (in any case), or `None` if we don't
+// know the property
+pub(crate) fn default_value_type_of(name: &str) -> Option<&'static Value> {
+    DEFAULT_VALUES.get(index_of(&crate::names::uppercase(name))?)
+}
+
+// How `parse_value` reads the value of the property named `name` (in any case), or `None` if
+// we don't know the property
+fn value_parser_of(name: &str) -> Option<ValueParser> {
+    VALUE_PARSERS.get(index_of(&crate::names::uppercase(name))?).copied()
+}
+
+/// Whether a `VALUE` parameter may give property `id` the value type `value`. Any type is
+/// allowed for a property we don't know.
+#[must_use]
+pub fn allows_value_type(id: PropertyId, value: &Value) -> bool {
+    ALLOWED_VALUES.get(id.0).is_none_or(|allowed| allowed.contains(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::names::Lookup;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn value_types() {
+        let mut lookup = Lookup::new();
+        let dtstart = lookup.property_id("DTSTART").unwrap();
+        assert_eq!(default_value_type(dtstart), Some(&Value::DateTime));
+        assert!(allows_value_type(dtstart, &Value::Date));
+        assert!(!allows_value_type(dtstart, &Value::Text));
+        let x_prop = lookup.property_id("X-WR-CALNAME").unwrap();
+        assert_eq!(default_value_type(x_prop), None);
+        assert!(allows_value_type(x_prop, &Value::Text));
+        assert_eq!(value_parser_of("exdate"), Some(ValueParser::List));
+        assert_eq!(value_parser_of("GEO"), Some(ValueParser::Geo));
+        assert_eq!(value_parser_of("DTSTART"), Some(ValueParser::Single));
+        assert_eq!(value_parser_of("X-WR-CALNAME"), None);
+    }
+}
@@ -0,0 +1,650 @@
+// Parsing property values from their RFC 5545 text form (§ 3.3), the inverse of `format`.
+// Each parser takes the value as written (after the colon); those whose meaning depends on
+// the `VALUE` or `TZID` parameter take the whole preparsed property.
+use super::text::{split_text_list, unescape_text};
+use super::values::{Binary, Geo, Period, PropertyValue, Trigger, Values};
+use super::{default_value_type_of, value_parser_of};
+use crate::base64::Base64Reader;
+use crate::catalog;
+use crate::event::EventTime;
+use crate::names::uppercase;
+use crate::parameter::{Related, Tzid, Value};
+use crate::preparse::Prop;
+use crate::rrule::parse_rrule;
+use crate::timezone::TzResolver;
+use jiff::civil::{Date, DateTime, Time};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Span};
+use thiserror::Error;
+
+/// Why a property value couldn't be parsed
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ValueError {
+    /// The value isn't in the form its type requires, or names a date or time that doesn't
+    /// exist (like February 30)
+    #[error("{value:?} isn't a valid {expected} value")]
+    Malformed { expected: &'static str, value: String },
+    #[error("the TZID {0:?} names no known time zone")]
+    UnknownTzid(String),
+}
+impl ValueError {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValueError::Malformed { .. } => catalog::BAD_VALUE,
+            ValueError::UnknownTzid(_) => catalog::UNKNOWN_TZID,
+        }
+    }
+}
+
+// How `parse_value` reads a known property's value, beyond parsing its value type (the
+// `parser` of each property in `assets/properties.kdl`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ValueParser {
+    // A value of the property's type
+    Single,
+    // A comma-separated list of values of its type, like `CATEGORIES` or `EXDATE`
+    List,
+    // A latitude and longitude, separated by a semicolon
+    Geo,
+}
+
+fn malformed(expected: &'static str, value: &str) -> ValueError {
+    ValueError::Malformed { expected, value: value.to_string() }
+}
+
+// The first value of `prop`'s parameter `name` (in any case)
+pub(crate) fn parameter<'a>(prop: &Prop<'a>, name: &str) -> Option<&'a str> {
+    (prop.parameters().iter())
+        .find(|param| param.name().val().eq_ignore_ascii_case(name))
+        .and_then(|param| param.values().first())
+        .map(|value| value.val())
+}
+
+// Whether `text` is `n` ASCII digits
+fn digits(text: &str, n: usize) -> bool {
+    text.len() == n && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// A `DATE` value, like `20250303`
+pub fn parse_date(value: &str) -> Result<Date, ValueError> {
+    if !digits(value, 8) {
+        return Err(malformed("DATE", value));
+    }
+    Date::strptime("%Y%m%d", value).map_err(|_| malformed("DATE", value))
+}
+
+/// A `TIME` value, like `090000` or (in UTC) `090000Z`; the `Z`, if any, is dropped. A leap
+/// second (`60`), which RFC 5545 allows but jiff doesn't, is read as `59`.
+pub fn parse_time(value: &str) -> Result<Time, ValueError> {
+    let local = value.strip_suffix('Z').unwrap_or(value);
+    if !digits(local, 6) {
+        return Err(malformed("TIME", value));
+    }
+    let local = if local.ends_with("60") { format!("{}59", &local[..4]) } else { local.into() };
+    Time::strptime("%H%M%S", &local).map_err(|_| malformed("TIME", value))
+}
+
+/// The value of a `DATE-TIME` property like `DTSTART`: a `DATE` if the `VALUE` parameter
+/// says so, and otherwise a UTC date-time (with a `Z`), one in the time zone its `TZID`
+/// names, or a floating date-time
+pub fn parse_date_time(prop: &Prop) -> Result<EventTime, ValueError> {
+    parse_date_time_with(prop, &TzResolver::new())
+}
+
+/// Like `parse_date_time`, with `resolver` finding the zone the `TZID` names
+pub fn parse_date_time_with(prop: &Prop, resolver: &TzResolver) -> Result<EventTime, ValueError> {
+    let value = prop.value().val();
+    if parameter(prop, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) {
+        return parse_date(value).map(EventTime::Date);
+    }
+    date_time(value, parameter(prop, "TZID"), resolver)
+}
+
+// A `DATE-TIME` value in UTC (with a `Z`), in the zone `tzid` names, or floating
+fn date_time(
+    value: &str,
+    tzid: Option<&str>,
+    resolver: &TzResolver,
+) -> Result<EventTime, ValueError> {
+    let (dt, utc) = local_date_time(value)?;
+    let zoned = match tzid {
+        _ if utc => TimeZone::UTC.to_zoned(dt),
+        Some(tzid) => {
+            resolver.to_zoned(tzid, dt).ok_or_else(|| ValueError::UnknownTzid(tzid.into()))?
+        }
+        None => return Ok(EventTime::Floating(dt)),
+    };
+    zoned.map(EventTime::Zoned).map_err(|_| malformed("DATE-TIME", value))
+}
+
+// A `DATE-TIME` value's date and time, and whether it's in UTC (with a `Z`)
+fn local_date_time(value: &str) -> Result<(DateTime, bool), ValueError> {
+    let (local, utc) = match value.strip_suffix('Z') {
+        Some(local) => (local, true),
+        None => (value, false),
+    };
+    let Some((date, time)) = local.split_once('T') else {
+        return Err(malformed("DATE-TIME", value));
+    };
+    let (Ok(date), Ok(time)) = (parse_date(date), parse_time(time)) else {
+        return Err(malformed("DATE-TIME", value));
+    };
+    Ok((DateTime::from_parts(date, time), utc))
+}
+
+/// A `DURATION` value, like `P1W`, `-PT15M`, or `P15DT5H0M20S`. Weeks and days are taken as
+/// 7 and 1 days of 24 hours.
+pub fn parse_duration(value: &str) -> Result<SignedDuration, ValueError> {
+    let (days, secs) = duration(value).ok_or_else(|| malformed("DURATION", value))?;
+    (days.checked_mul(86_400).and_then(|days| days.checked_add(secs)))
+        .map(SignedDuration::from_secs)
+        .ok_or_else(|| malformed("DURATION", value))
+}
+
+/// A `DURATION` value as RFC 5545 § 3.3.6 means it: its weeks and days are nominal (calendar
+/// days, so `P1D` from 09:00 ends at 09:00 the next day even across a DST change), and its
+/// hours, minutes and seconds exact. Add it to a `Zoned` start to find the end.
+pub fn parse_duration_span(value: &str) -> Result<Span, ValueError> {
+    let (days, secs) = duration(value).ok_or_else(|| malformed("DURATION", value))?;
+    let span = (Span::new().try_days(days))
+        .and_then(|span| span.try_hours(secs / 3600))
+        .and_then(|span| span.try_minutes(secs % 3600 / 60))
+        .and_then(|span| span.try_seconds(secs % 60));
+    span.map_err(|_| malformed("DURATION", value))
+}
+
+// The days (weeks counting as 7) and seconds of a `DURATION` value, both negative for a
+// negative duration. As RFC 5545's grammar says, weeks stand alone, days may be followed by a
+// time, and a time (after a `T`) has hours, minutes and seconds in that order, at least one of
+// them and each at most once.
+fn duration(text: &str) -> Option<(i64, i64)> {
+    let (negative, text) = match text.as_bytes().first()? {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let text = text.strip_prefix('P')?;
+    let (date, time) = match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+    let days = match (date.ends_with('W'), time) {
+        (true, None) => components(date, &[(b'W', 7)])?,
+        (true, Some(_)) => return None,
+        (false, _) => components(date, &[(b'D', 1)])?,
+    };
+    let secs = match time {
+        Some("") => return None,
+        Some(time) => components(time, &[(b'H', 3600), (b'M', 60), (b'S', 1)])?,
+        None if date.is_empty() => return None,
+        None => 0,
+    };
+    let sign = if negative { -1 } else { 1 };
+    Some((sign * days, sign * secs))
+}
+
+// The total of the components of `text`, like `5H20S`: numbers, each followed by one of
+// `units` (with what one of it is worth), which come in that order, each at most once
+fn components(mut text: &str, mut units: &[(u8, i64)]) -> Option<i64> {
+    let mut total = 0i64;
+    while !text.is_empty() {
+        let digits = text.find(|c: char| !c.is_ascii_digit()).filter(|n| *n > 0)?;
+        let n: i64 = text[..digits].parse().ok()?;
+        let at = units.iter().position(|(unit, _)| *unit == text.as_bytes()[digits])?;
+        total = total.checked_add(n.checked_mul(units[at].1)?)?;
+        (text, units) = (&text[digits + 1..], &units[at + 1..]);
+    }
+    Some(total)
+}
+
+/// The value of a `PERIOD` property like `FREEBUSY`: one or more periods, each a start and
+/// end or a start and duration, keeping the form. Date-times in the zone the `TZID`
+/// parameter names are converted to UTC, and floating ones are read as UTC.
+pub fn parse_period(prop: &Prop) -> Result<Values<Period>, ValueError> {
+    periods(prop, &TzResolver::new())
+}
+
+fn periods(prop: &Prop, resolver: &TzResolver) -> Result<Values<Period>, ValueError> {
+    let tzid = parameter(prop, "TZID");
+    let instant = |text: &str| match date_time(text, tzid, resolver)? {
+        EventTime::Zoned(zoned) => Ok(zoned.timestamp()),
+        EventTime::Floating(dt) => (dt.to_zoned(TimeZone::UTC).map(|utc| utc.timestamp()))
+            .map_err(|_| malformed("DATE-TIME", text)),
+        EventTime::Date(_) => Err(malformed("DATE-TIME", text)),
+    };
+    let periods = (prop.value().val().split(','))
+        .map(|period| {
+            let Some((start, end)) = period.split_once('/') else {
+                return Err(malformed("PERIOD", period));
+            };
+            let start = instant(start)?;
+            if end.starts_with(['P', '+', '-']) {
+                Ok(Period::Start { start, duration: parse_duration(end)? })
+            } else {
+                Ok(Period::Explicit { start, end: instant(end)? })
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Values::from_vec(periods).ok_or_else(|| malformed("PERIOD", ""))
+}
+
+/// A `UTC-OFFSET` value, like `+0100` or `-050030`: hours up to 23, minutes and seconds up
+/// to 59. `-0000`, which RFC 5545 forbids, is an error.
+pub fn parse_utc_offset(value: &str) -> Result<SignedDuration, ValueError> {
+    let bad = || malformed("UTC-OFFSET", value);
+    let (sign, digits) = match value.as_bytes().first() {
+        Some(b'+') => (1, &value[1..]),
+        Some(b'-') => (-1, &value[1..]),
+        _ => return Err(bad()),
+    };
+    if !(self::digits(digits, 4) || self::digits(digits, 6)) {
+        return Err(bad());
+    }
+    let part = |n: usize| digits.get(n..n + 2).map_or(Ok(0), str::parse::<i64>);
+    let (Ok(hours), Ok(minutes), Ok(seconds)) = (part(0), part(2), part(4)) else {
+        return Err(bad());
+    };
+    let secs = hours * 3600 + minutes * 60 + seconds;
+    if hours > 23 || minutes > 59 || seconds > 59 || (sign < 0 && secs == 0) {
+        return Err(bad());
+    }
+    Ok(SignedDuration::from_secs(sign * secs))
+}
+
+/// The value of a property with `ENCODING=BASE64` (and, as RFC 5545 requires of such a
+/// property, `VALUE=BINARY`), decoded. Its `Display` output is the base64 text again.
+pub fn parse_binary(prop: &Prop) -> Result<Binary, ValueError> {
+    let value = prop.value().val();
+    let binary = Binary::new(binary_text(prop)?);
+    binary.bytes().map_err(|_| malformed("BINARY", value))?;
+    Ok(binary)
+}
+
+/// Like `parse_binary`, but decoding the value as it's read, for a large `ATTACH`; a read
+/// fails with `io::ErrorKind::InvalidData` at the first byte that isn't valid base64
+pub fn binary_reader<'a>(prop: &Prop<'a>) -> Result<Base64Reader<&'a [u8]>, ValueError> {
+    Ok(Base64Reader::new(binary_text(prop)?.as_bytes()))
+}
+
+// The base64 text of a `BINARY` value, checking the parameters
+fn binary_text<'a>(prop: &Prop<'a>) -> Result<&'a str, ValueError> {
+    let value = prop.value().val();
+    let is =
+        |name, wanted: &str| parameter(prop, name).is_some_and(|v| v.eq_ignore_ascii_case(wanted));
+    if is("ENCODING", "BASE64")
+        && parameter(prop, "VALUE").is_none_or(|v| v.eq_ignore_ascii_case("BINARY"))
+    {
+        Ok(value)
+    } else {
+        Err(malformed("BINARY", value))
+    }
+}
+
+/// A `FLOAT` value, like `-122.082932`: digits with an optional sign and fraction
+pub fn parse_float(value: &str) -> Result<f64, ValueError> {
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, "0"));
+    let all_digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    if !(all_digits(whole) && all_digits(fraction)) {
+        return Err(malformed("FLOAT", value));
+    }
+    value.parse().map_err(|_| malformed("FLOAT", value))
+}
+
+/// A `GEO` value, like `37.386013;-122.082932`: a latitude and longitude separated by a
+/// semicolon, each in range
+pub fn parse_geo(value: &str) -> Result<Geo, ValueError> {
+    let (lat, lon) = value.split_once(';').ok_or_else(|| malformed("GEO", value))?;
+    Geo::new(parse_float(lat)?, parse_float(lon)?).ok_or_else(|| malformed("GEO", value))
+}
+
+/// The `GAP` parameter of a property (RFC 9253 § 6.2), if it has one
+pub fn parse_gap(prop: &Prop) -> Result<Option<SignedDuration>, ValueError> {
+    parameter(prop, "GAP").map(parse_duration).transpose()
+}
+
+/// The value of a `TRIGGER` property: a duration before or after the start or end of the
+/// component, as its `RELATED` parameter says, or (with `VALUE=DATE-TIME`) a UTC date-time
+pub fn parse_trigger(prop: &Prop) -> Result<Trigger, ValueError> {
+    let value = prop.value().val();
+    if parameter(prop, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE-TIME")) {
+        return match parse_date_time(prop)? {
+            EventTime::Zoned(zoned) if value.ends_with('Z') => Ok(Trigger::At(zoned.timestamp())),
+            _ => Err(malformed("UTC DATE-TIME", value)),
+        };
+    }
+    let related = match parameter(prop, "RELATED") {
+        Some(related) if related.eq_ignore_ascii_case("END") => Related::End,
+        _ => Related::Start,
+    };
+    Ok(Trigger::Relative { duration: parse_duration(value)?, related })
+}
+
+/// The value of any property, parsed as the type its `VALUE` parameter names — or, without
+/// one, its default type (`TEXT` for properties we don't know, and for value types we don't
+/// know). Lists of dates, date-times, and the like give one value per comma-separated item,
+/// as do the `TEXT` lists of `CATEGORIES` and `RESOURCES`; other `TEXT` is one value. `TEXT` is
+/// unescaped. Date-times are UTC, zoned, or floating as written, except that those with a
+/// `TZID` no zone is found for keep it, floating (see `PropertyValue::DateTimeList`).
+pub fn parse_value(prop: &Prop) -> Result<PropertyValue, ValueError> {
+    parse_value_with(prop, &TzResolver::new())
+}
+
+/// Like `parse_value`, with `resolver` finding the zones `TZID`s name
+pub fn parse_value_with(prop: &Prop, resolver: &TzResolver) -> Result<PropertyValue, ValueError> {
+    let (name, value) = (prop.name.val(), prop.value().val());
+    let value_type = match parameter(prop, "VALUE") {
+        Some(value_type) => Value::from(value_type),
+        None => default_value_type_of(name).cloned().unwrap_or(Value::Text),
+    };
+    let parser = value_parser_of(name).unwrap_or(ValueParser::Single);
+    Ok(match value_type {
+        Value::Binary => PropertyValue::Binary(parse_binary(prop)?),
+        Value::Boolean => match uppercase(value).as_ref() {
+            "TRUE" => PropertyValue::Boolean(true),
+            "FALSE" => PropertyValue::Boolean(false),
+            _ => return Err(malformed("BOOLEAN", value)),
+        },
+        Value::CalAddress => PropertyValue::CalAddress(value.to_string()),
+        Value::Date => PropertyValue::Date(each(value, parse_date)?),
+        Value::DateTime => date_times(prop, resolver)?,
+        Value::Duration => PropertyValue::Duration(each(value, parse_duration)?),
+        Value::Float if parser == ValueParser::Geo => parse_geo(value)?.into(),
+        Value::Float => PropertyValue::Float(each(value, parse_float)?),
+        Value::Integer => PropertyValue::Integer(each(value, parse_integer)?),
+        Value::Period => PropertyValue::Period(periods(prop, resolver)?),
+        Value::Recur => match parse_rrule(&mut value.as_bytes()) {
+            Ok(rrule) => PropertyValue::Recur(Box::new(rrule)),
+            Err(_) => return Err(malformed("RECUR", value)),
+        },
+        Value::Time => PropertyValue::Time(each(value, parse_time)?),
+        Value::Uri | Value::XmlReference => PropertyValue::Uri(value.to_string()),
+        Value::UtcOffset => PropertyValue::UtcOffset(parse_utc_offset(value)?),
+        Value::Text | Value::Uid | Value::Other(_) if parser == ValueParser::List => {
+            let texts = Values::from_vec(split_text_list(value));
+            PropertyValue::Text(texts.ok_or_else(|| malformed("TEXT", value))?)
+        }
+        Value::Text | Value::Uid | Value::Other(_) => {
+            PropertyValue::Text(Values::new(unescape_text(value)))
+        }
+    })
+}
+
+// A comma-separated list of values, each parsed by `parse`
+fn each<T>(value: &str, parse: fn(&str) -> Result<T, ValueError>) -> Result<Values<T>, ValueError> {
+    let values = value.split(',').map(parse).collect::<Result<_, _>>()?;
+    Values::from_vec(values).ok_or_else(|| malformed("list", value))
+}
+
+// An `INTEGER` value: digits with an optional sign, in the range of an `i32`
+fn parse_integer(value: &str) -> Result<i32, ValueError> {
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+    if unsigned.is_empty() || !unsigned.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(malformed("INTEGER", value));
+    }
+    value.parse().map_err(|_| malformed("INTEGER", value))
+}
+
+// A list of `DATE-TIME` values, which must all be UTC or all local: in the zone the `TZID`
+// names, floating without one, or, if `resolver` can't resolve the `TZID`, floating with it
+fn date_times(prop: &Prop, resolver: &TzResolver) -> Result<PropertyValue, ValueError> {
+    let value = prop.value().val();
+    let bad = || malformed("DATE-TIME", value);
+    let times: Vec<_> = value.split(',').map(local_date_time).collect::<Result<_, _>>()?;
+    if times.iter().all(|(_, utc)| *utc) {
+        let timestamps = (times.iter())
+            .map(|(dt, _)| TimeZone::UTC.to_timestamp(*dt).map_err(|_| bad()))
+            .collect::<Result<_, _>>()?;
+        return Values::from_vec(timestamps).map(PropertyValue::DateTimeUtc).ok_or_else(bad);
+    }
+    if times.iter().any(|(_, utc)| *utc) {
+        return Err(bad());
+    }
+    let local = times.into_iter().map(|(dt, _)| dt);
+    let Some(tzid) = parameter(prop, "TZID") else {
+        return Values::from_vec(local.collect()).map(PropertyValue::DateTime).ok_or_else(bad);
+    };
+    match resolver.resolve(tzid) {
+        Some(zone) => {
+            let zoned = local.map(|dt| zone.to_zoned(dt).map_err(|_| bad()));
+            let values = Values::from_vec(zoned.collect::<Result<_, _>>()?).ok_or_else(bad)?;
+            Ok(PropertyValue::DateTimeZoned(values))
+        }
+        None => {
+            let values = Values::from_vec(local.collect()).ok_or_else(bad)?;
+            Ok(PropertyValue::DateTimeList { tzid: Some(Tzid::from(tzid)), values })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cautious"))]
+mod test {
+    use super::*;
+    use crate::preparse::cautious_preparse;
+    use crate::property::format_utc_offset;
+    use jiff::civil::{date, datetime, time};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn dates_and_times() {
+        assert_eq!(parse_date("20250303"), Ok(date(2025, 3, 3)));
+        assert_eq!(parse_date("20250230").unwrap_err().code(), catalog::BAD_VALUE);
+        assert!(parse_date("2025033").is_err());
+        assert!(parse_date("+2025033").is_err());
+        assert_eq!(parse_time("093015"), Ok(time(9, 30, 15, 0)));
+        assert_eq!(parse_time("235960Z"), Ok(time(23, 59, 59, 0)));
+        assert!(parse_time("0930").is_err());
+        assert!(parse_time("246000").is_err());
+    }
+    #[test]
+    fn date_times() {
+        let parse = |line: &str| parse_date_time(&cautious_preparse(line.as_bytes()).unwrap());
+        let nine = datetime(2025, 3, 3, 9, 0, 0, 0);
+        assert_eq!(parse("DTSTART:20250303T090000"), Ok(EventTime::Floating(nine)));
+        let utc = nine.to_zoned(TimeZone::UTC).unwrap();
+        assert_eq!(parse("DTSTART:20250303T090000Z"), Ok(EventTime::Zoned(utc)));
+        let paris = nine.in_tz("Europe/Paris").unwrap();
+        let zoned = parse("DTSTART;tzid=Europe/Paris:20250303T090000");
+        assert_eq!(zoned, Ok(EventTime::Zoned(paris.clone())));
+        assert_eq!(parse("DTSTART;VALUE=DATE:20250303"), Ok(EventTime::Date(date(2025, 3, 3))));
+        assert!(parse("DTSTART;VALUE=DATE:20250303T090000").is_err());
+        assert!(parse("DTSTART:20250303").is_err());
+        let unknown = parse("DTSTART;TZID=Mars/Olympus:20250303T090000").unwrap_err();
+        assert_eq!(unknown, ValueError::UnknownTzid("Mars/Olympus".to_string()));
+        let guid = parse("DTSTART;TZID=/mozilla.org/20050126_1/Europe/Paris:20250303T090000");
+        assert_eq!(guid, Ok(EventTime::Zoned(paris.clone())));
+        let resolver = TzResolver::new().fallback(|tzid| {
+            (tzid == "Romance Standard Time").then(|| TimeZone::get("Europe/Paris").unwrap())
+        });
+        let windows = "DTSTART;TZID=Romance Standard Time:20250303T090000";
+        let prop = cautious_preparse(windows.as_bytes()).unwrap();
+        assert_eq!(parse_date_time_with(&prop, &resolver), Ok(EventTime::Zoned(paris)));
+        assert!(parse(windows).is_err());
+    }
+    #[test]
+    fn durations() {
+        let secs = |text| parse_duration(text).map(|d| d.as_secs());
+        assert_eq!(secs("P15DT5H0M20S"), Ok(15 * 86_400 + 5 * 3600 + 20));
+        assert_eq!(secs("-PT30M"), Ok(-1800));
+        assert_eq!(secs("+P2W"), Ok(14 * 86_400));
+        assert!(secs("P").is_err());
+        assert!(secs("PT1D").is_err());
+        assert_eq!(secs("PT1H20S"), Ok(3600 + 20));
+        for bad in ["PT", "P1DT", "PT5M1H", "PT1H1H", "P1D2D", "P1W2D", "P2D1W", "P1WT1H", "PT1HT"]
+        {
+            assert!(secs(bad).is_err(), "{bad} should be rejected");
+        }
+        assert_eq!(secs("P1H").unwrap_err().code(), catalog::BAD_VALUE);
+        let gap = |line: &str| parse_gap(&cautious_preparse(line.as_bytes()).unwrap());
+        assert_eq!(gap("RELATED-TO;GAP=PT1H:x"), Ok(Some(SignedDuration::from_hours(1))));
+        assert_eq!(gap("RELATED-TO:x"), Ok(None));
+        assert!(gap("RELATED-TO;GAP=1H:x").is_err());
+
+        let span = |text| parse_duration_span(text).map(Span::fieldwise);
+        assert_eq!(span("P1W"), Ok(Span::new().days(7).fieldwise()));
+        assert_eq!(span("-P1DT25H"), Ok(Span::new().days(-1).hours(-25).fieldwise()));
+        assert_eq!(span("PT90M"), Ok(Span::new().hours(1).minutes(30).fieldwise()));
+        assert!(span("P").is_err());
+    }
+    #[test]
+    fn periods() {
+        let parse = |line: &str| parse_period(&cautious_preparse(line.as_bytes()).unwrap());
+        let line = "FREEBUSY:20250303T090000Z/20250303T100000Z,20250303T140000Z/PT1H30M";
+        let periods = parse(line).unwrap();
+        let nine = datetime(2025, 3, 3, 9, 0, 0, 0).to_zoned(TimeZone::UTC).unwrap().timestamp();
+        let ten = nine + SignedDuration::from_hours(1);
+        assert_eq!(periods[0], Period::Explicit { start: nine, end: ten });
+        assert!(matches!(periods[1], Period::Start { duration, .. } if duration.as_mins() == 90));
+        assert_eq!(PropertyValue::Period(periods).to_string(), line["FREEBUSY:".len()..]);
+        let paris = parse("RDATE;VALUE=PERIOD;TZID=Europe/Paris:20250303T100000/PT1H").unwrap();
+        assert_eq!(paris[0].start(), nine);
+        assert_eq!(paris[0].end(), Some(ten));
+        assert!(parse("FREEBUSY:20250303T090000Z").is_err());
+        assert!(parse("FREEBUSY:20250303T090000Z/P1H").is_err());
+        assert!(parse("FREEBUSY:20250303/20250304").is_err());
+    }
+    #[test]
+    fn utc_offsets() {
+        let secs = |text| parse_utc_offset(text).map(|d| d.as_secs());
+        assert_eq!(secs("+0100"), Ok(3600));
+        assert_eq!(secs("-050030"), Ok(-(5 * 3600 + 30)));
+        assert_eq!(secs("+0000"), Ok(0));
+        for bad in ["-0000", "0100", "+100", "+01000", "+2400", "+0060", "+010060", "+01a0"] {
+            assert!(secs(bad).is_err(), "{bad}");
+        }
+        for text in ["+0100", "-050030", "+0000"] {
+            assert_eq!(format_utc_offset(parse_utc_offset(text).unwrap()), text);
+        }
+    }
+    #[test]
+    fn binary() {
+        let line = "ATTACH;FMTTYPE=text/plain;ENCODING=BASE64;VALUE=BINARY:SGVsbG8=";
+        let prop = cautious_preparse(line.as_bytes()).unwrap();
+        let binary = parse_binary(&prop).unwrap();
+        assert_eq!(binary.bytes(), Ok(&b"Hello"[..]));
+        assert_eq!(PropertyValue::from(binary).to_string(), "SGVsbG8=");
+        let mut streamed = Vec::new();
+        std::io::Read::read_to_end(&mut binary_reader(&prop).unwrap(), &mut streamed).unwrap();
+        assert_eq!(streamed, b"Hello");
+        let parse = |line: &str| parse_binary(&cautious_preparse(line.as_bytes()).unwrap());
+        assert!(parse("ATTACH;ENCODING=base64:SGk=").is_ok());
+        assert!(parse("ATTACH;ENCODING=BASE64;VALUE=BINARY:SGk").is_err());
+        assert!(parse("ATTACH;VALUE=BINARY:SGk=").is_err());
+        assert!(parse("ATTACH;ENCODING=BASE64;VALUE=URI:SGk=").is_err());
+    }
+    #[test]
+    fn floats_and_geo() {
+        assert_eq!(parse_float("-122.082932"), Ok(-122.082_932));
+        assert_eq!(parse_float("+5"), Ok(5.0));
+        for bad in ["", "1e5", "inf", ".5", "5.", "1.2.3", "--1"] {
+            assert!(parse_float(bad).is_err(), "{bad}");
+        }
+        let geo = parse_geo("37.386013;-122.082932").unwrap();
+        assert_eq!((geo.lat, geo.lon), (37.386_013, -122.082_932));
+        let value = PropertyValue::from(geo);
+        assert_eq!(value.formatted("GEO", None).to_string(), "37.386013;-122.082932");
+        assert!(parse_geo("91;0").is_err());
+        assert!(parse_geo("0;180.5").is_err());
+        assert!(parse_geo("37.386013,-122.082932").is_err());
+    }
+    #[test]
+    fn triggers() {
+        let parse = |line: &str| parse_trigger(&cautious_preparse(line.as_bytes()).unwrap());
+        let before =
+            Trigger::Relative { duration: -SignedDuration::from_mins(15), related: Related::Start };
+        assert_eq!(parse("TRIGGER:-PT15M"), Ok(before));
+        let after =
+            Trigger::Relative { duration: SignedDuration::from_mins(5), related: Related::End };
+        assert_eq!(parse("TRIGGER;RELATED=END:PT5M"), Ok(after));
+        let at = datetime(2025, 3, 3, 9, 0, 0, 0).to_zoned(TimeZone::UTC).unwrap().timestamp();
+        assert_eq!(parse("TRIGGER;VALUE=DATE-TIME:20250303T090000Z"), Ok(Trigger::At(at)));
+        assert!(parse("TRIGGER;VALUE=DATE-TIME:20250303T090000").is_err());
+        for line in [
+            "TRIGGER:-PT15M",
+            "TRIGGER;RELATED=END:PT5M",
+            "TRIGGER;VALUE=DATE-TIME:20250303T090000Z",
+        ] {
+            assert_eq!(parse(line).unwrap().to_string(), line.rsplit(':').next().unwrap());
+        }
+    }
+    #[test]
+    fn values_by_type() {
+        let value = |line: &str| parse_value(&cautious_preparse(line.as_bytes()).unwrap());
+        let text = |texts: &[&str]| {
+            let texts = texts.iter().map(ToString::to_string).collect();
+            PropertyValue::Text(Values::from_vec(texts).unwrap())
+        };
+        assert_eq!(value(r"SUMMARY:Lunch\, then a walk"), Ok(text(&["Lunch, then a walk"])));
+        assert_eq!(value("CATEGORIES:Work,Team"), Ok(text(&["Work", "Team"])));
+        assert_eq!(value("RESOURCES:Projector,Whiteboard"), Ok(text(&["Projector", "Whiteboard"])));
+        assert_eq!(value("SUMMARY:Lunch,then a walk"), Ok(text(&["Lunch,then a walk"])));
+        assert_eq!(value(r"X-NOTE:a\,b,c"), Ok(text(&["a,b,c"])));
+        assert_eq!(value("X-ANYTHING:a;b"), Ok(text(&["a;b"])));
+        assert_eq!(value("X-COUNT;VALUE=INTEGER:-3"), Ok(PropertyValue::from(-3)));
+        assert_eq!(value("PRIORITY:1"), Ok(PropertyValue::from(1)));
+        assert!(value("PRIORITY:high").is_err());
+        assert_eq!(value("DTSTART;VALUE=DATE:20250303"), Ok(PropertyValue::from(date(2025, 3, 3))));
+        let start = datetime(2025, 3, 3, 9, 0, 0, 0);
+        assert_eq!(value("DTSTART:20250303T090000"), Ok(PropertyValue::from(start)));
+        let utc = start.to_zoned(TimeZone::UTC).unwrap().timestamp();
+        assert_eq!(value("DTSTAMP:20250303T090000Z"), Ok(PropertyValue::from(utc)));
+        let paris = start.in_tz("Europe/Paris").unwrap();
+        assert_eq!(
+            value("DTSTART;TZID=Europe/Paris:20250303T090000"),
+            Ok(PropertyValue::from(paris.clone()))
+        );
+        let resolver = TzResolver::new().fallback(|_| TimeZone::get("Europe/Paris").ok());
+        let prop = cautious_preparse(b"DTSTART;TZID=Romance Standard Time:20250303T090000");
+        assert_eq!(parse_value_with(&prop.unwrap(), &resolver), Ok(PropertyValue::from(paris)));
+        assert!(value("RDATE:20250303T090000,20250304T090000Z").is_err());
+        let Ok(PropertyValue::DateTimeList { tzid, values }) =
+            value("EXDATE;TZID=Nowhere/Special:20250303T090000,20250310T090000")
+        else {
+            panic!("EXDATE should be a list");
+        };
+        let later = datetime(2025, 3, 10, 9, 0, 0, 0);
+        assert_eq!(
+            (tzid.unwrap().as_str(), values.as_slice()),
+            ("Nowhere/Special", &[start, later][..])
+        );
+        // DTSTART the same way as EXDATE
+        let Ok(PropertyValue::DateTimeList { tzid, values }) =
+            value("DTSTART;TZID=Nowhere/Special:20250303T090000")
+        else {
+            panic!("an unresolved TZID should be kept");
+        };
+        assert_eq!((tzid.unwrap().as_str(), values.first()), ("Nowhere/Special", &start));
+        let Ok(PropertyValue::DateTimeZoned(values)) = value(
+            "EXDATE;TZID=/mozilla.org/20050126_1/Europe/Paris:20250303T090000,20250310T090000",
+        ) else {
+            panic!("EXDATE should be zoned");
+        };
+        assert_eq!(values.as_slice().len(), 2);
+        assert_eq!(values.first().time_zone().iana_name(), Some("Europe/Paris"));
+        assert_eq!(
+            value("GEO:37.5;-122.25"),
+            Ok(PropertyValue::from(Geo::new(37.5, -122.25).unwrap()))
+        );
+        assert_eq!(
+            value("TZOFFSETTO:+0100"),
+            Ok(PropertyValue::UtcOffset(SignedDuration::from_hours(1)))
+        );
+        assert_eq!(value("X-FLAG;VALUE=BOOLEAN:true"), Ok(PropertyValue::Boolean(true)));
+        assert_eq!(
+            value("ATTENDEE:mailto:a@b.c"),
+            Ok(PropertyValue::CalAddress("mailto:a@b.c".into()))
+        );
+        assert_eq!(
+            value("URL:https://example.com"),
+            Ok(PropertyValue::Uri("https://example.com".into()))
+        );
+        assert!(matches!(value("RRULE:FREQ=DAILY"), Ok(PropertyValue::Recur(_))));
+        assert!(matches!(value("X-RULE;VALUE=RECUR:FREQ=WEEKLY"), Ok(PropertyValue::Recur(_))));
+        assert_eq!(value("RRULE:FREQ=SOMETIMES").unwrap_err().code(), catalog::BAD_VALUE);
+        assert!(matches!(value("FREEBUSY:20250303T090000Z/PT1H"), Ok(PropertyValue::Period(_))));
+        let duration = PropertyValue::from(SignedDuration::from_mins(-15));
+        assert_eq!(value("TRIGGER:-PT15M"), Ok(duration));
+    }
+}
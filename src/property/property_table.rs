@@ -0,0 +1,1243 @@
+use super::parse::ValueParser;
+use super::values::PropertyValue;
+use crate::names::PropertyId;
+use crate::parameter::Value;
+use litemap::LiteMap;
+
+/// A property we know (one in `assets/properties.kdl`). Properties defined by later RFCs may
+/// become new variants in minor releases, so matches need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum PropertyName {
+    Action,            // RFC 5545, § 3.8.6.1
+    Attach,            // RFC 5545, § 3.8.1.1
+    Attendee,          // RFC 5545, § 3.8.4.1
+    CalendarAddress,   // RFC 9073, § 6.4
+    Calscale,          // RFC 5545, § 3.7.1
+    Categories,        // RFC 5545, § 3.8.1.2
+    Class,             // RFC 5545, § 3.8.1.3
+    Color,             // RFC 7986, § 5.9
+    Comment,           // RFC 5545, § 3.8.1.4
+    Completed,         // RFC 5545, § 3.8.2.1
+    Concept,           // RFC 9253, § 8.1
+    Conference,        // RFC 7986, § 5.11
+    Contact,           // RFC 5545, § 3.8.4.2
+    Created,           // RFC 5545, § 3.8.7.1
+    Description,       // RFC 5545, § 3.8.1.5
+    Dtend,             // RFC 5545, § 3.8.2.2
+    Dtstamp,           // RFC 5545, § 3.8.7.2
+    Dtstart,           // RFC 5545, § 3.8.2.4
+    Due,               // RFC 5545, § 3.8.2.3
+    Duration,          // RFC 5545, § 3.8.2.5
+    Exdate,            // RFC 5545, § 3.8.5.1
+    Freebusy,          // RFC 5545, § 3.8.2.6
+    Geo,               // RFC 5545, § 3.8.1.6
+    Image,             // RFC 7986, § 5.10
+    LastModified,      // RFC 5545, § 3.8.7.3
+    Link,              // RFC 9253, § 8.2
+    Location,          // RFC 5545, § 3.8.1.7
+    LocationType,      // RFC 9073, § 6.1
+    Method,            // RFC 5545, § 3.7.2
+    Name,              // RFC 7986, § 5.1
+    Organizer,         // RFC 5545, § 3.8.4.3
+    ParticipantType,   // RFC 9073, § 6.2
+    PercentComplete,   // RFC 5545, § 3.8.1.8
+    Priority,          // RFC 5545, § 3.8.1.9
+    Prodid,            // RFC 5545, § 3.7.3
+    Rdate,             // RFC 5545, § 3.8.5.2
+    RecurrenceId,      // RFC 5545, § 3.8.4.4
+    Refid,             // RFC 9253, § 8.3
+    RefreshInterval,   // RFC 7986, § 5.7
+    RelatedTo,         // RFC 5545, § 3.8.4.5
+    Repeat,            // RFC 5545, § 3.8.6.2
+    RequestStatus,     // RFC 5545, § 3.8.8.3
+    ResourceType,      // RFC 9073, § 6.3
+    Resources,         // RFC 5545, § 3.8.1.10
+    Rrule,             // RFC 5545, § 3.8.5.3
+    Sequence,          // RFC 5545, § 3.8.7.4
+    Source,            // RFC 7986, § 5.8
+    Status,            // RFC 5545, § 3.8.1.11
+    StructuredData,    // RFC 9073, § 6.6
+    StyledDescription, // RFC 9073, § 6.5
+    Summary,           // RFC 5545, § 3.8.1.12
+    Transp,            // RFC 5545, § 3.8.2.7
+    Trigger,           // RFC 5545, § 3.8.6.3
+    Tzid,              // RFC 5545, § 3.8.3.1
+    Tzname,            // RFC 5545, § 3.8.3.2
+    Tzoffsetfrom,      // RFC 5545, § 3.8.3.3
+    Tzoffsetto,        // RFC 5545, § 3.8.3.4
+    Tzurl,             // RFC 5545, § 3.8.3.5
+    Uid,               // RFC 5545, § 3.8.4.7
+    Url,               // RFC 5545, § 3.8.4.6
+    Version,           // RFC 5545, § 3.7.4
+}
+
+impl PropertyName {
+    /// The property's name, in uppercase
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        NAMES[self as usize]
+    }
+    /// The property's interned name (every `Lookup` gives known properties the same ids)
+    #[must_use]
+    pub fn id(self) -> PropertyId {
+        PropertyId(self as usize)
+    }
+    /// The property named `name` (in any case), if we know it
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        index_of(&crate::names::uppercase(name)).map(|n| ALL[n])
+    }
+    /// The property with interned name `id`, if we know it
+    #[must_use]
+    pub fn from_id(id: PropertyId) -> Option<Self> {
+        ALL.get(id.0).copied()
+    }
+    /// Whether a component may have more than one of the property
+    #[must_use]
+    pub fn is_multiple(self) -> bool {
+        MULTIPLE[self as usize]
+    }
+}
+
+/// Typed values of the properties we know, by property. Each property may have several
+/// values — one per content line — kept in the order they were added; the getter of a
+/// property a component may have only one of returns the first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Properties(LiteMap<usize, Vec<PropertyValue>>);
+
+/// The names of the properties we know, in order by name; a name's index is its
+/// `PropertyId`.
+pub(crate) const NAMES: [&str; 61] = [
+    "ACTION",             // RFC 5545, § 3.8.6.1
+    "ATTACH",             // RFC 5545, § 3.8.1.1
+    "ATTENDEE",           // RFC 5545, § 3.8.4.1
+    "CALENDAR-ADDRESS",   // RFC 9073, § 6.4
+    "CALSCALE",           // RFC 5545, § 3.7.1
+    "CATEGORIES",         // RFC 5545, § 3.8.1.2
+    "CLASS",              // RFC 5545, § 3.8.1.3
+    "COLOR",              // RFC 7986, § 5.9
+    "COMMENT",            // RFC 5545, § 3.8.1.4
+    "COMPLETED",          // RFC 5545, § 3.8.2.1
+    "CONCEPT",            // RFC 9253, § 8.1
+    "CONFERENCE",         // RFC 7986, § 5.11
+    "CONTACT",            // RFC 5545, § 3.8.4.2
+    "CREATED",            // RFC 5545, § 3.8.7.1
+    "DESCRIPTION",        // RFC 5545, § 3.8.1.5
+    "DTEND",              // RFC 5545, § 3.8.2.2
+    "DTSTAMP",            // RFC 5545, § 3.8.7.2
+    "DTSTART",            // RFC 5545, § 3.8.2.4
+    "DUE",                // RFC 5545, § 3.8.2.3
+    "DURATION",           // RFC 5545, § 3.8.2.5
+    "EXDATE",             // RFC 5545, § 3.8.5.1
+    "FREEBUSY",           // RFC 5545, § 3.8.2.6
+    "GEO",                // RFC 5545, § 3.8.1.6
+    "IMAGE",              // RFC 7986, § 5.10
+    "LAST-MODIFIED",      // RFC 5545, § 3.8.7.3
+    "LINK",               // RFC 9253, § 8.2
+    "LOCATION",           // RFC 5545, § 3.8.1.7
+    "LOCATION-TYPE",      // RFC 9073, § 6.1
+    "METHOD",             // RFC 5545, § 3.7.2
+    "NAME",               // RFC 7986, § 5.1
+    "ORGANIZER",          // RFC 5545, § 3.8.4.3
+    "PARTICIPANT-TYPE",   // RFC 9073, § 6.2
+    "PERCENT-COMPLETE",   // RFC 5545, § 3.8.1.8
+    "PRIORITY",           // RFC 5545, § 3.8.1.9
+    "PRODID",             // RFC 5545, § 3.7.3
+    "RDATE",              // RFC 5545, § 3.8.5.2
+    "RECURRENCE-ID",      // RFC 5545, § 3.8.4.4
+    "REFID",              // RFC 9253, § 8.3
+    "REFRESH-INTERVAL",   // RFC 7986, § 5.7
+    "RELATED-TO",         // RFC 5545, § 3.8.4.5
+    "REPEAT",             // RFC 5545, § 3.8.6.2
+    "REQUEST-STATUS",     // RFC 5545, § 3.8.8.3
+    "RESOURCE-TYPE",      // RFC 9073, § 6.3
+    "RESOURCES",          // RFC 5545, § 3.8.1.10
+    "RRULE",              // RFC 5545, § 3.8.5.3
+    "SEQUENCE",           // RFC 5545, § 3.8.7.4
+    "SOURCE",             // RFC 7986, § 5.8
+    "STATUS",             // RFC 5545, § 3.8.1.11
+    "STRUCTURED-DATA",    // RFC 9073, § 6.6
+    "STYLED-DESCRIPTION", // RFC 9073, § 6.5
+    "SUMMARY",            // RFC 5545, § 3.8.1.12
+    "TRANSP",             // RFC 5545, § 3.8.2.7
+    "TRIGGER",            // RFC 5545, § 3.8.6.3
+    "TZID",               // RFC 5545, § 3.8.3.1
+    "TZNAME",             // RFC 5545, § 3.8.3.2
+    "TZOFFSETFROM",       // RFC 5545, § 3.8.3.3
+    "TZOFFSETTO",         // RFC 5545, § 3.8.3.4
+    "TZURL",              // RFC 5545, § 3.8.3.5
+    "UID",                // RFC 5545, § 3.8.4.7
+    "URL",                // RFC 5545, § 3.8.4.6
+    "VERSION",            // RFC 5545, § 3.7.4
+];
+/// The index in `NAMES` of `name`, if it's there (names are all uppercase)
+pub(crate) fn index_of(name: &str) -> Option<usize> {
+    let candidates: &[usize] = match (name.len(), *name.as_bytes().first()?) {
+        (3, b'D') => &[18],            // DUE
+        (3, b'G') => &[22],            // GEO
+        (3, b'U') => &[58, 59],        // UID URL
+        (4, b'L') => &[25],            // LINK
+        (4, b'N') => &[29],            // NAME
+        (4, b'T') => &[53],            // TZID
+        (5, b'C') => &[6, 7],          // CLASS COLOR
+        (5, b'D') => &[15],            // DTEND
+        (5, b'I') => &[23],            // IMAGE
+        (5, b'R') => &[35, 37, 44],    // RDATE REFID RRULE
+        (5, b'T') => &[57],            // TZURL
+        (6, b'A') => &[0, 1],          // ACTION ATTACH
+        (6, b'E') => &[20],            // EXDATE
+        (6, b'M') => &[28],            // METHOD
+        (6, b'P') => &[34],            // PRODID
+        (6, b'R') => &[40],            // REPEAT
+        (6, b'S') => &[46, 47],        // SOURCE STATUS
+        (6, b'T') => &[51, 54],        // TRANSP TZNAME
+        (7, b'C') => &[8, 10, 12, 13], // COMMENT CONCEPT CONTACT CREATED
+        (7, b'D') => &[16, 17],        // DTSTAMP DTSTART
+        (7, b'S') => &[50],            // SUMMARY
+        (7, b'T') => &[52],            // TRIGGER
+        (7, b'V') => &[60],            // VERSION
+        (8, b'A') => &[2],             // ATTENDEE
+        (8, b'C') => &[4],             // CALSCALE
+        (8, b'D') => &[19],            // DURATION
+        (8, b'F') => &[21],            // FREEBUSY
+        (8, b'L') => &[26],            // LOCATION
+        (8, b'P') => &[33],            // PRIORITY
+        (8, b'S') => &[45],            // SEQUENCE
+        (9, b'C') => &[9],             // COMPLETED
+        (9, b'O') => &[30],            // ORGANIZER
+        (9, b'R') => &[43],            // RESOURCES
+        (10, b'C') => &[5, 11],        // CATEGORIES CONFERENCE
+        (10, b'R') => &[39],           // RELATED-TO
+        (10, b'T') => &[56],           // TZOFFSETTO
+        (11, b'D') => &[14],           // DESCRIPTION
+        (12, b'T') => &[55],           // TZOFFSETFROM
+        (13, b'L') => &[24, 27],       // LAST-MODIFIED LOCATION-TYPE
+        (13, b'R') => &[36, 42],       // RECURRENCE-ID RESOURCE-TYPE
+        (14, b'R') => &[41],           // REQUEST-STATUS
+        (15, b'S') => &[48],           // STRUCTURED-DATA
+        (16, b'C') => &[3],            // CALENDAR-ADDRESS
+        (16, b'P') => &[31, 32],       // PARTICIPANT-TYPE PERCENT-COMPLETE
+        (16, b'R') => &[38],           // REFRESH-INTERVAL
+        (18, b'S') => &[49],           // STYLED-DESCRIPTION
+        _ => return None,
+    };
+    candidates.iter().copied().find(|&n| NAMES[n] == name)
+}
+/// The value type of each property when it has no `VALUE` parameter
+pub(crate) const DEFAULT_VALUES: [Value; 61] = [
+    Value::Text,       // ACTION
+    Value::Uri,        // ATTACH
+    Value::CalAddress, // ATTENDEE
+    Value::CalAddress, // CALENDAR-ADDRESS
+    Value::Text,       // CALSCALE
+    Value::Text,       // CATEGORIES
+    Value::Text,       // CLASS
+    Value::Text,       // COLOR
+    Value::Text,       // COMMENT
+    Value::DateTime,   // COMPLETED
+    Value::Uri,        // CONCEPT
+    Value::Uri,        // CONFERENCE
+    Value::Text,       // CONTACT
+    Value::DateTime,   // CREATED
+    Value::Text,       // DESCRIPTION
+    Value::DateTime,   // DTEND
+    Value::DateTime,   // DTSTAMP
+    Value::DateTime,   // DTSTART
+    Value::DateTime,   // DUE
+    Value::Duration,   // DURATION
+    Value::DateTime,   // EXDATE
+    Value::Period,     // FREEBUSY
+    Value::Float,      // GEO
+    Value::Uri,        // IMAGE
+    Value::DateTime,   // LAST-MODIFIED
+    Value::Uri,        // LINK
+    Value::Text,       // LOCATION
+    Value::Text,       // LOCATION-TYPE
+    Value::Text,       // METHOD
+    Value::Text,       // NAME
+    Value::CalAddress, // ORGANIZER
+    Value::Text,       // PARTICIPANT-TYPE
+    Value::Integer,    // PERCENT-COMPLETE
+    Value::Integer,    // PRIORITY
+    Value::Text,       // PRODID
+    Value::DateTime,   // RDATE
+    Value::DateTime,   // RECURRENCE-ID
+    Value::Text,       // REFID
+    Value::Duration,   // REFRESH-INTERVAL
+    Value::Text,       // RELATED-TO
+    Value::Integer,    // REPEAT
+    Value::Text,       // REQUEST-STATUS
+    Value::Text,       // RESOURCE-TYPE
+    Value::Text,       // RESOURCES
+    Value::Recur,      // RRULE
+    Value::Integer,    // SEQUENCE
+    Value::Uri,        // SOURCE
+    Value::Text,       // STATUS
+    Value::Text,       // STRUCTURED-DATA
+    Value::Text,       // STYLED-DESCRIPTION
+    Value::Text,       // SUMMARY
+    Value::Text,       // TRANSP
+    Value::Duration,   // TRIGGER
+    Value::Text,       // TZID
+    Value::Text,       // TZNAME
+    Value::UtcOffset,  // TZOFFSETFROM
+    Value::UtcOffset,  // TZOFFSETTO
+    Value::Uri,        // TZURL
+    Value::Text,       // UID
+    Value::Uri,        // URL
+    Value::Text,       // VERSION
+];
+/// The value types each property may have: its default, and the others a `VALUE`
+/// parameter may choose
+pub(crate) const ALLOWED_VALUES: [&[Value]; 61] = [
+    &[Value::Text],                                 // ACTION
+    &[Value::Uri, Value::Binary],                   // ATTACH
+    &[Value::CalAddress],                           // ATTENDEE
+    &[Value::CalAddress],                           // CALENDAR-ADDRESS
+    &[Value::Text],                                 // CALSCALE
+    &[Value::Text],                                 // CATEGORIES
+    &[Value::Text],                                 // CLASS
+    &[Value::Text],                                 // COLOR
+    &[Value::Text],                                 // COMMENT
+    &[Value::DateTime],                             // COMPLETED
+    &[Value::Uri],                                  // CONCEPT
+    &[Value::Uri],                                  // CONFERENCE
+    &[Value::Text],                                 // CONTACT
+    &[Value::DateTime],                             // CREATED
+    &[Value::Text],                                 // DESCRIPTION
+    &[Value::DateTime, Value::Date],                // DTEND
+    &[Value::DateTime],                             // DTSTAMP
+    &[Value::DateTime, Value::Date],                // DTSTART
+    &[Value::DateTime, Value::Date],                // DUE
+    &[Value::Duration],                             // DURATION
+    &[Value::DateTime, Value::Date],                // EXDATE
+    &[Value::Period],                               // FREEBUSY
+    &[Value::Float],                                // GEO
+    &[Value::Uri, Value::Binary],                   // IMAGE
+    &[Value::DateTime],                             // LAST-MODIFIED
+    &[Value::Uri, Value::Uid, Value::XmlReference], // LINK
+    &[Value::Text],                                 // LOCATION
+    &[Value::Text],                                 // LOCATION-TYPE
+    &[Value::Text],                                 // METHOD
+    &[Value::Text],                                 // NAME
+    &[Value::CalAddress],                           // ORGANIZER
+    &[Value::Text],                                 // PARTICIPANT-TYPE
+    &[Value::Integer],                              // PERCENT-COMPLETE
+    &[Value::Integer],                              // PRIORITY
+    &[Value::Text],                                 // PRODID
+    &[Value::DateTime, Value::Date, Value::Period], // RDATE
+    &[Value::DateTime, Value::Date],                // RECURRENCE-ID
+    &[Value::Text],                                 // REFID
+    &[Value::Duration],                             // REFRESH-INTERVAL
+    &[Value::Text],                                 // RELATED-TO
+    &[Value::Integer],                              // REPEAT
+    &[Value::Text],                                 // REQUEST-STATUS
+    &[Value::Text],                                 // RESOURCE-TYPE
+    &[Value::Text],                                 // RESOURCES
+    &[Value::Recur],                                // RRULE
+    &[Value::Integer],                              // SEQUENCE
+    &[Value::Uri],                                  // SOURCE
+    &[Value::Text],                                 // STATUS
+    &[Value::Text, Value::Binary, Value::Uri],      // STRUCTURED-DATA
+    &[Value::Text, Value::Uri],                     // STYLED-DESCRIPTION
+    &[Value::Text],                                 // SUMMARY
+    &[Value::Text],                                 // TRANSP
+    &[Value::Duration, Value::DateTime],            // TRIGGER
+    &[Value::Text],                                 // TZID
+    &[Value::Text],                                 // TZNAME
+    &[Value::UtcOffset],                            // TZOFFSETFROM
+    &[Value::UtcOffset],                            // TZOFFSETTO
+    &[Value::Uri],                                  // TZURL
+    &[Value::Text],                                 // UID
+    &[Value::Uri],                                  // URL
+    &[Value::Text],                                 // VERSION
+];
+/// Every `PropertyName`, in order
+const ALL: [PropertyName; 61] = [
+    PropertyName::Action,
+    PropertyName::Attach,
+    PropertyName::Attendee,
+    PropertyName::CalendarAddress,
+    PropertyName::Calscale,
+    PropertyName::Categories,
+    PropertyName::Class,
+    PropertyName::Color,
+    PropertyName::Comment,
+    PropertyName::Completed,
+    PropertyName::Concept,
+    PropertyName::Conference,
+    PropertyName::Contact,
+    PropertyName::Created,
+    PropertyName::Description,
+    PropertyName::Dtend,
+    PropertyName::Dtstamp,
+    PropertyName::Dtstart,
+    PropertyName::Due,
+    PropertyName::Duration,
+    PropertyName::Exdate,
+    PropertyName::Freebusy,
+    PropertyName::Geo,
+    PropertyName::Image,
+    PropertyName::LastModified,
+    PropertyName::Link,
+    PropertyName::Location,
+    PropertyName::LocationType,
+    PropertyName::Method,
+    PropertyName::Name,
+    PropertyName::Organizer,
+    PropertyName::ParticipantType,
+    PropertyName::PercentComplete,
+    PropertyName::Priority,
+    PropertyName::Prodid,
+    PropertyName::Rdate,
+    PropertyName::RecurrenceId,
+    PropertyName::Refid,
+    PropertyName::RefreshInterval,
+    PropertyName::RelatedTo,
+    PropertyName::Repeat,
+    PropertyName::RequestStatus,
+    PropertyName::ResourceType,
+    PropertyName::Resources,
+    PropertyName::Rrule,
+    PropertyName::Sequence,
+    PropertyName::Source,
+    PropertyName::Status,
+    PropertyName::StructuredData,
+    PropertyName::StyledDescription,
+    PropertyName::Summary,
+    PropertyName::Transp,
+    PropertyName::Trigger,
+    PropertyName::Tzid,
+    PropertyName::Tzname,
+    PropertyName::Tzoffsetfrom,
+    PropertyName::Tzoffsetto,
+    PropertyName::Tzurl,
+    PropertyName::Uid,
+    PropertyName::Url,
+    PropertyName::Version,
+];
+/// Whether a component may have more than one of each property
+const MULTIPLE: [bool; 61] = [
+    false, // ACTION
+    true,  // ATTACH
+    true,  // ATTENDEE
+    false, // CALENDAR-ADDRESS
+    false, // CALSCALE
+    true,  // CATEGORIES
+    false, // CLASS
+    false, // COLOR
+    true,  // COMMENT
+    false, // COMPLETED
+    true,  // CONCEPT
+    true,  // CONFERENCE
+    true,  // CONTACT
+    false, // CREATED
+    false, // DESCRIPTION
+    false, // DTEND
+    false, // DTSTAMP
+    false, // DTSTART
+    false, // DUE
+    false, // DURATION
+    true,  // EXDATE
+    true,  // FREEBUSY
+    false, // GEO
+    true,  // IMAGE
+    false, // LAST-MODIFIED
+    true,  // LINK
+    false, // LOCATION
+    false, // LOCATION-TYPE
+    false, // METHOD
+    true,  // NAME
+    false, // ORGANIZER
+    false, // PARTICIPANT-TYPE
+    false, // PERCENT-COMPLETE
+    false, // PRIORITY
+    false, // PRODID
+    true,  // RDATE
+    false, // RECURRENCE-ID
+    true,  // REFID
+    false, // REFRESH-INTERVAL
+    true,  // RELATED-TO
+    false, // REPEAT
+    true,  // REQUEST-STATUS
+    false, // RESOURCE-TYPE
+    true,  // RESOURCES
+    false, // RRULE
+    false, // SEQUENCE
+    false, // SOURCE
+    false, // STATUS
+    true,  // STRUCTURED-DATA
+    true,  // STYLED-DESCRIPTION
+    false, // SUMMARY
+    false, // TRANSP
+    false, // TRIGGER
+    false, // TZID
+    true,  // TZNAME
+    false, // TZOFFSETFROM
+    false, // TZOFFSETTO
+    false, // TZURL
+    false, // UID
+    false, // URL
+    false, // VERSION
+];
+/// How `parse_value` reads each property's value
+pub(crate) const VALUE_PARSERS: [ValueParser; 61] = [
+    ValueParser::Single, // ACTION
+    ValueParser::Single, // ATTACH
+    ValueParser::Single, // ATTENDEE
+    ValueParser::Single, // CALENDAR-ADDRESS
+    ValueParser::Single, // CALSCALE
+    ValueParser::List,   // CATEGORIES
+    ValueParser::Single, // CLASS
+    ValueParser::Single, // COLOR
+    ValueParser::Single, // COMMENT
+    ValueParser::Single, // COMPLETED
+    ValueParser::Single, // CONCEPT
+    ValueParser::Single, // CONFERENCE
+    ValueParser::Single, // CONTACT
+    ValueParser::Single, // CREATED
+    ValueParser::Single, // DESCRIPTION
+    ValueParser::Single, // DTEND
+    ValueParser::Single, // DTSTAMP
+    ValueParser::Single, // DTSTART
+    ValueParser::Single, // DUE
+    ValueParser::Single, // DURATION
+    ValueParser::List,   // EXDATE
+    ValueParser::List,   // FREEBUSY
+    ValueParser::Geo,    // GEO
+    ValueParser::Single, // IMAGE
+    ValueParser::Single, // LAST-MODIFIED
+    ValueParser::Single, // LINK
+    ValueParser::Single, // LOCATION
+    ValueParser::Single, // LOCATION-TYPE
+    ValueParser::Single, // METHOD
+    ValueParser::Single, // NAME
+    ValueParser::Single, // ORGANIZER
+    ValueParser::Single, // PARTICIPANT-TYPE
+    ValueParser::Single, // PERCENT-COMPLETE
+    ValueParser::Single, // PRIORITY
+    ValueParser::Single, // PRODID
+    ValueParser::List,   // RDATE
+    ValueParser::Single, // RECURRENCE-ID
+    ValueParser::Single, // REFID
+    ValueParser::Single, // REFRESH-INTERVAL
+    ValueParser::Single, // RELATED-TO
+    ValueParser::Single, // REPEAT
+    ValueParser::Single, // REQUEST-STATUS
+    ValueParser::Single, // RESOURCE-TYPE
+    ValueParser::List,   // RESOURCES
+    ValueParser::Single, // RRULE
+    ValueParser::Single, // SEQUENCE
+    ValueParser::Single, // SOURCE
+    ValueParser::Single, // STATUS
+    ValueParser::Single, // STRUCTURED-DATA
+    ValueParser::Single, // STYLED-DESCRIPTION
+    ValueParser::Single, // SUMMARY
+    ValueParser::Single, // TRANSP
+    ValueParser::Single, // TRIGGER
+    ValueParser::Single, // TZID
+    ValueParser::Single, // TZNAME
+    ValueParser::Single, // TZOFFSETFROM
+    ValueParser::Single, // TZOFFSETTO
+    ValueParser::Single, // TZURL
+    ValueParser::Single, // UID
+    ValueParser::Single, // URL
+    ValueParser::Single, // VERSION
+];
+
+impl Properties {
+    #[must_use]
+    pub fn new() -> Self {
+        Properties::default()
+    }
+    /// The values of property `name`, in the order they were added
+    #[must_use]
+    pub fn get(&self, name: PropertyName) -> &[PropertyValue] {
+        self.0.get(&(name as usize)).map_or(&[], Vec::as_slice)
+    }
+    /// The first value of property `name`
+    #[must_use]
+    pub fn first(&self, name: PropertyName) -> Option<&PropertyValue> {
+        self.get(name).first()
+    }
+    /// Add a value of property `name`, after any it already has
+    pub fn add(&mut self, name: PropertyName, value: PropertyValue) {
+        match self.0.get_mut(&(name as usize)) {
+            Some(values) => values.push(value),
+            None => drop(self.0.insert(name as usize, vec![value])),
+        }
+    }
+    /// Replace any values of property `name` with `value`
+    pub fn set(&mut self, name: PropertyName, value: PropertyValue) {
+        self.0.insert(name as usize, vec![value]);
+    }
+    /// Remove property `name`, returning its values
+    pub fn remove(&mut self, name: PropertyName) -> Vec<PropertyValue> {
+        self.0.remove(&(name as usize)).unwrap_or_default()
+    }
+    /// Every value, with its property, in order by property name
+    pub fn iter(&self) -> impl Iterator<Item = (PropertyName, &PropertyValue)> {
+        (self.0.iter()).flat_map(|(n, values)| values.iter().map(move |value| (ALL[*n], value)))
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the `ACTION` property ([RFC 5545, § 3.8.6.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.6.1)).
+    #[must_use]
+    pub fn action(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Action)
+    }
+    /// Set the `ACTION` property ([RFC 5545, § 3.8.6.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.6.1)).
+    pub fn set_action(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Action, value);
+    }
+
+    /// Get the `ATTACH` properties ([RFC 5545, § 3.8.1.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.1)).
+    #[must_use]
+    pub fn attach(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Attach)
+    }
+    /// Add a value of the `ATTACH` property ([RFC 5545, § 3.8.1.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.1)).
+    pub fn add_attach(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Attach, value);
+    }
+
+    /// Get the `ATTENDEE` properties ([RFC 5545, § 3.8.4.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.1)).
+    #[must_use]
+    pub fn attendee(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Attendee)
+    }
+    /// Add a value of the `ATTENDEE` property ([RFC 5545, § 3.8.4.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.1)).
+    pub fn add_attendee(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Attendee, value);
+    }
+
+    /// Get the `CALENDAR-ADDRESS` property ([RFC 9073, § 6.4](https://datatracker.ietf.org/doc/html/rfc9073#section-6.4)).
+    #[must_use]
+    pub fn calendar_address(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::CalendarAddress)
+    }
+    /// Set the `CALENDAR-ADDRESS` property ([RFC 9073, § 6.4](https://datatracker.ietf.org/doc/html/rfc9073#section-6.4)).
+    pub fn set_calendar_address(&mut self, value: PropertyValue) {
+        self.set(PropertyName::CalendarAddress, value);
+    }
+
+    /// Get the `CALSCALE` property ([RFC 5545, § 3.7.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.1)).
+    #[must_use]
+    pub fn calscale(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Calscale)
+    }
+    /// Set the `CALSCALE` property ([RFC 5545, § 3.7.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.1)).
+    pub fn set_calscale(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Calscale, value);
+    }
+
+    /// Get the `CATEGORIES` properties ([RFC 5545, § 3.8.1.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.2)).
+    #[must_use]
+    pub fn categories(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Categories)
+    }
+    /// Add a value of the `CATEGORIES` property ([RFC 5545, § 3.8.1.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.2)).
+    pub fn add_categories(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Categories, value);
+    }
+
+    /// Get the `CLASS` property ([RFC 5545, § 3.8.1.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.3)).
+    #[must_use]
+    pub fn class(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Class)
+    }
+    /// Set the `CLASS` property ([RFC 5545, § 3.8.1.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.3)).
+    pub fn set_class(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Class, value);
+    }
+
+    /// Get the `COLOR` property ([RFC 7986, § 5.9](https://datatracker.ietf.org/doc/html/rfc7986#section-5.9)).
+    #[must_use]
+    pub fn color(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Color)
+    }
+    /// Set the `COLOR` property ([RFC 7986, § 5.9](https://datatracker.ietf.org/doc/html/rfc7986#section-5.9)).
+    pub fn set_color(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Color, value);
+    }
+
+    /// Get the `COMMENT` properties ([RFC 5545, § 3.8.1.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.4)).
+    #[must_use]
+    pub fn comment(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Comment)
+    }
+    /// Add a value of the `COMMENT` property ([RFC 5545, § 3.8.1.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.4)).
+    pub fn add_comment(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Comment, value);
+    }
+
+    /// Get the `COMPLETED` property ([RFC 5545, § 3.8.2.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.1)).
+    #[must_use]
+    pub fn completed(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Completed)
+    }
+    /// Set the `COMPLETED` property ([RFC 5545, § 3.8.2.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.1)).
+    pub fn set_completed(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Completed, value);
+    }
+
+    /// Get the `CONCEPT` properties ([RFC 9253, § 8.1](https://datatracker.ietf.org/doc/html/rfc9253#section-8.1)).
+    #[must_use]
+    pub fn concept(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Concept)
+    }
+    /// Add a value of the `CONCEPT` property ([RFC 9253, § 8.1](https://datatracker.ietf.org/doc/html/rfc9253#section-8.1)).
+    pub fn add_concept(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Concept, value);
+    }
+
+    /// Get the `CONFERENCE` properties ([RFC 7986, § 5.11](https://datatracker.ietf.org/doc/html/rfc7986#section-5.11)).
+    #[must_use]
+    pub fn conference(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Conference)
+    }
+    /// Add a value of the `CONFERENCE` property ([RFC 7986, § 5.11](https://datatracker.ietf.org/doc/html/rfc7986#section-5.11)).
+    pub fn add_conference(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Conference, value);
+    }
+
+    /// Get the `CONTACT` properties ([RFC 5545, § 3.8.4.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.2)).
+    #[must_use]
+    pub fn contact(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Contact)
+    }
+    /// Add a value of the `CONTACT` property ([RFC 5545, § 3.8.4.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.2)).
+    pub fn add_contact(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Contact, value);
+    }
+
+    /// Get the `CREATED` property ([RFC 5545, § 3.8.7.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.1)).
+    #[must_use]
+    pub fn created(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Created)
+    }
+    /// Set the `CREATED` property ([RFC 5545, § 3.8.7.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.1)).
+    pub fn set_created(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Created, value);
+    }
+
+    /// Get the `DESCRIPTION` property ([RFC 5545, § 3.8.1.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.5)).
+    #[must_use]
+    pub fn description(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Description)
+    }
+    /// Set the `DESCRIPTION` property ([RFC 5545, § 3.8.1.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.5)).
+    pub fn set_description(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Description, value);
+    }
+
+    /// Get the `DTEND` property ([RFC 5545, § 3.8.2.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.2)).
+    #[must_use]
+    pub fn dtend(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Dtend)
+    }
+    /// Set the `DTEND` property ([RFC 5545, § 3.8.2.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.2)).
+    pub fn set_dtend(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Dtend, value);
+    }
+
+    /// Get the `DTSTAMP` property ([RFC 5545, § 3.8.7.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.2)).
+    #[must_use]
+    pub fn dtstamp(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Dtstamp)
+    }
+    /// Set the `DTSTAMP` property ([RFC 5545, § 3.8.7.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.2)).
+    pub fn set_dtstamp(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Dtstamp, value);
+    }
+
+    /// Get the `DTSTART` property ([RFC 5545, § 3.8.2.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.4)).
+    #[must_use]
+    pub fn dtstart(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Dtstart)
+    }
+    /// Set the `DTSTART` property ([RFC 5545, § 3.8.2.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.4)).
+    pub fn set_dtstart(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Dtstart, value);
+    }
+
+    /// Get the `DUE` property ([RFC 5545, § 3.8.2.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.3)).
+    #[must_use]
+    pub fn due(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Due)
+    }
+    /// Set the `DUE` property ([RFC 5545, § 3.8.2.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.3)).
+    pub fn set_due(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Due, value);
+    }
+
+    /// Get the `DURATION` property ([RFC 5545, § 3.8.2.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.5)).
+    #[must_use]
+    pub fn duration(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Duration)
+    }
+    /// Set the `DURATION` property ([RFC 5545, § 3.8.2.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.5)).
+    pub fn set_duration(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Duration, value);
+    }
+
+    /// Get the `EXDATE` properties ([RFC 5545, § 3.8.5.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.5.1)).
+    #[must_use]
+    pub fn exdate(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Exdate)
+    }
+    /// Add a value of the `EXDATE` property ([RFC 5545, § 3.8.5.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.5.1)).
+    pub fn add_exdate(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Exdate, value);
+    }
+
+    /// Get the `FREEBUSY` properties ([RFC 5545, § 3.8.2.6](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.6)).
+    #[must_use]
+    pub fn freebusy(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Freebusy)
+    }
+    /// Add a value of the `FREEBUSY` property ([RFC 5545, § 3.8.2.6](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.6)).
+    pub fn add_freebusy(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Freebusy, value);
+    }
+
+    /// Get the `GEO` property ([RFC 5545, § 3.8.1.6](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.6)).
+    #[must_use]
+    pub fn geo(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Geo)
+    }
+    /// Set the `GEO` property ([RFC 5545, § 3.8.1.6](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.6)).
+    pub fn set_geo(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Geo, value);
+    }
+
+    /// Get the `IMAGE` properties ([RFC 7986, § 5.10](https://datatracker.ietf.org/doc/html/rfc7986#section-5.10)).
+    #[must_use]
+    pub fn image(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Image)
+    }
+    /// Add a value of the `IMAGE` property ([RFC 7986, § 5.10](https://datatracker.ietf.org/doc/html/rfc7986#section-5.10)).
+    pub fn add_image(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Image, value);
+    }
+
+    /// Get the `LAST-MODIFIED` property ([RFC 5545, § 3.8.7.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.3)).
+    #[must_use]
+    pub fn last_modified(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::LastModified)
+    }
+    /// Set the `LAST-MODIFIED` property ([RFC 5545, § 3.8.7.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.3)).
+    pub fn set_last_modified(&mut self, value: PropertyValue) {
+        self.set(PropertyName::LastModified, value);
+    }
+
+    /// Get the `LINK` properties ([RFC 9253, § 8.2](https://datatracker.ietf.org/doc/html/rfc9253#section-8.2)).
+    #[must_use]
+    pub fn link(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Link)
+    }
+    /// Add a value of the `LINK` property ([RFC 9253, § 8.2](https://datatracker.ietf.org/doc/html/rfc9253#section-8.2)).
+    pub fn add_link(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Link, value);
+    }
+
+    /// Get the `LOCATION` property ([RFC 5545, § 3.8.1.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.7)).
+    #[must_use]
+    pub fn location(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Location)
+    }
+    /// Set the `LOCATION` property ([RFC 5545, § 3.8.1.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.7)).
+    pub fn set_location(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Location, value);
+    }
+
+    /// Get the `LOCATION-TYPE` property ([RFC 9073, § 6.1](https://datatracker.ietf.org/doc/html/rfc9073#section-6.1)).
+    #[must_use]
+    pub fn location_type(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::LocationType)
+    }
+    /// Set the `LOCATION-TYPE` property ([RFC 9073, § 6.1](https://datatracker.ietf.org/doc/html/rfc9073#section-6.1)).
+    pub fn set_location_type(&mut self, value: PropertyValue) {
+        self.set(PropertyName::LocationType, value);
+    }
+
+    /// Get the `METHOD` property ([RFC 5545, § 3.7.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.2)).
+    #[must_use]
+    pub fn method(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Method)
+    }
+    /// Set the `METHOD` property ([RFC 5545, § 3.7.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.2)).
+    pub fn set_method(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Method, value);
+    }
+
+    /// Get the `NAME` properties ([RFC 7986, § 5.1](https://datatracker.ietf.org/doc/html/rfc7986#section-5.1)).
+    #[must_use]
+    pub fn name(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Name)
+    }
+    /// Add a value of the `NAME` property ([RFC 7986, § 5.1](https://datatracker.ietf.org/doc/html/rfc7986#section-5.1)).
+    pub fn add_name(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Name, value);
+    }
+
+    /// Get the `ORGANIZER` property ([RFC 5545, § 3.8.4.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.3)).
+    #[must_use]
+    pub fn organizer(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Organizer)
+    }
+    /// Set the `ORGANIZER` property ([RFC 5545, § 3.8.4.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.3)).
+    pub fn set_organizer(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Organizer, value);
+    }
+
+    /// Get the `PARTICIPANT-TYPE` property ([RFC 9073, § 6.2](https://datatracker.ietf.org/doc/html/rfc9073#section-6.2)).
+    #[must_use]
+    pub fn participant_type(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::ParticipantType)
+    }
+    /// Set the `PARTICIPANT-TYPE` property ([RFC 9073, § 6.2](https://datatracker.ietf.org/doc/html/rfc9073#section-6.2)).
+    pub fn set_participant_type(&mut self, value: PropertyValue) {
+        self.set(PropertyName::ParticipantType, value);
+    }
+
+    /// Get the `PERCENT-COMPLETE` property ([RFC 5545, § 3.8.1.8](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.8)).
+    #[must_use]
+    pub fn percent_complete(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::PercentComplete)
+    }
+    /// Set the `PERCENT-COMPLETE` property ([RFC 5545, § 3.8.1.8](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.8)).
+    pub fn set_percent_complete(&mut self, value: PropertyValue) {
+        self.set(PropertyName::PercentComplete, value);
+    }
+
+    /// Get the `PRIORITY` property ([RFC 5545, § 3.8.1.9](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.9)).
+    #[must_use]
+    pub fn priority(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Priority)
+    }
+    /// Set the `PRIORITY` property ([RFC 5545, § 3.8.1.9](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.9)).
+    pub fn set_priority(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Priority, value);
+    }
+
+    /// Get the `PRODID` property ([RFC 5545, § 3.7.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.3)).
+    #[must_use]
+    pub fn prodid(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Prodid)
+    }
+    /// Set the `PRODID` property ([RFC 5545, § 3.7.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.3)).
+    pub fn set_prodid(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Prodid, value);
+    }
+
+    /// Get the `RDATE` properties ([RFC 5545, § 3.8.5.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.5.2)).
+    #[must_use]
+    pub fn rdate(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Rdate)
+    }
+    /// Add a value of the `RDATE` property ([RFC 5545, § 3.8.5.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.5.2)).
+    pub fn add_rdate(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Rdate, value);
+    }
+
+    /// Get the `RECURRENCE-ID` property ([RFC 5545, § 3.8.4.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.4)).
+    #[must_use]
+    pub fn recurrence_id(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::RecurrenceId)
+    }
+    /// Set the `RECURRENCE-ID` property ([RFC 5545, § 3.8.4.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.4)).
+    pub fn set_recurrence_id(&mut self, value: PropertyValue) {
+        self.set(PropertyName::RecurrenceId, value);
+    }
+
+    /// Get the `REFID` properties ([RFC 9253, § 8.3](https://datatracker.ietf.org/doc/html/rfc9253#section-8.3)).
+    #[must_use]
+    pub fn refid(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Refid)
+    }
+    /// Add a value of the `REFID` property ([RFC 9253, § 8.3](https://datatracker.ietf.org/doc/html/rfc9253#section-8.3)).
+    pub fn add_refid(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Refid, value);
+    }
+
+    /// Get the `REFRESH-INTERVAL` property ([RFC 7986, § 5.7](https://datatracker.ietf.org/doc/html/rfc7986#section-5.7)).
+    #[must_use]
+    pub fn refresh_interval(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::RefreshInterval)
+    }
+    /// Set the `REFRESH-INTERVAL` property ([RFC 7986, § 5.7](https://datatracker.ietf.org/doc/html/rfc7986#section-5.7)).
+    pub fn set_refresh_interval(&mut self, value: PropertyValue) {
+        self.set(PropertyName::RefreshInterval, value);
+    }
+
+    /// Get the `RELATED-TO` properties ([RFC 5545, § 3.8.4.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.5)).
+    #[must_use]
+    pub fn related_to(&self) -> &[PropertyValue] {
+        self.get(PropertyName::RelatedTo)
+    }
+    /// Add a value of the `RELATED-TO` property ([RFC 5545, § 3.8.4.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.5)).
+    pub fn add_related_to(&mut self, value: PropertyValue) {
+        self.add(PropertyName::RelatedTo, value);
+    }
+
+    /// Get the `REPEAT` property ([RFC 5545, § 3.8.6.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.6.2)).
+    #[must_use]
+    pub fn repeat(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Repeat)
+    }
+    /// Set the `REPEAT` property ([RFC 5545, § 3.8.6.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.6.2)).
+    pub fn set_repeat(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Repeat, value);
+    }
+
+    /// Get the `REQUEST-STATUS` properties ([RFC 5545, § 3.8.8.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.8.3)).
+    #[must_use]
+    pub fn request_status(&self) -> &[PropertyValue] {
+        self.get(PropertyName::RequestStatus)
+    }
+    /// Add a value of the `REQUEST-STATUS` property ([RFC 5545, § 3.8.8.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.8.3)).
+    pub fn add_request_status(&mut self, value: PropertyValue) {
+        self.add(PropertyName::RequestStatus, value);
+    }
+
+    /// Get the `RESOURCE-TYPE` property ([RFC 9073, § 6.3](https://datatracker.ietf.org/doc/html/rfc9073#section-6.3)).
+    #[must_use]
+    pub fn resource_type(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::ResourceType)
+    }
+    /// Set the `RESOURCE-TYPE` property ([RFC 9073, § 6.3](https://datatracker.ietf.org/doc/html/rfc9073#section-6.3)).
+    pub fn set_resource_type(&mut self, value: PropertyValue) {
+        self.set(PropertyName::ResourceType, value);
+    }
+
+    /// Get the `RESOURCES` properties ([RFC 5545, § 3.8.1.10](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.10)).
+    #[must_use]
+    pub fn resources(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Resources)
+    }
+    /// Add a value of the `RESOURCES` property ([RFC 5545, § 3.8.1.10](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.10)).
+    pub fn add_resources(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Resources, value);
+    }
+
+    /// Get the `RRULE` property ([RFC 5545, § 3.8.5.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.5.3)).
+    #[must_use]
+    pub fn rrule(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Rrule)
+    }
+    /// Set the `RRULE` property ([RFC 5545, § 3.8.5.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.5.3)).
+    pub fn set_rrule(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Rrule, value);
+    }
+
+    /// Get the `SEQUENCE` property ([RFC 5545, § 3.8.7.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.4)).
+    #[must_use]
+    pub fn sequence(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Sequence)
+    }
+    /// Set the `SEQUENCE` property ([RFC 5545, § 3.8.7.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.7.4)).
+    pub fn set_sequence(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Sequence, value);
+    }
+
+    /// Get the `SOURCE` property ([RFC 7986, § 5.8](https://datatracker.ietf.org/doc/html/rfc7986#section-5.8)).
+    #[must_use]
+    pub fn source(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Source)
+    }
+    /// Set the `SOURCE` property ([RFC 7986, § 5.8](https://datatracker.ietf.org/doc/html/rfc7986#section-5.8)).
+    pub fn set_source(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Source, value);
+    }
+
+    /// Get the `STATUS` property ([RFC 5545, § 3.8.1.11](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.11)).
+    #[must_use]
+    pub fn status(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Status)
+    }
+    /// Set the `STATUS` property ([RFC 5545, § 3.8.1.11](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.11)).
+    pub fn set_status(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Status, value);
+    }
+
+    /// Get the `STRUCTURED-DATA` properties ([RFC 9073, § 6.6](https://datatracker.ietf.org/doc/html/rfc9073#section-6.6)).
+    #[must_use]
+    pub fn structured_data(&self) -> &[PropertyValue] {
+        self.get(PropertyName::StructuredData)
+    }
+    /// Add a value of the `STRUCTURED-DATA` property ([RFC 9073, § 6.6](https://datatracker.ietf.org/doc/html/rfc9073#section-6.6)).
+    pub fn add_structured_data(&mut self, value: PropertyValue) {
+        self.add(PropertyName::StructuredData, value);
+    }
+
+    /// Get the `STYLED-DESCRIPTION` properties ([RFC 9073, § 6.5](https://datatracker.ietf.org/doc/html/rfc9073#section-6.5)).
+    #[must_use]
+    pub fn styled_description(&self) -> &[PropertyValue] {
+        self.get(PropertyName::StyledDescription)
+    }
+    /// Add a value of the `STYLED-DESCRIPTION` property ([RFC 9073, § 6.5](https://datatracker.ietf.org/doc/html/rfc9073#section-6.5)).
+    pub fn add_styled_description(&mut self, value: PropertyValue) {
+        self.add(PropertyName::StyledDescription, value);
+    }
+
+    /// Get the `SUMMARY` property ([RFC 5545, § 3.8.1.12](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.12)).
+    #[must_use]
+    pub fn summary(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Summary)
+    }
+    /// Set the `SUMMARY` property ([RFC 5545, § 3.8.1.12](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.1.12)).
+    pub fn set_summary(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Summary, value);
+    }
+
+    /// Get the `TRANSP` property ([RFC 5545, § 3.8.2.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.7)).
+    #[must_use]
+    pub fn transp(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Transp)
+    }
+    /// Set the `TRANSP` property ([RFC 5545, § 3.8.2.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.2.7)).
+    pub fn set_transp(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Transp, value);
+    }
+
+    /// Get the `TRIGGER` property ([RFC 5545, § 3.8.6.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.6.3)).
+    #[must_use]
+    pub fn trigger(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Trigger)
+    }
+    /// Set the `TRIGGER` property ([RFC 5545, § 3.8.6.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.6.3)).
+    pub fn set_trigger(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Trigger, value);
+    }
+
+    /// Get the `TZID` property ([RFC 5545, § 3.8.3.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.1)).
+    #[must_use]
+    pub fn tzid(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Tzid)
+    }
+    /// Set the `TZID` property ([RFC 5545, § 3.8.3.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.1)).
+    pub fn set_tzid(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Tzid, value);
+    }
+
+    /// Get the `TZNAME` properties ([RFC 5545, § 3.8.3.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.2)).
+    #[must_use]
+    pub fn tzname(&self) -> &[PropertyValue] {
+        self.get(PropertyName::Tzname)
+    }
+    /// Add a value of the `TZNAME` property ([RFC 5545, § 3.8.3.2](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.2)).
+    pub fn add_tzname(&mut self, value: PropertyValue) {
+        self.add(PropertyName::Tzname, value);
+    }
+
+    /// Get the `TZOFFSETFROM` property ([RFC 5545, § 3.8.3.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.3)).
+    #[must_use]
+    pub fn tzoffsetfrom(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Tzoffsetfrom)
+    }
+    /// Set the `TZOFFSETFROM` property ([RFC 5545, § 3.8.3.3](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.3)).
+    pub fn set_tzoffsetfrom(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Tzoffsetfrom, value);
+    }
+
+    /// Get the `TZOFFSETTO` property ([RFC 5545, § 3.8.3.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.4)).
+    #[must_use]
+    pub fn tzoffsetto(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Tzoffsetto)
+    }
+    /// Set the `TZOFFSETTO` property ([RFC 5545, § 3.8.3.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.4)).
+    pub fn set_tzoffsetto(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Tzoffsetto, value);
+    }
+
+    /// Get the `TZURL` property ([RFC 5545, § 3.8.3.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.5)).
+    #[must_use]
+    pub fn tzurl(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Tzurl)
+    }
+    /// Set the `TZURL` property ([RFC 5545, § 3.8.3.5](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.3.5)).
+    pub fn set_tzurl(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Tzurl, value);
+    }
+
+    /// Get the `UID` property ([RFC 5545, § 3.8.4.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.7)).
+    #[must_use]
+    pub fn uid(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Uid)
+    }
+    /// Set the `UID` property ([RFC 5545, § 3.8.4.7](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.7)).
+    pub fn set_uid(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Uid, value);
+    }
+
+    /// Get the `URL` property ([RFC 5545, § 3.8.4.6](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.6)).
+    #[must_use]
+    pub fn url(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Url)
+    }
+    /// Set the `URL` property ([RFC 5545, § 3.8.4.6](https://datatracker.ietf.org/doc/html/rfc5545#section-3.8.4.6)).
+    pub fn set_url(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Url, value);
+    }
+
+    /// Get the `VERSION` property ([RFC 5545, § 3.7.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.4)).
+    #[must_use]
+    pub fn version(&self) -> Option<&PropertyValue> {
+        self.first(PropertyName::Version)
+    }
+    /// Set the `VERSION` property ([RFC 5545, § 3.7.4](https://datatracker.ietf.org/doc/html/rfc5545#section-3.7.4)).
+    pub fn set_version(&mut self, value: PropertyValue) {
+        self.set(PropertyName::Version, value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn property_names_are_sorted() {
+        let mut sorted = NAMES;
+        sorted.sort_unstable();
+        assert_eq!(NAMES, sorted);
+    }
+    #[test]
+    fn default_values_are_allowed() {
+        for (n, default) in DEFAULT_VALUES.iter().enumerate() {
+            assert!(ALLOWED_VALUES[n].contains(default), "{}", NAMES[n]);
+        }
+    }
+    #[test]
+    fn property_names_match_ids() {
+        let mut lookup = crate::names::Lookup::new();
+        for (n, name) in ALL.into_iter().enumerate() {
+            assert_eq!(name as usize, n);
+            assert_eq!(PropertyName::from_name(&name.name().to_lowercase()), Some(name));
+            assert_eq!(lookup.property_id(name.name()).unwrap(), name.id());
+            assert_eq!(PropertyName::from_id(name.id()), Some(name));
+        }
+        assert_eq!(PropertyName::from_name("X-WR-CALNAME"), None);
+    }
+    #[test]
+    fn typed_getters() {
+        let mut properties = Properties::new();
+        assert_eq!(properties.summary(), None);
+        properties.set_summary(PropertyValue::from("Lunch".to_string()));
+        properties.set_summary(PropertyValue::from("Dinner".to_string()));
+        properties.add_attendee(PropertyValue::CalAddress("mailto:a@example.com".into()));
+        properties.add_attendee(PropertyValue::CalAddress("mailto:b@example.com".into()));
+        assert_eq!(properties.summary(), Some(&PropertyValue::from("Dinner".to_string())));
+        assert_eq!(properties.attendee().len(), 2);
+        assert!(PropertyName::Attendee.is_multiple() && !PropertyName::Summary.is_multiple());
+        let names: Vec<_> = properties.iter().map(|(name, _)| name.name()).collect();
+        assert_eq!(names, ["ATTENDEE", "ATTENDEE", "SUMMARY"]);
+        assert_eq!(properties.remove(PropertyName::Attendee).len(), 2);
+        assert_eq!(properties.get(PropertyName::Attendee), &[]);
+    }
+}
@@ -0,0 +1,175 @@
+// `TEXT` values ([RFC 5545 § 3.3.11](https://datatracker.ietf.org/doc/html/rfc5545#section-3.3.11)):
+// in a content line, backslashes, semicolons, and commas are escaped with a backslash, and
+// newlines (LF or CRLF) are written `\n` (or `\N`). No other control character but tab can be
+// written; we write its Unicode control picture instead.
+use std::fmt;
+
+/// How newlines are escaped when writing a `TEXT` value. RFC 5545 allows `\n` and `\N`, and
+/// readers (including ours) must accept both, but some consumers only understand one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NewlineEscape {
+    /// `\n`, which almost every producer writes
+    #[default]
+    Lowercase,
+    /// `\N`
+    Uppercase,
+}
+
+/// A `TEXT` value, unescaped. Its `Display` output is the escaped form, ready for a content
+/// line.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Text(String);
+
+impl Text {
+    /// The text of an escaped value, as it appears in a content line
+    #[must_use]
+    pub fn from_escaped(escaped: &str) -> Self {
+        Text(unescape_text(escaped))
+    }
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Text(text)
+    }
+}
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        Text(text.to_string())
+    }
+}
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_escaped(f, &self.0, NewlineEscape::default())
+    }
+}
+
+/// `text` escaped for a content line, with newlines written `\n`
+#[must_use]
+pub fn escape_text(text: &str) -> String {
+    escape_text_with(text, NewlineEscape::default())
+}
+
+/// `text` escaped for a content line, with newlines escaped as `newline` says
+#[must_use]
+pub fn escape_text_with(text: &str, newline: NewlineEscape) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    // Writing to a `String` never fails
+    let _ = write_escaped(&mut escaped, text, newline);
+    escaped
+}
+
+pub(crate) fn write_escaped(
+    w: &mut impl fmt::Write,
+    text: &str,
+    newline: NewlineEscape,
+) -> fmt::Result {
+    let newline = match newline {
+        NewlineEscape::Lowercase => "\\n",
+        NewlineEscape::Uppercase => "\\N",
+    };
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' | ';' | ',' => write!(w, "\\{c}")?,
+            '\n' => w.write_str(newline)?,
+            // CRLF is one newline
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\t' => w.write_char(c)?,
+            // `TEXT` can't hold any other control character, nor can any escape represent one,
+            // so we write its Unicode control picture (like `␀` or `␍`) in its place
+            '\0'..='\u{1F}' => w.write_char(char::from_u32(0x2400 + u32::from(c)).unwrap_or(c))?,
+            '\u{7F}' => w.write_char('\u{2421}')?,
+            c => w.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// An escaped `TEXT` value with its escapes undone. A backslash before any other character
+/// (which RFC 5545 doesn't allow) is dropped, and one at the end is kept.
+#[must_use]
+pub fn unescape_text(escaped: &str) -> String {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(escaped) => unescaped.push(escaped),
+                None => unescaped.push('\\'),
+            },
+            (c, false) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// A multi-valued `TEXT` value (like `CATEGORIES`), split at the commas that aren't escaped,
+/// with each value unescaped
+#[must_use]
+pub fn split_text_list(escaped: &str) -> Vec<String> {
+    let mut items = vec![];
+    let (mut start, mut backslash) = (0, false);
+    for (n, c) in escaped.char_indices() {
+        if c == ',' && !backslash {
+            items.push(unescape_text(&escaped[start..n]));
+            start = n + 1;
+        }
+        backslash = c == '\\' && !backslash;
+    }
+    items.push(unescape_text(&escaped[start..]));
+    items
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn escapes() {
+        let text = "Lunch; then a walk, by the river\\lake\r\nBring shoes";
+        let escaped = r"Lunch\; then a walk\, by the river\\lake\nBring shoes";
+        assert_eq!(escape_text(text), escaped);
+        assert_eq!(unescape_text(escaped), text.replace('\r', ""));
+        assert_eq!(unescape_text(r"A\NB\:C\"), "A\nB:C\\");
+        assert_eq!(Text::from_escaped(escaped).to_string(), escaped);
+        assert_eq!(Text::from("a,b").to_string(), r"a\,b");
+    }
+    #[test]
+    fn control_characters() {
+        assert_eq!(escape_text("a\r\nb\rc\r"), "a\\nb\u{240D}c\u{240D}");
+        assert_eq!(escape_text("tab\there\0\x1B[1m\x7F"), "tab\there\u{2400}\u{241B}[1m\u{2421}");
+    }
+    #[test]
+    fn newline_styles() {
+        // As Google Calendar and Outlook write them, and as older Lotus Notes exports do
+        let lower =
+            r"Join the meeting\nhttps://meet.example.com/abc-defg-hij\n\nDial-in: +1 555 0100";
+        let upper =
+            r"Join the meeting\Nhttps://meet.example.com/abc-defg-hij\N\NDial-in: +1 555 0100";
+        let text =
+            "Join the meeting\nhttps://meet.example.com/abc-defg-hij\n\nDial-in: +1 555 0100";
+        assert_eq!(unescape_text(lower), text);
+        assert_eq!(unescape_text(upper), text);
+        assert_eq!(escape_text(text), lower);
+        assert_eq!(escape_text_with(text, NewlineEscape::Lowercase), lower);
+        assert_eq!(escape_text_with(text, NewlineEscape::Uppercase), upper);
+        // A mix, as hand-edited files have; and a backslash before N isn't a newline
+        assert_eq!(unescape_text(r"a\nb\Nc\\Nd"), "a\nb\nc\\Nd");
+        assert_eq!(escape_text_with("a\\Nb\r\n", NewlineEscape::Uppercase), r"a\\Nb\N");
+    }
+    #[test]
+    fn lists() {
+        assert_eq!(split_text_list(r"a,b\,c,d\\,e"), vec!["a", "b,c", "d\\", "e"]);
+        assert_eq!(split_text_list(""), vec![""]);
+    }
+}
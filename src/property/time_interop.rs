@@ -0,0 +1,143 @@
+// Conversions between `PropertyValue` and the `time` crate's types, for projects standardized
+// on `time` rather than jiff. They go through the jiff types `PropertyValue` holds:
+//  * a `time::Date` is a `DATE`, and a `PrimitiveDateTime` a floating `DATE-TIME`;
+//  * an `OffsetDateTime` is a `DATE-TIME` in UTC or, for any other offset, in that fixed
+//    offset; and a `DATE-TIME` in UTC or in a time zone converts to an `OffsetDateTime` with
+//    the offset in effect then.
+// As with the jiff types, only a single-valued list converts to a `time` type. jiff and
+// `time` support slightly different ranges, so conversions both ways can fail.
+use super::{ConversionError, PropertyValue};
+use jiff::tz::{Offset, TimeZone};
+use jiff::{Timestamp, Zoned, civil};
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+fn out_of_range<E>(_: E) -> ConversionError {
+    ConversionError::OutOfRange
+}
+
+fn date_from_jiff(date: civil::Date) -> Result<Date, ConversionError> {
+    let month = Month::try_from(u8::try_from(date.month()).map_err(out_of_range)?);
+    let day = u8::try_from(date.day()).map_err(out_of_range)?;
+    Date::from_calendar_date(i32::from(date.year()), month.map_err(out_of_range)?, day)
+        .map_err(out_of_range)
+}
+fn date_to_jiff(date: Date) -> Result<civil::Date, ConversionError> {
+    let year = i16::try_from(date.year()).map_err(out_of_range)?;
+    let month = i8::try_from(u8::from(date.month())).map_err(out_of_range)?;
+    let day = i8::try_from(date.day()).map_err(out_of_range)?;
+    civil::Date::new(year, month, day).map_err(out_of_range)
+}
+fn time_from_jiff(time: civil::Time) -> Result<Time, ConversionError> {
+    let part = |n: i8| u8::try_from(n).map_err(out_of_range);
+    let nanosecond = u32::try_from(time.subsec_nanosecond()).map_err(out_of_range)?;
+    Time::from_hms_nano(part(time.hour())?, part(time.minute())?, part(time.second())?, nanosecond)
+        .map_err(out_of_range)
+}
+fn time_to_jiff(time: Time) -> Result<civil::Time, ConversionError> {
+    let part = |n: u8| i8::try_from(n).map_err(out_of_range);
+    let nanosecond = i32::try_from(time.nanosecond()).map_err(out_of_range)?;
+    civil::Time::new(part(time.hour())?, part(time.minute())?, part(time.second())?, nanosecond)
+        .map_err(out_of_range)
+}
+
+impl TryFrom<&PropertyValue> for Date {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        date_from_jiff(civil::Date::try_from(value)?)
+    }
+}
+impl TryFrom<Date> for PropertyValue {
+    type Error = ConversionError;
+    fn try_from(date: Date) -> Result<Self, ConversionError> {
+        Ok(PropertyValue::from(date_to_jiff(date)?))
+    }
+}
+
+/// A floating `DATE-TIME`
+impl TryFrom<&PropertyValue> for PrimitiveDateTime {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        let dt = civil::DateTime::try_from(value)?;
+        Ok(PrimitiveDateTime::new(date_from_jiff(dt.date())?, time_from_jiff(dt.time())?))
+    }
+}
+impl TryFrom<PrimitiveDateTime> for PropertyValue {
+    type Error = ConversionError;
+    fn try_from(dt: PrimitiveDateTime) -> Result<Self, ConversionError> {
+        let (date, time) = (date_to_jiff(dt.date())?, time_to_jiff(dt.time())?);
+        Ok(PropertyValue::from(date.to_datetime(time)))
+    }
+}
+
+/// A `DATE-TIME` in UTC, or in a time zone (with the offset in effect then)
+impl TryFrom<&PropertyValue> for OffsetDateTime {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        let (instant, offset) = match value {
+            PropertyValue::DateTimeZoned(_) => {
+                let zoned = Zoned::try_from(value)?;
+                (zoned.timestamp(), zoned.offset())
+            }
+            _ => (Timestamp::try_from(value)?, Offset::UTC),
+        };
+        let offset = UtcOffset::from_whole_seconds(offset.seconds()).map_err(out_of_range)?;
+        let utc = OffsetDateTime::from_unix_timestamp_nanos(instant.as_nanosecond());
+        Ok(utc.map_err(out_of_range)?.to_offset(offset))
+    }
+}
+impl TryFrom<OffsetDateTime> for PropertyValue {
+    type Error = ConversionError;
+    fn try_from(dt: OffsetDateTime) -> Result<Self, ConversionError> {
+        let instant =
+            Timestamp::from_nanosecond(dt.unix_timestamp_nanos()).map_err(out_of_range)?;
+        if dt.offset().is_utc() {
+            return Ok(PropertyValue::from(instant));
+        }
+        let offset = Offset::from_seconds(dt.offset().whole_seconds()).map_err(out_of_range)?;
+        Ok(PropertyValue::from(instant.to_zoned(TimeZone::fixed(offset))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use time::macros::{date, datetime};
+
+    #[test]
+    fn dates() {
+        let value = PropertyValue::try_from(date!(2025 - 03 - 03)).unwrap();
+        assert_eq!(civil::Date::try_from(&value), Ok(civil::date(2025, 3, 3)));
+        assert_eq!(Date::try_from(&value), Ok(date!(2025 - 03 - 03)));
+        let wrong = PrimitiveDateTime::try_from(&value);
+        assert_eq!(wrong, Err(ConversionError::WrongType { expected: "DATE-TIME", found: "DATE" }));
+    }
+    #[test]
+    fn floating_date_times() {
+        let dt = datetime!(2025-03-03 09:30:15.5);
+        let value = PropertyValue::try_from(dt).unwrap();
+        let expected = civil::datetime(2025, 3, 3, 9, 30, 15, 500_000_000);
+        assert_eq!(civil::DateTime::try_from(&value), Ok(expected));
+        assert_eq!(PrimitiveDateTime::try_from(&value), Ok(dt));
+    }
+    #[test]
+    fn offset_date_times() {
+        let utc = datetime!(2025-03-03 09:00 UTC);
+        let value = PropertyValue::try_from(utc).unwrap();
+        assert_eq!(Timestamp::try_from(&value), Ok("2025-03-03T09:00:00Z".parse().unwrap()));
+        assert_eq!(OffsetDateTime::try_from(&value), Ok(utc));
+
+        let paris = datetime!(2025-03-03 10:00 +1);
+        let value = PropertyValue::try_from(paris).unwrap();
+        let zoned = Zoned::try_from(&value).unwrap();
+        assert_eq!(zoned.timestamp(), "2025-03-03T09:00:00Z".parse().unwrap());
+        let converted = OffsetDateTime::try_from(&value).unwrap();
+        assert_eq!((converted, converted.offset()), (paris, paris.offset()));
+
+        // A zoned DATE-TIME gets the offset in effect then
+        let zoned: Zoned = "2025-07-01T10:00[Europe/Paris]".parse().unwrap();
+        let converted = OffsetDateTime::try_from(&PropertyValue::from(zoned)).unwrap();
+        assert_eq!(converted, datetime!(2025-07-01 10:00 +2));
+        assert_eq!(converted.offset().whole_hours(), 2);
+    }
+}
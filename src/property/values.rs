@@ -2,24 +2,405 @@ use jiff::{
     SignedDuration, Timestamp, Zoned,
     civil::{Date, DateTime, Time},
 };
-use nonempty::NonEmpty;
+use std::ops::Deref;
+use std::sync::OnceLock;
+use thiserror::Error;
 
+use crate::base64::{self, Base64Error};
+use crate::parameter::{Related, Tzid};
 use crate::rrule::RRule;
 
+/// When an alarm goes off: the value of a `TRIGGER` property
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    /// A duration (negative for before) from the start or end of the alarm's component
+    Relative { duration: SignedDuration, related: Related },
+    /// An instant
+    At(Timestamp),
+}
+
+/// A `PERIOD` value, in the form it was written: a start and end, or a start and duration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Explicit { start: Timestamp, end: Timestamp },
+    Start { start: Timestamp, duration: SignedDuration },
+}
+impl Period {
+    #[must_use]
+    pub fn start(&self) -> Timestamp {
+        match self {
+            Period::Explicit { start, .. } | Period::Start { start, .. } => *start,
+        }
+    }
+    /// The end of the period, or `None` if its duration takes it out of range
+    #[must_use]
+    pub fn end(&self) -> Option<Timestamp> {
+        match self {
+            Period::Explicit { end, .. } => Some(*end),
+            Period::Start { start, duration } => start.checked_add(*duration).ok(),
+        }
+    }
+}
+
+/// The value of a `GEO` property: a latitude from -90 to 90 and a longitude from -180 to
+/// 180, in degrees
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geo {
+    pub lat: f64,
+    pub lon: f64,
+}
+impl Geo {
+    /// The position, or `None` if either coordinate is out of range
+    #[must_use]
+    pub fn new(lat: f64, lon: f64) -> Option<Self> {
+        ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon))
+            .then_some(Geo { lat, lon })
+    }
+}
+
+/// One or more values, for properties like `EXDATE` and `CATEGORIES` that take a list. It
+/// derefs to a slice, which is never empty.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Values<T>(Vec<T>);
+
+impl<T> Values<T> {
+    /// A list of one value
+    pub fn new(first: T) -> Self {
+        Values(vec![first])
+    }
+    /// A list of `values`, or `None` if there aren't any
+    #[must_use]
+    pub fn from_vec(values: Vec<T>) -> Option<Self> {
+        (!values.is_empty()).then_some(Values(values))
+    }
+    /// The first value, which is always there
+    #[must_use]
+    pub fn first(&self) -> &T {
+        &self.0[0]
+    }
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+    pub fn push(&mut self, value: T) {
+        self.0.push(value);
+    }
+    #[must_use]
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for Values<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+impl<T> AsRef<[T]> for Values<T> {
+    fn as_ref(&self) -> &[T] {
+        &self.0
+    }
+}
+impl<'a, T> IntoIterator for &'a Values<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+impl<T> IntoIterator for Values<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// A `BINARY` value. We keep the base64 text as written and only decode it the first time
+/// `bytes` is called, so large inline attachments the application never looks at cost
+/// nothing to decode.
+#[derive(Clone, Debug, Default)]
+pub struct Binary {
+    base64: String,
+    decoded: OnceLock<Result<Vec<u8>, Base64Error>>,
+}
+
+impl Binary {
+    /// A value from its base64 text, which isn't checked until `bytes` is called
+    pub fn new(base64: impl Into<String>) -> Self {
+        Binary { base64: base64.into(), decoded: OnceLock::new() }
+    }
+    /// A value holding `bytes`, encoded as base64
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Binary { base64: base64::encode(bytes), decoded: OnceLock::from(Ok(bytes.to_vec())) }
+    }
+    /// The base64 text
+    #[must_use]
+    pub fn base64(&self) -> &str {
+        &self.base64
+    }
+    /// The decoded bytes. Decoding happens on the first call; later calls reuse the result.
+    ///
+    /// # Errors
+    /// Returns an error if the text isn't valid base64.
+    pub fn bytes(&self) -> Result<&[u8], Base64Error> {
+        match self.decoded.get_or_init(|| base64::decode(self.base64.as_bytes())) {
+            Ok(bytes) => Ok(bytes),
+            Err(err) => Err(*err),
+        }
+    }
+    /// Whether the value has been decoded yet
+    #[must_use]
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+}
+
+impl PartialEq for Binary {
+    fn eq(&self, other: &Self) -> bool {
+        self.base64 == other.base64
+    }
+}
+impl Eq for Binary {}
+
+/// A typed property value. Value types added by later RFCs may become new variants in minor
+/// releases, so matches need a wildcard arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum PropertyValue {
-    Binary(Vec<u8>),
+    Binary(Binary),
     Boolean(bool),
     CalAddress(String),
-    Date(NonEmpty<Date>),
-    DateTime(NonEmpty<DateTime>),
-    DateTimeUtc(NonEmpty<Timestamp>),
-    DateTimeZoned(NonEmpty<Zoned>),
-    Duration(NonEmpty<SignedDuration>),
-    Float(NonEmpty<f64>),
-    Period((Timestamp, Timestamp)), // Is it always Timestamp? Do we need to remember start/end vs start/duration?
+    Date(Values<Date>),
+    DateTime(Values<DateTime>),
+    DateTimeUtc(Values<Timestamp>),
+    DateTimeZoned(Values<Zoned>),
+    // Local date-times with a TZID that named no zone we could find when they were parsed. We
+    // keep it, so that a layer that knows more (the calendar's VTIMEZONEs, say) can resolve it.
+    DateTimeList { tzid: Option<Tzid>, values: Values<DateTime> },
+    Duration(Values<SignedDuration>),
+    Float(Values<f64>),
+    Integer(Values<i32>),
+    Period(Values<Period>),
     Recur(Box<RRule>),
-    Text(NonEmpty<String>),
-    Time(NonEmpty<Time>),
+    Text(Values<String>),
+    Time(Values<Time>),
     Uri(String),
     UtcOffset(SignedDuration),
 }
+
+impl PropertyValue {
+    /// The name of the value's type, as used in the `VALUE` parameter
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        use PropertyValue::*;
+        match self {
+            Binary(_) => "BINARY",
+            Boolean(_) => "BOOLEAN",
+            CalAddress(_) => "CAL-ADDRESS",
+            Date(_) => "DATE",
+            DateTime(_) | DateTimeUtc(_) | DateTimeZoned(_) | DateTimeList { .. } => "DATE-TIME",
+            Duration(_) => "DURATION",
+            Float(_) => "FLOAT",
+            Integer(_) => "INTEGER",
+            Period(_) => "PERIOD",
+            Recur(_) => "RECUR",
+            Text(_) => "TEXT",
+            Time(_) => "TIME",
+            Uri(_) => "URI",
+            UtcOffset(_) => "UTC-OFFSET",
+        }
+    }
+    pub(crate) fn wrong_type(&self, expected: &'static str) -> ConversionError {
+        ConversionError::WrongType { expected, found: self.type_name() }
+    }
+}
+
+/// Why a `PropertyValue` couldn't be converted to a Rust type
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ConversionError {
+    #[error("expected a {expected} value, found a {found} value")]
+    WrongType { expected: &'static str, found: &'static str },
+    #[error("expected a single value, found a list of {0}")]
+    MultipleValues(usize),
+    #[error("the TZID names no known time zone")]
+    UnknownTimeZone,
+    #[error("the date or time is outside the supported range")]
+    OutOfRange,
+    #[error("expected a latitude from -90 to 90 and a longitude from -180 to 180")]
+    NotGeo,
+}
+
+fn single<T: Clone>(values: &Values<T>) -> Result<T, ConversionError> {
+    match values.len() {
+        1 => Ok(values.first().clone()),
+        n => Err(ConversionError::MultipleValues(n)),
+    }
+}
+
+// Conversions to and from a Rust type and the variant holding a list of that type. Only a
+// single-valued list converts to the Rust type.
+macro_rules! convert_single {
+    ($($ty:ty => $variant:ident $name:literal,)*) => {$(
+        impl TryFrom<&PropertyValue> for $ty {
+            type Error = ConversionError;
+            fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+                match value {
+                    PropertyValue::$variant(values) => single(values),
+                    other => Err(other.wrong_type($name)),
+                }
+            }
+        }
+        impl From<$ty> for PropertyValue {
+            fn from(value: $ty) -> Self {
+                PropertyValue::$variant(Values::new(value))
+            }
+        }
+    )*};
+}
+convert_single! {
+    Date => Date "DATE",
+    DateTime => DateTime "DATE-TIME",
+    Timestamp => DateTimeUtc "DATE-TIME",
+    Zoned => DateTimeZoned "DATE-TIME",
+    SignedDuration => Duration "DURATION",
+    f64 => Float "FLOAT",
+    i32 => Integer "INTEGER",
+    Period => Period "PERIOD",
+    Time => Time "TIME",
+}
+
+/// `TEXT`, `CAL-ADDRESS`, and `URI` values all convert to `String`
+impl TryFrom<&PropertyValue> for String {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        match value {
+            PropertyValue::Text(values) => single(values),
+            PropertyValue::CalAddress(text) | PropertyValue::Uri(text) => Ok(text.clone()),
+            other => Err(other.wrong_type("TEXT")),
+        }
+    }
+}
+/// A `String` converts to a `TEXT` value
+impl From<String> for PropertyValue {
+    fn from(value: String) -> Self {
+        PropertyValue::Text(Values::new(value))
+    }
+}
+
+impl TryFrom<&PropertyValue> for bool {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        match value {
+            PropertyValue::Boolean(b) => Ok(*b),
+            other => Err(other.wrong_type("BOOLEAN")),
+        }
+    }
+}
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        PropertyValue::Boolean(value)
+    }
+}
+
+impl TryFrom<&PropertyValue> for RRule {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        match value {
+            PropertyValue::Recur(rrule) => Ok((**rrule).clone()),
+            other => Err(other.wrong_type("RECUR")),
+        }
+    }
+}
+impl From<RRule> for PropertyValue {
+    fn from(value: RRule) -> Self {
+        PropertyValue::Recur(Box::new(value))
+    }
+}
+
+impl TryFrom<&PropertyValue> for Binary {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        match value {
+            PropertyValue::Binary(binary) => Ok(binary.clone()),
+            other => Err(other.wrong_type("BINARY")),
+        }
+    }
+}
+impl From<Binary> for PropertyValue {
+    fn from(value: Binary) -> Self {
+        PropertyValue::Binary(value)
+    }
+}
+
+/// A `GEO` value is a `FLOAT` pair
+impl TryFrom<&PropertyValue> for Geo {
+    type Error = ConversionError;
+    fn try_from(value: &PropertyValue) -> Result<Self, ConversionError> {
+        match value {
+            PropertyValue::Float(floats) => match floats.as_slice() {
+                [lat, lon] => Geo::new(*lat, *lon).ok_or(ConversionError::NotGeo),
+                _ => Err(ConversionError::NotGeo),
+            },
+            other => Err(other.wrong_type("FLOAT")),
+        }
+    }
+}
+impl From<Geo> for PropertyValue {
+    fn from(geo: Geo) -> Self {
+        let mut floats = Values::new(geo.lat);
+        floats.push(geo.lon);
+        PropertyValue::Float(floats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn binary_decodes_lazily() {
+        let attachment = Binary::new("SGVsbG8sIHdvcmxkIQ==");
+        assert!(!attachment.is_decoded());
+        assert_eq!(attachment.bytes(), Ok(&b"Hello, world!"[..]));
+        assert!(attachment.is_decoded());
+        assert_eq!(attachment, Binary::from_bytes(b"Hello, world!"));
+        assert_eq!(Binary::new("SGVsbG8*").bytes(), Err(Base64Error(7)));
+    }
+    #[test]
+    fn values_are_never_empty() {
+        assert_eq!(Values::<u8>::from_vec(vec![]), None);
+        let mut values = Values::new(1);
+        values.push(2);
+        assert_eq!((values.first(), values.as_slice()), (&1, &[1, 2][..]));
+        assert_eq!(values.iter().sum::<i32>(), 3);
+        assert_eq!(values.into_vec(), vec![1, 2]);
+    }
+    #[test]
+    fn conversions() {
+        let date = jiff::civil::date(2025, 3, 3);
+        let value = PropertyValue::from(date);
+        assert_eq!(Date::try_from(&value), Ok(date));
+        assert_eq!(
+            DateTime::try_from(&value),
+            Err(ConversionError::WrongType { expected: "DATE-TIME", found: "DATE" })
+        );
+        let list =
+            PropertyValue::Date(Values::from_vec(vec![date, date.tomorrow().unwrap()]).unwrap());
+        assert_eq!(Date::try_from(&list), Err(ConversionError::MultipleValues(2)));
+
+        let uri = PropertyValue::Uri("https://example.com".to_string());
+        assert_eq!(String::try_from(&uri).as_deref(), Ok("https://example.com"));
+        assert_eq!(bool::try_from(&PropertyValue::from(true)), Ok(true));
+        assert_eq!(f64::try_from(&PropertyValue::from(1.5)), Ok(1.5));
+
+        let geo = Geo::new(37.386_013, -122.082_932).unwrap();
+        assert_eq!(Geo::try_from(&PropertyValue::from(geo)), Ok(geo));
+        assert_eq!(Geo::try_from(&PropertyValue::from(1.5)), Err(ConversionError::NotGeo));
+        assert_eq!(Geo::new(90.5, 0.0), None);
+        assert_eq!(Geo::new(0.0, -180.5), None);
+    }
+}
@@ -0,0 +1,519 @@
+//! # Recurrence sets
+//! The instances of a recurring component: its `DTSTART`, the occurrences of its `RRULE`s and
+//! its `RDATE`s, less its `EXDATE`s and the occurrences of its `EXRULE`s (which RFC 5545
+//! deprecates, but older producers still write), and less any days excluded by another
+//! calendar — say, a public-holiday feed. Every rule is anchored on `DTSTART`; an `EXRULE`
+//! only excludes `DTSTART` itself if `DTSTART` matches it.
+//!
+//! ## When an instance coincides with a holiday
+//! `excluding` removes instances that coincide with the other set's *all-day* instances:
+//!  * an instance coincides with an all-day instance on date *D* when it starts on *D* in its
+//!    own set's time zone (its civil date, for a floating or all-day set), whatever time of
+//!    day it starts;
+//!  * an all-day instance covers one day — the day it starts — even if the event it came from
+//!    lasts longer, so give each day of a multi-day holiday its own instance; and
+//!  * timed instances in the other set never exclude anything, since a timed event doesn't
+//!    make a day a holiday.
+use crate::dateset::DateSet;
+use crate::event::EventTime;
+use crate::expansion::{ExpansionLimits, ExpansionProgress, Hook, LimitReached};
+use crate::rrule::{Occurrences, RRule};
+use jiff::civil::{Date, Time};
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+use std::iter::Peekable;
+use std::vec;
+
+/// The instances of a recurring component
+#[derive(Clone, Debug)]
+pub struct RecurrenceSet {
+    all_day: bool,
+    start: Zoned,
+    rrules: Vec<RRule>,
+    exrules: Vec<RRule>,
+    rdates: DateSet,
+    exdates: DateSet,
+    // All-day sets whose days are excluded
+    excluded_days: Vec<RecurrenceSet>,
+}
+
+impl RecurrenceSet {
+    /// The set with the single instance `dtstart`. Floating and all-day times have no zone;
+    /// we keep them in UTC, which leaves their civil date-times unchanged.
+    #[must_use]
+    pub fn new(dtstart: &EventTime) -> Self {
+        let (all_day, start) = match dtstart {
+            EventTime::Date(date) => (true, in_utc(date.to_datetime(Time::midnight()))),
+            EventTime::Floating(dt) => (false, in_utc(*dt)),
+            EventTime::Zoned(zoned) => (false, zoned.clone()),
+        };
+        RecurrenceSet {
+            all_day,
+            rrules: Vec::new(),
+            exrules: Vec::new(),
+            rdates: [start.clone()].into_iter().collect(),
+            start,
+            exdates: DateSet::new(),
+            excluded_days: Vec::new(),
+        }
+    }
+    /// Whether `DTSTART` is a `DATE`, so that every instance is an all-day instance
+    #[must_use]
+    pub fn is_all_day(&self) -> bool {
+        self.all_day
+    }
+    /// The time zone of `DTSTART` (UTC for floating and all-day sets), in which floating
+    /// `RDATE` and `EXDATE` values should be interpreted
+    #[must_use]
+    pub fn zone(&self) -> &TimeZone {
+        self.start.time_zone()
+    }
+    /// Whether the set has finitely many instances: whether each `RRULE` has a `COUNT` or
+    /// `UNTIL`
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        self.rrules.iter().all(RRule::is_finite)
+    }
+    /// Add the occurrences of an `RRULE` property
+    pub fn add_rrule(&mut self, rule: RRule) {
+        self.rrules.push(rule);
+    }
+    /// Remove the occurrences of an `EXRULE` property
+    pub fn add_exrule(&mut self, rule: RRule) {
+        self.exrules.push(rule);
+    }
+    /// Add the date-times of an `RDATE` property
+    pub fn add_rdates(&mut self, rdates: &DateSet) {
+        self.rdates = self.rdates.union(rdates);
+    }
+    /// Add the date-times of an `EXDATE` property
+    pub fn add_exdates(&mut self, exdates: &DateSet) {
+        self.exdates = self.exdates.union(exdates);
+    }
+
+    /// The instances, in time order. The set must be finite (see `is_finite`); use `iter`
+    /// for the first few instances of one that isn't.
+    #[must_use]
+    pub fn instances(&self) -> Vec<Zoned> {
+        self.iter().collect()
+    }
+    /// The instances, in time order, without duplicates: an instance that more than one rule
+    /// or `RDATE` produces appears once
+    #[must_use]
+    pub fn iter(&self) -> Instances {
+        let start = EventTime::Zoned(self.start.clone());
+        let exrules = (self.exrules.iter())
+            .map(|rule| {
+                let mut occurrences = Lookahead::new(rule.iter(&self.start));
+                if !rule.matches(&start) {
+                    occurrences.next();
+                }
+                occurrences
+            })
+            .collect();
+        Instances {
+            set: self.clone(),
+            rdates: self.rdates.iter().cloned().collect::<Vec<_>>().into_iter().peekable(),
+            rrules: self.rrules.iter().map(|rule| Lookahead::new(rule.iter(&self.start))).collect(),
+            exrules,
+            excluded_days: self.excluded_days.iter().map(|set| Days::new(set.iter())).collect(),
+            last: None,
+            limits: None,
+            emitted: 0,
+            limit_reached: None,
+            hook: None,
+            cancelled: false,
+        }
+    }
+    /// The set without the instances that coincide with `other`'s all-day instances (see
+    /// the module documentation for what coincides). `other` needn't be finite: its instances
+    /// are found as iteration reaches them.
+    #[must_use]
+    pub fn excluding(&self, other: &RecurrenceSet) -> RecurrenceSet {
+        let mut set = self.clone();
+        if other.all_day {
+            set.excluded_days.push(other.clone());
+        }
+        set
+    }
+
+    // The civil date `start` is on, in this set's zone
+    fn date_of(&self, start: &Zoned) -> Date {
+        start.with_time_zone(self.zone().clone()).date()
+    }
+}
+
+impl IntoIterator for &RecurrenceSet {
+    type Item = Zoned;
+    type IntoIter = Instances;
+
+    fn into_iter(self) -> Instances {
+        self.iter()
+    }
+}
+
+/// The iterator `RecurrenceSet::iter` returns
+#[derive(Clone, Debug)]
+pub struct Instances {
+    set: RecurrenceSet,
+    rdates: Peekable<vec::IntoIter<Zoned>>,
+    rrules: Vec<Lookahead>,
+    exrules: Vec<Lookahead>,
+    excluded_days: Vec<Days>,
+    last: Option<Timestamp>,
+    limits: Option<ExpansionLimits>,
+    emitted: usize,
+    limit_reached: Option<LimitReached>,
+    hook: Option<Hook>,
+    cancelled: bool,
+}
+
+impl Instances {
+    /// Stop at the first of `limits` reached: before an instance past `max_instances` or
+    /// `max_span_years`, or when a rule tests `max_candidates` candidates without finding
+    /// its next occurrence. `limit_reached` then says which it was.
+    #[must_use]
+    pub fn with_limits(self, limits: ExpansionLimits) -> Self {
+        // We count instances here, as the rules' occurrences include excluded ones
+        let rule_limits = ExpansionLimits { max_instances: usize::MAX, ..limits };
+        let limited = |rules: Vec<Lookahead>| {
+            (rules.into_iter())
+                .map(|rule| Lookahead {
+                    occurrences: rule.occurrences.with_limits(rule_limits),
+                    ..rule
+                })
+                .collect()
+        };
+        let excluded_days = (self.excluded_days.into_iter())
+            .map(|days| Days { instances: days.instances.with_limits(rule_limits), ..days })
+            .collect();
+        Instances {
+            rrules: limited(self.rrules),
+            exrules: limited(self.exrules),
+            excluded_days,
+            limits: Some(limits),
+            ..self
+        }
+    }
+    /// The limit that stopped iteration, if one did (see `with_limits`)
+    #[must_use]
+    pub fn limit_reached(&self) -> Option<LimitReached> {
+        self.limit_reached
+    }
+    /// Call `should_continue` before each instance, and while a rule searches for its next
+    /// occurrence (see `Occurrences::should_continue`), and stop as soon as it returns
+    /// `false`. Its progress counts the candidates all the set's rules have tested and the
+    /// instances returned. `is_cancelled` then says so.
+    #[must_use]
+    pub fn should_continue(
+        self,
+        should_continue: impl Fn(&ExpansionProgress) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.with_hook(Hook::new(should_continue))
+    }
+    fn with_hook(self, hook: Hook) -> Self {
+        let hooked = |rules: Vec<Lookahead>| {
+            (rules.into_iter())
+                .map(|rule| {
+                    let occurrences = rule.occurrences.with_hook(hook.part());
+                    Lookahead { occurrences, ..rule }
+                })
+                .collect()
+        };
+        let excluded_days = (self.excluded_days.into_iter())
+            .map(|days| Days { instances: days.instances.with_hook(hook.part()), ..days })
+            .collect();
+        Instances {
+            rrules: hooked(self.rrules),
+            exrules: hooked(self.exrules),
+            excluded_days,
+            hook: Some(hook),
+            ..self
+        }
+    }
+    /// Whether iteration stopped because it was cancelled (see `should_continue`)
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    // Check the limits before returning `instance` (or, with `None`, before ending), stopping
+    // if one is reached. A rule (or set of excluded days) that ran out of candidates or
+    // instances stops the set; one that reached the end of the span just has no more
+    // occurrences, as the set has none past it either.
+    fn within_limits(&mut self, instance: Option<&Zoned>) -> bool {
+        let Some(limits) = &self.limits else {
+            return true;
+        };
+        let rules = self.rrules.iter().chain(&self.exrules);
+        let days = self.excluded_days.iter().filter_map(|days| days.instances.limit_reached());
+        let reached = (rules.filter_map(|rule| rule.occurrences.limit_reached()))
+            .chain(days)
+            .find(|reached| *reached != LimitReached::Span);
+        let checked = match (reached, instance) {
+            (Some(reached), _) => Err(reached),
+            (None, Some(instance)) => {
+                let next = instance.with_time_zone(self.set.zone().clone()).datetime();
+                limits.check(self.set.start.datetime(), self.emitted, 0, next)
+            }
+            (None, None) => Ok(()),
+        };
+        if let Err(reached) = checked {
+            self.limit_reached = Some(reached);
+        }
+        checked.is_ok()
+    }
+
+    // Whether an `EXDATE`, an `EXRULE`, or an excluded day removes `instance`. Instances come
+    // in order, so each `EXRULE` and set of excluded days only needs to catch up to this one.
+    fn is_excluded(&mut self, instance: &Zoned) -> bool {
+        let at = instance.timestamp();
+        let date = self.set.date_of(instance);
+        let mut excluded = self.set.exdates.contains(instance);
+        for days in &mut self.excluded_days {
+            while days.next.is_some_and(|day| day < date) {
+                days.advance();
+            }
+            excluded |= days.next == Some(date);
+        }
+        for exrule in &mut self.exrules {
+            while exrule.peek().is_some_and(|ex| ex.timestamp() < at) {
+                exrule.next();
+            }
+            excluded |= exrule.peek().is_some_and(|ex| ex.timestamp() == at);
+        }
+        excluded
+    }
+}
+
+impl Iterator for Instances {
+    type Item = Zoned;
+
+    fn next(&mut self) -> Option<Zoned> {
+        loop {
+            if self.limit_reached.is_some() || self.cancelled {
+                return None;
+            }
+            let mut rules = self.rrules.iter().chain(&self.exrules);
+            if self.hook.as_ref().is_some_and(|hook| !hook.should_continue())
+                || rules.any(|rule| rule.occurrences.is_cancelled())
+                || self.excluded_days.iter().any(|days| days.instances.is_cancelled())
+            {
+                self.cancelled = true;
+                return None;
+            }
+            // Take the earliest next instance: an `RDATE`'s, or the earliest rule's
+            let rule = (self.rrules.iter_mut().enumerate())
+                .filter_map(|(n, rule)| Some((rule.peek()?.timestamp(), n)))
+                .min();
+            let rdate = self.rdates.peek().map(Zoned::timestamp);
+            let instance = match (rdate, rule) {
+                (None, None) => {
+                    self.within_limits(None);
+                    return None;
+                }
+                (Some(rdate), Some((at, n))) if at < rdate => self.rrules[n].next(),
+                (Some(_), _) => self.rdates.next(),
+                (None, Some((_, n))) => self.rrules[n].next(),
+            }?;
+            let at = instance.timestamp();
+            if self.last.is_some_and(|last| at <= last) {
+                continue;
+            }
+            self.last = Some(at);
+            if !self.is_excluded(&instance) {
+                if !self.within_limits(Some(&instance)) {
+                    return None;
+                }
+                self.emitted += 1;
+                if let Some(hook) = &self.hook {
+                    hook.add_occurrence();
+                }
+                return Some(instance);
+            }
+        }
+    }
+}
+
+// A rule's occurrences, with the next one taken ahead of time. Unlike `Peekable`, it still
+// lets us ask the occurrences whether they reached a limit.
+#[derive(Clone, Debug)]
+struct Lookahead {
+    occurrences: Occurrences,
+    next: Option<Zoned>,
+}
+
+impl Lookahead {
+    fn new(mut occurrences: Occurrences) -> Self {
+        let next = occurrences.next();
+        Lookahead { occurrences, next }
+    }
+    fn peek(&self) -> Option<&Zoned> {
+        self.next.as_ref()
+    }
+    fn next(&mut self) -> Option<Zoned> {
+        let next = self.occurrences.next();
+        std::mem::replace(&mut self.next, next)
+    }
+}
+
+// An all-day set's instances, as the days they fall on, with the next taken ahead of time
+#[derive(Clone, Debug)]
+struct Days {
+    instances: Instances,
+    next: Option<Date>,
+}
+
+impl Days {
+    fn new(instances: Instances) -> Self {
+        let mut days = Days { instances, next: None };
+        days.advance();
+        days
+    }
+    fn advance(&mut self) {
+        self.next = self.instances.next().map(|start| self.instances.set.date_of(&start));
+    }
+}
+
+// UTC can represent every civil date-time, so we never get the default
+fn in_utc(dt: jiff::civil::DateTime) -> Zoned {
+    dt.to_zoned(TimeZone::UTC).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rrule::parse_rrule;
+    use jiff::civil::{date, datetime};
+    use pretty_assertions::assert_eq;
+    use std::sync::{Arc, Mutex};
+
+    fn rule(text: &str) -> RRule {
+        parse_rrule(&mut format!("{text}\r\n").as_bytes()).unwrap()
+    }
+
+    fn holidays(dates: &[Date]) -> RecurrenceSet {
+        let mut set = RecurrenceSet::new(&EventTime::Date(dates[0]));
+        let days = dates.iter().map(|d| in_utc(d.to_datetime(Time::midnight())));
+        set.add_rdates(&days.collect());
+        set
+    }
+    fn weekly_standups(zone: &str) -> RecurrenceSet {
+        let at = |day| datetime(2025, 12, day, 9, 30, 0, 0).in_tz(zone).unwrap();
+        let mut set = RecurrenceSet::new(&EventTime::Zoned(at(22)));
+        set.add_rdates(&[at(25), at(29)].into_iter().collect());
+        set
+    }
+
+    #[test]
+    fn holidays_remove_instances_on_the_same_date() {
+        let christmas = holidays(&[date(2025, 12, 25), date(2025, 12, 26)]);
+        let standups = weekly_standups("Asia/Tokyo").excluding(&christmas);
+        let days: Vec<_> = standups.instances().iter().map(Zoned::day).collect();
+        // 09:30 on the 25th in Tokyo is the 25th, even though it's the 24th in UTC
+        assert_eq!(days, vec![22, 29]);
+    }
+    #[test]
+    fn holidays_without_end() {
+        let mut christmas = RecurrenceSet::new(&EventTime::Date(date(2020, 12, 25)));
+        christmas.add_rrule(rule("FREQ=YEARLY"));
+        assert!(!christmas.is_finite());
+        let mut standups = weekly_standups("UTC");
+        standups.add_rrule(rule("FREQ=DAILY;COUNT=10"));
+        let standups = standups.excluding(&christmas);
+        let days: Vec<_> = standups.instances().iter().map(Zoned::day).collect();
+        assert_eq!(days, vec![22, 23, 24, 26, 27, 28, 29, 30, 31]);
+    }
+    #[test]
+    fn timed_instances_dont_exclude_days() {
+        let meeting = RecurrenceSet::new(&EventTime::Floating(datetime(2025, 12, 25, 0, 0, 0, 0)));
+        let standups = weekly_standups("UTC").excluding(&meeting);
+        assert_eq!(standups.instances().len(), 3);
+    }
+    #[test]
+    fn exdates_and_all_day_sets() {
+        let mut set = holidays(&[date(2025, 1, 1), date(2025, 1, 2)]);
+        assert!(set.is_all_day());
+        set.add_exdates(&[in_utc(datetime(2025, 1, 2, 0, 0, 0, 0))].into_iter().collect());
+        let days = set.instances().iter().map(Zoned::date).collect::<Vec<_>>();
+        assert_eq!(days, vec![date(2025, 1, 1)]);
+        let floating = RecurrenceSet::new(&EventTime::Floating(datetime(2025, 1, 1, 8, 0, 0, 0)));
+        assert!(floating.excluding(&set).instances().is_empty());
+    }
+    #[test]
+    fn rules_and_dates_merge() {
+        let at = |day, hour| datetime(2025, 3, day, hour, 0, 0, 0).in_tz("Europe/Paris").unwrap();
+        let mut set = RecurrenceSet::new(&EventTime::Zoned(at(3, 9)));
+        set.add_rrule(rule("FREQ=DAILY;COUNT=5"));
+        set.add_rrule(rule("FREQ=WEEKLY;BYDAY=MO,FR;COUNT=4"));
+        set.add_exrule(rule("FREQ=DAILY;BYDAY=WE"));
+        // An RDATE in UTC that's the same instant as an RRULE occurrence isn't repeated
+        set.add_rdates(&[at(4, 9).with_time_zone(TimeZone::UTC), at(4, 12)].into_iter().collect());
+        set.add_exdates(&[at(6, 9)].into_iter().collect());
+        assert!(set.is_finite());
+        let in_paris = |i: Zoned| i.in_tz("Europe/Paris").map(|i| (i.day(), i.hour())).unwrap();
+        let days: Vec<_> = set.iter().map(in_paris).collect();
+        assert_eq!(days, vec![(3, 9), (4, 9), (4, 12), (7, 9), (10, 9), (14, 9)]);
+        assert_eq!(set.instances().len(), 6);
+    }
+    #[test]
+    fn unbounded_rules() {
+        let start = datetime(2025, 3, 3, 9, 0, 0, 0);
+        let mut set = RecurrenceSet::new(&EventTime::Floating(start));
+        set.add_rrule(rule("FREQ=DAILY"));
+        // DTSTART isn't on a weekend, so the EXRULE doesn't exclude it
+        set.add_exrule(rule("FREQ=WEEKLY;BYDAY=SA,SU"));
+        assert!(!set.is_finite());
+        let days: Vec<_> = set.iter().take(7).map(|i| i.day()).collect();
+        assert_eq!(days, vec![3, 4, 5, 6, 7, 10, 11]);
+    }
+    #[test]
+    fn limits() {
+        let start = datetime(2025, 3, 3, 9, 0, 0, 0);
+        let mut set = RecurrenceSet::new(&EventTime::Floating(start));
+        set.add_rrule(rule("FREQ=DAILY"));
+        set.add_exrule(rule("FREQ=WEEKLY;BYDAY=SA,SU"));
+        // Excluded instances don't count
+        let limits = ExpansionLimits { max_instances: 6, ..ExpansionLimits::interactive() };
+        let mut instances = set.iter().with_limits(limits);
+        let days: Vec<_> = instances.by_ref().map(|i| i.day()).collect();
+        assert_eq!(days, vec![3, 4, 5, 6, 7, 10]);
+        assert_eq!(instances.limit_reached(), Some(LimitReached::Instances));
+
+        // An RDATE past the span isn't an instance, but one before the end of a rule is
+        let later = |years| in_utc(start.checked_add(jiff::Span::new().years(years)).unwrap());
+        let mut set = RecurrenceSet::new(&EventTime::Floating(start));
+        set.add_rrule(rule("FREQ=YEARLY;COUNT=3"));
+        set.add_rdates(&[later(9), later(11)].into_iter().collect());
+        let mut instances = set.iter().with_limits(ExpansionLimits::interactive());
+        let years: Vec<_> = instances.by_ref().map(|i| i.year()).collect();
+        assert_eq!(years, vec![2025, 2026, 2027, 2034]);
+        assert_eq!(instances.limit_reached(), Some(LimitReached::Span));
+
+        // A rule that never matches runs out of candidates
+        let mut set = RecurrenceSet::new(&EventTime::Floating(start));
+        set.add_rrule(rule("FREQ=DAILY;BYMONTH=2;BYMONTHDAY=30"));
+        let few = ExpansionLimits { max_candidates: 1000, ..ExpansionLimits::interactive() };
+        let mut instances = set.iter().with_limits(few);
+        assert_eq!(instances.by_ref().count(), 1);
+        assert_eq!(instances.limit_reached(), Some(LimitReached::Candidates));
+    }
+    #[test]
+    fn cancellation() {
+        let start = datetime(2025, 3, 3, 9, 0, 0, 0);
+        let mut set = RecurrenceSet::new(&EventTime::Floating(start));
+        set.add_rrule(rule("FREQ=DAILY"));
+        set.add_rrule(rule("FREQ=DAILY;INTERVAL=2"));
+        let last = Arc::new(Mutex::new(ExpansionProgress::default()));
+        let progress = Arc::clone(&last);
+        let mut instances = set.iter().should_continue(move |p: &ExpansionProgress| {
+            *progress.lock().unwrap() = *p;
+            p.occurrences < 3
+        });
+        assert_eq!(instances.by_ref().count(), 3);
+        assert!(instances.is_cancelled() && instances.limit_reached().is_none());
+        // The instances, and the candidates both rules tested (each looking ahead)
+        let last = *last.lock().unwrap();
+        assert_eq!(last, ExpansionProgress { candidates: 6, occurrences: 3 });
+    }
+}
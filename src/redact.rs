@@ -0,0 +1,239 @@
+//! # Redaction
+//! Strip or hash personally identifiable content from a calendar, keeping its structure and
+//! times, so that problem feeds can be shared in bug reports. Hashing is deterministic, so the
+//! same attendee gets the same stand-in everywhere in the calendar.
+use crate::display::truncate_display;
+use crate::fnv::fnv1a;
+use crate::names::{Lookup, PropertyId};
+use crate::preparse::{LocStr, Prop};
+use crate::writer::quote_value_of;
+use std::fmt::Write;
+
+/// How to replace personally identifiable values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Redaction {
+    /// Replace the value with an empty (or minimal placeholder) value
+    Strip,
+    /// Replace the value with a stable hash of the original
+    Hash,
+}
+
+// Properties whose whole value is free text that might identify someone
+const TEXT_PROPERTIES: [&str; 5] = ["SUMMARY", "DESCRIPTION", "LOCATION", "COMMENT", "CONTACT"];
+// Properties whose value is a calendar address
+const ADDRESS_PROPERTIES: [&str; 2] = ["ATTENDEE", "ORGANIZER"];
+// Parameters whose values might identify someone
+const PARAMETERS: [&str; 8] =
+    ["CN", "EMAIL", "SENT-BY", "DELEGATED-FROM", "DELEGATED-TO", "MEMBER", "ALTREP", "DIR"];
+
+/// Return `prop` as an (unfolded) content line, with the values of text properties
+/// (`SUMMARY`, `DESCRIPTION`, `LOCATION`, `COMMENT`, `CONTACT`), calendar addresses
+/// (`ATTENDEE` and `ORGANIZER`), `X-` properties, and identifying parameters like `CN` and
+/// `EMAIL` redacted.
+#[must_use]
+pub fn redact(prop: &Prop, redaction: Redaction) -> String {
+    let name = prop.name.val();
+    let mut line = String::from(name);
+    for param in prop.parameters() {
+        let param_name = param.name().val();
+        let sensitive = is_one_of(param_name, &PARAMETERS);
+        write!(line, ";{param_name}=").unwrap();
+        for (n, value) in param.values().iter().enumerate() {
+            if n > 0 {
+                line.push(',');
+            }
+            if sensitive {
+                push_param_value(&mut line, param_name, &redacted(value, redaction));
+            } else {
+                push_param_value(&mut line, param_name, value.val());
+            }
+        }
+    }
+    line.push(':');
+    let value = prop.value();
+    if is_one_of(name, &TEXT_PROPERTIES) || is_extension(name) {
+        line.push_str(&redacted_text(value, redaction));
+    } else if is_one_of(name, &ADDRESS_PROPERTIES) {
+        line.push_str(&redacted(value, redaction));
+    } else {
+        line.push_str(value.val());
+    }
+    line
+}
+
+impl Prop<'_> {
+    /// A single-line representation of the property for logs: values longer than
+    /// `max_value_len` columns are trimmed (see `display::truncate_display`), and the
+    /// properties in `redact` have their value, and any identifying parameters like `CN`,
+    /// replaced by a stable hash, as with `Redaction::Hash`.
+    #[must_use]
+    pub fn to_log_string(
+        &self,
+        lookup: &Lookup,
+        max_value_len: usize,
+        redact: &[PropertyId],
+    ) -> String {
+        let name = self.name.val();
+        let masked = redact.iter().any(|id| {
+            lookup.property_name(*id).is_some_and(|masked| masked.eq_ignore_ascii_case(name))
+        });
+        let mut line = String::from(name);
+        for param in self.parameters() {
+            let param_name = param.name().val();
+            let sensitive = masked && is_one_of(param_name, &PARAMETERS);
+            write!(line, ";{param_name}=").unwrap();
+            for (n, value) in param.values().iter().enumerate() {
+                if n > 0 {
+                    line.push(',');
+                }
+                if sensitive {
+                    push_param_value(&mut line, param_name, &redacted(value, Redaction::Hash));
+                } else {
+                    push_param_value(
+                        &mut line,
+                        param_name,
+                        &truncate_display(value.val(), max_value_len),
+                    );
+                }
+            }
+        }
+        line.push(':');
+        let value = self.value();
+        if !masked {
+            line.push_str(&truncate_display(value.val(), max_value_len));
+        } else if is_one_of(name, &ADDRESS_PROPERTIES) {
+            line.push_str(&redacted(value, Redaction::Hash));
+        } else {
+            line.push_str(&hashed(value.val()));
+        }
+        line
+    }
+}
+
+fn is_one_of(name: &str, names: &[&str]) -> bool {
+    names.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+fn is_extension(name: &str) -> bool {
+    name.get(..2).is_some_and(|prefix| prefix.eq_ignore_ascii_case("X-"))
+}
+
+// Free text: empty when stripping
+fn redacted_text(value: &LocStr, redaction: Redaction) -> String {
+    match redaction {
+        Redaction::Strip => String::new(),
+        Redaction::Hash => hashed(value.val()),
+    }
+}
+// Addresses and URIs keep their scheme, so they still look like addresses and URIs
+fn redacted(value: &LocStr, redaction: Redaction) -> String {
+    let value = value.val();
+    let (scheme, rest) = match value.split_once(':') {
+        Some((scheme, rest)) if scheme.bytes().all(|b| b.is_ascii_alphabetic()) => {
+            (Some(scheme), rest)
+        }
+        _ => (None, value),
+    };
+    let stand_in = match redaction {
+        Redaction::Strip => "redacted".to_string(),
+        Redaction::Hash => hashed(rest),
+    };
+    match scheme {
+        Some(scheme) if scheme.eq_ignore_ascii_case("mailto") => {
+            format!("{scheme}:{stand_in}@example.invalid")
+        }
+        Some(scheme) => format!("{scheme}:{stand_in}"),
+        None => stand_in,
+    }
+}
+
+// A stable hash, so the same input always gets the same stand-in
+fn hashed(value: &str) -> String {
+    format!("h{:016x}", fnv1a(value.as_bytes()))
+}
+
+// Write `value` as a value of parameter `param`, quoted as `ContentWriter` quotes it. Double
+// quotes and control characters, which no parameter value can hold, are dropped.
+fn push_param_value(line: &mut String, param: &str, value: &str) {
+    match quote_value_of(param, value) {
+        Some(quoted) => line.push_str(&quoted),
+        None => {
+            let representable = |&c: &char| c != '"' && (c == '\t' || !c.is_ascii_control());
+            let value: String = value.chars().filter(representable).collect();
+            push_param_value(line, param, &value);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cautious"))]
+mod test {
+    use super::*;
+    use crate::preparse::cautious_preparse;
+    use pretty_assertions::assert_eq;
+
+    fn redact_line(line: &str, redaction: Redaction) -> String {
+        redact(&cautious_preparse(line.as_bytes()).unwrap(), redaction)
+    }
+    #[test]
+    fn structure_and_times_are_kept() {
+        for line in [
+            "DTSTART;TZID=America/New_York:20240618T183000",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,WE",
+            r#"X-APPLE-STRUCTURED-LOCATION;VALUE=URI;X-TITLE="a, b":geo:48.1,11.5"#,
+        ] {
+            let (name, _) = line.split_once([';', ':']).unwrap();
+            let redacted = redact_line(line, Redaction::Strip);
+            assert!(redacted.starts_with(name), "{redacted}");
+            if !is_extension(name) {
+                assert_eq!(redacted, line);
+            }
+        }
+    }
+    #[test]
+    fn strip() {
+        assert_eq!(redact_line("SUMMARY:Dinner with Alice", Redaction::Strip), "SUMMARY:");
+        assert_eq!(
+            redact_line(
+                r#"ATTENDEE;ROLE=CHAIR;CN="Example, Alice":mailto:alice@example.com"#,
+                Redaction::Strip
+            ),
+            "ATTENDEE;ROLE=CHAIR;CN=redacted:mailto:redacted@example.invalid"
+        );
+        assert_eq!(redact_line("X-WR-CALNAME:Alice's calendar", Redaction::Strip), "X-WR-CALNAME:");
+        // Parameter values are quoted as the writer quotes them
+        assert_eq!(
+            redact_line(
+                r#"ATTENDEE;DELEGATED-TO="mailto:b@example.com";X-NOTE="two words":mailto:a@b"#,
+                Redaction::Strip
+            ),
+            r#"ATTENDEE;DELEGATED-TO="mailto:redacted@example.invalid";X-NOTE="two words":mailto:redacted@example.invalid"#
+        );
+    }
+    #[test]
+    fn log_strings() {
+        let mut lookup = Lookup::new();
+        let redact = ["DESCRIPTION", "ATTENDEE"].map(|name| lookup.property_id(name).unwrap());
+        let log = |line: &str| {
+            cautious_preparse(line.as_bytes()).unwrap().to_log_string(&lookup, 12, &redact)
+        };
+        assert_eq!(log("SUMMARY:Quarterly planning offsite"), "SUMMARY:Quarterly p…");
+        assert_eq!(
+            log("DESCRIPTION:Bring the secret plans"),
+            format!("DESCRIPTION:{}", hashed("Bring the secret plans"))
+        );
+        let attendee = log(r#"ATTENDEE;ROLE=CHAIR;CN="Doe, Jane":mailto:jane@example.com"#);
+        assert!(attendee.starts_with("ATTENDEE;ROLE=CHAIR;CN=h"), "{attendee}");
+        assert!(attendee.ends_with("@example.invalid") && !attendee.contains("jane"), "{attendee}");
+    }
+    #[test]
+    fn hashes_are_stable() {
+        let organizer = redact_line("ORGANIZER:mailto:alice@example.com", Redaction::Hash);
+        let attendee = redact_line(
+            "ATTENDEE;DELEGATED-FROM=\"mailto:alice@example.com\":mailto:bob@example.com",
+            Redaction::Hash,
+        );
+        let alice = organizer.strip_prefix("ORGANIZER:").unwrap();
+        assert!(alice.starts_with("mailto:h") && alice.ends_with("@example.invalid"));
+        assert!(attendee.starts_with(&format!("ATTENDEE;DELEGATED-FROM=\"{alice}\":mailto:h")));
+        assert!(!attendee.ends_with(&format!(":{alice}")));
+    }
+}
@@ -1,48 +1,79 @@
 use crate::Weekday;
-use crate::rrule_error::{ModalResult, RRuleError};
+use crate::messages::{English, Messages};
+use crate::names::{name_eq, uppercase, uppercase_bytes};
+use crate::rrule_error::{Message, ModalResult, RRuleError, RRuleErrorKind};
+use crate::warning::{IgnoreWarnings, Observation, Warning, Warnings, warn_if_lowercase};
 
+use crate::event::EventTime;
+use crate::preparse::Prop;
 use bstr::B;
-use jiff::civil::{Date, DateTime};
-use jiff::{Timestamp, tz::TimeZone};
+use jiff::civil::{Date, DateTime, Time, time};
+use jiff::{Span, Timestamp, ToSpan, tz::TimeZone};
 use memchr::memchr;
 use paste::paste;
-use std::num::{NonZero, NonZeroI8};
-use std::ops::RangeInclusive;
+use std::fmt;
+use std::num::{NonZero, NonZeroI8, NonZeroU32};
+use std::ops::{Range, RangeInclusive};
+use thiserror::Error;
 
 use winnow::ascii::{Caseless, Int, crlf, dec_int, dec_uint, digit1};
 use winnow::combinator::{alt, cut_err, fail, opt, separated};
 use winnow::error::ErrMode;
 use winnow::{self, Parser};
 
+mod iter;
+pub use crate::recurrence::{Instances, RecurrenceSet};
+pub use iter::Occurrences;
+
 // Error message constants.
 // We allow non-uppercase because LONG_STRINGS_OF_UPPERCASE_ARE_HARDER_TO_READ
 //==============================================================================
 //
 #[allow(non_upper_case_globals)]
 mod msg {
+    use super::{Message, RRuleErrorKind as Kind};
+    const fn msg(kind: Kind, text: &'static str) -> Message {
+        Message::new(kind, text)
+    }
 
-    pub(super) const Expected_day_abbreviation: &str =
-        "Expected a day-of-week abbreviation: SU, MO, TU, WE, TH, FR, or SA";
-    pub(super) const FREQ_needs_Frequency: &str = "FREQ takes a frequency, from SECONDLY to YEARLY";
-    pub(super) const Expected_equal_sign: &str =
-        "Expected a rule part name followed by an equal sign (=)";
-    pub(super) const Bad_usize: &str = "Expected an unsigned integer";
-    pub(super) const Unknown_rule_part: &str = "Unrecognized RRule rule part";
-    pub(super) const FREQ_required: &str = "RRule must have a FREQ rule part";
-    pub(super) const Too_many_FREQs: &str =
-        "RRule must have exactly one FREQ rule part; found multiple";
+    pub(super) const Expected_day_abbreviation: Message =
+        msg(Kind::BadDay, "Expected a day-of-week abbreviation: SU, MO, TU, WE, TH, FR, or SA");
+    pub(super) const FREQ_needs_Frequency: Message =
+        msg(Kind::BadFrequency, "FREQ takes a frequency, from SECONDLY to YEARLY");
+    pub(super) const Expected_equal_sign: Message =
+        msg(Kind::ExpectedEqualSign, "Expected a rule part name followed by an equal sign (=)");
+    pub(super) const Bad_usize: Message = msg(Kind::BadNumber, "Expected an unsigned integer");
+    pub(super) const Zero_INTERVAL: Message =
+        msg(Kind::ZeroInterval, "INTERVAL must be at least 1");
+    pub(super) const Unknown_rule_part: Message =
+        msg(Kind::UnknownRulePart, "Unrecognized RRule rule part");
+    pub(super) const FREQ_required: Message =
+        msg(Kind::FreqRequired, "RRule must have a FREQ rule part");
+    pub(super) const Too_many_FREQs: Message =
+        msg(Kind::RepeatedRulePart, "RRule must have exactly one FREQ rule part; found multiple");
 
     // ByDay_range is the only range that's not macro-created simultaneously with
     // the error message referring to it. We create it here so human eyes can
     // check that the bounds are the same in both places.
     pub(super) const ByDay_range: super::RangeInclusive<i8> = -53..=53;
-    pub(super) const Expected_ByDay_offset: &str =
-        "The number part of a BYDAY list item must be nonzero and between -53 and 53";
-    pub(super) const Did_you_mean_semicolon: &str = "Did you mean to use a semicolon here?";
-    pub(super) const UNTIL_expects: &str = "UNTIL expects yyyymmdd[Thhmmss[Z]]: a date, optionally \
-        followed by T and a time, and an optional Z to indicate UTC";
-    pub(super) const Not_a_time: &str =
-        "This doesn't seem to be a legal date, date-time, or timestamp";
+    pub(super) const Expected_ByDay_offset: Message = msg(
+        Kind::BadByDayOffset,
+        "The number part of a BYDAY list item must be nonzero and between -53 and 53",
+    );
+    pub(super) const Did_you_mean_semicolon: Message =
+        msg(Kind::CommaForSemicolon, "Did you mean to use a semicolon here?");
+    pub(super) const UNTIL_expects: Message = msg(
+        Kind::BadUntil,
+        "UNTIL expects yyyymmdd[Thhmmss[Z]]: a date, optionally \
+        followed by T and a time, and an optional Z to indicate UTC",
+    );
+    pub(super) const Not_a_time: Message =
+        msg(Kind::NotATime, "This doesn't seem to be a legal date, date-time, or timestamp");
+    pub(super) const Not_an_RRULE: Message = msg(Kind::NotAnRRule, "Expected an RRULE property");
+    pub(super) const Unexpected_parameter: Message = msg(
+        Kind::UnexpectedParameter,
+        "An RRULE property can only have X- parameters and parameters we don't know",
+    );
 }
 
 // Error message macros
@@ -54,14 +85,20 @@ macro_rules! too_many {
     };
     ($name:ident) => {
         paste! {
-            concat!("RRule can have at most one ", stringify!([<$name:upper>]), " rule part")
+            Message::new(
+                RRuleErrorKind::RepeatedRulePart,
+                concat!("RRule can have at most one ", stringify!([<$name:upper>]), " rule part"),
+            )
         }
     };
 }
 macro_rules! index_msg {
     ($name:ident, $min:literal, $max:literal) => {
         paste! {
-            concat!(stringify!([<$name:upper>]), " takes a list of numbers from ", $min, " to ", $max)
+            Message::new(
+                RRuleErrorKind::BadIndexList,
+                concat!(stringify!([<$name:upper>]), " takes a list of numbers from ", $min, " to ", $max),
+            )
         }
     };
 }
@@ -71,36 +108,433 @@ macro_rules! index_msg {
 macro_rules! offset_msg {
     ($name:ident, $min:literal, $max:literal) => {
         paste! {
-            concat!(stringify!([<$name:upper>]), " takes a list of nonzero numbers from ", stringify!($min), " to ", $max)
+            Message::new(
+                RRuleErrorKind::BadOffsetList,
+                concat!(stringify!([<$name:upper>]), " takes a list of nonzero numbers from ", stringify!($min), " to ", $max),
+            )
         }
     };
 }
 
-/// Storage for an `RRule`
-#[derive(Default, Debug, Clone, PartialEq)]
+/// Storage for an `RRule`. Equality is semantic: rules that differ only in the order of
+/// their rule parts or list items, in duplicated list items, or in spelling out a default
+/// (`INTERVAL=1`, `WKST=MO`) are equal.
+#[derive(Default, Debug, Clone)]
 pub struct RRule {
     freq: Frequency,
     count: Option<u32>,
     until: Option<When>,
-    interval: Option<u32>,
+    interval: Option<NonZeroU32>,
     by_second: Vec<u8>,
     by_minute: Vec<u8>,
     by_hour: Vec<u8>,
-    by_day: Vec<WeekdaySpec>,
+    by_day: Vec<ByDay>,
     by_month_day: Vec<i8>,
     by_year_day: Vec<i16>,
     by_week_no: Vec<i8>,
     by_month: Vec<u8>,
     by_set_pos: Vec<i16>,
     wk_st: Option<Weekday>,
+    extensions: Vec<(String, String)>,
+}
+
+impl RRule {
+    /// A copy of `self` in canonical form: default values removed, and the `BYxxx` lists
+    /// sorted, with duplicates removed. `BYDAY` puts the weekdays without ordinals first,
+    /// from Sunday to Saturday, and drops ordinal weekdays (like `1MO`) that the same weekday
+    /// without an ordinal already covers. Two rules are semantically equal exactly when their
+    /// canonical forms are identical.
+    #[must_use]
+    pub fn canonicalize(&self) -> RRule {
+        fn sorted<T: Ord + Copy>(list: &[T]) -> Vec<T> {
+            let mut list = list.to_vec();
+            list.sort_unstable();
+            list.dedup();
+            list
+        }
+        // An ordinal weekday is redundant next to the same weekday without one
+        let mut by_day: Vec<_> = (self.by_day.iter())
+            .filter(|d| d.ordinal.is_none() || !self.by_day.contains(&(None, d.weekday).into()))
+            .copied()
+            .collect();
+        by_day.sort_unstable_by_key(|d| (d.ordinal, d.weekday.to_sunday_zero_offset()));
+        by_day.dedup();
+        let mut sorted_extensions = self.extensions.clone();
+        sorted_extensions.sort();
+        sorted_extensions.dedup();
+        RRule {
+            freq: self.freq,
+            count: self.count,
+            until: self.until.clone(),
+            interval: self.interval.filter(|n| n.get() != 1),
+            by_second: sorted(&self.by_second),
+            by_minute: sorted(&self.by_minute),
+            by_hour: sorted(&self.by_hour),
+            by_day,
+            by_month_day: sorted(&self.by_month_day),
+            by_year_day: sorted(&self.by_year_day),
+            by_week_no: sorted(&self.by_week_no),
+            by_month: sorted(&self.by_month),
+            by_set_pos: sorted(&self.by_set_pos),
+            wk_st: self.wk_st.filter(|day| *day != Weekday::Monday),
+            extensions: sorted_extensions,
+        }
+    }
+
+    /// The rule in a preparsed `RRULE` property. RFC 5545 allows no parameters on `RRULE`
+    /// but `X-` and IANA ones, so a parameter we know (like `TZID`) is an error.
+    ///
+    /// # Errors
+    /// Returns an error if the property isn't an `RRULE`, has a parameter we know, or has a
+    /// value `parse_rrule` rejects.
+    pub fn from_prop(prop: &Prop) -> Result<RRule, RRuleError> {
+        if !name_eq(prop.name.val(), "RRULE") {
+            return Err(RRuleError::new(msg::Not_an_RRULE, None));
+        }
+        let known = |name: &str| crate::parameter::index_of(&uppercase(name)).is_some();
+        if prop.parameters().iter().any(|param| known(param.name().val())) {
+            return Err(RRuleError::new(msg::Unexpected_parameter, None));
+        }
+        let value = prop.value().val().as_bytes();
+        parse_rrule.parse(value).map_err(winnow::error::ParseError::into_inner)
+    }
+
+    /// The `FREQ` part
+    #[must_use]
+    pub fn freq(&self) -> Frequency {
+        self.freq
+    }
+    /// The `COUNT` part, if the rule has one
+    #[must_use]
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+    /// The `UNTIL` part: a `DATE`, a floating `DATE-TIME`, or a UTC `DATE-TIME`
+    #[must_use]
+    pub fn until(&self) -> Option<EventTime> {
+        Some(match self.until.as_ref()? {
+            When::Date(date) => EventTime::Date(*date),
+            When::DateTime(dt) => EventTime::Floating(*dt),
+            When::Timestamp(ts) => EventTime::Zoned(ts.to_zoned(TimeZone::UTC)),
+        })
+    }
+    /// The `INTERVAL` part, if the rule has one (see `interval_or_default`)
+    #[must_use]
+    pub fn interval(&self) -> Option<NonZeroU32> {
+        self.interval
+    }
+    /// The `BYSECOND` list, in the order written (like the other `BYxxx` lists); empty if
+    /// the rule has none
+    #[must_use]
+    pub fn by_second(&self) -> &[u8] {
+        &self.by_second
+    }
+    /// The `BYMINUTE` list
+    #[must_use]
+    pub fn by_minute(&self) -> &[u8] {
+        &self.by_minute
+    }
+    /// The `BYHOUR` list
+    #[must_use]
+    pub fn by_hour(&self) -> &[u8] {
+        &self.by_hour
+    }
+    /// The `BYDAY` list, in the order written
+    #[must_use]
+    pub fn by_day(&self) -> &[ByDay] {
+        &self.by_day
+    }
+    /// The `BYMONTHDAY` list; negative days count from the end of the month
+    #[must_use]
+    pub fn by_month_day(&self) -> &[i8] {
+        &self.by_month_day
+    }
+    /// The `BYYEARDAY` list; negative days count from the end of the year
+    #[must_use]
+    pub fn by_year_day(&self) -> &[i16] {
+        &self.by_year_day
+    }
+    /// The `BYWEEKNO` list; negative weeks count from the end of the year
+    #[must_use]
+    pub fn by_week_no(&self) -> &[i8] {
+        &self.by_week_no
+    }
+    /// The `BYMONTH` list
+    #[must_use]
+    pub fn by_month(&self) -> &[u8] {
+        &self.by_month
+    }
+    /// The `BYSETPOS` list; negative positions count from the end of each set
+    #[must_use]
+    pub fn by_set_pos(&self) -> &[i16] {
+        &self.by_set_pos
+    }
+    /// The `WKST` part, if the rule has one (weeks start on Monday otherwise)
+    #[must_use]
+    pub fn wk_st(&self) -> Option<Weekday> {
+        self.wk_st
+    }
+    /// The rule parts the rule has, each name (in uppercase) with its value as `Display`
+    /// writes it, in the order `Display` writes them
+    pub fn parts(&self) -> impl Iterator<Item = (&str, String)> {
+        fn list<T: fmt::Display>(items: &[T]) -> Option<String> {
+            let items: Vec<_> = items.iter().map(T::to_string).collect();
+            (!items.is_empty()).then(|| items.join(","))
+        }
+        let until = self.until.as_ref().map(|until| match until {
+            When::Date(date) => date.strftime("%Y%m%d").to_string(),
+            When::DateTime(dt) => dt.strftime("%Y%m%dT%H%M%S").to_string(),
+            When::Timestamp(ts) => ts.strftime("%Y%m%dT%H%M%SZ").to_string(),
+        });
+        let standard = [
+            ("FREQ", Some(self.freq.name().to_string())),
+            ("UNTIL", until),
+            ("COUNT", self.count.map(|n| n.to_string())),
+            ("INTERVAL", self.interval.map(|n| n.to_string())),
+            ("BYSECOND", list(&self.by_second)),
+            ("BYMINUTE", list(&self.by_minute)),
+            ("BYHOUR", list(&self.by_hour)),
+            ("BYDAY", list(&self.by_day)),
+            ("BYMONTHDAY", list(&self.by_month_day)),
+            ("BYYEARDAY", list(&self.by_year_day)),
+            ("BYWEEKNO", list(&self.by_week_no)),
+            ("BYMONTH", list(&self.by_month)),
+            ("BYSETPOS", list(&self.by_set_pos)),
+            ("WKST", self.wk_st.map(|day| weekday_name(day).to_string())),
+        ];
+        let extensions = self.extensions.iter().map(|(name, value)| (name.as_str(), value.clone()));
+        (standard.into_iter()).filter_map(|(name, value)| Some((name, value?))).chain(extensions)
+    }
+
+    /// Whether `start` satisfies the rule's `BYxxx` parts, other than `BYSETPOS` (which
+    /// depends on the other instances). The time parts aren't checked for an all-day start,
+    /// and a zoned start is checked at its local time.
+    ///
+    /// RFC 5545 makes `DTSTART` the first instance even if it doesn't satisfy the rule, so
+    /// expansion always includes it; see `check_dtstart`.
+    #[must_use]
+    pub fn matches(&self, start: &EventTime) -> bool {
+        let (date, time) = match start {
+            EventTime::Date(date) => (*date, None),
+            EventTime::Floating(dt) => (dt.date(), Some(dt.time())),
+            EventTime::Zoned(zoned) => (zoned.date(), Some(zoned.time())),
+        };
+        self.matches_date(date)
+            && time.is_none_or(|time| {
+                has(&self.by_hour, time.hour().cast_unsigned())
+                    && has(&self.by_minute, time.minute().cast_unsigned())
+                    && has(&self.by_second, time.second().cast_unsigned())
+            })
+    }
+    // Whether `date` satisfies the rule's date parts: `BYMONTH` through `BYDAY`
+    fn matches_date(&self, date: Date) -> bool {
+        // Whether `n`, counting back from the end if it's negative, is the `index`th of `len`
+        fn is_nth(n: i16, index: i16, len: i16) -> bool {
+            n == index || n == index - len - 1
+        }
+        let wk_st = self.wk_st.unwrap_or(Weekday::Monday);
+        let (week, weeks) = week_of_year(date, wk_st);
+        let in_month = self.freq == Frequency::Monthly || !self.by_month.is_empty();
+        // Ordinals count weekdays in the month or year, depending on the rule; otherwise
+        // they're meaningless, and we ignore them
+        let by_day = |d: &ByDay| match d.ordinal {
+            Some(_) if in_month => d.resolve_in_month(date.year(), date.month()) == Some(date),
+            Some(_) if self.freq == Frequency::Yearly => {
+                d.resolve_in_year(date.year()) == Some(date)
+            }
+            _ => d.weekday == date.weekday(),
+        };
+        let (month, month_day, days_in_month) =
+            (date.month().cast_unsigned(), date.day(), date.days_in_month());
+        has(&self.by_month, month)
+            && (self.by_week_no.is_empty()
+                || (self.by_week_no.iter()).any(|n| is_nth((*n).into(), week, weeks)))
+            && (self.by_year_day.is_empty()
+                || (self.by_year_day.iter())
+                    .any(|n| is_nth(*n, date.day_of_year(), date.days_in_year())))
+            && (self.by_month_day.is_empty()
+                || (self.by_month_day.iter())
+                    .any(|n| is_nth((*n).into(), month_day.into(), days_in_month.into())))
+            && (self.by_day.is_empty() || self.by_day.iter().any(by_day))
+    }
+    /// Warn if `dtstart` doesn't satisfy the rule (see `matches`), and return whether it
+    /// does. The warning has `span`, which should locate the rule — say, the `RRULE`'s value
+    /// in its content line (as `RcalConfig::parse_calendar_with` gives it).
+    pub fn check_dtstart(
+        &self,
+        dtstart: &EventTime,
+        span: Range<usize>,
+        warnings: &mut dyn Warnings,
+    ) -> bool {
+        let matches = self.matches(dtstart);
+        if !matches {
+            warnings.warn(Warning::new(Observation::DtstartNotInRule, span));
+        }
+        matches
+    }
+    /// Whether the rule has finitely many instances: whether it has a `COUNT` or an `UNTIL`.
+    /// A rule with `COUNT=0` is finite, with no instances at all.
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        self.count.is_some() || self.until.is_some()
+    }
+    /// The `INTERVAL`, or its default of 1
+    #[must_use]
+    pub fn interval_or_default(&self) -> NonZeroU32 {
+        self.interval.unwrap_or(NonZeroU32::MIN)
+    }
+    /// The rule parts we didn't recognize, kept by `parse_rrule_lenient`: each name (in
+    /// uppercase) and value (as written), in the order written
+    #[must_use]
+    pub fn extensions(&self) -> &[(String, String)] {
+        &self.extensions
+    }
+    /// The rule as an `RRULE` content line (without a line ending), writing the value as
+    /// `Display` does. Parsing the value gives back an equal rule.
+    #[must_use]
+    pub fn to_ics_string(&self) -> String {
+        format!("RRULE:{self}")
+    }
+
+    /// Check that `UNTIL` has the form RFC 5545 requires given the component's `DTSTART`: a
+    /// `DATE` if `DTSTART` is a `DATE`, a floating `DATE-TIME` if `DTSTART` is floating, and
+    /// a UTC `DATE-TIME` if `DTSTART` has a time zone (UTC included)
+    pub fn check_until(&self, dtstart: &EventTime) -> Result<(), UntilMismatch> {
+        match (dtstart, &self.until) {
+            (_, None)
+            | (EventTime::Date(_), Some(When::Date(_)))
+            | (EventTime::Floating(_), Some(When::DateTime(_)))
+            | (EventTime::Zoned(_), Some(When::Timestamp(_))) => Ok(()),
+            (EventTime::Date(_), _) => Err(UntilMismatch::ExpectedDate),
+            (EventTime::Floating(_), _) => Err(UntilMismatch::ExpectedFloating),
+            (EventTime::Zoned(_), _) => Err(UntilMismatch::ExpectedUtc),
+        }
+    }
+    /// For lenient parsing: if `check_until` fails, convert `UNTIL` to the form `DTSTART`
+    /// requires, and return the mismatch that was fixed. A `DATE` becomes the last second of
+    /// that day (in `DTSTART`'s time zone, if it has one), so the day's instances are still
+    /// included; a floating `DATE-TIME` is taken to be in `DTSTART`'s time zone; and a UTC
+    /// `DATE-TIME` becomes its UTC date or clock time.
+    pub fn fix_until(&mut self, dtstart: &EventTime) -> Option<UntilMismatch> {
+        let mismatch = self.check_until(dtstart).err()?;
+        let last_second = |date: Date| date.to_datetime(Time::constant(23, 59, 59, 0));
+        let until = match (dtstart, self.until.take()?) {
+            (EventTime::Date(_), When::DateTime(dt)) => When::Date(dt.date()),
+            (EventTime::Date(_), When::Timestamp(ts)) => {
+                When::Date(ts.to_zoned(TimeZone::UTC).date())
+            }
+            (EventTime::Floating(_), When::Date(date)) => When::DateTime(last_second(date)),
+            (EventTime::Floating(_), When::Timestamp(ts)) => {
+                When::DateTime(ts.to_zoned(TimeZone::UTC).datetime())
+            }
+            (EventTime::Zoned(start), When::Date(date)) => {
+                When::Timestamp(in_zone(last_second(date), start.time_zone()))
+            }
+            (EventTime::Zoned(start), When::DateTime(dt)) => {
+                When::Timestamp(in_zone(dt, start.time_zone()))
+            }
+            (_, until) => until,
+        };
+        self.until = Some(until);
+        Some(mismatch)
+    }
+
+    /// Describe the rule in English, like "every 2 weeks on Monday and Wednesday, 4 times"
+    #[must_use]
+    pub fn describe(&self) -> String {
+        self.describe_with(&English)
+    }
+    /// Describe the rule using `messages` for the wording. The description covers `FREQ`,
+    /// `INTERVAL`, `BYDAY`, `BYMONTHDAY`, `BYMONTH`, `COUNT`, and `UNTIL`; other rule parts
+    /// aren't mentioned.
+    #[must_use]
+    pub fn describe_with(&self, messages: &dyn Messages) -> String {
+        let mut phrases = vec![messages.every(self.interval_or_default().get(), self.freq)];
+        if !self.by_day.is_empty() {
+            let days = match weekday_set(&self.by_day) {
+                Some(WEEKDAYS) => vec![messages.weekdays()],
+                Some(WEEKEND) => vec![messages.weekend()],
+                _ => (self.by_day.iter())
+                    .map(|d| match d.ordinal {
+                        None => messages.weekday(d.weekday),
+                        Some(n) => messages.nth_weekday(n.get(), d.weekday),
+                    })
+                    .collect(),
+            };
+            phrases.push(messages.on_days(&days));
+        }
+        if !self.by_month_day.is_empty() {
+            phrases.push(messages.on_month_days(&self.by_month_day));
+        }
+        if !self.by_month.is_empty() {
+            let months: Vec<_> = self.by_month.iter().map(|m| messages.month(*m)).collect();
+            phrases.push(messages.in_months(&months));
+        }
+        let limit = match (self.count, &self.until) {
+            (Some(count), _) => Some(messages.times(count)),
+            (None, Some(When::Date(date))) => Some(messages.until(&date.to_string())),
+            (None, Some(When::DateTime(dt))) => Some(messages.until(&dt.to_string())),
+            (None, Some(When::Timestamp(ts))) => Some(messages.until(&ts.to_string())),
+            (None, None) => None,
+        };
+        messages.description(&phrases, limit)
+    }
+
+    // The fields, for comparison (tuples only implement `PartialEq` up to 12 elements)
+    #[allow(clippy::type_complexity)]
+    fn fields(
+        &self,
+    ) -> (
+        (
+            &Frequency,
+            &Option<u32>,
+            &Option<When>,
+            &Option<NonZeroU32>,
+            &Option<Weekday>,
+            &[(String, String)],
+        ),
+        (&[u8], &[u8], &[u8], &[ByDay], &[i8], &[i16], &[i8], &[u8], &[i16]),
+    ) {
+        (
+            (&self.freq, &self.count, &self.until, &self.interval, &self.wk_st, &self.extensions),
+            (
+                &self.by_second,
+                &self.by_minute,
+                &self.by_hour,
+                &self.by_day,
+                &self.by_month_day,
+                &self.by_year_day,
+                &self.by_week_no,
+                &self.by_month,
+                &self.by_set_pos,
+            ),
+        )
+    }
+}
+impl PartialEq for RRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonicalize().fields() == other.canonicalize().fields()
+    }
+}
+/// The rule as an RRULE value (without a line ending): uppercase rule parts, `FREQ` first and
+/// then the others in the order RFC 5545 lists them, with any extensions last
+impl fmt::Display for RRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.parts().enumerate() {
+            write!(f, "{}{name}={value}", if i == 0 { "" } else { ";" })?;
+        }
+        Ok(())
+    }
 }
 
 // Frequency =====================================================================
 // We derive Default only because that makes it easier to handle the `freq` field,
 // which unlike the others is not optional.
 //
+/// The `FREQ` rule part: the basic unit a rule repeats in
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
-enum Frequency {
+#[non_exhaustive]
+pub enum Frequency {
     Secondly,
     Minutely,
     Hourly,
@@ -110,6 +544,56 @@ enum Frequency {
     #[default]
     Yearly,
 }
+impl Frequency {
+    // The `FREQ` value
+    fn name(self) -> &'static str {
+        match self {
+            Frequency::Secondly => "SECONDLY",
+            Frequency::Minutely => "MINUTELY",
+            Frequency::Hourly => "HOURLY",
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+    /// `n` of the frequency's periods, as a span (`None` if that's beyond jiff's range)
+    #[must_use]
+    pub fn periods(self, n: i64) -> Option<Span> {
+        let span = Span::new();
+        match self {
+            Frequency::Secondly => span.try_seconds(n),
+            Frequency::Minutely => span.try_minutes(n),
+            Frequency::Hourly => span.try_hours(n),
+            Frequency::Daily => span.try_days(n),
+            Frequency::Weekly => span.try_weeks(n),
+            Frequency::Monthly => span.try_months(n),
+            Frequency::Yearly => span.try_years(n),
+        }
+        .ok()
+    }
+    /// The period containing `at`: the second, minute, hour, day, week (starting on `wk_st`),
+    /// month or year, from its first instant up to (but not including) the next period's. It's
+    /// `None` if the period runs past jiff's range of dates.
+    #[must_use]
+    pub fn period_of(self, at: DateTime, wk_st: Weekday) -> Option<Range<DateTime>> {
+        let (date, midnight) = (at.date(), Time::midnight());
+        let start = match self {
+            Frequency::Secondly => date.to_datetime(time(at.hour(), at.minute(), at.second(), 0)),
+            Frequency::Minutely => date.to_datetime(time(at.hour(), at.minute(), 0, 0)),
+            Frequency::Hourly => date.to_datetime(time(at.hour(), 0, 0, 0)),
+            Frequency::Daily => date.to_datetime(midnight),
+            Frequency::Weekly => {
+                let days = i64::from(date.weekday().since(wk_st));
+                date.checked_sub(days.days()).ok()?.to_datetime(midnight)
+            }
+            Frequency::Monthly => date.first_of_month().to_datetime(midnight),
+            Frequency::Yearly => date.first_of_year().to_datetime(midnight),
+        };
+        let end = start.checked_add(self.periods(1)?).ok()?;
+        Some(start..end)
+    }
+}
 // Parse a `Frequency`
 fn frequency(input: &mut &[u8]) -> ModalResult<Frequency> {
     use Frequency::*;
@@ -126,7 +610,7 @@ fn frequency(input: &mut &[u8]) -> ModalResult<Frequency> {
     .parse_next(input)
 }
 
-// `Weekday` and `WeekdaySpec` =============================
+// `Weekday` and `ByDay` =============================
 // Parse a Weekday
 fn weekday(input: &mut &[u8]) -> ModalResult<Weekday> {
     use Weekday::*;
@@ -142,13 +626,90 @@ fn weekday(input: &mut &[u8]) -> ModalResult<Weekday> {
     )))
     .parse_next(input)
 }
-// WeekdaySpec
-type WeekdaySpec = (Option<NonZeroI8>, Weekday);
+// A weekday's abbreviation, as in `BYDAY` and `WKST`
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Monday => "MO",
+        Weekday::Tuesday => "TU",
+        Weekday::Wednesday => "WE",
+        Weekday::Thursday => "TH",
+        Weekday::Friday => "FR",
+        Weekday::Saturday => "SA",
+        Weekday::Sunday => "SU",
+    }
+}
+// Sets of weekdays, as bits numbered from Sunday (bit 0)
+const WEEKDAYS: u8 = 0b011_1110;
+const WEEKEND: u8 = 0b100_0001;
+// The set of weekdays in `by_day`, if none of them has an ordinal
+fn weekday_set(by_day: &[ByDay]) -> Option<u8> {
+    (by_day.iter()).try_fold(0, |set, d| {
+        d.ordinal.is_none().then(|| set | 1 << d.weekday.to_sunday_zero_offset())
+    })
+}
+/// A `BYDAY` list item: a weekday, with an optional ordinal picking out one occurrence of
+/// it in the month or year (`2TU` is the second Tuesday, `-1FR` the last Friday)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ByDay {
+    /// From -53 to 53
+    pub ordinal: Option<NonZeroI8>,
+    pub weekday: Weekday,
+}
+impl ByDay {
+    /// The date of the `ordinal`th `weekday` in `month` of `year`, counting back from the end
+    /// of the month if the ordinal is negative. `None` if the month has no such weekday
+    /// (there's no fifth Monday in most months), if the month doesn't exist, or if there's no
+    /// ordinal — a bare weekday means every one of them, not one date.
+    #[must_use]
+    pub fn resolve_in_month(self, year: i16, month: i8) -> Option<Date> {
+        let first = Date::new(year, month, 1).ok()?;
+        self.resolve_between(first, first.last_of_month())
+    }
+    /// The date of the `ordinal`th `weekday` in `year`, counting back from the end of the
+    /// year if the ordinal is negative. `None` if the year has no such weekday (only some
+    /// years have a 53rd Thursday), if the year is out of range, or if there's no ordinal.
+    #[must_use]
+    pub fn resolve_in_year(self, year: i16) -> Option<Date> {
+        self.resolve_between(Date::new(year, 1, 1).ok()?, Date::new(year, 12, 31).ok()?)
+    }
+    // The `ordinal`th `weekday` from `first` to `last`, inclusive. (jiff has
+    // `nth_weekday_of_month`, but nothing like it for a year, so we do both the same way.)
+    fn resolve_between(self, first: Date, last: Date) -> Option<Date> {
+        let nth = i32::from(self.ordinal?.get());
+        let date = if nth > 0 {
+            let first = first + i64::from(self.weekday.since(first.weekday())).days();
+            first.checked_add((7 * (nth - 1)).days()).ok()?
+        } else {
+            let last = last - i64::from(last.weekday().since(self.weekday)).days();
+            last.checked_add((7 * (nth + 1)).days()).ok()?
+        };
+        (first..=last).contains(&date).then_some(date)
+    }
+}
+/// The `BYDAY` list item as written: `MO`, `2TU`, `-1FR`
+impl fmt::Display for ByDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{ordinal}")?;
+        }
+        f.write_str(weekday_name(self.weekday))
+    }
+}
+impl From<(Option<NonZeroI8>, Weekday)> for ByDay {
+    fn from((ordinal, weekday): (Option<NonZeroI8>, Weekday)) -> Self {
+        ByDay { ordinal, weekday }
+    }
+}
+impl From<ByDay> for (Option<NonZeroI8>, Weekday) {
+    fn from(by_day: ByDay) -> Self {
+        (by_day.ordinal, by_day.weekday)
+    }
+}
 // The ByDay rule part takes either an unadorned day abbreviation (ByDay=TU
 // means every Tuesday in the relevant time period), or an offset followed by
 // a day abbreviation (1TU means the first Tuesday in the relevant period, and
 // -1TU means the last Tuesday in the relevant period.)
-fn weekday_spec(input: &mut &[u8]) -> ModalResult<WeekdaySpec> {
+fn weekday_spec(input: &mut &[u8]) -> ModalResult<ByDay> {
     let offset = match input.first() {
         Some(ch) if (*ch == b'+') || *ch == b'-' || ch.is_ascii_digit() => NonZero::new(
             dec_int
@@ -159,9 +720,9 @@ fn weekday_spec(input: &mut &[u8]) -> ModalResult<WeekdaySpec> {
         _ => None,
     };
     let day_of_week = weekday.parse_next(input)?;
-    Ok((offset, day_of_week))
+    Ok(ByDay { ordinal: offset, weekday: day_of_week })
 }
-fn weekday_list(input: &mut &[u8]) -> ModalResult<Vec<WeekdaySpec>> {
+fn weekday_list(input: &mut &[u8]) -> ModalResult<Vec<ByDay>> {
     separated(1.., cut_err(weekday_spec), b',').parse_next(input)
 }
 
@@ -172,6 +733,57 @@ enum When {
     DateTime(DateTime),
     Timestamp(Timestamp),
 }
+// Whether `value` is in `list`, or `list` is empty (meaning any value will do)
+fn has<T: PartialEq + Copy>(list: &[T], value: T) -> bool {
+    list.is_empty() || list.contains(&value)
+}
+
+// The week of the year `date` is in, and the number of weeks in that year, with weeks starting
+// on `wk_st`. Week 1 is the first week with at least four days in the year, so a date near
+// the start or end of the year may be in a week of the year before or after.
+fn week_of_year(date: Date, wk_st: Weekday) -> (i16, i16) {
+    // The first day of week 1 of `year`
+    let first_week = |year: i16| {
+        let jan1 = Date::new(year, 1, 1).unwrap_or(date);
+        let offset = jan1.weekday().since(wk_st);
+        let days = if offset <= 3 { -i64::from(offset) } else { 7 - i64::from(offset) };
+        jan1.checked_add(days.days()).unwrap_or(jan1)
+    };
+    let mut start = first_week(date.year());
+    let mut next = first_week(date.year() + 1);
+    if date < start {
+        (next, start) = (start, first_week(date.year() - 1));
+    } else if date >= next {
+        (start, next) = (next, first_week(date.year() + 2));
+    }
+    let weeks = |from: Date, to: Date| i16::try_from((to - from).get_days() / 7).unwrap_or(0);
+    (weeks(start, date) + 1, weeks(start, next))
+}
+
+// `dt` in `zone`. Only date-times at the very end of jiff's range can't be converted, and for
+// those, `Timestamp::MAX` limits a rule just as well.
+fn in_zone(dt: DateTime, zone: &TimeZone) -> Timestamp {
+    zone.to_timestamp(dt).unwrap_or(Timestamp::MAX)
+}
+
+/// How an RRULE's `UNTIL` disagrees with its component's `DTSTART`
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum UntilMismatch {
+    #[error("UNTIL must be a DATE when DTSTART is a DATE")]
+    ExpectedDate,
+    #[error("UNTIL must be a DATE-TIME without a time zone when DTSTART is floating")]
+    ExpectedFloating,
+    #[error("UNTIL must be a UTC DATE-TIME when DTSTART has a time zone")]
+    ExpectedUtc,
+}
+impl UntilMismatch {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        crate::catalog::RRULE_UNTIL_MISMATCH
+    }
+}
+
 fn when(input: &mut &[u8]) -> ModalResult<When> {
     fn wrap<T: Default>(r: Result<T, jiff::Error>) -> ModalResult<T> {
         r.map_err(|e| RRuleError::cut(msg::Not_a_time, Some(Box::new(e))))
@@ -196,11 +808,11 @@ fn when(input: &mut &[u8]) -> ModalResult<When> {
 // refers to January and September. We call a list of such indices an IndexList
 //
 struct IndexList {
-    msg: &'static str,
+    msg: Message,
     range: RangeInclusive<u8>,
 }
 impl IndexList {
-    const fn new(msg: &'static str, range: RangeInclusive<u8>) -> Self {
+    const fn new(msg: Message, range: RangeInclusive<u8>) -> Self {
         IndexList { msg, range }
     }
 }
@@ -223,11 +835,11 @@ impl Parser<&[u8], Vec<u8>, ErrMode<RRuleError>> for IndexList {
 //
 #[derive(Clone)]
 struct OffsetList<N: Int + PartialOrd + Default> {
-    msg: &'static str,
+    msg: Message,
     range: RangeInclusive<N>,
 }
 impl<N: Int + PartialOrd + Default> OffsetList<N> {
-    const fn new(msg: &'static str, range: RangeInclusive<N>) -> Self {
+    const fn new(msg: Message, range: RangeInclusive<N>) -> Self {
         Self { msg, range }
     }
 }
@@ -253,15 +865,33 @@ macro_rules! list {
     };
 }
 ///
-/// `parse_rrule` parses a recurrence rule.  Here `input` must be a single line
-/// ending with `\cr\lf`. We use `&[u8]` on the assumption that the line has been
-/// unfolded using `&[u8]` rather than `str`. This is the easiest way to follow
-/// RFC 5545's advice: "It is possible for very simple implementations to
+/// `parse_rrule` parses a recurrence rule.  Here `input` is an RRULE value, either by
+/// itself (as preparse gives it) or ending with `\cr\lf`. We use `&[u8]` on the assumption
+/// that the line has been unfolded using `&[u8]` rather than `str`. This is the easiest way
+/// to follow RFC 5545's advice: "It is possible for very simple implementations to
 /// generate improperly folded lines in the middle of a UTF-8 multi-octet
 /// sequence.  For this reason, implementations need to unfold lines in such
 /// a way to properly restore the original sequence.""
 pub fn parse_rrule(input: &mut &[u8]) -> ModalResult<RRule> {
+    parse_rrule_with(input, &mut IgnoreWarnings)
+}
+/// Like `parse_rrule`, but reports rule part names and values that weren't in uppercase, and
+/// `COUNT=0` (which means the rule has no instances), to `warnings`. Warning spans are byte
+/// offsets into the original `input`.
+pub fn parse_rrule_with(input: &mut &[u8], warnings: &mut dyn Warnings) -> ModalResult<RRule> {
+    parse(input, warnings, false)
+}
+/// Like `parse_rrule_with`, but tolerates rule parts we don't recognize, as RFC 5545 allows
+/// consumers to: a part whose name is an IANA token or `X-` name is kept, with its value as
+/// written, in `RRule::extensions`, and reported to `warnings`. The rule's `Display` output
+/// writes extensions back out. `INTERVAL=0`, an error otherwise, is treated as `INTERVAL=1`
+/// with a warning.
+pub fn parse_rrule_lenient(input: &mut &[u8], warnings: &mut dyn Warnings) -> ModalResult<RRule> {
+    parse(input, warnings, true)
+}
+fn parse(input: &mut &[u8], warnings: &mut dyn Warnings, lenient: bool) -> ModalResult<RRule> {
     let mut rrule = RRule::default();
+    let original_len = input.len();
 
     macro_rules! fail {
         ($why:expr) => {
@@ -287,26 +917,34 @@ pub fn parse_rrule(input: &mut &[u8]) -> ModalResult<RRule> {
     }
 
     let mut freq = None;
-    let mut name: Vec<u8>;
-    // Every RRule line must end in CRLF, so we use that to trigger end-of-parse
-    while crlf::<&[u8], RRuleError>.parse_next(input).is_err() {
+    // The rule ends at a CRLF, if there is one, or at the end of the input
+    while !input.is_empty() && crlf::<&[u8], RRuleError>.parse_next(input).is_err() {
         // Extract the rule part name into 'name' and resume parsing after the equal sign
         let Some(eq) = memchr(b'=', input) else {
             fail!(msg::Expected_equal_sign);
         };
-        name = input[0..eq].to_vec();
-        name.make_ascii_uppercase();
         let old_input = *input;
+        let name = uppercase_bytes(&old_input[0..eq]);
         *input = &input[eq + 1..];
+        let value_input = *input;
 
         match &name[..] {
             FREQ => match freq {
                 None => freq = Some(frequency.parse_next(input)?),
                 Some(_) => fail!(msg::Too_many_FREQs),
             },
-            COUNT => get_option!(count, dec_uint.context(msg::Bad_usize)),
+            COUNT => {
+                let offset = original_len - input.len();
+                get_option!(count, dec_uint.context(msg::Bad_usize));
+                if rrule.count == Some(0) && !lenient {
+                    let span = offset..original_len - input.len();
+                    warnings.warn(Warning::new(Observation::ZeroCount, span));
+                }
+            }
             UNTIL => get_option!(until, when),
-            INTERVAL => get_option!(interval, dec_uint.context(msg::Bad_usize)),
+            INTERVAL => get_option!(interval, |i: &mut &[u8]| {
+                interval(i, original_len - i.len(), lenient, warnings)
+            }),
             BYSECOND => get_vec!(by_second, list!(BySecond, 0, 60)),
             BYMINUTE => get_vec!(by_minute, list!(ByMinute, 0, 59)),
             BYHOUR => get_vec!(by_hour, list!(ByHour, 0, 23)),
@@ -317,14 +955,33 @@ pub fn parse_rrule(input: &mut &[u8]) -> ModalResult<RRule> {
             BYWEEKNO => get_vec!(by_week_no, list!(ByWeekNo<i8>, -53, 53)),
             BYSETPOS => get_vec!(by_set_pos, list!(BySetPos<i16>, -366, 366)),
             WKST => get_option!(wk_st, weekday),
+            _ if lenient && !name.is_empty() && name.iter().all(is_name_char) => {
+                let end = input.iter().position(|b| matches!(b, b';' | b'\r' | b'\n'));
+                let end = end.unwrap_or(input.len());
+                let name_start = original_len - old_input.len();
+                let span = name_start..name_start + eq + 1 + end;
+                warnings.warn(Warning::new(Observation::UnknownRulePartKept, span));
+                // The name is all ASCII, so it's never lossy
+                let name = String::from_utf8_lossy(&name).into_owned();
+                rrule.extensions.push((name, String::from_utf8_lossy(&input[..end]).into_owned()));
+                *input = &input[end..];
+                if input.first() == Some(&b';') {
+                    *input = &input[1..];
+                }
+                continue;
+            }
             _ => {
-                if name[0] == b',' {
+                if name.first() == Some(&b',') {
                     *input = old_input;
                     fail!(msg::Did_you_mean_semicolon);
                 }
                 fail!(msg::Unknown_rule_part);
             }
         }
+        let name_start = original_len - old_input.len();
+        warn_if_lowercase(warnings, &old_input[..eq], name_start);
+        let value = &value_input[..value_input.len() - input.len()];
+        warn_if_lowercase(warnings, value, name_start + eq + 1);
         // Rule parts are separated by semicolons
         if input.first() == Some(&b';') {
             *input = &input[1..];
@@ -339,6 +996,36 @@ pub fn parse_rrule(input: &mut &[u8]) -> ModalResult<RRule> {
     Ok(rrule)
 }
 
+// Parse an `INTERVAL` value. Zero would never advance, so it's an error — or, if `lenient`,
+// 1 with a warning whose span starts at `offset`.
+fn interval(
+    input: &mut &[u8],
+    offset: usize,
+    lenient: bool,
+    warnings: &mut dyn Warnings,
+) -> ModalResult<NonZeroU32> {
+    let before = *input;
+    let interval = dec_uint.context(msg::Bad_usize).parse_next(input)?;
+    match NonZeroU32::new(interval) {
+        Some(interval) => Ok(interval),
+        None if lenient => {
+            let span = offset..offset + before.len() - input.len();
+            warnings.warn(Warning::new(Observation::ZeroIntervalReplaced, span));
+            Ok(NonZeroU32::MIN)
+        }
+        None => {
+            *input = before;
+            cut_err(fail.context(msg::Zero_INTERVAL)).parse_next(input)
+        }
+    }
+}
+
+// Rule part names, like other names in RFC 5545, are made of letters, digits, and dashes
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_name_char(b: &u8) -> bool {
+    b.is_ascii_alphanumeric() || *b == b'-'
+}
+
 // We need these `const` definitations because we can't use `"X".as_bytes()` in a pattern
 const FREQ: &[u8] = "FREQ".as_bytes();
 const COUNT: &[u8] = "COUNT".as_bytes();
@@ -378,7 +1065,7 @@ mod test {
         let ok_cases = [
             ("FREQ=SECONDLY\r\n", rrule!(Secondly)),
             ("count=0;FREQ=SECONDLY\r\n", rrule!(Secondly, count: Some(0))),
-            ("INTERVAL=0;FREQ=SECONDLY\r\n", rrule!(Secondly, interval: Some(0))),
+            ("INTERVAL=2;FREQ=SECONDLY\r\n", rrule!(Secondly, interval: NonZeroU32::new(2))),
             (
                 "count=0;FREQ=SECONDLY;WkSt=WE\r\n",
                 rrule!(Secondly, count: Some(0), wk_st: Some(Wednesday)),
@@ -389,9 +1076,9 @@ mod test {
             (
                 "BYDAY=MO,23Tu,-9sa;FREQ=yearly\r\n",
                 rrule!(Yearly, by_day: vec![
-                    (None, Monday),
-                    (NonZeroI8::new(23), Tuesday),
-                    (NonZeroI8::new(-9), Saturday)
+                    (None, Monday).into(),
+                    (NonZeroI8::new(23), Tuesday).into(),
+                    (NonZeroI8::new(-9), Saturday).into()
                 ]),
             ),
             ("BYMONTH=1,12,9;FREQ=yearly\r\n", rrule!(Yearly, by_month: vec![1,12,9])),
@@ -414,8 +1101,8 @@ mod test {
         ];
         for case in ok_cases {
             let result = parse_rrule.parse_peek(B(&case.0));
-            if result.is_ok() {
-                assert_eq!(result.unwrap(), (B(""), case.clone().1), "Case: {}", case.0);
+            if let Ok(result) = result {
+                assert_eq!(result, (B(""), case.clone().1), "Case: {}", case.0);
             } else {
                 let input = case.0.as_bytes();
                 match parse_rrule.parse(input) {
@@ -432,19 +1119,28 @@ mod test {
         }
     }
     #[test]
+    fn test_parse_rrule_without_crlf() {
+        let rule = parse_rrule.parse(b"FREQ=WEEKLY;COUNT=4;BYDAY=MO,WE").unwrap();
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;COUNT=4;BYDAY=MO,WE");
+        let (rest, rule) =
+            parse_rrule.parse_peek(B("FREQ=DAILY;UNTIL=20250303\r\nRDATE:x")).unwrap();
+        assert_eq!((rest, rule.freq()), (B("RDATE:x"), Frequency::Daily));
+    }
+    #[test]
     fn test_parse_rrule_errors() {
         let error_cases = [
             ("\r\n", msg::FREQ_required),
             ("FREQ=Monthly,count=42\r\n", msg::Did_you_mean_semicolon),
-            ("", msg::Expected_equal_sign),
-            ("Freq=Yearly", msg::Expected_equal_sign),
+            ("", msg::FREQ_required),
+            ("Freq=Yearly;Count", msg::Expected_equal_sign),
             ("Foo=bar", msg::Unknown_rule_part),
             ("Freq=Yearly;FREQ=Monthly\r\n", msg::Too_many_FREQs),
             ("Freq=Neverly\r\n", msg::FREQ_needs_Frequency),
             ("Freq=Yearly;WksT=MO;wkst=SU\r\n", too_many!(WkSt)),
             ("Freq=Yearly;Count=0;COUNT=4\r\n", too_many!(Count)),
             ("Freq=Yearly;Count=-1\r\n", msg::Bad_usize),
-            ("Freq=Yearly;Interval=0;INTERVAL=4\r\n", too_many!(Interval)),
+            ("Freq=Yearly;Interval=2;INTERVAL=4\r\n", too_many!(Interval)),
+            ("Freq=Yearly;Interval=0\r\n", msg::Zero_INTERVAL),
             ("Freq=Yearly;Interval=-1\r\n", msg::Bad_usize),
             ("Freq=Yearly;WKST=XX\r\n", msg::Expected_day_abbreviation),
             ("Freq=Yearly;BySecond=0,60,61\r\n", index_msg!(BySecond, 0, 60)),
@@ -479,11 +1175,125 @@ mod test {
                 panic!("No error for {case:?}")
             };
             let err = err.into_inner().unwrap();
-            assert_eq!(err.context(), vec![case.1], "Unexpected error for {case:?}:\n{err:?}\n");
+            assert_eq!(
+                err.context(),
+                vec![case.1.text],
+                "Unexpected error for {case:?}:\n{err:?}\n"
+            );
+            assert_eq!(err.kind(), case.1.kind, "Unexpected kind for {case:?}");
+            assert_ne!(err.code(), crate::catalog::RRULE_OTHER, "No code for {case:?}");
+        }
+    }
+
+    #[test]
+    fn test_semantic_equality() {
+        let parse = |text: &str| parse_rrule.parse(text.as_bytes()).unwrap();
+        let weekly = parse("FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4\r\n");
+        for same in [
+            "count=4;byday=we,mo;freq=weekly\r\n",
+            "FREQ=WEEKLY;INTERVAL=1;BYDAY=MO,WE,MO;COUNT=4;WKST=MO\r\n",
+        ] {
+            assert_eq!(parse(same), weekly, "{same}");
+        }
+        for different in [
+            "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4;INTERVAL=2\r\n",
+            "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4;WKST=SU\r\n",
+            "FREQ=WEEKLY;BYDAY=MO,1WE;COUNT=4\r\n",
+        ] {
+            assert_ne!(parse(different), weekly, "{different}");
+        }
+
+        let canonical =
+            parse("BYMONTH=12,1,12;BYDAY=-1FR,SU,1MO;FREQ=YEARLY;INTERVAL=1\r\n").canonicalize();
+        assert_eq!(canonical.by_month, vec![1, 12]);
+        assert_eq!(
+            canonical.by_day,
+            vec![
+                (None, Weekday::Sunday).into(),
+                (NonZeroI8::new(-1), Weekday::Friday).into(),
+                (NonZeroI8::new(1), Weekday::Monday).into()
+            ]
+        );
+        assert_eq!(canonical.interval, None);
+        let merged = parse("FREQ=MONTHLY;BYDAY=1MO,SA,TU,MO,-1TU,2SU,1SU\r\n").canonicalize();
+        assert_eq!(merged.to_string(), "FREQ=MONTHLY;BYDAY=MO,TU,SA,1SU,2SU");
+        assert_eq!(merged, parse("FREQ=MONTHLY;BYDAY=TU,MO,SA,2SU,1SU\r\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "cautious")]
+    fn test_from_prop() {
+        use crate::{catalog, preparse::cautious_preparse};
+        let from = |line: &str| RRule::from_prop(&cautious_preparse(line.as_bytes()).unwrap());
+        let rule = from("rrule;X-SOURCE=app:FREQ=WEEKLY;BYDAY=MO").unwrap();
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;BYDAY=MO");
+        let code = |line| from(line).unwrap_err().code();
+        assert_eq!(code("EXRULE:FREQ=DAILY"), catalog::RRULE_NOT_AN_RRULE);
+        assert_eq!(code("RRULE;TZID=UTC:FREQ=DAILY"), catalog::RRULE_UNEXPECTED_PARAMETER);
+        assert_eq!(code("RRULE:FREQ=DAILY;FREQ=WEEKLY"), catalog::RRULE_REPEATED_RULE_PART);
+    }
+    #[test]
+    fn test_describe() {
+        let describe = |text: &str| parse_rrule.parse(text.as_bytes()).unwrap().describe();
+        assert_eq!(describe("FREQ=DAILY\r\n"), "every day");
+        assert_eq!(
+            describe("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=4\r\n"),
+            "every 2 weeks on Monday and Wednesday, 4 times"
+        );
+        assert_eq!(
+            describe("FREQ=YEARLY;BYMONTH=11;BYDAY=4TH;UNTIL=20301231\r\n"),
+            "every year on the fourth Thursday in November, until 2030-12-31"
+        );
+        assert_eq!(
+            describe("FREQ=MONTHLY;BYMONTHDAY=1,-1;COUNT=1\r\n"),
+            "every month on the first and last day of the month, once"
+        );
+        assert_eq!(describe("FREQ=WEEKLY;BYDAY=FR,MO,TU,WE,TH,MO\r\n"), "every week on weekdays");
+        assert_eq!(describe("FREQ=WEEKLY;BYDAY=SU,SA\r\n"), "every week on weekends");
+        assert_eq!(
+            describe("FREQ=MONTHLY;BYDAY=SA,1SU\r\n"),
+            "every month on Saturday and the first Sunday"
+        );
+    }
+    #[test]
+    fn test_describe_with() {
+        struct Terse;
+        impl Messages for Terse {
+            fn every(&self, interval: u32, freq: Frequency) -> String {
+                format!("{freq:?}/{interval}")
+            }
+            fn weekday(&self, day: Weekday) -> String {
+                format!("{day:?}")[..2].to_string()
+            }
+            fn list(&self, items: &[String]) -> String {
+                items.join(",")
+            }
         }
+        let rrule = parse_rrule.parse(b"FREQ=WEEKLY;BYDAY=TU,TH\r\n").unwrap();
+        assert_eq!(rrule.describe_with(&Terse), "Weekly/1 on Tu,Th");
     }
 
-    fn error_info<T: std::fmt::Debug>(
+    #[test]
+    fn test_parse_rrule_warnings() {
+        use crate::warning::{Observation::NormalizedCase, Warning};
+        let mut warnings = Vec::new();
+        let input = b"FREQ=Weekly;BYDAY=MO,tu;COUNT=3;wkst=SU\r\n";
+        parse_rrule_with(&mut &input[..], &mut warnings).unwrap();
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::new(NormalizedCase, 5..11),
+                Warning::new(NormalizedCase, 18..23),
+                Warning::new(NormalizedCase, 32..36),
+            ]
+        );
+
+        warnings.clear();
+        parse_rrule_with(&mut &b"FREQ=DAILY;INTERVAL=2\r\n"[..], &mut warnings).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    fn error_info<T: fmt::Debug>(
         err: Result<T, ParseError<&[u8], RRuleError>>,
     ) -> (usize, Vec<&'static str>) {
         let err = err.unwrap_err();
@@ -492,7 +1302,7 @@ mod test {
     }
     #[test]
     fn test_offsets() {
-        let context = vec![index_msg!(BySecond, 0, 60)];
+        let context = vec![index_msg!(BySecond, 0, 60).text];
 
         let input = "Freq=Yearly;BySecond=0,60,-1\r\n".as_bytes();
         assert_eq!(error_info(parse_rrule.parse(input)), (26, context.clone()),);
@@ -508,7 +1318,7 @@ mod test {
             let input = &base;
             assert_eq!(
                 error_info(weekday_spec.parse(input)),
-                (good.len(), vec![msg::Expected_day_abbreviation])
+                (good.len(), vec![msg::Expected_day_abbreviation.text])
             );
         }
         for bad in ["0", "54", "-54"] {
@@ -516,8 +1326,247 @@ mod test {
             let input = &base;
             assert_eq!(
                 error_info(weekday_spec.parse(input)),
-                (0, vec![msg::Expected_ByDay_offset])
+                (0, vec![msg::Expected_ByDay_offset.text])
             );
         }
     }
+
+    #[test]
+    fn resolving_by_day() {
+        use Weekday::*;
+        let by_day = |n, weekday| ByDay { ordinal: NonZeroI8::new(n), weekday };
+        let rule = parse_rrule.parse(B("FREQ=MONTHLY;BYDAY=2TU,-1FR\r\n")).unwrap();
+        let [second_tuesday, last_friday] = rule.by_day() else { panic!() };
+        assert_eq!(second_tuesday.resolve_in_month(2025, 9), Some(civil::date(2025, 9, 9)));
+        assert_eq!(last_friday.resolve_in_month(2025, 2), Some(civil::date(2025, 2, 28)));
+        assert_eq!(by_day(5, Monday).resolve_in_month(2025, 9), Some(civil::date(2025, 9, 29)));
+        assert_eq!(by_day(5, Monday).resolve_in_month(2025, 10), None);
+        assert_eq!(by_day(-5, Monday).resolve_in_month(2025, 10), None);
+        assert_eq!(by_day(1, Monday).resolve_in_month(2025, 13), None);
+        assert_eq!(by_day(0, Monday).resolve_in_month(2025, 9), None);
+
+        assert_eq!(by_day(1, Wednesday).resolve_in_year(2025), Some(civil::date(2025, 1, 1)));
+        assert_eq!(by_day(1, Thursday).resolve_in_year(2025), Some(civil::date(2025, 1, 2)));
+        assert_eq!(by_day(-1, Wednesday).resolve_in_year(2025), Some(civil::date(2025, 12, 31)));
+        assert_eq!(by_day(-2, Tuesday).resolve_in_year(2025), Some(civil::date(2025, 12, 23)));
+        assert_eq!(by_day(53, Thursday).resolve_in_year(2026), Some(civil::date(2026, 12, 31)));
+        assert_eq!(by_day(53, Thursday).resolve_in_year(2025), None);
+        assert_eq!(by_day(-53, Thursday).resolve_in_year(2025), None);
+        assert_eq!(by_day(-53, Friday).resolve_in_year(2027), Some(civil::date(2027, 1, 1)));
+
+        let spec: (Option<NonZeroI8>, Weekday) = by_day(-1, Friday).into();
+        assert_eq!(ByDay::from(spec), *last_friday);
+    }
+
+    #[test]
+    fn unknown_rule_parts() {
+        let input = B("FREQ=WEEKLY;X-NAME=Value;BYDAY=MO;RSCALE=gregorian\r\n");
+        assert_eq!(error_info(parse_rrule.parse(input)), (19, vec![msg::Unknown_rule_part.text]));
+
+        let mut warnings = Vec::new();
+        let rule = parse_rrule_lenient(&mut &input[..], &mut warnings).unwrap();
+        assert_eq!(
+            rule.extensions(),
+            &[("X-NAME".into(), "Value".into()), ("RSCALE".into(), "gregorian".into())]
+        );
+        let spans: Vec<_> = warnings.iter().map(|w| w.span.clone()).collect();
+        assert_eq!(spans, vec![12..24, 34..50]);
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;BYDAY=MO;X-NAME=Value;RSCALE=gregorian");
+
+        // Malformed names are still errors
+        let input = B("FREQ=WEEKLY;X_NAME=1\r\n");
+        let result = (|i: &mut &[u8]| parse_rrule_lenient(i, &mut IgnoreWarnings)).parse(input);
+        assert_eq!(error_info(result), (19, vec![msg::Unknown_rule_part.text]));
+    }
+    #[test]
+    fn display() {
+        for (input, output) in [
+            ("freq=daily\r\n", "FREQ=DAILY"),
+            (
+                "wkst=su;byday=-1fr,mo;until=20250101T120000Z;freq=monthly;interval=2\r\n",
+                "FREQ=MONTHLY;UNTIL=20250101T120000Z;INTERVAL=2;BYDAY=-1FR,MO;WKST=SU",
+            ),
+            (
+                "BYSETPOS=-1;BYMONTH=1,2;BYWEEKNO=3;BYYEARDAY=-4;BYMONTHDAY=5;BYHOUR=6;\
+                 BYMINUTE=7;BYSECOND=8;COUNT=9;FREQ=YEARLY;UNTIL=20250101\r\n",
+                "FREQ=YEARLY;UNTIL=20250101;COUNT=9;BYSECOND=8;BYMINUTE=7;BYHOUR=6;BYMONTHDAY=5;\
+                 BYYEARDAY=-4;BYWEEKNO=3;BYMONTH=1,2;BYSETPOS=-1",
+            ),
+        ] {
+            let rule = parse_rrule.parse(B(input)).unwrap();
+            assert_eq!(rule.to_string(), output);
+            assert_eq!(parse_rrule.parse(B(&format!("{rule}\r\n"))).unwrap(), rule);
+        }
+        let input = B("freq=weekly;x-name=Value;byday=su,1mo;bysecond=60\r\n");
+        let rule = parse_rrule_lenient(&mut &input[..], &mut IgnoreWarnings).unwrap();
+        let line = rule.to_ics_string();
+        assert_eq!(line, "RRULE:FREQ=WEEKLY;BYSECOND=60;BYDAY=SU,1MO;X-NAME=Value");
+        let value = format!("{}\r\n", &line["RRULE:".len()..]);
+        let reparsed = parse_rrule_lenient(&mut value.as_bytes(), &mut IgnoreWarnings).unwrap();
+        assert_eq!(reparsed, rule);
+        assert_eq!(reparsed.extensions(), rule.extensions());
+    }
+
+    #[test]
+    fn getters_and_parts() {
+        let input = B("FREQ=MONTHLY;BYDAY=-1FR,MO;COUNT=3;BYSETPOS=2;UNTIL=20250101T120000Z\r\n");
+        let rule = parse_rrule.parse(input).unwrap();
+        assert_eq!(rule.freq(), Frequency::Monthly);
+        assert_eq!(rule.count(), Some(3));
+        let until = civil::datetime(2025, 1, 1, 12, 0, 0, 0).in_tz("UTC").unwrap();
+        assert_eq!(rule.until(), Some(EventTime::Zoned(until)));
+        assert_eq!((rule.interval(), rule.interval_or_default().get()), (None, 1));
+        assert_eq!(rule.by_day()[0].to_string(), "-1FR");
+        assert_eq!((rule.by_set_pos(), rule.by_month()), (&[2][..], &[][..]));
+        assert_eq!(rule.wk_st(), None);
+        let parts: Vec<_> = rule.parts().collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("FREQ", "MONTHLY".to_string()),
+                ("UNTIL", "20250101T120000Z".to_string()),
+                ("COUNT", "3".to_string()),
+                ("BYDAY", "-1FR,MO".to_string()),
+                ("BYSETPOS", "2".to_string()),
+            ]
+        );
+    }
+    #[test]
+    fn until_must_match_dtstart() {
+        let rule = |until: &str| {
+            let input = format!("FREQ=DAILY;UNTIL={until}\r\n");
+            parse_rrule.parse(B(&input)).unwrap()
+        };
+        let all_day = EventTime::Date(civil::date(2025, 3, 1));
+        let floating = EventTime::Floating(civil::datetime(2025, 3, 1, 9, 0, 0, 0));
+        let zoned = civil::datetime(2025, 3, 1, 9, 0, 0, 0).in_tz("America/New_York").unwrap();
+        let zoned = EventTime::Zoned(zoned);
+        for (dtstart, until, mismatch, fixed) in [
+            (&all_day, "20250310", None, "20250310"),
+            (&all_day, "20250310T090000", Some(UntilMismatch::ExpectedDate), "20250310"),
+            (&all_day, "20250310T020000Z", Some(UntilMismatch::ExpectedDate), "20250310"),
+            (&floating, "20250310T090000", None, "20250310T090000"),
+            (&floating, "20250310", Some(UntilMismatch::ExpectedFloating), "20250310T235959"),
+            (
+                &floating,
+                "20250310T090000Z",
+                Some(UntilMismatch::ExpectedFloating),
+                "20250310T090000",
+            ),
+            (&zoned, "20250310T130000Z", None, "20250310T130000Z"),
+            // New York is on EDT (UTC-4) from March 9
+            (&zoned, "20250310T090000", Some(UntilMismatch::ExpectedUtc), "20250310T130000Z"),
+            (&zoned, "20250310", Some(UntilMismatch::ExpectedUtc), "20250311T035959Z"),
+        ] {
+            let mut rule = rule(until);
+            assert_eq!(rule.check_until(dtstart).err(), mismatch, "{until}");
+            assert_eq!(rule.fix_until(dtstart), mismatch, "{until}");
+            assert_eq!(rule.to_string(), format!("FREQ=DAILY;UNTIL={fixed}"));
+            assert_eq!(rule.check_until(dtstart), Ok(()));
+        }
+    }
+
+    #[test]
+    fn zero_interval() {
+        let input = B("FREQ=DAILY;INTERVAL=0\r\n");
+        assert_eq!(error_info(parse_rrule.parse(input)), (20, vec![msg::Zero_INTERVAL.text]));
+        let mut warnings = Vec::new();
+        let rule = parse_rrule_lenient(&mut &input[..], &mut warnings).unwrap();
+        assert_eq!(rule.interval_or_default().get(), 1);
+        assert_eq!(rule.to_string(), "FREQ=DAILY;INTERVAL=1");
+        assert_eq!(warnings, vec![Warning::new(Observation::ZeroIntervalReplaced, 20..21)]);
+        let rule = parse_rrule.parse(B("FREQ=DAILY\r\n")).unwrap();
+        assert_eq!(rule.interval_or_default().get(), 1);
+    }
+
+    #[test]
+    fn zero_count() {
+        let input = B("FREQ=DAILY;COUNT=0\r\n");
+        let mut warnings = Vec::new();
+        let rule = parse_rrule_with(&mut &input[..], &mut warnings).unwrap();
+        assert!(rule.is_finite());
+        assert_eq!(warnings, vec![Warning::new(Observation::ZeroCount, 17..18)]);
+        let mut warnings = Vec::new();
+        parse_rrule_lenient(&mut &input[..], &mut warnings).unwrap();
+        assert_eq!(warnings, vec![]);
+
+        assert!(parse_rrule.parse(B("FREQ=DAILY;UNTIL=20250101\r\n")).unwrap().is_finite());
+        assert!(!parse_rrule.parse(B("FREQ=DAILY\r\n")).unwrap().is_finite());
+    }
+
+    #[test]
+    fn frequency_periods() {
+        let at = civil::datetime(2025, 3, 5, 14, 30, 15, 500);
+        let period = |freq: Frequency, wk_st| {
+            let period = freq.period_of(at, wk_st).unwrap();
+            (period.start.to_string(), period.end.to_string())
+        };
+        let expected = |start: &str, end: &str| (start.to_string(), end.to_string());
+        use Frequency::*;
+        assert_eq!(
+            period(Secondly, Weekday::Monday),
+            expected("2025-03-05T14:30:15", "2025-03-05T14:30:16")
+        );
+        assert_eq!(
+            period(Hourly, Weekday::Monday),
+            expected("2025-03-05T14:00:00", "2025-03-05T15:00:00")
+        );
+        // 2025-03-05 is a Wednesday
+        assert_eq!(
+            period(Weekly, Weekday::Monday),
+            expected("2025-03-03T00:00:00", "2025-03-10T00:00:00")
+        );
+        assert_eq!(
+            period(Weekly, Weekday::Thursday),
+            expected("2025-02-27T00:00:00", "2025-03-06T00:00:00")
+        );
+        assert_eq!(
+            period(Monthly, Weekday::Monday),
+            expected("2025-03-01T00:00:00", "2025-04-01T00:00:00")
+        );
+        assert_eq!(
+            period(Yearly, Weekday::Monday),
+            expected("2025-01-01T00:00:00", "2026-01-01T00:00:00")
+        );
+        assert_eq!(Daily.periods(3).map(|span| span.to_string()).as_deref(), Some("P3D"));
+        assert!(Yearly.periods(i64::MAX).is_none());
+        assert_eq!(
+            Yearly.period_of(civil::datetime(9999, 6, 1, 0, 0, 0, 0), Weekday::Monday),
+            None
+        );
+    }
+    #[test]
+    fn matching_dtstart() {
+        let rule = |text: &str| parse_rrule.parse(B(&format!("{text}\r\n"))).unwrap();
+        let day = |y, m, d| EventTime::Date(civil::date(y, m, d));
+        let at = |y, m, d, h| EventTime::Floating(civil::datetime(y, m, d, h, 0, 0, 0));
+        for (text, matching, other) in [
+            ("FREQ=MONTHLY;BYDAY=2TU", at(2025, 9, 9, 9), at(2025, 9, 16, 9)),
+            ("FREQ=YEARLY;BYDAY=20MO", day(2025, 5, 19), day(2025, 5, 26)),
+            ("FREQ=YEARLY;BYMONTH=5;BYDAY=-1MO", day(2025, 5, 26), day(2025, 5, 19)),
+            // 2024-12-30 is in week 1 of 2025, and 2024 has 52 weeks
+            ("FREQ=YEARLY;BYWEEKNO=1;BYDAY=MO", day(2024, 12, 30), day(2024, 12, 23)),
+            ("FREQ=YEARLY;BYWEEKNO=-1", day(2024, 12, 23), day(2024, 12, 30)),
+            // With weeks starting on Sunday, 2024-12-29 (not 12-30) starts week 1 of 2025
+            ("FREQ=YEARLY;BYWEEKNO=1;WKST=SU", day(2024, 12, 29), day(2024, 12, 28)),
+            ("FREQ=YEARLY;BYWEEKNO=1", day(2024, 12, 30), day(2024, 12, 29)),
+            ("FREQ=YEARLY;BYYEARDAY=-1,100", day(2024, 12, 31), day(2025, 12, 30)),
+            ("FREQ=MONTHLY;BYMONTHDAY=-1", day(2025, 2, 28), day(2024, 2, 28)),
+            ("FREQ=DAILY;BYHOUR=9,17", at(2025, 1, 1, 17), at(2025, 1, 1, 10)),
+            ("FREQ=DAILY;BYHOUR=9", day(2025, 1, 1), at(2025, 1, 1, 10)),
+            ("FREQ=DAILY;BYMONTH=3", day(2025, 3, 1), day(2025, 4, 1)),
+        ] {
+            let rule = rule(text);
+            assert!(rule.matches(&matching), "{text}: {matching:?}");
+            assert!(!rule.matches(&other), "{text}: {other:?}");
+        }
+
+        let mut warnings = Vec::new();
+        assert!(!rule("FREQ=WEEKLY;BYDAY=MO").check_dtstart(
+            &day(2025, 1, 1),
+            6..26,
+            &mut warnings
+        ));
+        assert_eq!(warnings, vec![Warning::new(Observation::DtstartNotInRule, 6..26)]);
+    }
 }
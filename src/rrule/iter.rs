@@ -0,0 +1,500 @@
+// Expanding an `RRule` into its occurrences ([RFC 5545 § 3.3.10](https://datatracker.ietf.org/doc/html/rfc5545#section-3.3.10)).
+// We walk the rule's periods (its years, months, weeks, days, hours, minutes or seconds,
+// `INTERVAL` apart) from `DTSTART`'s, collect each period's candidate date-times — the days the
+// `BYxxx` date parts allow, at the times the time parts allow — and apply `BYSETPOS` to them.
+use super::{ByDay, Frequency, RRule, When};
+use crate::Weekday;
+use crate::expansion::{ExpansionLimits, ExpansionProgress, Hook, LimitReached};
+use jiff::civil::{Date, DateTime, Time};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp, ToSpan, Zoned};
+use std::collections::VecDeque;
+
+impl RRule {
+    /// The occurrences of the rule for a component starting at `dtstart`, in time order.
+    /// `DTSTART` is always the first occurrence, and `COUNT` includes it. Local times that
+    /// don't exist or happen twice resolve as jiff's `compatible` disambiguation does, and
+    /// an occurrence that resolves to the same instant as an earlier one is skipped.
+    ///
+    /// The parts `DTSTART` implies are filled in as RFC 5545 requires: a `YEARLY` rule
+    /// without `BYxxx` date parts repeats on `DTSTART`'s month and day, a `MONTHLY` rule on
+    /// its day, a `WEEKLY` rule on its weekday, and the time parts coarser than `FREQ`
+    /// default to `DTSTART`'s. Iteration stops at the end of jiff's range, so a rule that
+    /// never matches (`FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30`) ends after `DTSTART`.
+    #[must_use]
+    pub fn iter(&self, dtstart: &Zoned) -> Occurrences {
+        Occurrences::new(self, dtstart)
+    }
+}
+
+/// The iterator `RRule::iter` returns
+#[derive(Clone, Debug)]
+pub struct Occurrences {
+    // The rule, with the parts `DTSTART` implies filled in
+    rule: RRule,
+    zone: TimeZone,
+    start: DateTime,
+    // `DTSTART`, until we've returned it
+    first: Option<Zoned>,
+    // The index of the next period to expand, counting `DTSTART`'s as 0
+    period: i64,
+    // The rest of the last period's candidates, in order
+    pending: VecDeque<DateTime>,
+    emitted: u32,
+    last: Option<Timestamp>,
+    done: bool,
+    limits: Option<ExpansionLimits>,
+    // How many candidate dates (or, below `DAILY`, periods) we've tested
+    candidates: u64,
+    limit_reached: Option<LimitReached>,
+    hook: Option<Hook>,
+    cancelled: bool,
+}
+
+impl Occurrences {
+    fn new(rule: &RRule, dtstart: &Zoned) -> Self {
+        let start = dtstart.datetime();
+        let mut rule = rule.clone();
+        if rule.by_week_no.is_empty()
+            && rule.by_year_day.is_empty()
+            && rule.by_month_day.is_empty()
+            && rule.by_day.is_empty()
+        {
+            match rule.freq {
+                Frequency::Yearly => {
+                    if rule.by_month.is_empty() {
+                        rule.by_month = vec![start.month().cast_unsigned()];
+                    }
+                    rule.by_month_day = vec![start.day()];
+                }
+                Frequency::Monthly => rule.by_month_day = vec![start.day()],
+                Frequency::Weekly => {
+                    rule.by_day = vec![ByDay { ordinal: None, weekday: start.weekday() }];
+                }
+                _ => {}
+            }
+        }
+        let defaults = [
+            (Frequency::Hourly, &mut rule.by_hour, start.hour()),
+            (Frequency::Minutely, &mut rule.by_minute, start.minute()),
+            (Frequency::Secondly, &mut rule.by_second, start.second()),
+        ];
+        for (unit, list, value) in defaults {
+            if rule.freq > unit && list.is_empty() {
+                list.push(value.cast_unsigned());
+            }
+        }
+        for list in [&mut rule.by_hour, &mut rule.by_minute, &mut rule.by_second] {
+            list.sort_unstable();
+            list.dedup();
+        }
+        Occurrences {
+            first: (rule.count != Some(0)).then(|| dtstart.clone()),
+            rule,
+            zone: dtstart.time_zone().clone(),
+            start,
+            period: 0,
+            pending: VecDeque::new(),
+            emitted: 0,
+            last: None,
+            done: false,
+            limits: None,
+            candidates: 0,
+            limit_reached: None,
+            hook: None,
+            cancelled: false,
+        }
+    }
+
+    /// Stop at the first of `limits` reached: before an occurrence past `max_instances` or
+    /// `max_span_years`, or once testing `max_candidates` candidates hasn't found the next
+    /// one. `limit_reached` then says which it was.
+    #[must_use]
+    pub fn with_limits(self, limits: ExpansionLimits) -> Self {
+        Occurrences { limits: Some(limits), ..self }
+    }
+    /// The limit that stopped iteration, if one did (see `with_limits`)
+    #[must_use]
+    pub fn limit_reached(&self) -> Option<LimitReached> {
+        self.limit_reached
+    }
+    /// Call `should_continue` before testing each candidate, and stop as soon as it returns
+    /// `false` — say, once a GUI's user gives up on a rule that's taking too long. It's the
+    /// same hook `BufReadContent::content_lines_with` takes, with the expansion's progress.
+    /// `is_cancelled` then says so.
+    #[must_use]
+    pub fn should_continue(
+        self,
+        should_continue: impl Fn(&ExpansionProgress) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.with_hook(Hook::new(should_continue))
+    }
+    pub(crate) fn with_hook(self, hook: Hook) -> Self {
+        Occurrences { hook: Some(hook), ..self }
+    }
+    /// Whether iteration stopped because it was cancelled (see `should_continue`)
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn add_candidates(&mut self, candidates: u64) {
+        self.candidates += candidates;
+        if let Some(hook) = &self.hook {
+            hook.add_candidates(candidates);
+        }
+    }
+    fn add_occurrence(&mut self, at: Timestamp) {
+        self.emitted += 1;
+        self.last = Some(at);
+        if let Some(hook) = &self.hook {
+            hook.add_occurrence();
+        }
+    }
+
+    // Whether a period starting at `start` is past the span the limits allow, noting it if so
+    fn past_span(&mut self, start: DateTime) -> bool {
+        let past = (self.limits).is_some_and(|limits| start >= limits.span_end(self.start));
+        if past {
+            self.limit_reached = Some(LimitReached::Span);
+        }
+        past
+    }
+
+    // Check the limits before returning the occurrence at `next` (or, with `None`, before
+    // expanding another period), stopping if one is reached
+    fn within_limits(&mut self, next: Option<DateTime>) -> bool {
+        let Some(limits) = &self.limits else {
+            return true;
+        };
+        let instances = usize::try_from(self.emitted).unwrap_or(usize::MAX);
+        let checked = match next {
+            Some(next) => limits.check(self.start, instances, self.candidates, next),
+            None if self.candidates >= limits.max_candidates => Err(LimitReached::Candidates),
+            None => Ok(()),
+        };
+        if let Err(reached) = checked {
+            (self.limit_reached, self.done) = (Some(reached), true);
+        }
+        checked.is_ok()
+    }
+
+    // Whether `zoned` is after `UNTIL`
+    fn is_after_until(&self, zoned: &Zoned) -> bool {
+        match &self.rule.until {
+            None => false,
+            Some(When::Date(date)) => zoned.date() > *date,
+            Some(When::DateTime(dt)) => zoned.datetime() > *dt,
+            Some(When::Timestamp(ts)) => zoned.timestamp() > *ts,
+        }
+    }
+
+    // Queue the candidates of the next period; false once periods run past jiff's range
+    fn expand(&mut self) -> bool {
+        let interval = i64::from(self.rule.interval_or_default().get());
+        let k = self.period;
+        self.period += 1;
+        let start = self.start.date();
+        let dates: Vec<Date> = match self.rule.freq {
+            Frequency::Yearly => {
+                let Some(year) = (i64::from(start.year()) + k * interval)
+                    .try_into()
+                    .ok()
+                    .filter(|year| (-9999..=9999).contains(year))
+                else {
+                    return false;
+                };
+                let months: Vec<i8> = if self.rule.by_month.is_empty() {
+                    (1..=12).collect()
+                } else {
+                    self.rule.by_month.iter().map(|m| m.cast_signed()).collect()
+                };
+                months
+                    .into_iter()
+                    .filter_map(|month| Date::new(year, month, 1).ok())
+                    .flat_map(|first| {
+                        first.series(1.day()).take_while(move |d| d.month() == first.month())
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months =
+                    i64::from(start.year()) * 12 + i64::from(start.month()) - 1 + k * interval;
+                let (Ok(year), Ok(month)) =
+                    (i16::try_from(months.div_euclid(12)), i8::try_from(months.rem_euclid(12) + 1))
+                else {
+                    return false;
+                };
+                let Ok(first) = Date::new(year, month, 1) else {
+                    return false;
+                };
+                first.series(1.day()).take_while(|d| d.month() == month).collect()
+            }
+            Frequency::Weekly => {
+                let wk_st = self.rule.wk_st.unwrap_or(Weekday::Monday);
+                let Some(first) = (Frequency::Weekly.period_of(self.start, wk_st))
+                    .and_then(|week| week.start.date().checked_add((7 * k * interval).days()).ok())
+                else {
+                    return false;
+                };
+                first.series(1.day()).take(7).collect()
+            }
+            Frequency::Daily => match start.checked_add((k * interval).days()) {
+                Ok(date) => vec![date],
+                Err(_) => return false,
+            },
+            Frequency::Hourly | Frequency::Minutely | Frequency::Secondly => {
+                return self.expand_within_day(k, interval);
+            }
+        };
+        let first = dates.first().map(|date| date.to_datetime(Time::midnight()));
+        if first.is_some_and(|first| self.past_span(first)) {
+            return false;
+        }
+        self.add_candidates(dates.len() as u64);
+        let times = self.times(&self.rule.by_hour, &self.rule.by_minute);
+        let candidates = (dates.into_iter())
+            .filter(|date| self.rule.matches_date(*date))
+            .flat_map(|date| times.iter().map(move |time| date.to_datetime(*time)))
+            .collect();
+        self.queue(candidates);
+        true
+    }
+
+    // Queue the candidates of period `k` of an `HOURLY`, `MINUTELY` or `SECONDLY` rule. When
+    // the period's day, hour or minute is ruled out, we skip the periods left in it.
+    fn expand_within_day(&mut self, k: i64, interval: i64) -> bool {
+        let unit = match self.rule.freq {
+            Frequency::Hourly => 3600,
+            Frequency::Minutely => 60,
+            _ => 1,
+        };
+        let step = unit * interval;
+        let Some(at) = k
+            .checked_mul(step)
+            .and_then(|secs| self.start.checked_add(SignedDuration::from_secs(secs)).ok())
+        else {
+            return false;
+        };
+        if self.past_span(at) {
+            return false;
+        }
+        self.add_candidates(1);
+        let (hour, minute) = (at.hour().cast_unsigned(), at.minute().cast_unsigned());
+        let into_day = i64::from(at.hour()) * 3600 + i64::from(at.minute()) * 60;
+        let into_day = into_day + i64::from(at.second());
+        // How far it is to the next day, hour or minute the rule allows
+        let skip = if !self.rule.matches_date(at.date()) {
+            86400 - into_day
+        } else if !super::has(&self.rule.by_hour, hour) {
+            3600 - into_day % 3600
+        } else if self.rule.freq < Frequency::Hourly && !super::has(&self.rule.by_minute, minute) {
+            60 - into_day % 60
+        } else {
+            let minutes = if self.rule.freq < Frequency::Hourly {
+                vec![minute]
+            } else {
+                self.rule.by_minute.clone()
+            };
+            let mut times = self.times(&[hour], &minutes);
+            if self.rule.freq == Frequency::Secondly {
+                times.retain(|time| time.second() == at.second());
+            }
+            let candidates = times.into_iter().map(|time| at.date().to_datetime(time)).collect();
+            self.queue(candidates);
+            return true;
+        };
+        // The first period at or after the skip
+        let Some(secs) = (k * step).checked_add(skip) else {
+            return false;
+        };
+        self.period = self.period.max((secs + step - 1) / step);
+        true
+    }
+
+    // The times of day made of `hours`, `minutes` and the rule's seconds, in order
+    fn times(&self, hours: &[u8], minutes: &[u8]) -> Vec<Time> {
+        let mut times = Vec::new();
+        for hour in hours {
+            for minute in minutes {
+                for second in &self.rule.by_second {
+                    // `BYSECOND=60` names a leap second, which jiff's civil times don't have
+                    if let Ok(time) =
+                        Time::new(hour.cast_signed(), minute.cast_signed(), second.cast_signed(), 0)
+                    {
+                        times.push(time);
+                    }
+                }
+            }
+        }
+        times
+    }
+
+    // Queue a period's candidates, which are in order, picking out the `BYSETPOS` ones
+    fn queue(&mut self, candidates: Vec<DateTime>) {
+        if self.rule.by_set_pos.is_empty() {
+            self.pending.extend(candidates);
+            return;
+        }
+        let len = candidates.len();
+        let mut chosen: Vec<DateTime> = (self.rule.by_set_pos.iter())
+            .filter_map(|pos| {
+                let n = usize::from(pos.unsigned_abs());
+                let index = if *pos > 0 { n - 1 } else { len.checked_sub(n)? };
+                candidates.get(index).copied()
+            })
+            .collect();
+        chosen.sort_unstable();
+        chosen.dedup();
+        self.pending.extend(chosen);
+    }
+}
+
+impl Iterator for Occurrences {
+    type Item = Zoned;
+
+    fn next(&mut self) -> Option<Zoned> {
+        loop {
+            if self.done || self.rule.count.is_some_and(|count| self.emitted >= count) {
+                self.done = true;
+                return None;
+            }
+            if self.hook.as_ref().is_some_and(|hook| !hook.should_continue()) {
+                (self.cancelled, self.done) = (true, true);
+                return None;
+            }
+            if let Some(first) = self.first.take() {
+                self.add_occurrence(first.timestamp());
+                return Some(first);
+            }
+            let Some(dt) = self.pending.pop_front() else {
+                self.done = !(self.within_limits(None) && self.expand());
+                continue;
+            };
+            if dt <= self.start {
+                continue;
+            }
+            let Ok(zoned) = dt.to_zoned(self.zone.clone()) else {
+                self.done = true;
+                continue;
+            };
+            if self.is_after_until(&zoned) {
+                self.done = true;
+                continue;
+            }
+            if self.last.is_some_and(|last| zoned.timestamp() <= last) {
+                continue;
+            }
+            if !self.within_limits(Some(dt)) {
+                return None;
+            }
+            self.add_occurrence(zoned.timestamp());
+            return Some(zoned);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rrule::parse_rrule;
+    use jiff::civil::datetime;
+    use pretty_assertions::assert_eq;
+    use std::sync::{Arc, Mutex};
+
+    fn expand(rule: &str, dtstart: DateTime, zone: &str, n: usize) -> Vec<DateTime> {
+        let rule = parse_rrule(&mut rule.as_bytes()).unwrap();
+        let dtstart = dtstart.in_tz(zone).unwrap();
+        rule.iter(&dtstart).take(n).map(|zoned| zoned.datetime()).collect()
+    }
+
+    #[test]
+    fn set_positions() {
+        // The last weekday of each month
+        let last = expand(
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+            datetime(2025, 1, 31, 17, 0, 0, 0),
+            "UTC",
+            4,
+        );
+        let days: Vec<_> = last.iter().map(|dt| (dt.month(), dt.day())).collect();
+        assert_eq!(days, vec![(1, 31), (2, 28), (3, 31), (4, 30)]);
+    }
+    #[test]
+    fn within_a_day() {
+        let rule = "FREQ=MINUTELY;INTERVAL=20;BYHOUR=9,10";
+        let times = expand(rule, datetime(2025, 3, 3, 9, 0, 0, 0), "UTC", 7);
+        let times: Vec<_> = times.iter().map(|dt| (dt.day(), dt.hour(), dt.minute())).collect();
+        assert_eq!(
+            times,
+            vec![
+                (3, 9, 0),
+                (3, 9, 20),
+                (3, 9, 40),
+                (3, 10, 0),
+                (3, 10, 20),
+                (3, 10, 40),
+                (4, 9, 0)
+            ]
+        );
+    }
+    #[test]
+    fn nonexistent_times_move_forward() {
+        // 02:30 doesn't exist on 9 March 2025 in New York
+        let rule = "FREQ=DAILY;COUNT=3";
+        let times = expand(rule, datetime(2025, 3, 8, 2, 30, 0, 0), "America/New_York", 5);
+        let times: Vec<_> = times.iter().map(|dt| (dt.day(), dt.hour())).collect();
+        assert_eq!(times, vec![(8, 2), (9, 3), (10, 2)]);
+    }
+    #[test]
+    fn rules_that_run_out() {
+        let start = datetime(2024, 2, 29, 0, 0, 0, 0);
+        assert_eq!(expand("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30", start, "UTC", 5), vec![start]);
+        assert_eq!(expand("FREQ=DAILY;COUNT=0", start, "UTC", 5), vec![]);
+        let leap_days = expand("FREQ=YEARLY;UNTIL=20330101T000000Z", start, "UTC", 5);
+        let years: Vec<_> = leap_days.into_iter().map(DateTime::year).collect();
+        assert_eq!(years, vec![2024, 2028, 2032]);
+    }
+    #[test]
+    fn limits() {
+        let limited = |rule: &str, limits| {
+            let rule = parse_rrule(&mut rule.as_bytes()).unwrap();
+            let start = datetime(2024, 2, 29, 9, 0, 0, 0).in_tz("UTC").unwrap();
+            let mut occurrences = rule.iter(&start).with_limits(limits);
+            let years: Vec<_> = occurrences.by_ref().map(|zoned| zoned.year()).collect();
+            assert_eq!(occurrences.next(), None);
+            (years.len(), years.last().copied(), occurrences.limit_reached())
+        };
+        let limits = ExpansionLimits { max_instances: 3, ..ExpansionLimits::interactive() };
+        assert_eq!(limited("FREQ=DAILY", limits), (3, Some(2024), Some(LimitReached::Instances)));
+        assert_eq!(limited("FREQ=DAILY;COUNT=3", limits), (3, Some(2024), None));
+        // Leap days, up to ten years out
+        let span = limited("FREQ=YEARLY", ExpansionLimits::interactive());
+        assert_eq!(span, (3, Some(2032), Some(LimitReached::Span)));
+        let never = limited("FREQ=MONTHLY;BYMONTHDAY=31;BYMONTH=2", ExpansionLimits::batch());
+        assert_eq!(never, (1, Some(2024), Some(LimitReached::Span)));
+        let few = ExpansionLimits { max_candidates: 1000, ..ExpansionLimits::interactive() };
+        let never = limited("FREQ=DAILY;BYMONTHDAY=31;BYMONTH=2", few);
+        assert_eq!(never, (1, Some(2024), Some(LimitReached::Candidates)));
+    }
+    #[test]
+    fn cancellation() {
+        let rule = parse_rrule(&mut &b"FREQ=SECONDLY;BYMONTH=2;BYMONTHDAY=30"[..]).unwrap();
+        let start = datetime(2025, 3, 3, 9, 0, 0, 0).in_tz("UTC").unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let progress = Arc::clone(&seen);
+        let mut occurrences = rule.iter(&start).should_continue(move |p: &ExpansionProgress| {
+            progress.lock().unwrap().push(*p);
+            p.candidates < 1000
+        });
+        assert_eq!(occurrences.next(), Some(start));
+        assert!(!occurrences.is_cancelled());
+        // The rule never matches, so we'd search to the end of jiff's range
+        assert_eq!(occurrences.next(), None);
+        assert!(occurrences.is_cancelled() && occurrences.limit_reached().is_none());
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0], ExpansionProgress { candidates: 0, occurrences: 0 });
+        assert_eq!(seen[1], ExpansionProgress { candidates: 0, occurrences: 1 });
+        assert_eq!(seen.last(), Some(&ExpansionProgress { candidates: 1000, occurrences: 1 }));
+    }
+}
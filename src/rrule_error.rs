@@ -7,10 +7,72 @@ use winnow::stream::Stream;
 
 pub(crate) type ModalResult<T> = winnow::ModalResult<T, RRuleError>;
 
+/// The kind of problem an error message reports, which gives the error its code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RRuleErrorKind {
+    ExpectedEqualSign,
+    UnknownRulePart,
+    FreqRequired,
+    RepeatedRulePart,
+    BadFrequency,
+    BadNumber,
+    BadDay,
+    BadByDayOffset,
+    BadIndexList,
+    BadOffsetList,
+    CommaForSemicolon,
+    BadUntil,
+    NotATime,
+    ZeroInterval,
+    NotAnRRule,
+    UnexpectedParameter,
+    Other,
+}
+impl RRuleErrorKind {
+    /// The kind's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        use crate::catalog as code;
+        match self {
+            RRuleErrorKind::ExpectedEqualSign => code::RRULE_EXPECTED_EQUAL_SIGN,
+            RRuleErrorKind::UnknownRulePart => code::RRULE_UNKNOWN_RULE_PART,
+            RRuleErrorKind::FreqRequired => code::RRULE_FREQ_REQUIRED,
+            RRuleErrorKind::RepeatedRulePart => code::RRULE_REPEATED_RULE_PART,
+            RRuleErrorKind::BadFrequency => code::RRULE_BAD_FREQUENCY,
+            RRuleErrorKind::BadNumber => code::RRULE_BAD_NUMBER,
+            RRuleErrorKind::BadDay => code::RRULE_BAD_DAY,
+            RRuleErrorKind::BadByDayOffset => code::RRULE_BAD_BYDAY_OFFSET,
+            RRuleErrorKind::BadIndexList => code::RRULE_BAD_INDEX_LIST,
+            RRuleErrorKind::BadOffsetList => code::RRULE_BAD_OFFSET_LIST,
+            RRuleErrorKind::CommaForSemicolon => code::RRULE_COMMA_FOR_SEMICOLON,
+            RRuleErrorKind::BadUntil => code::RRULE_BAD_UNTIL,
+            RRuleErrorKind::NotATime => code::RRULE_NOT_A_TIME,
+            RRuleErrorKind::ZeroInterval => code::RRULE_ZERO_INTERVAL,
+            RRuleErrorKind::NotAnRRule => code::RRULE_NOT_AN_RRULE,
+            RRuleErrorKind::UnexpectedParameter => code::RRULE_UNEXPECTED_PARAMETER,
+            RRuleErrorKind::Other => code::RRULE_OTHER,
+        }
+    }
+}
+
+/// An error message, with the kind of problem it reports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub kind: RRuleErrorKind,
+    pub text: &'static str,
+}
+impl Message {
+    #[must_use]
+    pub const fn new(kind: RRuleErrorKind, text: &'static str) -> Self {
+        Message { kind, text }
+    }
+}
+
 /// Our `Error` type is modeled on `winnow::error::ContextError`
 #[derive(Debug)]
 pub struct RRuleError {
-    message: Vec<&'static str>,
+    message: Vec<Message>,
     cause: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
 }
 impl RRuleError {
@@ -18,7 +80,7 @@ impl RRuleError {
     #[must_use]
     #[inline]
     pub fn new(
-        msg: &'static str,
+        msg: Message,
         cause: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
     ) -> Self {
         Self { message: vec![msg], cause }
@@ -27,7 +89,7 @@ impl RRuleError {
     #[must_use]
     #[inline]
     pub fn cut(
-        msg: &'static str,
+        msg: Message,
         cause: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
     ) -> ErrMode<Self> {
         ErrMode::Cut(Self { message: vec![msg], cause })
@@ -36,7 +98,18 @@ impl RRuleError {
     #[must_use]
     #[inline]
     pub fn context(&self) -> Vec<&'static str> {
-        self.message.clone()
+        self.message.iter().map(|message| message.text).collect()
+    }
+    /// The kind of problem the innermost message reports
+    #[must_use]
+    pub fn kind(&self) -> RRuleErrorKind {
+        self.message.first().map_or(RRuleErrorKind::Other, |message| message.kind)
+    }
+
+    /// The error's stable code (see `catalog`), determined by its kind
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        self.kind().code()
     }
 
     /// The underlying [`std::error::Error`] (if any)  
@@ -64,13 +137,13 @@ impl Default for RRuleError {
     }
 }
 
-impl AddContext<&[u8], &'static str> for RRuleError {
+impl AddContext<&[u8], Message> for RRuleError {
     #[inline]
     fn add_context(
         mut self,
         _input: &&[u8],
         _token_start: &<&[u8] as Stream>::Checkpoint,
-        context: &'static str,
+        context: Message,
     ) -> Self {
         self.message.push(context);
         self
@@ -0,0 +1,101 @@
+//! # Sanitizing input
+//! Lenient-mode fixes applied to a content line's bytes before preparsing, for feeds with
+//! well-known defects. Each fix reports a warning, so nothing is changed silently.
+//! `RcalConfig::parse_calendar_with` applies them when its quirks are `Quirks::Interop`.
+use crate::warning::{Observation, Warning, Warnings};
+use std::borrow::Cow;
+
+/// Replace Windows-1252 characters in the range `0x80`–`0x9F` (smart quotes, dashes,
+/// daggers, `€`, `™`, and so on) that appear in otherwise UTF-8 text with their Unicode
+/// equivalents, warning about each. Valid UTF-8 is never changed, and neither are invalid
+/// bytes outside that range or the five bytes Windows-1252 leaves undefined, so preparsing
+/// still reports those.
+pub fn sanitize_cp1252<'a>(line: &'a [u8], warnings: &mut dyn Warnings) -> Cow<'a, [u8]> {
+    if line.is_ascii() || str::from_utf8(line).is_ok() {
+        return Cow::Borrowed(line);
+    }
+    let mut sanitized = Vec::with_capacity(line.len() + 8);
+    let mut offset = 0;
+    for chunk in line.utf8_chunks() {
+        sanitized.extend_from_slice(chunk.valid().as_bytes());
+        offset += chunk.valid().len();
+        for b in chunk.invalid() {
+            match cp1252(*b) {
+                Some(c) => {
+                    let mut buf = [0; 4];
+                    sanitized.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                    warnings.warn(Warning::new(Observation::Cp1252Replaced, offset..offset + 1));
+                }
+                None => sanitized.push(*b),
+            }
+            offset += 1;
+        }
+    }
+    Cow::Owned(sanitized)
+}
+
+// The Unicode character for a Windows-1252 byte in `0x80..=0x9F`
+fn cp1252(b: u8) -> Option<char> {
+    const CHARS: [Option<char>; 32] = [
+        Some('€'),
+        None,
+        Some('‚'),
+        Some('ƒ'),
+        Some('„'),
+        Some('…'),
+        Some('†'),
+        Some('‡'),
+        Some('ˆ'),
+        Some('‰'),
+        Some('Š'),
+        Some('‹'),
+        Some('Œ'),
+        None,
+        Some('Ž'),
+        None,
+        None,
+        Some('\u{2018}'),
+        Some('\u{2019}'),
+        Some('\u{201C}'),
+        Some('\u{201D}'),
+        Some('•'),
+        Some('–'),
+        Some('—'),
+        Some('˜'),
+        Some('™'),
+        Some('š'),
+        Some('›'),
+        Some('œ'),
+        None,
+        Some('ž'),
+        Some('Ÿ'),
+    ];
+    CHARS.get(usize::from(b.wrapping_sub(0x80))).copied().flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn smart_quotes_are_replaced() {
+        let mut warnings = Vec::new();
+        let line = b"SUMMARY:Caf\xc3\xa9 \x93special\x94 \x96 \x805";
+        let sanitized = sanitize_cp1252(line, &mut warnings);
+        assert_eq!(str::from_utf8(&sanitized), Ok("SUMMARY:Café “special” – €5"));
+        let spans: Vec<_> = warnings.into_iter().map(|w| w.span).collect();
+        assert_eq!(spans, vec![14..15, 22..23, 24..25, 26..27]);
+    }
+    #[test]
+    fn other_bytes_are_left_alone() {
+        let mut warnings = Vec::new();
+        assert!(matches!(
+            sanitize_cp1252("SUMMARY:“ok”".as_bytes(), &mut warnings),
+            Cow::Borrowed(_)
+        ));
+        let line = b"SUMMARY:\x81\xff";
+        assert_eq!(sanitize_cp1252(line, &mut warnings), &line[..]);
+        assert_eq!(warnings, vec![]);
+    }
+}
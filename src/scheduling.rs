@@ -0,0 +1,378 @@
+//! # Scheduling
+//! The iTIP protocol ([RFC 5546](https://datatracker.ietf.org/doc/html/rfc5546)) exchanges
+//! events in messages whose `METHOD` says what they do: an organizer `PUBLISH`es an event or
+//! `REQUEST`s attendance, attendees `REPLY` with their `PARTSTAT` or propose a `COUNTER`, and
+//! so on. `ScheduledEvent` is the organizer's copy of an event, and `apply` checks each
+//! message against the event's scheduling state before applying it:
+//!
+//! | State       | Allowed methods                                             |
+//! |-------------|-------------------------------------------------------------|
+//! | `Published` | `PUBLISH`, `REQUEST`, `ADD`, `CANCEL`                       |
+//! | `Requested` | `REQUEST`, `REPLY`, `REFRESH`, `COUNTER`, `ADD`, `CANCEL`   |
+//! | `Countered` | the same, and `DECLINECOUNTER`                              |
+//! | `Cancelled` | `REQUEST` (rescheduling the event), `CANCEL`                |
+//!
+//! A `COUNTER` leaves the event `Countered` until the organizer declines it or sends a new
+//! `REQUEST`. A `CANCEL` sets `STATUS:CANCELLED` on the event, or on just the instance its
+//! `RECURRENCE-ID` names; a `REQUEST` can't set that status itself.
+use crate::catalog;
+use crate::event::{Event, EventStatus, EventTime};
+use crate::parameter::PartStat;
+use crate::property::same_address;
+use std::fmt;
+use thiserror::Error;
+
+/// The `METHOD` of an iTIP message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Method {
+    Publish,
+    Request,
+    Reply,
+    Add,
+    Cancel,
+    Refresh,
+    Counter,
+    DeclineCounter,
+    Other(String),
+}
+impl From<&str> for Method {
+    /// The method named by a `METHOD` property's value (ignoring case)
+    fn from(name: &str) -> Self {
+        match name.to_ascii_uppercase().as_str() {
+            "PUBLISH" => Method::Publish,
+            "REQUEST" => Method::Request,
+            "REPLY" => Method::Reply,
+            "ADD" => Method::Add,
+            "CANCEL" => Method::Cancel,
+            "REFRESH" => Method::Refresh,
+            "COUNTER" => Method::Counter,
+            "DECLINECOUNTER" => Method::DeclineCounter,
+            _ => Method::Other(name.to_string()),
+        }
+    }
+}
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Method::Publish => "PUBLISH",
+            Method::Request => "REQUEST",
+            Method::Reply => "REPLY",
+            Method::Add => "ADD",
+            Method::Cancel => "CANCEL",
+            Method::Refresh => "REFRESH",
+            Method::Counter => "COUNTER",
+            Method::DeclineCounter => "DECLINECOUNTER",
+            Method::Other(name) => name,
+        })
+    }
+}
+
+/// Where an event is in the iTIP exchange (see the module documentation)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchedulingState {
+    Published,
+    Requested,
+    Countered,
+    Cancelled,
+}
+impl fmt::Display for SchedulingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SchedulingState::Published => "published",
+            SchedulingState::Requested => "requested",
+            SchedulingState::Countered => "countered",
+            SchedulingState::Cancelled => "cancelled",
+        })
+    }
+}
+
+/// Why `ScheduledEvent::apply` rejected a message
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SchedulingError {
+    #[error("METHOD:{method} isn't allowed when the event is {state}")]
+    MethodNotAllowed { method: Method, state: SchedulingState },
+    #[error("a REPLY can't change PARTSTAT from {from} to {to}")]
+    PartStat { from: PartStat, to: PartStat },
+    #[error("{0} replied, but isn't an attendee")]
+    UnknownAttendee(String),
+    #[error("a REQUEST can't set STATUS:CANCELLED; send a CANCEL instead")]
+    CancelledRequest,
+    #[error("an ADD needs a RECURRENCE-ID")]
+    NoRecurrenceId,
+}
+impl SchedulingError {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            SchedulingError::MethodNotAllowed { .. } => catalog::METHOD_NOT_ALLOWED,
+            SchedulingError::PartStat { .. } => catalog::PARTSTAT_TRANSITION,
+            SchedulingError::UnknownAttendee(_) => catalog::UNKNOWN_ATTENDEE,
+            SchedulingError::CancelledRequest => catalog::CANCELLED_REQUEST,
+            SchedulingError::NoRecurrenceId => catalog::NO_RECURRENCE_ID,
+        }
+    }
+}
+
+/// Check that a `REPLY` may change an attendee's `PARTSTAT` from `from` to `to`. A reply
+/// must respond, so it can't set `NEEDS-ACTION`; `COMPLETED` and `IN-PROCESS` are for to-dos,
+/// not events; and an attendee who has delegated their attendance can only repeat that,
+/// since the response is now the delegate's to give.
+///
+/// # Errors
+/// Returns `SchedulingError::PartStat` if the change isn't allowed.
+pub fn check_partstat(from: &PartStat, to: &PartStat) -> Result<(), SchedulingError> {
+    let allowed = match to {
+        PartStat::NeedsAction(_) | PartStat::Completed | PartStat::InProcess => false,
+        PartStat::Delegated => true,
+        PartStat::Accepted | PartStat::Declined | PartStat::Tentative => {
+            *from != PartStat::Delegated
+        }
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(SchedulingError::PartStat { from: from.clone(), to: to.clone() })
+    }
+}
+
+/// The organizer's copy of an event, and where it is in the iTIP exchange
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledEvent {
+    event: Event,
+    state: SchedulingState,
+}
+
+impl ScheduledEvent {
+    /// An event the organizer has sent with `method`, which must be `PUBLISH` or `REQUEST`
+    ///
+    /// # Errors
+    /// Returns an error for any other method, or for a `REQUEST` with `STATUS:CANCELLED`.
+    pub fn new(method: &Method, event: Event) -> Result<Self, SchedulingError> {
+        let state = match method {
+            Method::Publish => SchedulingState::Published,
+            Method::Request if event.status() == Some(EventStatus::Cancelled) => {
+                return Err(SchedulingError::CancelledRequest);
+            }
+            Method::Request => SchedulingState::Requested,
+            _ => {
+                let state = SchedulingState::Published;
+                return Err(SchedulingError::MethodNotAllowed { method: method.clone(), state });
+            }
+        };
+        Ok(ScheduledEvent { event, state })
+    }
+    #[must_use]
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+    #[must_use]
+    pub fn state(&self) -> SchedulingState {
+        self.state
+    }
+
+    /// Apply an iTIP message: its `METHOD`, and the event it carries. A message with a
+    /// `RECURRENCE-ID` is about that instance, and a `PUBLISH`, `REQUEST` or `ADD` with one
+    /// overrides it. Nothing changes if the message is rejected.
+    ///
+    /// # Errors
+    /// Returns an error if the method isn't allowed in the current state (see the module
+    /// documentation), or if the message itself breaks the protocol.
+    pub fn apply(&mut self, method: &Method, message: &Event) -> Result<(), SchedulingError> {
+        use SchedulingState::{Cancelled, Countered, Published, Requested};
+        let instance = message.recurrence_id();
+        let next = match (method, self.state) {
+            (Method::Publish, Published) | (Method::Cancel, _) if instance.is_some() => self.state,
+            (Method::Publish, Published) => Published,
+            (Method::Request, _) | (Method::DeclineCounter, Countered) => Requested,
+            (Method::Cancel, _) => Cancelled,
+            (Method::Add, Published | Requested | Countered)
+            | (Method::Reply | Method::Refresh, Requested | Countered) => self.state,
+            (Method::Counter, Requested | Countered) => Countered,
+            _ => {
+                let state = self.state;
+                return Err(SchedulingError::MethodNotAllowed { method: method.clone(), state });
+            }
+        };
+        match method {
+            Method::Request if message.status() == Some(EventStatus::Cancelled) => {
+                return Err(SchedulingError::CancelledRequest);
+            }
+            Method::Add if instance.is_none() => return Err(SchedulingError::NoRecurrenceId),
+            Method::Publish | Method::Request | Method::Add => match instance {
+                Some(recurrence_id) => {
+                    self.event.set_override(recurrence_id.clone(), message.clone());
+                }
+                None => self.event = message.clone(),
+            },
+            Method::Reply => self.reply(message)?,
+            Method::Cancel => match instance {
+                Some(recurrence_id) => {
+                    let mut cancelled = self.override_of(recurrence_id).unwrap_or(message).clone();
+                    cancelled.set_status(EventStatus::Cancelled);
+                    self.event.set_override(recurrence_id.clone(), cancelled);
+                }
+                None => self.event.set_status(EventStatus::Cancelled),
+            },
+            _ => {}
+        }
+        self.state = next;
+        Ok(())
+    }
+
+    // Record the `PARTSTAT`s in a `REPLY`, on the instance it names if we have an override
+    // for it, and otherwise on the event
+    fn reply(&mut self, message: &Event) -> Result<(), SchedulingError> {
+        let instance = (message.recurrence_id())
+            .and_then(|id| self.override_of(id).map(|o| (id.clone(), o.clone())));
+        let target = instance.as_ref().map_or(&self.event, |(_, o)| o);
+        // An override without attendees of its own has the event's
+        let mut attendees = if target.attendees().is_empty() {
+            self.event.attendees().to_vec()
+        } else {
+            target.attendees().to_vec()
+        };
+        for reply in message.attendees() {
+            let Some(attendee) =
+                attendees.iter_mut().find(|a| same_address(&a.address, &reply.address))
+            else {
+                return Err(SchedulingError::UnknownAttendee(reply.address.clone()));
+            };
+            check_partstat(attendee.partstat(), reply.partstat())?;
+            attendee.parameters.set_partstat(reply.partstat().clone());
+        }
+        match instance {
+            Some((recurrence_id, mut instance)) => {
+                instance.set_attendees(attendees);
+                self.event.set_override(recurrence_id, instance);
+            }
+            None => self.event.set_attendees(attendees),
+        }
+        Ok(())
+    }
+    // Our override of the instance at `recurrence_id`, if we have one
+    fn override_of(&self, recurrence_id: &EventTime) -> Option<&Event> {
+        self.event.overrides().iter().find(|o| o.recurrence_id() == Some(recurrence_id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::Attendee;
+    use jiff::civil::datetime;
+    use pretty_assertions::assert_eq;
+
+    fn meeting(partstats: &[(&str, PartStat)]) -> Event {
+        let mut event = Event::new(EventTime::Floating(datetime(2025, 3, 3, 9, 0, 0, 0)));
+        event.set_attendees(
+            (partstats.iter())
+                .map(|(address, partstat)| {
+                    let mut attendee = Attendee::new(*address);
+                    attendee.parameters.set_partstat(partstat.clone());
+                    attendee
+                })
+                .collect(),
+        );
+        event
+    }
+    fn partstats(event: &Event) -> Vec<String> {
+        event.attendees().iter().map(|a| a.partstat().to_string()).collect()
+    }
+
+    #[test]
+    fn replies() {
+        let needs_action = PartStat::NeedsAction(None);
+        let invitation =
+            meeting(&[("mailto:a@b.c", needs_action.clone()), ("mailto:d@e.f", needs_action)]);
+        let mut scheduled = ScheduledEvent::new(&Method::Request, invitation).unwrap();
+        let reply = meeting(&[("mailto:d@e.f", PartStat::Accepted)]);
+        scheduled.apply(&Method::Reply, &reply).unwrap();
+        assert_eq!(partstats(scheduled.event()), vec!["NEEDS-ACTION", "ACCEPTED"]);
+
+        let stranger = meeting(&[("mailto:x@y.z", PartStat::Accepted)]);
+        let err = scheduled.apply(&Method::Reply, &stranger).unwrap_err();
+        assert_eq!(err, SchedulingError::UnknownAttendee("mailto:x@y.z".to_string()));
+        let undecided = meeting(&[("mailto:d@e.f", PartStat::NeedsAction(None))]);
+        let err = scheduled.apply(&Method::Reply, &undecided).unwrap_err();
+        assert_eq!(err.to_string(), "a REPLY can't change PARTSTAT from ACCEPTED to NEEDS-ACTION");
+        assert_eq!(err.code(), catalog::PARTSTAT_TRANSITION);
+        assert_eq!(partstats(scheduled.event()), vec!["NEEDS-ACTION", "ACCEPTED"]);
+    }
+    #[test]
+    fn replies_from_the_same_address() {
+        let needs_action = PartStat::NeedsAction(None);
+        let invitation =
+            meeting(&[("mailto:a@b.c", needs_action.clone()), ("mailto:d@e.f", needs_action)]);
+        let mut scheduled = ScheduledEvent::new(&Method::Request, invitation).unwrap();
+        // Clients often change the case of the address they were invited at, or its scheme
+        let reply = meeting(&[("MAILTO:D@e.f", PartStat::Accepted)]);
+        scheduled.apply(&Method::Reply, &reply).unwrap();
+        assert_eq!(partstats(scheduled.event()), vec!["NEEDS-ACTION", "ACCEPTED"]);
+        let reply = meeting(&[("A@B.C", PartStat::Tentative)]);
+        scheduled.apply(&Method::Reply, &reply).unwrap();
+        assert_eq!(partstats(scheduled.event()), vec!["TENTATIVE", "ACCEPTED"]);
+
+        // A different address is still a stranger
+        let stranger = meeting(&[("mailto:d@e.f.g", PartStat::Declined)]);
+        let err = scheduled.apply(&Method::Reply, &stranger).unwrap_err();
+        assert_eq!(err, SchedulingError::UnknownAttendee("mailto:d@e.f.g".to_string()));
+    }
+    #[test]
+    fn partstat_transitions() {
+        use PartStat::*;
+        assert!(check_partstat(&Accepted, &Declined).is_ok());
+        assert!(check_partstat(&NeedsAction(None), &Delegated).is_ok());
+        assert!(check_partstat(&Delegated, &Delegated).is_ok());
+        assert!(check_partstat(&Delegated, &Accepted).is_err());
+        assert!(check_partstat(&Tentative, &InProcess).is_err());
+    }
+    #[test]
+    fn counters_and_cancellation() {
+        let mut scheduled = ScheduledEvent::new(&Method::Request, meeting(&[])).unwrap();
+        let err = scheduled.apply(&Method::DeclineCounter, &meeting(&[])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "METHOD:DECLINECOUNTER isn't allowed when the event is requested"
+        );
+        scheduled.apply(&Method::Counter, &meeting(&[])).unwrap();
+        assert_eq!(scheduled.state(), SchedulingState::Countered);
+        scheduled.apply(&Method::DeclineCounter, &meeting(&[])).unwrap();
+        assert_eq!(scheduled.state(), SchedulingState::Requested);
+
+        // Cancelling one instance leaves the rest scheduled
+        let mut instance = meeting(&[]);
+        let recurrence_id = EventTime::Floating(datetime(2025, 3, 10, 9, 0, 0, 0));
+        instance.set_override(recurrence_id.clone(), meeting(&[]));
+        let instance = &instance.overrides()[0];
+        scheduled.apply(&Method::Cancel, instance).unwrap();
+        assert_eq!(scheduled.state(), SchedulingState::Requested);
+        assert_eq!(scheduled.event().overrides()[0].status(), Some(EventStatus::Cancelled));
+
+        scheduled.apply(&Method::Cancel, &meeting(&[])).unwrap();
+        assert_eq!(scheduled.state(), SchedulingState::Cancelled);
+        assert_eq!(scheduled.event().status(), Some(EventStatus::Cancelled));
+        let err = scheduled.apply(&Method::Reply, &meeting(&[])).unwrap_err();
+        assert_eq!(err.code(), catalog::METHOD_NOT_ALLOWED);
+        let mut cancelled = meeting(&[]);
+        cancelled.set_status(EventStatus::Cancelled);
+        let err = scheduled.apply(&Method::Request, &cancelled).unwrap_err();
+        assert_eq!(err, SchedulingError::CancelledRequest);
+        scheduled.apply(&Method::Request, &meeting(&[])).unwrap();
+        assert_eq!(scheduled.state(), SchedulingState::Requested);
+        assert_eq!(scheduled.event().status(), None);
+    }
+    #[test]
+    fn methods() {
+        assert_eq!(Method::from("declineCounter"), Method::DeclineCounter);
+        assert_eq!(Method::from("X-POLL").to_string(), "X-POLL");
+        let err = ScheduledEvent::new(&Method::Reply, meeting(&[])).unwrap_err();
+        assert_eq!(err.code(), catalog::METHOD_NOT_ALLOWED);
+        let mut published = ScheduledEvent::new(&Method::Publish, meeting(&[])).unwrap();
+        assert!(published.apply(&Method::Counter, &meeting(&[])).is_err());
+        assert_eq!(
+            published.apply(&Method::Add, &meeting(&[])),
+            Err(SchedulingError::NoRecurrenceId)
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! # Subscriptions
+//! Helpers for clients that poll calendar feeds, following the `REFRESH-INTERVAL` property
+//! ([RFC 7986, § 5.7](https://datatracker.ietf.org/doc/html/rfc7986#section-5.7)).
+use crate::fnv::fnv1a;
+use jiff::{SignedDuration, Timestamp};
+
+/// The fraction of the refresh interval `next_refresh` may add as jitter
+pub const JITTER_FRACTION: f64 = 0.1;
+
+/// A stable offset in `[0, max_jitter)` for the subscription identified by `uid`. The same UID
+/// always gets the same offset, while different UIDs are spread evenly, so thousands of
+/// subscriptions with the same `REFRESH-INTERVAL` don't all refresh at once.
+#[must_use]
+pub fn refresh_jitter(uid: &str, max_jitter: SignedDuration) -> SignedDuration {
+    let max_nanos = i64::try_from(max_jitter.as_nanos()).unwrap_or(i64::MAX);
+    if max_nanos <= 0 {
+        return SignedDuration::ZERO;
+    }
+    let offset = fnv1a(uid.as_bytes()) % max_nanos.unsigned_abs();
+    SignedDuration::from_nanos(i64::try_from(offset).unwrap_or_default())
+}
+
+/// When to refresh the subscription identified by `uid` next: `refresh_interval` after
+/// `last_refresh`, plus a stable jitter of up to `JITTER_FRACTION` of the interval.
+#[must_use]
+pub fn next_refresh(
+    uid: &str,
+    last_refresh: Timestamp,
+    refresh_interval: SignedDuration,
+) -> Timestamp {
+    let max_jitter = refresh_interval.mul_f64(JITTER_FRACTION);
+    let delay = refresh_interval.saturating_add(refresh_jitter(uid, max_jitter));
+    last_refresh.checked_add(delay).unwrap_or(Timestamp::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn jitter_is_stable_and_bounded() {
+        let max = SignedDuration::from_mins(30);
+        let uids: Vec<String> = (0..1000).map(|n| format!("feed-{n}@example.com")).collect();
+        let jitters: Vec<_> = uids.iter().map(|uid| refresh_jitter(uid, max)).collect();
+        for (uid, jitter) in uids.iter().zip(&jitters) {
+            assert_eq!(refresh_jitter(uid, max), *jitter);
+            assert!(!jitter.is_negative() && *jitter < max);
+        }
+        // Spread out: every 3-minute bucket gets some
+        let mut buckets = [0; 10];
+        for jitter in &jitters {
+            buckets[usize::try_from(jitter.as_secs() / 180).unwrap()] += 1;
+        }
+        assert!(buckets.iter().all(|n| *n > 50), "{buckets:?}");
+    }
+    #[test]
+    fn no_jitter_without_room() {
+        assert_eq!(refresh_jitter("x", SignedDuration::ZERO), SignedDuration::ZERO);
+        assert_eq!(refresh_jitter("x", SignedDuration::from_secs(-5)), SignedDuration::ZERO);
+    }
+    #[test]
+    fn next_refresh_is_within_the_jitter_window() {
+        let last: Timestamp = "2025-03-03T12:00:00Z".parse().unwrap();
+        let interval = SignedDuration::from_hours(1);
+        let next = next_refresh("uid-1", last, interval);
+        assert!(next >= last + interval);
+        assert!(next < last + interval + SignedDuration::from_mins(6));
+        assert_eq!(next, next_refresh("uid-1", last, interval));
+    }
+}
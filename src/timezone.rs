@@ -0,0 +1,329 @@
+//! # Time zones
+//! A `TZID` parameter should name either a `VTIMEZONE` defined in the same calendar, or (as a
+//! practical matter) a zone in the IANA time zone database. Feeds with bogus zone names are
+//! common enough that it's worth catching them when a calendar is ingested.
+//!
+//! `TzResolver` maps a `TZID` to a jiff `TimeZone`: by its IANA name, by the zone a globally
+//! unique id (starting with `/`) wraps, or through a mapping the application supplies for
+//! names like Outlook's `Pacific Standard Time`, and failing all of those, through the
+//! calendar's `VTIMEZONE` for it, which `VTimeZone` evaluates. Merged calendars often define
+//! the same `VTIMEZONE` more than once; `dedup_timezones` keeps one definition of each.
+use crate::component::Component;
+use crate::event::EventTime;
+use crate::expansion::ExpansionLimits;
+use crate::parameter::Tzid;
+use crate::preparse::{Prop, preparse_all_errors};
+use crate::rrule::parse_rrule;
+use crate::warning::{Observation, Warning, Warnings};
+use jiff::civil::{DateTime, Time};
+use jiff::{SignedDuration, tz};
+use std::collections::HashMap;
+
+mod resolver;
+mod vtimezone;
+pub use resolver::{ResolvedZone, TzResolver};
+pub use vtimezone::{VTimeZone, VTimeZoneError};
+
+/// A `TZID` parameter that names neither a `VTIMEZONE` in the calendar nor a zone in jiff's
+/// time zone database
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownTzid {
+    /// The index (in the `calendar` slice) of the property with the `TZID` parameter
+    pub index: usize,
+    pub tzid: Tzid,
+    /// The closest known zone name, if there's one reasonably close
+    pub suggestion: Option<Tzid>,
+}
+
+impl UnknownTzid {
+    /// The stable code for this kind of problem (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        crate::catalog::UNKNOWN_TZID
+    }
+}
+
+/// Check that every `TZID` parameter in `calendar` (the preparsed content lines of an
+/// iCalendar object) resolves to a `VTIMEZONE` in `calendar` or to jiff's time zone database,
+/// returning the ones that don't.
+#[must_use]
+pub fn validate_tzids(calendar: &[Prop]) -> Vec<UnknownTzid> {
+    validate_tzids_with(calendar, &TzResolver::new())
+}
+
+/// Like `validate_tzids`, but a `TZID` is known if it names a `VTIMEZONE` in `calendar` or
+/// `resolver` resolves it
+#[must_use]
+pub fn validate_tzids_with(calendar: &[Prop], resolver: &TzResolver) -> Vec<UnknownTzid> {
+    // Only VTIMEZONE components have a TZID *property*
+    let embedded: Vec<&str> = calendar
+        .iter()
+        .filter(|prop| prop.name.val().eq_ignore_ascii_case("TZID"))
+        .map(|prop| prop.value().val())
+        .collect();
+
+    let mut unknown = Vec::new();
+    let mut verdicts: HashMap<&str, Option<Option<Tzid>>> = HashMap::new();
+    for (index, prop) in calendar.iter().enumerate() {
+        for param in prop.parameters() {
+            if !param.name().val().eq_ignore_ascii_case("TZID") {
+                continue;
+            }
+            for tzid in param.values().iter().map(|value| value.val()) {
+                let verdict = verdicts.entry(tzid).or_insert_with(|| {
+                    if embedded.contains(&tzid) || resolver.resolve(tzid).is_some() {
+                        None
+                    } else {
+                        Some(closest_match(tzid, &embedded))
+                    }
+                });
+                if let Some(suggestion) = verdict {
+                    let (tzid, suggestion) = (Tzid::from(tzid), suggestion.clone());
+                    unknown.push(UnknownTzid { index, tzid, suggestion });
+                }
+            }
+        }
+    }
+    unknown
+}
+
+/// Remove duplicate `VTIMEZONE`s (with the same `TZID`) from `calendar`, keeping the one whose
+/// observances cover the widest span of time — the first, if several tie — in place of the
+/// first. Copies with the same observances are dropped silently; if the definitions of a
+/// `TZID` conflict, we warn (with an empty span, since the conflict is between components).
+/// Returns the `TZID`s that had duplicates, in order.
+pub fn dedup_timezones(calendar: &mut Component, warnings: &mut dyn Warnings) -> Vec<Tzid> {
+    // Each TZID, with the indexes of its definitions
+    let mut definitions: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (n, component) in calendar.components.iter().enumerate() {
+        if !component.name.eq_ignore_ascii_case("VTIMEZONE") {
+            continue;
+        }
+        let Some(tzid) = component.value_of("TZID") else { continue };
+        match definitions.iter_mut().find(|(t, _)| *t == tzid) {
+            Some((_, indexes)) => indexes.push(n),
+            None => definitions.push((tzid, vec![n])),
+        }
+    }
+    let mut duplicated = Vec::new();
+    let mut replacements = Vec::new();
+    let mut removed = vec![false; calendar.components.len()];
+    for (tzid, indexes) in definitions.into_iter().filter(|(_, indexes)| indexes.len() > 1) {
+        let zones: Vec<&Component> = indexes.iter().map(|n| &calendar.components[*n]).collect();
+        if zones.iter().any(|zone| zone.components != zones[0].components) {
+            warnings.warn(Warning::new(Observation::ConflictingTimezones, 0..0));
+        }
+        let mut widest = 0;
+        let coverage: Vec<_> = zones.iter().map(|zone| coverage(zone)).collect();
+        for (n, width) in coverage.iter().enumerate() {
+            if *width > coverage[widest] {
+                widest = n;
+            }
+        }
+        replacements.push((indexes[0], zones[widest].clone()));
+        indexes[1..].iter().for_each(|n| removed[*n] = true);
+        duplicated.push(Tzid::from(tzid));
+    }
+    for (n, zone) in replacements {
+        calendar.components[n] = zone;
+    }
+    let mut n = 0;
+    calendar.components.retain(|_| {
+        n += 1;
+        !removed[n - 1]
+    });
+    duplicated
+}
+
+// How long a span of time a `VTIMEZONE`'s observances cover: from the earliest `DTSTART` to
+// the last onset — an `RRULE`'s `UNTIL`, or its last onset by `COUNT`, or (for a rule that
+// doesn't end, or whose `COUNT` takes more than interactive expansion limits allow to reach)
+// no end at all. `None` for a zone without observances we can read.
+fn coverage(zone: &Component) -> Option<SignedDuration> {
+    let parse = |text: &str| DateTime::strptime("%Y%m%dT%H%M%S", text).ok();
+    let mut span: Option<(DateTime, DateTime)> = None;
+    for observance in &zone.components {
+        let Some(start) = observance.value_of("DTSTART").and_then(parse) else { continue };
+        let mut end = start;
+        for property in &observance.properties {
+            let (prop, _) = preparse_all_errors(property.line.as_bytes());
+            let (Some(value), name) = (prop.value.map(|v| v.val), property.name()) else {
+                continue;
+            };
+            if name.eq_ignore_ascii_case("RDATE") {
+                end = value.split(',').filter_map(parse).fold(end, DateTime::max);
+            } else if name.eq_ignore_ascii_case("RRULE") {
+                let Ok(rule) = parse_rrule(&mut value.as_bytes()) else {
+                    continue;
+                };
+                let last = match (rule.until(), start.to_zoned(tz::TimeZone::UTC)) {
+                    (Some(EventTime::Date(date)), _) => Some(date.to_datetime(Time::midnight())),
+                    (Some(EventTime::Floating(until)), _) => Some(until),
+                    (Some(EventTime::Zoned(until)), _) => Some(until.datetime()),
+                    (None, Ok(dtstart)) if rule.is_finite() => {
+                        let limits = ExpansionLimits::interactive();
+                        let mut onsets = rule.iter(&dtstart).with_limits(limits);
+                        let last = onsets.by_ref().last();
+                        last.filter(|_| onsets.limit_reached().is_none()).map(|l| l.datetime())
+                    }
+                    _ => None,
+                };
+                end = end.max(last.unwrap_or(DateTime::MAX));
+            }
+        }
+        span = Some(span.map_or((start, end), |(first, last)| (first.min(start), last.max(end))));
+    }
+    span.map(|(first, last)| last.duration_since(first))
+}
+
+// The embedded or tzdb zone name with the smallest (case-insensitive) edit distance from
+// `tzid`, as long as that distance is small compared to the length of `tzid`
+fn closest_match(tzid: &str, embedded: &[&str]) -> Option<Tzid> {
+    let threshold = tzid.chars().count() / 4 + 1;
+    let tzdb = tz::db().available().map(|name| name.as_str().to_string());
+    embedded
+        .iter()
+        .map(|name| (*name).to_string())
+        .chain(tzdb)
+        .map(|name| (edit_distance(tzid, &name), name))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min()
+        .map(|(_, name)| Tzid::from(name))
+}
+
+// Levenshtein distance, ignoring ASCII case
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().map(|c| c.to_ascii_lowercase()).enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(all(test, feature = "cautious"))]
+mod test {
+    use super::*;
+    use crate::preparse::cautious_preparse;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("America/New_York", "america/new_york"), 0);
+        assert_eq!(edit_distance("America/New_Yrok", "America/New_York"), 2);
+    }
+    #[test]
+    fn custom_tzids() {
+        assert!(Tzid::from("/example.com/20250101/America/New_York").is_custom());
+        assert!(!Tzid::from("America/New_York").is_custom());
+    }
+    #[test]
+    fn unknown_tzids_are_reported() {
+        let lines = [
+            "BEGIN:VTIMEZONE",
+            "TZID:Pacific Standard Time",
+            "END:VTIMEZONE",
+            "DTSTART;TZID=Pacific Standard Time:20240415T130000",
+            "DTSTART;TZID=america/new_york:20240415T130000",
+            "DTSTART;TZID=America/New_Yrok:20240415T130000",
+            "DTEND;TZID=Pacific Standard Tim:20240415T140000",
+            "RDATE;TZID=Nowhere/Special:20240415T130000",
+            "EXDATE;TZID=/mozilla.org/20050126_1/America/New_York:20240415T130000",
+        ];
+        let calendar: Vec<_> =
+            lines.iter().map(|line| cautious_preparse(line.as_bytes()).unwrap()).collect();
+        let unknown = |index, tzid: &str, suggestion: Option<&str>| UnknownTzid {
+            index,
+            tzid: tzid.into(),
+            suggestion: suggestion.map(Tzid::from),
+        };
+        assert_eq!(
+            validate_tzids(&calendar),
+            vec![
+                unknown(5, "America/New_Yrok", Some("America/New_York")),
+                unknown(6, "Pacific Standard Tim", Some("Pacific Standard Time")),
+                unknown(7, "Nowhere/Special", None),
+            ]
+        );
+        let resolver = TzResolver::new().fallback(|tzid| {
+            tzid.eq_ignore_ascii_case("Nowhere/Special").then_some(tz::TimeZone::UTC)
+        });
+        assert_eq!(validate_tzids_with(&calendar, &resolver).len(), 2);
+    }
+    #[test]
+    fn coverage_of_rules() {
+        use crate::names::Lookup;
+        use crate::unfolded::BufReadContent;
+        let days_covered = |rrule: &str| {
+            let input = format!(
+                "BEGIN:VTIMEZONE\r\nTZID:X\r\nBEGIN:STANDARD\r\nDTSTART:20000101T000000\r\n\
+                 RRULE:{rrule}\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n"
+            );
+            let lines = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+            let zone = Component::parse(lines, &mut Lookup::new()).unwrap();
+            coverage(&zone).map(|span| span.as_hours() / 24)
+        };
+        assert_eq!(days_covered("FREQ=YEARLY;UNTIL=20100101T000000Z"), Some(3653));
+        assert_eq!(days_covered("FREQ=DAILY;COUNT=3"), Some(2));
+        // Too many onsets to count quickly, so as open-ended as a rule without an end
+        let start = jiff::civil::date(2000, 1, 1).to_datetime(Time::midnight());
+        let open_ended = Some(DateTime::MAX.duration_since(start).as_hours() / 24);
+        assert_eq!(days_covered("FREQ=SECONDLY;COUNT=4000000000"), open_ended);
+        assert_eq!(days_covered("FREQ=YEARLY"), open_ended);
+    }
+    #[test]
+    fn duplicate_timezones() {
+        use crate::names::Lookup;
+        use crate::unfolded::BufReadContent;
+        use crate::warning::Warning;
+
+        let zone = |tzid: &str, rrule: &str| {
+            let lines = [
+                "BEGIN:VTIMEZONE",
+                &format!("TZID:{tzid}"),
+                "BEGIN:STANDARD",
+                "DTSTART:19671029T020000",
+                &format!("RRULE:{rrule}"),
+                "TZOFFSETFROM:-0400",
+                "TZOFFSETTO:-0500",
+                "END:STANDARD",
+                "END:VTIMEZONE",
+            ];
+            lines.join("\r\n") + "\r\n"
+        };
+        let input = [
+            "BEGIN:VCALENDAR\r\n".to_string(),
+            zone("Eastern", "FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU;UNTIL=20061029T060000Z"),
+            zone("Pacific", "FREQ=YEARLY;BYMONTH=11;BYDAY=1SU"),
+            zone("Eastern", "FREQ=YEARLY;BYMONTH=11;BYDAY=1SU"),
+            zone("Pacific", "FREQ=YEARLY;BYMONTH=11;BYDAY=1SU"),
+            "END:VCALENDAR\r\n".to_string(),
+        ]
+        .concat();
+        let lines = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+        let mut calendar = Component::parse(lines, &mut Lookup::new()).unwrap();
+        let mut warnings = Vec::new();
+        let duplicated = dedup_timezones(&mut calendar, &mut warnings);
+        assert_eq!(duplicated, vec![Tzid::from("Eastern"), Tzid::from("Pacific")]);
+        // Only Eastern's definitions conflict, and the one without an UNTIL covers more
+        assert_eq!(warnings, vec![Warning::new(Observation::ConflictingTimezones, 0..0)]);
+        let rules: Vec<_> = (calendar.components.iter())
+            .map(|zone| (zone.value_of("TZID"), zone.components[0].value_of("RRULE")))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                (Some("Eastern"), Some("FREQ=YEARLY;BYMONTH=11;BYDAY=1SU")),
+                (Some("Pacific"), Some("FREQ=YEARLY;BYMONTH=11;BYDAY=1SU")),
+            ]
+        );
+    }
+}
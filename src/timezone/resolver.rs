@@ -0,0 +1,216 @@
+// Resolving `TZID`s to time zones. Most calendars use IANA zone names, which jiff's time zone
+// database knows; some use globally unique ids (starting with `/`) that wrap one; and some,
+// Outlook's Windows zone names among them, need a mapping only the application has — or,
+// failing that, the calendar's own `VTIMEZONE`.
+use super::VTimeZone;
+use crate::component::Component;
+use crate::names::name_eq;
+use jiff::Zoned;
+use jiff::civil::DateTime;
+use jiff::tz::{self, Disambiguation, TimeZone};
+use std::fmt;
+
+/// Maps `TZID`s to time zones. A `TZID` naming a zone in jiff's time zone database (in any
+/// case) resolves to that zone. A globally unique one, like
+/// `/mozilla.org/20050126_1/America/New_York`, resolves to the zone its path ends with. Any
+/// other goes to the fallback, if there is one, and then to the `VTIMEZONE` with that `TZID`,
+/// if we were given one.
+#[derive(Default)]
+pub struct TzResolver {
+    fallback: Option<Box<Fallback>>,
+    vtimezones: Vec<VTimeZone>,
+    disambiguation: Disambiguation,
+}
+
+/// The zone a `TZID` names: one jiff knows, or one only a `VTIMEZONE` defines
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResolvedZone {
+    TimeZone(TimeZone),
+    VTimeZone(VTimeZone),
+}
+
+impl ResolvedZone {
+    /// The local date-time `local` in this zone. jiff can't carry a `VTIMEZONE`'s rules, so
+    /// in one of those the result is in the fixed offset in effect at that instant.
+    ///
+    /// # Errors
+    /// Returns an error if the instant is out of jiff's range.
+    pub fn to_zoned(&self, local: DateTime) -> Result<Zoned, jiff::Error> {
+        self.to_zoned_with(local, Disambiguation::Compatible)
+    }
+    /// Like `to_zoned`, resolving a local time a DST transition skips or repeats as
+    /// `disambiguation` says. In a `VTIMEZONE`, such a time is read as RFC 5545 says (see
+    /// `VTimeZone::to_timestamp`), whatever `disambiguation` is.
+    ///
+    /// # Errors
+    /// Returns an error if the instant is out of jiff's range, or `disambiguation` is
+    /// `Disambiguation::Reject` and the local time is skipped or repeated.
+    pub fn to_zoned_with(
+        &self,
+        local: DateTime,
+        disambiguation: Disambiguation,
+    ) -> Result<Zoned, jiff::Error> {
+        match self {
+            ResolvedZone::TimeZone(zone) => {
+                zone.to_ambiguous_zoned(local).disambiguate(disambiguation)
+            }
+            ResolvedZone::VTimeZone(zone) => {
+                let instant = zone.to_timestamp(local)?;
+                Ok(instant.to_zoned(TimeZone::fixed(zone.offset_at(instant))))
+            }
+        }
+    }
+}
+
+type Fallback = dyn Fn(&str) -> Option<TimeZone> + Send + Sync;
+
+impl TzResolver {
+    #[must_use]
+    pub fn new() -> Self {
+        TzResolver::default()
+    }
+    /// Ask `fallback` for the zone of a `TZID` the database doesn't know, such as
+    /// `Pacific Standard Time`
+    #[must_use]
+    pub fn fallback(
+        mut self,
+        fallback: impl Fn(&str) -> Option<TimeZone> + Send + Sync + 'static,
+    ) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+    /// Fall back on `zone` for its `TZID`
+    #[must_use]
+    pub fn vtimezone(mut self, zone: VTimeZone) -> Self {
+        self.vtimezones.push(zone);
+        self
+    }
+    /// Fall back on the `VTIMEZONE`s of `calendar` (those that can be evaluated) for their
+    /// `TZID`s
+    #[must_use]
+    pub fn vtimezones_of(self, calendar: &Component) -> Self {
+        (calendar.components.iter())
+            .filter(|component| name_eq(&component.name, "VTIMEZONE"))
+            .filter_map(|component| VTimeZone::from_component(component).ok())
+            .fold(self, TzResolver::vtimezone)
+    }
+    /// Resolve a local time that a DST transition skips or repeats as `disambiguation` says,
+    /// rather than as RFC 5545 does (`Disambiguation::Compatible`)
+    #[must_use]
+    pub fn disambiguation(mut self, disambiguation: Disambiguation) -> Self {
+        self.disambiguation = disambiguation;
+        self
+    }
+    /// The local date-time `local` in the zone `tzid` names, with the resolver's
+    /// disambiguation (see `ResolvedZone::to_zoned_with`), or `None` if we can't tell which
+    /// zone that is
+    #[must_use]
+    pub fn to_zoned(&self, tzid: &str, local: DateTime) -> Option<Result<Zoned, jiff::Error>> {
+        let zone = self.resolve(tzid)?;
+        Some(zone.to_zoned_with(local, self.disambiguation))
+    }
+    /// The time zone `tzid` names, if we can tell
+    #[must_use]
+    pub fn resolve(&self, tzid: &str) -> Option<ResolvedZone> {
+        self.time_zone(tzid).map(ResolvedZone::TimeZone).or_else(|| {
+            let zone = self.vtimezones.iter().find(|zone| zone.tzid() == tzid);
+            zone.cloned().map(ResolvedZone::VTimeZone)
+        })
+    }
+    // The zone jiff knows that `tzid` names, if we can tell
+    fn time_zone(&self, tzid: &str) -> Option<TimeZone> {
+        if let Ok(zone) = tz::db().get(tzid) {
+            return Some(zone);
+        }
+        if let Some(path) = tzid.strip_prefix('/') {
+            // The longest tail of the path that's a zone name, so `America/Argentina/Salta`
+            // isn't taken for `Argentina/Salta`
+            let tails = path.match_indices('/').map(|(n, _)| &path[n + 1..]);
+            let zone = std::iter::once(path).chain(tails).find_map(|tail| tz::db().get(tail).ok());
+            if zone.is_some() {
+                return zone;
+            }
+        }
+        self.fallback.as_ref().and_then(|fallback| fallback(tzid))
+    }
+}
+
+impl fmt::Debug for TzResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let vtimezones: Vec<_> = self.vtimezones.iter().map(VTimeZone::tzid).collect();
+        (f.debug_struct("TzResolver"))
+            .field("fallback", &self.fallback.is_some())
+            .field("vtimezones", &vtimezones)
+            .field("disambiguation", &self.disambiguation)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn name(zone: Option<ResolvedZone>) -> Option<String> {
+        match zone {
+            Some(ResolvedZone::TimeZone(zone)) => zone.iana_name().map(str::to_string),
+            _ => None,
+        }
+    }
+    const UTC: Option<ResolvedZone> = Some(ResolvedZone::TimeZone(TimeZone::UTC));
+
+    #[test]
+    fn iana_names() {
+        let resolver = TzResolver::new();
+        assert_eq!(name(resolver.resolve("Europe/Paris")), Some("Europe/Paris".into()));
+        assert_eq!(name(resolver.resolve("america/new_york")), Some("America/New_York".into()));
+        assert_eq!(resolver.resolve("UTC"), UTC);
+        assert_eq!(resolver.resolve("Pacific Standard Time"), None);
+    }
+    #[test]
+    fn globally_unique_ids() {
+        let resolver = TzResolver::new();
+        let resolve = |tzid| name(resolver.resolve(tzid));
+        let new_york = Some("America/New_York".to_string());
+        assert_eq!(resolve("/mozilla.org/20050126_1/America/New_York"), new_york);
+        assert_eq!(resolve("/softwarestudio.org/Olson_20011030_5/America/New_York"), new_york);
+        assert_eq!(resolve("/America/New_York"), new_york);
+        let salta = Some("America/Argentina/Salta".to_string());
+        assert_eq!(resolve("/citadel.org/20190914_1/America/Argentina/Salta"), salta);
+        assert_eq!(resolve("/example.com/Nowhere/Special"), None);
+        assert_eq!(resolve("/example.com/"), None);
+    }
+    #[test]
+    fn fallback() {
+        let resolver = TzResolver::new().fallback(|tzid| match tzid {
+            "Pacific Standard Time" => TimeZone::get("America/Los_Angeles").ok(),
+            "/example.com/Nowhere/Special" => Some(TimeZone::UTC),
+            _ => None,
+        });
+        let los_angeles = Some("America/Los_Angeles".to_string());
+        assert_eq!(name(resolver.resolve("Pacific Standard Time")), los_angeles);
+        assert_eq!(resolver.resolve("/example.com/Nowhere/Special"), UTC);
+        // The database comes first
+        assert_eq!(name(resolver.resolve("Europe/Paris")), Some("Europe/Paris".into()));
+        assert_eq!(resolver.resolve("Eastern"), None);
+        assert_eq!(
+            format!("{resolver:?}"),
+            "TzResolver { fallback: true, vtimezones: [], disambiguation: Compatible }"
+        );
+    }
+    #[test]
+    fn disambiguation() {
+        // 02:30 doesn't exist in New York on 2025-03-09
+        let gap = DateTime::constant(2025, 3, 9, 2, 30, 0, 0);
+        let time = |resolver: &TzResolver| {
+            resolver.to_zoned("America/New_York", gap).map(|zoned| zoned.map(|z| z.time()))
+        };
+        let resolver = TzResolver::new();
+        assert_eq!(time(&resolver).unwrap().unwrap(), jiff::civil::time(3, 30, 0, 0));
+        let earlier = TzResolver::new().disambiguation(Disambiguation::Earlier);
+        assert_eq!(time(&earlier).unwrap().unwrap(), jiff::civil::time(1, 30, 0, 0));
+        let reject = TzResolver::new().disambiguation(Disambiguation::Reject);
+        assert!(time(&reject).unwrap().is_err());
+        assert!(resolver.to_zoned("Eastern", gap).is_none());
+    }
+}
@@ -0,0 +1,477 @@
+// Evaluating `VTIMEZONE`s. When a `TZID` names no zone anyone knows, the calendar's
+// `VTIMEZONE` for it is all there is: its `STANDARD` and `DAYLIGHT` observances, with their
+// `RRULE`s, `RDATE`s and offsets, give the UTC offset at any instant.
+use crate::catalog;
+use crate::component::Component;
+use crate::expansion::ExpansionLimits;
+use crate::names::name_eq;
+use crate::preparse::preparse_all_errors;
+use crate::property::{parse_date, parse_time, parse_utc_offset};
+use crate::rrule::{Frequency, Occurrences, parse_rrule};
+use jiff::Timestamp;
+use jiff::civil::DateTime;
+use jiff::tz::{Offset, TimeZone};
+use std::sync::{Arc, Mutex, PoisonError};
+use thiserror::Error;
+
+/// Why a `VTIMEZONE` couldn't be evaluated
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum VTimeZoneError {
+    #[error("the VTIMEZONE has no TZID")]
+    NoTzid,
+    #[error("the VTIMEZONE has no STANDARD or DAYLIGHT observances")]
+    NoObservances,
+    #[error("a {observance} observance has no {property}")]
+    MissingProperty { observance: String, property: &'static str },
+    #[error("a {observance} observance's {property} {value:?} can't be read")]
+    BadValue { observance: String, property: &'static str, value: String },
+    #[error("a {observance} observance's RRULE repeats more often than daily")]
+    SubDailyRule { observance: String },
+}
+impl VTimeZoneError {
+    /// The error's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            VTimeZoneError::NoTzid => catalog::TIMEZONE_NO_TZID,
+            VTimeZoneError::NoObservances => catalog::TIMEZONE_NO_OBSERVANCES,
+            VTimeZoneError::MissingProperty { .. } => catalog::TIMEZONE_MISSING_PROPERTY,
+            VTimeZoneError::BadValue { .. } => catalog::TIMEZONE_BAD_VALUE,
+            VTimeZoneError::SubDailyRule { .. } => catalog::TIMEZONE_SUB_DAILY_RULE,
+        }
+    }
+}
+
+/// A `VTIMEZONE`, evaluated. Each observance's onsets — its `DTSTART`, the instances of its
+/// `RRULE`, and its `RDATE`s — are local times in its `TZOFFSETFROM`, after which its
+/// `TZOFFSETTO` is in effect until the next onset of any observance. Before the first onset,
+/// the first observance's `TZOFFSETFROM` is.
+///
+/// An `RRULE`'s onsets are found as they're needed, and kept: clones share them. A rule
+/// that repeats more often than daily is refused, and the search for onsets stops after a
+/// million candidates, past which the last offset found stays in effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VTimeZone {
+    tzid: String,
+    observances: Arc<[Observance]>,
+}
+
+#[derive(Debug)]
+struct Observance {
+    from: Offset,
+    to: Offset,
+    // `DTSTART`, the first onset
+    start: Timestamp,
+    // The `RDATE`s, in order
+    rdates: Vec<Timestamp>,
+    // The `RRULE` as written, and its onsets
+    rrule: Option<(String, Mutex<Onsets>)>,
+}
+
+// An `RRULE`'s onsets, in order, as far as they've been needed
+#[derive(Debug)]
+struct Onsets {
+    found: Vec<Timestamp>,
+    // The rest, until they run out or the search reaches `SEARCH_LIMITS`
+    rest: Option<Occurrences>,
+}
+
+// How far to look for a rule's onsets: a yearly rule from 1601 (where Outlook starts them)
+// reaches the year 9999 well within this
+const SEARCH_LIMITS: ExpansionLimits = ExpansionLimits {
+    max_instances: usize::MAX,
+    max_span_years: 10_000,
+    max_candidates: 1_000_000,
+};
+
+impl VTimeZone {
+    /// Evaluate `component`, a `VTIMEZONE`
+    ///
+    /// # Errors
+    /// Returns a `VTimeZoneError` if the component has no `TZID` or no observances, or an
+    /// observance lacks a `DTSTART`, `TZOFFSETFROM` or `TZOFFSETTO`, has one (or an `RRULE` or
+    /// `RDATE`) we can't read, or has an `RRULE` more frequent than `DAILY`.
+    pub fn from_component(component: &Component) -> Result<VTimeZone, VTimeZoneError> {
+        let tzid = component.value_of("TZID").ok_or(VTimeZoneError::NoTzid)?.to_string();
+        let observances = (component.components.iter())
+            .filter(|c| name_eq(&c.name, "STANDARD") || name_eq(&c.name, "DAYLIGHT"))
+            .map(Observance::from_component)
+            .collect::<Result<Arc<[_]>, _>>()?;
+        if observances.is_empty() {
+            return Err(VTimeZoneError::NoObservances);
+        }
+        Ok(VTimeZone { tzid, observances })
+    }
+    #[must_use]
+    pub fn tzid(&self) -> &str {
+        &self.tzid
+    }
+    /// The offset from UTC at `instant`
+    #[must_use]
+    pub fn offset_at(&self, instant: Timestamp) -> Offset {
+        self.offset_after(|_| Some(instant))
+    }
+    /// The instant the local date-time `local` stands for. As RFC 5545 § 3.3.5 has it, a
+    /// time skipped by a transition (02:30 on the night clocks go forward an hour) is read
+    /// with the offset before the transition, as is a time repeated by one.
+    ///
+    /// # Errors
+    /// Returns an error if the instant is out of jiff's range.
+    pub fn to_timestamp(&self, local: DateTime) -> Result<Timestamp, jiff::Error> {
+        // An onset is passed once the clock shows a time past both its offsets
+        let offset = self.offset_after(|o| o.from.max(o.to).to_timestamp(local).ok());
+        offset.to_timestamp(local)
+    }
+    // The `TZOFFSETTO` of the latest onset (of any observance) no later than its observance's
+    // `limit`, or before any, the first observance's `TZOFFSETFROM`
+    fn offset_after(&self, limit: impl Fn(&Observance) -> Option<Timestamp>) -> Offset {
+        let latest = (self.observances.iter())
+            .filter_map(|o| Some((o.last_onset(limit(o)?)?, o)))
+            .max_by_key(|(onset, _)| *onset);
+        match latest {
+            Some((_, observance)) => observance.to,
+            None => {
+                (self.observances.iter().min_by_key(|o| o.start)).map_or(Offset::UTC, |o| o.from)
+            }
+        }
+    }
+}
+
+impl Observance {
+    fn from_component(component: &Component) -> Result<Observance, VTimeZoneError> {
+        let observance = || component.name.clone();
+        let value = |property| {
+            let missing = || VTimeZoneError::MissingProperty { observance: observance(), property };
+            component.value_of(property).ok_or_else(missing)
+        };
+        let bad = |property, value: &str| VTimeZoneError::BadValue {
+            observance: observance(),
+            property,
+            value: value.to_string(),
+        };
+        let offset = |property| {
+            let text = value(property)?;
+            let offset = parse_utc_offset(text).map_err(|_| bad(property, text))?;
+            let seconds = i32::try_from(offset.as_secs()).map_err(|_| bad(property, text))?;
+            Offset::from_seconds(seconds).map_err(|_| bad(property, text))
+        };
+        let (from, to) = (offset("TZOFFSETFROM")?, offset("TZOFFSETTO")?);
+        let dtstart = value("DTSTART")?;
+        let start = (local_date_time(dtstart))
+            .and_then(|start| start.to_zoned(TimeZone::fixed(from)).ok())
+            .ok_or_else(|| bad("DTSTART", dtstart))?;
+        let mut rrule = None;
+        if let Some(text) = component.value_of("RRULE") {
+            let rule = parse_rrule(&mut text.as_bytes()).map_err(|_| bad("RRULE", text))?;
+            if rule.freq() < Frequency::Daily {
+                return Err(VTimeZoneError::SubDailyRule { observance: observance() });
+            }
+            let rest = Some(rule.iter(&start).with_limits(SEARCH_LIMITS));
+            rrule = Some((text.to_string(), Mutex::new(Onsets { found: Vec::new(), rest })));
+        }
+        let mut rdates = Vec::new();
+        for property in component.properties.iter().filter(|p| name_eq(p.name(), "RDATE")) {
+            let (prop, _) = preparse_all_errors(property.line.as_bytes());
+            for date in prop.value.map_or("", |value| value.val).split(',') {
+                let onset = local_date_time(date).and_then(|date| from.to_timestamp(date).ok());
+                rdates.push(onset.ok_or_else(|| bad("RDATE", date))?);
+            }
+        }
+        rdates.sort_unstable();
+        Ok(Observance { from, to, start: start.timestamp(), rdates, rrule })
+    }
+    // The observance's last onset no later than `limit`
+    fn last_onset(&self, limit: Timestamp) -> Option<Timestamp> {
+        let last = |onsets: &[Timestamp]| {
+            let n = onsets.partition_point(|onset| *onset <= limit);
+            n.checked_sub(1).map(|n| onsets[n])
+        };
+        let ruled = self.rrule.as_ref().and_then(|(_, onsets)| {
+            let mut onsets = onsets.lock().unwrap_or_else(PoisonError::into_inner);
+            onsets.find_past(limit);
+            last(&onsets.found)
+        });
+        let start = Some(self.start).filter(|start| *start <= limit);
+        start.max(last(&self.rdates)).max(ruled)
+    }
+}
+
+// Observances are the same if they're written the same, however far their onsets have been
+// found
+impl PartialEq for Observance {
+    fn eq(&self, other: &Self) -> bool {
+        let rule = |o: &Observance| o.rrule.as_ref().map(|(text, _)| text.clone());
+        (self.from, self.to, self.start) == (other.from, other.to, other.start)
+            && self.rdates == other.rdates
+            && rule(self) == rule(other)
+    }
+}
+
+impl Onsets {
+    // Find onsets until one is past `limit`, or there are no more
+    fn find_past(&mut self, limit: Timestamp) {
+        while self.found.last().is_none_or(|last| *last <= limit) {
+            match self.rest.as_mut().and_then(Iterator::next) {
+                Some(onset) => self.found.push(onset.timestamp()),
+                None => {
+                    self.rest = None;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// A local (not UTC) `DATE-TIME` value
+fn local_date_time(value: &str) -> Option<DateTime> {
+    let (date, time) = value.split_once('T')?;
+    if time.ends_with('Z') {
+        return None;
+    }
+    Some(DateTime::from_parts(parse_date(date).ok()?, parse_time(time).ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vtimezone(lines: &[&str]) -> Result<VTimeZone, VTimeZoneError> {
+        use crate::names::Lookup;
+        use crate::unfolded::BufReadContent;
+        let input = lines.join("\r\n") + "\r\n";
+        let lines = std::io::Cursor::new(input).content_lines().map(Result::unwrap);
+        VTimeZone::from_component(&Component::parse(lines, &mut Lookup::new()).unwrap())
+    }
+    // New York's rules since 1967, as Outlook writes them, under a name the database lacks
+    fn eastern() -> VTimeZone {
+        vtimezone(&[
+            "BEGIN:VTIMEZONE",
+            "TZID:Eastern Standard Time",
+            "BEGIN:STANDARD",
+            "DTSTART:19671029T020000",
+            "RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU;UNTIL=20061029T060000Z",
+            "TZOFFSETFROM:-0400",
+            "TZOFFSETTO:-0500",
+            "END:STANDARD",
+            "BEGIN:DAYLIGHT",
+            "DTSTART:19870405T020000",
+            "RRULE:FREQ=YEARLY;BYMONTH=4;BYDAY=1SU;UNTIL=20060402T070000Z",
+            "TZOFFSETFROM:-0500",
+            "TZOFFSETTO:-0400",
+            "END:DAYLIGHT",
+            "BEGIN:STANDARD",
+            "DTSTART:20071104T020000",
+            "RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU",
+            "TZOFFSETFROM:-0400",
+            "TZOFFSETTO:-0500",
+            "END:STANDARD",
+            "BEGIN:DAYLIGHT",
+            "DTSTART:20070311T020000",
+            "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU",
+            "TZOFFSETFROM:-0500",
+            "TZOFFSETTO:-0400",
+            "END:DAYLIGHT",
+            "END:VTIMEZONE",
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn agrees_with_the_database() {
+        let eastern = eastern();
+        assert_eq!(eastern.tzid(), "Eastern Standard Time");
+        let new_york = TimeZone::get("America/New_York").unwrap();
+        // Every half hour of the days around each transition, in years under each set of
+        // rules; jiff, like RFC 5545, reads skipped and repeated times with the earlier offset
+        let days = ["1999-04-04", "1999-10-31", "2025-03-09", "2025-11-02", "2101-03-13"];
+        for day in days {
+            let start: DateTime = format!("{day}T00:00").parse().unwrap();
+            for n in -48..96 {
+                let local = start.checked_add(jiff::SignedDuration::from_mins(30 * n)).unwrap();
+                let expected = new_york.to_zoned(local).unwrap().timestamp();
+                assert_eq!(eastern.to_timestamp(local).unwrap(), expected, "{local}");
+                assert_eq!(eastern.offset_at(expected), new_york.to_offset(expected), "{local}");
+            }
+        }
+        // Before the first onset
+        let early: Timestamp = "1960-06-01T12:00Z".parse().unwrap();
+        assert_eq!(eastern.offset_at(early), Offset::from_hours(-4).unwrap());
+    }
+    #[test]
+    fn onsets_are_found_as_needed() {
+        let eastern = eastern();
+        let found = |zone: &VTimeZone| {
+            (zone.observances.iter().filter_map(|o| o.rrule.as_ref()))
+                .map(|(_, onsets)| onsets)
+                .map(|onsets| onsets.lock().unwrap().found.len())
+                .sum::<usize>()
+        };
+        assert_eq!(found(&eastern), 0);
+        let copy = eastern.clone();
+        let instant = "2025-06-01T12:00Z".parse().unwrap();
+        assert_eq!(copy.offset_at(instant), Offset::from_hours(-4).unwrap());
+        // The clone found them for both, and no further than it needed
+        let so_far = found(&eastern);
+        assert!(so_far > 0 && so_far < 200, "{so_far}");
+        assert_eq!(copy, eastern);
+        // Much later, in a year each rule has to be followed to
+        let instant = "2400-01-01T12:00Z".parse().unwrap();
+        assert_eq!(eastern.offset_at(instant), Offset::from_hours(-5).unwrap());
+    }
+    #[cfg(feature = "cautious")]
+    #[test]
+    fn outlook_event() {
+        use crate::component::parse_calendar;
+        use crate::event::{Event, EventTime, EventTimeZone};
+        use crate::parameter::Tzid;
+        use crate::preparse::cautious_preparse;
+        use crate::timezone::{ResolvedZone, TzResolver};
+        // As Outlook sends an invitation: the zone under its Windows name, with rules from 1601
+        let input = [
+            "BEGIN:VCALENDAR",
+            "PRODID:-//Microsoft Corporation//Outlook 16.0 MIMEDIR//EN",
+            "VERSION:2.0",
+            "METHOD:REQUEST",
+            "BEGIN:VTIMEZONE",
+            "TZID:W. Europe Standard Time",
+            "BEGIN:STANDARD",
+            "DTSTART:16011028T030000",
+            "RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=10",
+            "TZOFFSETFROM:+0200",
+            "TZOFFSETTO:+0100",
+            "END:STANDARD",
+            "BEGIN:DAYLIGHT",
+            "DTSTART:16010325T020000",
+            "RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=3",
+            "TZOFFSETFROM:+0100",
+            "TZOFFSETTO:+0200",
+            "END:DAYLIGHT",
+            "END:VTIMEZONE",
+            "BEGIN:VEVENT",
+            "UID:040000008200E00074C5B7101A82E00800000000",
+            "SUMMARY;LANGUAGE=en-US:Planning",
+            "DTSTART;TZID=W. Europe Standard Time:20250328T090000",
+            "DTEND;TZID=W. Europe Standard Time:20250331T100000",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]
+        .join("\r\n")
+            + "\r\n";
+        let calendar = parse_calendar(input.as_bytes()).unwrap();
+        let resolver = TzResolver::new().vtimezones_of(&calendar.component);
+        let props: Vec<_> = (calendar.component.components[1].properties.iter())
+            .map(|property| cautious_preparse(property.line.as_bytes()).unwrap())
+            .collect();
+        let event = Event::from_props_with(&props, &resolver).unwrap();
+        assert_eq!(calendar.events, std::slice::from_ref(&event));
+        let instant = |time: Option<&EventTime>| match time {
+            Some(EventTime::Zoned(zoned)) => zoned.timestamp().to_string(),
+            other => panic!("{other:?} isn't zoned"),
+        };
+        // Before and after the clocks go forward on March 30
+        assert_eq!(instant(Some(event.dtstart())), "2025-03-28T08:00:00Z");
+        assert_eq!(instant(event.dtend()), "2025-03-31T08:00:00Z");
+        let tzid = Tzid::from("W. Europe Standard Time");
+        let zone = resolver.resolve(tzid.as_str());
+        let Some(ResolvedZone::VTimeZone(zone)) = zone else {
+            panic!("the TZID should resolve to the VTIMEZONE");
+        };
+        assert_eq!(event.timezone(), EventTimeZone::Defined { tzid: tzid.clone(), zone });
+        // Without the VTIMEZONE, the TZID is unknown, and the times floating
+        let event = Event::from_props(&props).unwrap();
+        assert_eq!(event.timezone(), EventTimeZone::Unresolved { tzid });
+        assert_eq!(event.dtstart(), &EventTime::Floating("2025-03-28T09:00".parse().unwrap()));
+    }
+    #[test]
+    fn rdates() {
+        let zone = vtimezone(&[
+            "BEGIN:VTIMEZONE",
+            "TZID:/example.com/Nowhere/Special",
+            "BEGIN:DAYLIGHT",
+            "DTSTART:20250301T020000",
+            "RDATE:20260301T020000,20270301T020000",
+            "TZOFFSETFROM:+0100",
+            "TZOFFSETTO:+0200",
+            "END:DAYLIGHT",
+            "BEGIN:STANDARD",
+            "DTSTART:20251001T030000",
+            "RDATE:20261001T030000",
+            "TZOFFSETFROM:+0200",
+            "TZOFFSETTO:+0100",
+            "END:STANDARD",
+            "END:VTIMEZONE",
+        ])
+        .unwrap();
+        let offset = |instant: &str| zone.offset_at(instant.parse().unwrap());
+        let hours = |hours| Offset::from_hours(hours).unwrap();
+        assert_eq!(offset("2025-02-28T12:00Z"), hours(1));
+        assert_eq!(offset("2025-06-01T12:00Z"), hours(2));
+        assert_eq!(offset("2026-01-01T12:00Z"), hours(1));
+        assert_eq!(offset("2026-03-01T00:59Z"), hours(1));
+        assert_eq!(offset("2026-03-01T01:00Z"), hours(2));
+        assert_eq!(offset("2027-12-01T12:00Z"), hours(2));
+        // Skipped, so read with the offset before the gap
+        let local = "2026-03-01T02:30".parse().unwrap();
+        assert_eq!(zone.to_timestamp(local).unwrap(), "2026-03-01T01:30Z".parse().unwrap());
+    }
+    #[test]
+    fn errors() {
+        let code = |lines: &[&str]| vtimezone(lines).unwrap_err().code();
+        assert_eq!(code(&["BEGIN:VTIMEZONE", "END:VTIMEZONE"]), catalog::TIMEZONE_NO_TZID);
+        let no_observances = ["BEGIN:VTIMEZONE", "TZID:X", "END:VTIMEZONE"];
+        assert_eq!(code(&no_observances), catalog::TIMEZONE_NO_OBSERVANCES);
+        let observance = |property: &str| {
+            let lines = [
+                "BEGIN:VTIMEZONE",
+                "TZID:X",
+                "BEGIN:STANDARD",
+                "DTSTART:20250101T000000",
+                property,
+                "TZOFFSETTO:+0100",
+                "END:STANDARD",
+                "END:VTIMEZONE",
+            ];
+            vtimezone(&lines).unwrap_err()
+        };
+        let missing = observance("X-NOTHING:here");
+        let expected = "TZOFFSETFROM";
+        assert_eq!(
+            missing,
+            VTimeZoneError::MissingProperty { observance: "STANDARD".into(), property: expected }
+        );
+        assert_eq!(missing.code(), catalog::TIMEZONE_MISSING_PROPERTY);
+        assert_eq!(observance("TZOFFSETFROM:+01").code(), catalog::TIMEZONE_BAD_VALUE);
+        let hourly = [
+            "BEGIN:VTIMEZONE",
+            "TZID:X",
+            "BEGIN:STANDARD",
+            "DTSTART:20250101T000000",
+            "RRULE:FREQ=HOURLY",
+            "TZOFFSETFROM:+0100",
+            "TZOFFSETTO:+0100",
+            "END:STANDARD",
+            "END:VTIMEZONE",
+        ];
+        let sub_daily = VTimeZoneError::SubDailyRule { observance: "STANDARD".into() };
+        assert_eq!(vtimezone(&hourly).unwrap_err(), sub_daily);
+        assert_eq!(sub_daily.code(), catalog::TIMEZONE_SUB_DAILY_RULE);
+        let bad_rdate = VTimeZoneError::BadValue {
+            observance: "STANDARD".into(),
+            property: "RDATE",
+            value: "20250101T000000Z".into(),
+        };
+        let lines = [
+            "BEGIN:VTIMEZONE",
+            "TZID:X",
+            "BEGIN:STANDARD",
+            "DTSTART:20240101T000000",
+            "RDATE:20250101T000000Z",
+            "TZOFFSETFROM:+0100",
+            "TZOFFSETTO:+0100",
+            "END:STANDARD",
+            "END:VTIMEZONE",
+        ];
+        assert_eq!(vtimezone(&lines).unwrap_err(), bad_rdate);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::error::CalendarError;
 use bstr::BString;
-use memchr::memchr;
+use memchr::{memchr, memchr2};
 use std::io::{self, ErrorKind};
 
 /// Reads content lines into `buf`, unfolding long lines as described in
@@ -13,6 +13,16 @@ use std::io::{self, ErrorKind};
 pub fn read_content_line_u8<R: io::BufRead + ?Sized>(
     r: &mut R,
     buf: &mut Vec<u8>,
+) -> Result<usize, io::Error> {
+    read_content_line(r, buf, &mut None)
+}
+
+// Like `read_content_line_u8`, but also sets `bare_lf` to the (1-based) number, within the
+// content line, of the first physical line that ended with a bare `b'\n'`
+fn read_content_line<R: io::BufRead + ?Sized>(
+    r: &mut R,
+    buf: &mut Vec<u8>,
+    bare_lf: &mut Option<usize>,
 ) -> Result<usize, io::Error> {
     // Adapted from the rust standard library's `read_until` in `io/mod.rs`
     macro_rules! fill_buf_to {
@@ -36,6 +46,8 @@ pub fn read_content_line_u8<R: io::BufRead + ?Sized>(
                     buf.extend_from_slice(&available[..newline]);
                     if buf.last() == Some(&b'\r') {
                         buf.pop();
+                    } else if bare_lf.is_none() {
+                        *bare_lf = Some(lines_read);
                     }
                     (true, newline + 1)
                 }
@@ -65,9 +77,27 @@ pub fn read_content_line_u8<R: io::BufRead + ?Sized>(
     }
 }
 
+/// How far a [`ContentLines`] iterator has gotten, passed to its `should_continue` callback.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Progress {
+    /// The number of physical input lines read so far
+    pub lines_read: usize,
+    /// The number of (unfolded) content lines returned so far
+    pub content_lines: usize,
+}
+
+/// The callback used by [`BufReadContent::content_lines`], which never cancels.
+pub type NeverCancel = fn(&Progress) -> bool;
+fn never_cancel(_: &Progress) -> bool {
+    true
+}
+
 #[derive(Debug)]
-pub struct ContentLines<R> {
-    lines_read: usize,
+pub struct ContentLines<R, F = NeverCancel> {
+    progress: Progress,
+    cancelled: bool,
+    strict_crlf: bool,
+    should_continue: F,
     r: R,
 }
 pub trait BufReadContent: io::BufRead {
@@ -75,23 +105,156 @@ pub trait BufReadContent: io::BufRead {
     where
         Self: Sized,
     {
-        ContentLines { lines_read: 1, r: self }
+        self.content_lines_with(never_cancel)
+    }
+    /// Like `content_lines`, but calls `should_continue` before reading each content line.
+    /// When `should_continue` returns `false` the iterator returns a single
+    /// `CalendarError::Cancelled` error and then stops, so a GUI can abort parsing a huge
+    /// feed without killing the thread.
+    fn content_lines_with<F>(self, should_continue: F) -> ContentLines<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Progress) -> bool,
+    {
+        ContentLines {
+            progress: Progress::default(),
+            cancelled: false,
+            strict_crlf: false,
+            should_continue,
+            r: self,
+        }
     }
 }
 impl<R: io::BufRead> BufReadContent for R {}
 
-impl<R: io::BufRead> Iterator for ContentLines<R> {
+impl<R, F> ContentLines<R, F> {
+    /// Require CRLF line endings, as RFC 5545 does. A content line with a bare `b'\n'` line
+    /// ending is returned as a `CalendarError::BareLineFeed` error, giving the input line
+    /// the bare line feed ends, instead of as content; iteration continues after it.
+    #[must_use]
+    pub fn strict_crlf(self) -> Self {
+        ContentLines { strict_crlf: true, ..self }
+    }
+    /// Split the content lines into separate `VCALENDAR` objects, for exports that
+    /// concatenate several in one file
+    pub fn calendars(self) -> Calendars<Self> {
+        Calendars { lines: self, pending: None }
+    }
+}
+
+/// An iterator over the `VCALENDAR` objects in a stream. Each item holds the content lines
+/// (with the input line each starts on) from a `BEGIN:VCALENDAR` through its
+/// `END:VCALENDAR`. Blank lines between calendars are skipped; any other content outside a
+/// calendar is an error, as is a calendar that's still open at the next `BEGIN:VCALENDAR` or
+/// at the end of the input.
+#[derive(Debug)]
+pub struct Calendars<I> {
+    lines: I,
+    // The `BEGIN:VCALENDAR` line of the next calendar, when we've read it while finishing the
+    // previous one
+    pending: Option<(usize, BString)>,
+}
+
+impl<I: Iterator<Item = Result<(usize, BString), CalendarError>>> Iterator for Calendars<I> {
+    type Item = Result<Vec<(usize, BString)>, CalendarError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut calendar = match self.pending.take() {
+            Some(begin) => vec![begin],
+            None => loop {
+                match self.lines.next()? {
+                    Err(err) => return Some(Err(err)),
+                    Ok((_, line)) if line.is_empty() => {}
+                    Ok((n, line)) if is_calendar_line(&line, true) => break vec![(n, line)],
+                    Ok((n, _)) => return Some(Err(CalendarError::OutsideCalendar(n))),
+                }
+            },
+        };
+        let start = calendar[0].0;
+        loop {
+            match self.lines.next() {
+                None => return Some(Err(CalendarError::UnterminatedCalendar(start))),
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok((n, line))) => {
+                    if is_calendar_line(&line, true) {
+                        self.pending = Some((n, line));
+                        return Some(Err(CalendarError::UnterminatedCalendar(start)));
+                    }
+                    let end = is_calendar_line(&line, false);
+                    calendar.push((n, line));
+                    if end {
+                        return Some(Ok(calendar));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What a content line is, judged by its name alone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind<'a> {
+    /// A `BEGIN` line, with the component name as written
+    BeginComponent(&'a [u8]),
+    /// An `END` line, with the component name as written
+    EndComponent(&'a [u8]),
+    /// Any other line, with the property name (empty if the line doesn't start with one)
+    Property(&'a [u8]),
+}
+
+/// Classify a content line without preparsing it: only the bytes up to the first `;` or `:`
+/// are looked at, or, for `BEGIN` and `END`, the whole line. A `BEGIN` or `END` with
+/// parameters (which RFC 5545 doesn't allow) counts as a property.
+#[must_use]
+pub fn classify_line(line: &[u8]) -> LineKind<'_> {
+    let end = memchr2(b';', b':', line).unwrap_or(line.len());
+    let (name, rest) = line.split_at(end);
+    match rest.split_first() {
+        Some((b':', component)) if name.eq_ignore_ascii_case(b"BEGIN") => {
+            LineKind::BeginComponent(component)
+        }
+        Some((b':', component)) if name.eq_ignore_ascii_case(b"END") => {
+            LineKind::EndComponent(component)
+        }
+        _ => LineKind::Property(name),
+    }
+}
+
+// Is `line` `BEGIN:VCALENDAR` (or, if not `begin`, `END:VCALENDAR`), ignoring case?
+fn is_calendar_line(line: &[u8], begin: bool) -> bool {
+    match classify_line(line) {
+        LineKind::BeginComponent(name) if begin => name.eq_ignore_ascii_case(b"VCALENDAR"),
+        LineKind::EndComponent(name) if !begin => name.eq_ignore_ascii_case(b"VCALENDAR"),
+        _ => false,
+    }
+}
+
+impl<R: io::BufRead, F: FnMut(&Progress) -> bool> Iterator for ContentLines<R, F> {
     type Item = Result<(usize, BString), CalendarError>;
 
     fn next(&mut self) -> Option<Result<(usize, BString), CalendarError>> {
+        if self.cancelled {
+            return None;
+        }
+        if !(self.should_continue)(&self.progress) {
+            self.cancelled = true;
+            return Some(Err(CalendarError::Cancelled(self.progress)));
+        }
         let mut buf = vec![];
-        match read_content_line_u8(&mut self.r, &mut buf) {
+        let mut bare_lf = None;
+        match read_content_line(&mut self.r, &mut buf, &mut bare_lf) {
             Err(e) => Some(Err(e.into())),
             Ok(0) => None,
             Ok(n) => {
-                let start_of_content_line = self.lines_read;
-                self.lines_read += n;
-                Some(Ok((start_of_content_line, buf.into())))
+                let start_of_content_line = self.progress.lines_read + 1;
+                self.progress.lines_read += n;
+                self.progress.content_lines += 1;
+                match bare_lf {
+                    Some(line) if self.strict_crlf => {
+                        Some(Err(CalendarError::BareLineFeed(start_of_content_line + line - 1)))
+                    }
+                    _ => Some(Ok((start_of_content_line, buf.into()))),
+                }
             }
         }
     }
@@ -217,4 +380,79 @@ mod test {
         assert_eq!(lines, 1);
         assert_eq!(buf.as_bstr(), second);
     }
+    #[test]
+    fn strict_crlf() {
+        let input = "A:1\r\nB:2\nC:3\r\n 4\nD:5\r\nE:6";
+        let lines: Vec<_> = (io::Cursor::new(input.as_bytes()).content_lines().strict_crlf())
+            .map(|line| line.map(|(n, _)| n).map_err(|err| err.to_string()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                Ok(1),
+                Err(CalendarError::BareLineFeed(2).to_string()),
+                Err(CalendarError::BareLineFeed(4).to_string()),
+                Ok(5),
+                Ok(6),
+            ]
+        );
+        // Lenient by default
+        assert_eq!(content_lines(input).len(), 5);
+    }
+    fn calendars(input: &str) -> Vec<Result<Vec<usize>, String>> {
+        (io::Cursor::new(input.as_bytes()).content_lines().calendars())
+            .map(|calendar| match calendar {
+                Ok(lines) => Ok(lines.into_iter().map(|(n, _)| n).collect()),
+                Err(err) => Err(err.to_string()),
+            })
+            .collect()
+    }
+    #[test]
+    fn concatenated_calendars() {
+        let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nEND:VCALENDAR\r\n\r\n\
+                     begin:vcalendar\r\nBEGIN:VEVENT\r\nEND:VEVENT\r\nEnd:VCalendar\r\n";
+        assert_eq!(calendars(input), vec![Ok(vec![1, 2, 3]), Ok(vec![5, 6, 7, 8])]);
+        assert_eq!(calendars(""), vec![]);
+    }
+    #[test]
+    fn line_kinds() {
+        assert_eq!(classify_line(b"begin:VEvent"), LineKind::BeginComponent(b"VEvent"));
+        assert_eq!(classify_line(b"END:VALARM"), LineKind::EndComponent(b"VALARM"));
+        assert_eq!(classify_line(b"DTSTART;TZID=X:1"), LineKind::Property(b"DTSTART"));
+        assert_eq!(classify_line(b"BEGIN;X=1:VEVENT"), LineKind::Property(b"BEGIN"));
+        assert_eq!(classify_line(b"BEGINNING:soon"), LineKind::Property(b"BEGINNING"));
+        assert_eq!(classify_line(b"X-NO-COLON"), LineKind::Property(b"X-NO-COLON"));
+        assert_eq!(classify_line(b""), LineKind::Property(b""));
+    }
+    #[test]
+    fn calendar_structure_errors() {
+        let input = "X-JUNK:1\r\nBEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+                     BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\nBEGIN:VCALENDAR\r\n";
+        assert_eq!(
+            calendars(input),
+            vec![
+                Err(CalendarError::OutsideCalendar(1).to_string()),
+                Err(CalendarError::UnterminatedCalendar(2).to_string()),
+                Ok(vec![4, 5]),
+                Err(CalendarError::UnterminatedCalendar(6).to_string()),
+            ]
+        );
+    }
+    #[test]
+    fn cancel_after_two_content_lines() {
+        let input = "A:1\r\nB:2\r\n  folded\r\nC:3\r\n";
+        let mut calls = 0;
+        let mut lines = io::Cursor::new(input.as_bytes()).content_lines_with(|progress| {
+            calls += 1;
+            progress.content_lines < 2
+        });
+        assert_eq!(lines.next().unwrap().unwrap().0, 1);
+        assert_eq!(lines.next().unwrap().unwrap().0, 2);
+        let Some(Err(CalendarError::Cancelled(progress))) = lines.next() else {
+            panic!("Expected cancellation")
+        };
+        assert_eq!(progress, Progress { lines_read: 3, content_lines: 2 });
+        assert!(lines.next().is_none());
+        assert_eq!(calls, 3);
+    }
 }
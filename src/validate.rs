@@ -0,0 +1,205 @@
+//! # Parameter validation
+//! Each parameter is defined for particular properties — `PARTSTAT` for `ATTENDEE`, `RANGE`
+//! for `RECURRENCE-ID`, and so on — and most may have only one value. A parameter anywhere
+//! else is meaningless, and usually means the producer has a bug. (The tables come from
+//! `assets/parameters.kdl`.)
+//!
+//! Some parameters also constrain each other:
+//!  * `ENCODING=BASE64` and `VALUE=BINARY` go together (RFC 5545 § 3.3.1);
+//!  * `VALUE` must name a type the property may have (`assets/properties.kdl`); and
+//!  * `TZID` doesn't apply to `DATE` values (RFC 5545 § 3.2.19).
+use crate::names::{Lookup, PropertyId};
+use crate::parameter::Value;
+use crate::preparse::{Param, Prop};
+use crate::property::allows_value_type;
+use std::ops::Range;
+use thiserror::Error;
+
+/// A parameter that's misused: its property, and the spans (in the property's content line)
+/// of the property name and of the parameter, from its name through its last value
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterProblem {
+    /// The index (in the `calendar` slice) of the property
+    pub index: usize,
+    pub property: Range<usize>,
+    pub parameter: Range<usize>,
+    pub misuse: ParameterMisuse,
+}
+
+/// How a parameter is misused
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ParameterMisuse {
+    #[error("the parameter doesn't apply to this property")]
+    NotApplicable,
+    #[error("the parameter may have only one value")]
+    MultipleValues,
+    #[error("ENCODING=BASE64 requires VALUE=BINARY")]
+    EncodingWithoutBinary,
+    #[error("VALUE=BINARY requires ENCODING=BASE64")]
+    BinaryWithoutEncoding,
+    #[error("the property can't have this VALUE type")]
+    ValueTypeNotAllowed,
+    #[error("TZID doesn't apply to DATE values")]
+    TzidOnDate,
+}
+impl ParameterMisuse {
+    /// The problem's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParameterMisuse::NotApplicable => crate::catalog::PARAMETER_NOT_APPLICABLE,
+            ParameterMisuse::MultipleValues => crate::catalog::PARAMETER_NOT_MULTI_VALUED,
+            ParameterMisuse::EncodingWithoutBinary | ParameterMisuse::BinaryWithoutEncoding => {
+                crate::catalog::ENCODING_VALUE_MISMATCH
+            }
+            ParameterMisuse::ValueTypeNotAllowed => crate::catalog::VALUE_TYPE_NOT_ALLOWED,
+            ParameterMisuse::TzidOnDate => crate::catalog::TZID_ON_DATE,
+        }
+    }
+}
+
+/// Check every parameter in `calendar` (the preparsed content lines of an iCalendar object),
+/// returning the ones on properties they don't apply to, with more values than they may have,
+/// or in combinations that don't make sense (see the module documentation).
+#[must_use]
+pub fn validate_parameters(calendar: &[Prop], lookup: &mut Lookup) -> Vec<ParameterProblem> {
+    let mut problems = Vec::new();
+    for (index, prop) in calendar.iter().enumerate() {
+        // Preparsed names are well-formed, so interning them never fails
+        let Ok(property) = lookup.property_id(prop.name.val()) else { continue };
+        let mut problem = |param, misuse| {
+            problems.push(ParameterProblem {
+                index,
+                property: prop.name.loc..prop.name.loc + prop.name.val().len(),
+                parameter: span(param),
+                misuse,
+            });
+        };
+        for param in prop.parameters() {
+            let Ok(id) = lookup.parameter_id(param.name().val()) else { continue };
+            if !id.applies_to(property) {
+                problem(param, ParameterMisuse::NotApplicable);
+            }
+            if param.values().len() > 1 && !id.is_multi_valued() {
+                problem(param, ParameterMisuse::MultipleValues);
+            }
+        }
+        check_combinations(prop, property, &mut problem);
+    }
+    problems
+}
+
+// Check the parameters of `prop` (whose id is `property`) against each other
+fn check_combinations<'p>(
+    prop: &'p Prop,
+    property: PropertyId,
+    problem: &mut impl FnMut(&'p Param<'p>, ParameterMisuse),
+) {
+    let find = |name: &str| {
+        prop.parameters().iter().find(|param| param.name().val().eq_ignore_ascii_case(name))
+    };
+    let first_value = |param: &Param<'p>| param.values().first().map(|value| value.val());
+    let value = find("VALUE");
+    let value_type = value.and_then(first_value).map(Value::from);
+    let encoding = find("ENCODING");
+    let base64 = encoding.and_then(first_value).is_some_and(|e| e.eq_ignore_ascii_case("BASE64"));
+    match (value, encoding) {
+        (_, Some(encoding)) if base64 && value_type != Some(Value::Binary) => {
+            problem(encoding, ParameterMisuse::EncodingWithoutBinary);
+        }
+        (Some(value), _) if !base64 && value_type == Some(Value::Binary) => {
+            problem(value, ParameterMisuse::BinaryWithoutEncoding);
+        }
+        _ => {}
+    }
+    if let (Some(value), Some(value_type)) = (value, &value_type) {
+        if !allows_value_type(property, value_type) {
+            problem(value, ParameterMisuse::ValueTypeNotAllowed);
+        }
+        if let (Value::Date, Some(tzid)) = (value_type, find("TZID")) {
+            problem(tzid, ParameterMisuse::TzidOnDate);
+        }
+    }
+}
+
+// The span of a parameter, from its name through its last value
+fn span(param: &Param) -> Range<usize> {
+    let start = param.name().loc;
+    let end = param
+        .values()
+        .last()
+        .map_or(start + param.name().val().len(), |value| value.loc + value.val().len());
+    start..end
+}
+
+#[cfg(all(test, feature = "cautious"))]
+mod test {
+    use super::*;
+    use crate::preparse::cautious_preparse;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn misused_parameters_are_reported() {
+        let lines = [
+            "ATTENDEE;PARTSTAT=ACCEPTED;MEMBER=\"mailto:a@b.c\",\"mailto:d@e.f\":mailto:g@h.i",
+            "SUMMARY;PARTSTAT=ACCEPTED;X-FOO=1,2:Lunch",
+            "X-BAR;PARTSTAT=ACCEPTED:Anything goes",
+            "ATTENDEE;ROLE=CHAIR,REQ-PARTICIPANT:mailto:g@h.i",
+        ];
+        let calendar: Vec<_> =
+            lines.iter().map(|line| cautious_preparse(line.as_bytes()).unwrap()).collect();
+        let problems = validate_parameters(&calendar, &mut Lookup::new());
+        let problem = |index, property, parameter, misuse| ParameterProblem {
+            index,
+            property,
+            parameter,
+            misuse,
+        };
+        assert_eq!(
+            problems,
+            vec![
+                problem(1, 0..7, 8..25, ParameterMisuse::NotApplicable),
+                problem(3, 0..8, 9..35, ParameterMisuse::MultipleValues),
+            ]
+        );
+        assert_eq!(&lines[3][9..35], "ROLE=CHAIR,REQ-PARTICIPANT");
+        assert_eq!(problems[0].misuse.code(), "RCAL0302");
+    }
+    #[test]
+    fn parameter_combinations() {
+        let lines = [
+            "ATTACH;ENCODING=BASE64;VALUE=BINARY:AAAA",
+            "ATTACH;ENCODING=BASE64:AAAA",
+            "ATTACH;VALUE=BINARY:AAAA",
+            "SUMMARY;VALUE=DATE:20250101",
+            "DTSTART;VALUE=DATE;TZID=Europe/Paris:20250101",
+            "RANGE;X-FOO=1:20250101",
+            "RECURRENCE-ID;RANGE=THISANDFUTURE:20250101T090000Z",
+            "DTSTART;RANGE=THISANDFUTURE:20250101T090000Z",
+        ];
+        let calendar: Vec<_> =
+            lines.iter().map(|line| cautious_preparse(line.as_bytes()).unwrap()).collect();
+        let problems = validate_parameters(&calendar, &mut Lookup::new());
+        let found: Vec<_> = problems
+            .iter()
+            .map(|p| {
+                (
+                    p.index,
+                    &lines[p.index][p.property.clone()],
+                    &lines[p.index][p.parameter.clone()],
+                    p.misuse,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "ATTACH", "ENCODING=BASE64", ParameterMisuse::EncodingWithoutBinary),
+                (2, "ATTACH", "VALUE=BINARY", ParameterMisuse::BinaryWithoutEncoding),
+                (3, "SUMMARY", "VALUE=DATE", ParameterMisuse::ValueTypeNotAllowed),
+                (4, "DTSTART", "TZID=Europe/Paris", ParameterMisuse::TzidOnDate),
+                (7, "DTSTART", "RANGE=THISANDFUTURE", ParameterMisuse::NotApplicable),
+            ]
+        );
+    }
+}
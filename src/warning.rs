@@ -0,0 +1,137 @@
+//! # Warnings
+//! Non-fatal observations made while parsing: the parse succeeded, but something about the
+//! input was odd. Parsing entry points that take a `&mut dyn Warnings` report each observation
+//! along with the span of input it applies to.
+use crate::catalog;
+use std::fmt;
+use std::ops::Range;
+
+/// What we noticed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Observation {
+    /// RFC 5545 names and keywords are case-insensitive, but conventionally uppercase; we
+    /// accepted a lowercase (or mixed-case) spelling.
+    NormalizedCase,
+    /// A Windows-1252 character in otherwise UTF-8 text was replaced by its Unicode
+    /// equivalent (see `sanitize::sanitize_cp1252`).
+    Cp1252Replaced,
+    /// An RRULE had a rule part we don't recognize; lenient parsing kept it as an extension
+    /// (see `rrule::parse_rrule_lenient`).
+    UnknownRulePartKept,
+    /// An RRULE had `INTERVAL=0`, which would never advance; lenient parsing treated it as
+    /// `INTERVAL=1`.
+    ZeroIntervalReplaced,
+    /// An RRULE had `COUNT=0`. That's legal syntax, and we take it to mean the rule has no
+    /// instances, but no producer following RFC 5545 should write it.
+    ZeroCount,
+    /// `DTSTART` doesn't satisfy the `BYxxx` parts of its RRULE. It's still the first
+    /// instance, as RFC 5545 requires, but the mismatch often means the producer has a bug.
+    DtstartNotInRule,
+    /// A calendar defines the same `TZID` more than once, with different observances (see
+    /// `timezone::dedup_timezones`).
+    ConflictingTimezones,
+}
+impl Observation {
+    /// The observation's stable code (see `catalog`)
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Observation::NormalizedCase => catalog::NORMALIZED_CASE,
+            Observation::Cp1252Replaced => catalog::CP1252_REPLACED,
+            Observation::UnknownRulePartKept => catalog::UNKNOWN_RULE_PART_KEPT,
+            Observation::ZeroIntervalReplaced => catalog::ZERO_INTERVAL_REPLACED,
+            Observation::ZeroCount => catalog::ZERO_COUNT,
+            Observation::DtstartNotInRule => catalog::DTSTART_NOT_IN_RULE,
+            Observation::ConflictingTimezones => catalog::CONFLICTING_TIMEZONES,
+        }
+    }
+}
+impl fmt::Display for Observation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Observation::NormalizedCase => write!(f, "accepted a name or keyword not in uppercase"),
+            Observation::Cp1252Replaced => {
+                write!(f, "replaced a Windows-1252 character with its Unicode equivalent")
+            }
+            Observation::UnknownRulePartKept => {
+                write!(f, "kept an unrecognized rule part as an extension")
+            }
+            Observation::ZeroIntervalReplaced => write!(f, "treated INTERVAL=0 as INTERVAL=1"),
+            Observation::ZeroCount => write!(f, "accepted COUNT=0, which means no instances"),
+            Observation::DtstartNotInRule => {
+                write!(f, "DTSTART doesn't match its RRULE, but is the first instance anyway")
+            }
+            Observation::ConflictingTimezones => {
+                write!(f, "kept one of several conflicting definitions of a VTIMEZONE")
+            }
+        }
+    }
+}
+
+/// An `Observation`, along with the byte offsets of the input it applies to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub observation: Observation,
+    pub span: Range<usize>,
+    /// For a warning about a whole calendar, the input line the (unfolded) content line
+    /// `span` is in starts on, counting from 1
+    pub line: Option<usize>,
+}
+impl Warning {
+    #[must_use]
+    pub fn new(observation: Observation, span: Range<usize>) -> Self {
+        Warning { observation, span, line: None }
+    }
+    /// The warning, with `span` in the content line starting on input line `line`
+    #[must_use]
+    pub fn at_line(self, line: usize) -> Self {
+        Warning { line: Some(line), ..self }
+    }
+}
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => {
+                write!(f, "{} on line {line}, at index {}", self.observation, self.span.start)
+            }
+            None => write!(f, "{} at index {}", self.observation, self.span.start),
+        }
+    }
+}
+
+/// A sink for `Warning`s. Use a `Vec<Warning>` to collect them, or `IgnoreWarnings` to
+/// discard them.
+pub trait Warnings {
+    fn warn(&mut self, warning: Warning);
+}
+impl Warnings for Vec<Warning> {
+    fn warn(&mut self, warning: Warning) {
+        self.push(warning);
+    }
+}
+
+// A `Warnings` sink that notes the input line of each warning (see `Warning::at_line`)
+// before passing it on
+pub(crate) struct AtLine<'a> {
+    pub(crate) line: usize,
+    pub(crate) warnings: &'a mut dyn Warnings,
+}
+impl Warnings for AtLine<'_> {
+    fn warn(&mut self, warning: Warning) {
+        self.warnings.warn(warning.at_line(self.line));
+    }
+}
+
+/// A `Warnings` sink that discards everything
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IgnoreWarnings;
+impl Warnings for IgnoreWarnings {
+    fn warn(&mut self, _warning: Warning) {}
+}
+
+// Warn (with a span starting at `start`) if `text` has any lowercase ASCII letters
+pub(crate) fn warn_if_lowercase(warnings: &mut dyn Warnings, text: &[u8], start: usize) {
+    if text.iter().any(u8::is_ascii_lowercase) {
+        warnings.warn(Warning::new(Observation::NormalizedCase, start..start + text.len()));
+    }
+}
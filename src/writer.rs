@@ -0,0 +1,375 @@
+//! # Writing content lines
+//! The inverse of `unfolded`: write content lines, folding those longer than 75 octets as
+//! [RFC 5545 § 3.1](https://datatracker.ietf.org/doc/html/rfc5545#section-3.1) requires.
+//! Folds never split a UTF-8 sequence. `write_content_line` writes a single line to any
+//! `io::Write`; `ContentWriter` wraps a writer for streaming a whole calendar.
+use crate::names::{Caseless, name_eq};
+use crate::parameter::Value;
+use crate::property::{NewlineEscape, PropertyValue, default_value_type_of};
+use std::borrow::Cow;
+use std::io;
+
+/// The longest a physical line may be, in octets, not counting the line ending
+pub const MAX_LINE_OCTETS: usize = 75;
+
+/// The line ending written after each physical line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `b"\r\n"`, as RFC 5545 requires
+    #[default]
+    Crlf,
+    /// A bare `b'\n'`, for pipelines that need it
+    Lf,
+}
+
+impl LineEnding {
+    #[must_use]
+    pub const fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Crlf => b"\r\n",
+            LineEnding::Lf => b"\n",
+        }
+    }
+}
+
+/// The most decimal places `ContentWriter::write_value` writes for `GEO` values by default:
+/// RFC 5545 § 3.8.1.6 recommends six, which is precise to about a tenth of a meter
+pub const GEO_DECIMALS: usize = 6;
+
+/// Writes content lines to `W`, folding and terminating each one
+#[derive(Debug)]
+pub struct ContentWriter<W> {
+    line_ending: LineEnding,
+    // The most decimal places to write for each property's floats
+    max_decimals: Vec<(Caseless<String>, usize)>,
+    newline_escape: NewlineEscape,
+    w: W,
+}
+
+impl<W: io::Write> ContentWriter<W> {
+    /// A writer using CRLF line endings
+    pub fn new(w: W) -> Self {
+        let max_decimals = vec![(Caseless("GEO".to_string()), GEO_DECIMALS)];
+        let (line_ending, newline_escape) = (LineEnding::default(), NewlineEscape::default());
+        ContentWriter { line_ending, max_decimals, newline_escape, w }
+    }
+    /// Use `line_ending` instead of CRLF
+    #[must_use]
+    pub fn line_ending(self, line_ending: LineEnding) -> Self {
+        ContentWriter { line_ending, ..self }
+    }
+    /// Escape newlines in the `TEXT` values `write_value` writes as `newline_escape` says
+    /// (rather than as `\n`)
+    #[must_use]
+    pub fn newline_escape(self, newline_escape: NewlineEscape) -> Self {
+        ContentWriter { newline_escape, ..self }
+    }
+    /// Round `FLOAT` values of property `name` to at most `decimals` decimal places (`GEO`
+    /// starts out with `GEO_DECIMALS`; other properties' floats are written in full)
+    #[must_use]
+    pub fn max_decimals(mut self, name: &str, decimals: usize) -> Self {
+        self.max_decimals.retain(|(n, _)| *n != Caseless(name));
+        self.max_decimals.push((Caseless(name.to_string()), decimals));
+        self
+    }
+
+    /// Write one (unfolded) content line, without its line ending.
+    ///
+    /// # Errors
+    /// Returns any error from the underlying writer.
+    pub fn write_content_line(&mut self, line: &[u8]) -> io::Result<()> {
+        write_folded(&mut self.w, line, self.line_ending)
+    }
+
+    /// Write a property from its name, parameters, and (already escaped) value, quoting
+    /// parameter values as needed (see `quote_param_value`). Each parameter is a name and its
+    /// values, which are written separated by commas.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a parameter value contains a double quote or a
+    /// control character, which no parameter value can represent, and otherwise any error
+    /// from the underlying writer.
+    pub fn write_property(
+        &mut self,
+        name: &str,
+        parameters: &[(&str, &[&str])],
+        value: &str,
+    ) -> io::Result<()> {
+        let mut line = String::from(name);
+        for (param, values) in parameters {
+            line.push(';');
+            line.push_str(param);
+            line.push('=');
+            for (n, value) in values.iter().enumerate() {
+                if n > 0 {
+                    line.push(',');
+                }
+                let quoted = quote_value_of(param, value).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{param} value {value:?} can't be written as a parameter value"),
+                    )
+                })?;
+                line.push_str(&quoted);
+            }
+        }
+        line.push(':');
+        line.push_str(value);
+        self.write_content_line(line.as_bytes())
+    }
+
+    /// Write a property with a typed value, adding the parameters the value needs unless
+    /// `parameters` already has them: `TZID` for date-times in a time zone, and `VALUE` if
+    /// the value's type isn't the property's default (`TEXT`, for properties we don't know).
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error for a float that isn't finite, and otherwise fails
+    /// like `write_property`.
+    pub fn write_value(
+        &mut self,
+        name: &str,
+        parameters: &[(&str, &[&str])],
+        value: &PropertyValue,
+    ) -> io::Result<()> {
+        if let PropertyValue::Float(floats) = value
+            && !floats.iter().all(|x| x.is_finite())
+        {
+            let message = format!("{name} has a float that isn't finite");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        let has = |wanted: &str| parameters.iter().any(|(p, _)| p.eq_ignore_ascii_case(wanted));
+        let default = default_value_type_of(name).unwrap_or(&Value::Text);
+        let value_type = [value.type_name()];
+        let tzid = value.tzid();
+        let tzid = tzid.as_ref().map(|tzid| [tzid.as_ref()]);
+        let mut all = parameters.to_vec();
+        if !has("VALUE") && Value::from(value.type_name()) != *default {
+            all.push(("VALUE", &value_type));
+        }
+        if let Some(tzid) = &tzid
+            && !has("TZID")
+        {
+            all.push(("TZID", tzid));
+        }
+        let max_decimals = (self.max_decimals.iter())
+            .find(|(n, _)| *n == Caseless(name))
+            .map(|(_, decimals)| *decimals);
+        let formatted = value.formatted(name, max_decimals).newline_escape(self.newline_escape);
+        let text = formatted.to_string();
+        self.write_property(name, &all, &text)
+    }
+
+    /// The underlying writer
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+}
+
+/// Write one (unfolded) content line to `w`, folded at 75 octets and ended with CRLF.
+///
+/// # Errors
+/// Returns any error from `w`.
+pub fn write_content_line<W: io::Write>(w: &mut W, line: &[u8]) -> io::Result<()> {
+    write_folded(w, line, LineEnding::Crlf)
+}
+
+fn write_folded<W: io::Write>(w: &mut W, line: &[u8], line_ending: LineEnding) -> io::Result<()> {
+    let ending = line_ending.as_bytes();
+    let (mut rest, mut room) = (line, MAX_LINE_OCTETS);
+    loop {
+        let end = fold_point(rest, room);
+        w.write_all(&rest[..end])?;
+        w.write_all(ending)?;
+        rest = &rest[end..];
+        if rest.is_empty() {
+            return Ok(());
+        }
+        w.write_all(b" ")?;
+        room = MAX_LINE_OCTETS - 1;
+    }
+}
+
+// Parameters whose values RFC 5545 requires to be quoted, whatever they contain
+const ALWAYS_QUOTED: [&str; 6] =
+    ["ALTREP", "DELEGATED-FROM", "DELEGATED-TO", "DIR", "MEMBER", "SENT-BY"];
+
+/// `value` as it should be written as a parameter value: unchanged if it's valid unquoted,
+/// and in double quotes if it contains a `;`, `:`, or `,` (which would end an unquoted
+/// value) or a space (which is valid, but trips up some consumers). Returns `None` if
+/// `value` contains a double quote or a control character other than tab, which can't
+/// appear even in a quoted value.
+#[must_use]
+pub fn quote_param_value(value: &str) -> Option<Cow<'_, str>> {
+    if !representable(value) {
+        None
+    } else if value.contains([';', ':', ',', ' ']) {
+        Some(Cow::Owned(format!("\"{value}\"")))
+    } else {
+        Some(Cow::Borrowed(value))
+    }
+}
+
+// `value` as it should be written as a value of parameter `param`, which some parameters
+// always quote
+pub(crate) fn quote_value_of<'a>(param: &str, value: &'a str) -> Option<Cow<'a, str>> {
+    if ALWAYS_QUOTED.iter().any(|q| name_eq(q, param)) {
+        representable(value).then(|| Cow::Owned(format!("\"{value}\"")))
+    } else {
+        quote_param_value(value)
+    }
+}
+
+fn representable(value: &str) -> bool {
+    !value.chars().any(|c| c == '"' || (c.is_ascii_control() && c != '\t'))
+}
+
+// The length of the longest prefix of `v` that fits in `room` octets without splitting a
+// UTF-8 sequence. Always at least one octet, so folding makes progress.
+fn fold_point(v: &[u8], room: usize) -> usize {
+    if v.len() <= room {
+        return v.len();
+    }
+    // Back up over continuation bytes (0b10xx_xxxx) to the start of a character
+    match (1..=room).rev().find(|&end| v[end] & 0xC0 != 0x80) {
+        Some(end) => end,
+        None => room,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bstr::ByteSlice;
+    use pretty_assertions::assert_eq;
+
+    fn written(line: &[u8], line_ending: LineEnding) -> String {
+        let mut writer = ContentWriter::new(Vec::new()).line_ending(line_ending);
+        writer.write_content_line(line).unwrap();
+        writer.into_inner().to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!(written(b"VERSION:2.0", LineEnding::default()), "VERSION:2.0\r\n");
+        assert_eq!(written(b"VERSION:2.0", LineEnding::Lf), "VERSION:2.0\n");
+    }
+    #[test]
+    fn long_lines_are_folded() {
+        let line = format!("DESCRIPTION:{}", "x".repeat(140));
+        let folded = written(line.as_bytes(), LineEnding::Crlf);
+        let physical: Vec<_> = folded.split("\r\n").map(str::len).collect();
+        assert_eq!(physical, vec![75, 75, 4, 0]);
+        assert_eq!(folded.replace("\r\n ", ""), format!("{line}\r\n"));
+    }
+    #[test]
+    fn folds_dont_split_characters() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = written(line.as_bytes(), LineEnding::Lf);
+        let first = folded.split('\n').next().unwrap();
+        assert_eq!(first.len(), 74);
+        assert_eq!(folded.replace("\n ", ""), format!("{line}\n"));
+    }
+    #[test]
+    fn write_content_line_without_a_content_writer() {
+        let line = format!("DESCRIPTION:{}", "x".repeat(100));
+        let mut out = Vec::new();
+        write_content_line(&mut out, line.as_bytes()).unwrap();
+        assert_eq!(out.to_str().unwrap(), written(line.as_bytes(), LineEnding::Crlf));
+    }
+    #[test]
+    fn minimal_quoting() {
+        let quoted = |value| quote_param_value(value).map(Cow::into_owned);
+        assert!(matches!(quote_param_value("America/New_York"), Some(Cow::Borrowed(_))));
+        assert_eq!(quoted("Jane Doe").as_deref(), Some(r#""Jane Doe""#));
+        assert_eq!(quoted("Doe, Jane").as_deref(), Some(r#""Doe, Jane""#));
+        assert_eq!(
+            quoted("/example.com/Zone: East").as_deref(),
+            Some(r#""/example.com/Zone: East""#)
+        );
+        assert_eq!(quoted("Zoë").as_deref(), Some("Zoë"));
+        assert_eq!(quoted(r#"Jane "JD" Doe"#), None);
+        assert_eq!(quoted("Jane\nDoe"), None);
+    }
+    #[test]
+    fn write_property() {
+        let mut writer = ContentWriter::new(Vec::new());
+        let cn: &[&str] = &["Doe, Jane"];
+        let tzid: &[&str] = &["/example.com/My Zone"];
+        let altrep: &[&str] = &["cid:part1.0001@example.org"];
+        let member: &[&str] = &["mailto:a@example.com", "mailto:b@example.com"];
+        writer
+            .write_property("ATTENDEE", &[("CN", cn), ("MEMBER", member)], "mailto:j@x.y")
+            .unwrap();
+        writer.write_property("DTSTART", &[("TZID", tzid)], "20250303T090000").unwrap();
+        writer.write_property("DTEND", &[("TZID", &["Europe/Paris"])], "20250303T100000").unwrap();
+        writer.write_property("DESCRIPTION", &[("ALTREP", altrep)], "See attached").unwrap();
+        let written = writer.into_inner().to_str().unwrap().replace("\r\n ", "");
+        assert_eq!(
+            written.lines().collect::<Vec<_>>(),
+            vec![
+                r#"ATTENDEE;CN="Doe, Jane";MEMBER="mailto:a@example.com","mailto:b@example.com":mailto:j@x.y"#,
+                r#"DTSTART;TZID="/example.com/My Zone":20250303T090000"#,
+                "DTEND;TZID=Europe/Paris:20250303T100000",
+                r#"DESCRIPTION;ALTREP="cid:part1.0001@example.org":See attached"#,
+            ]
+        );
+        let mut writer = ContentWriter::new(Vec::new());
+        let err = writer.write_property("X", &[("CN", &["\"quoted\""])], "x").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+    #[test]
+    fn write_value() {
+        use crate::property::Values;
+        use jiff::civil::{date, datetime};
+        let mut writer = ContentWriter::new(Vec::new()).line_ending(LineEnding::Lf);
+        let start = datetime(2025, 3, 3, 9, 0, 0, 0).in_tz("Europe/Paris").unwrap();
+        writer.write_value("DTSTART", &[], &PropertyValue::from(start)).unwrap();
+        writer.write_value("dtend", &[], &PropertyValue::from(date(2025, 3, 4))).unwrap();
+        let summary = PropertyValue::from("Café, then a walk".to_string());
+        writer.write_value("SUMMARY", &[("LANGUAGE", &["fr"])], &summary).unwrap();
+        writer.write_value("X-COUNT", &[], &PropertyValue::from(1.5)).unwrap();
+        writer.write_value("X-NOTE", &[], &PropertyValue::from("x".to_string())).unwrap();
+        let geo = PropertyValue::Float(Values::from_vec(vec![37.386_013_4, -122.1]).unwrap());
+        writer.write_value("GEO", &[], &geo).unwrap();
+        let mut writer = writer.max_decimals("geo", 2).max_decimals("X-COUNT", 0);
+        writer.write_value("GEO", &[], &geo).unwrap();
+        writer.write_value("X-COUNT", &[], &PropertyValue::from(2.25)).unwrap();
+        let nan = writer.write_value("GEO", &[], &PropertyValue::from(f64::NAN)).unwrap_err();
+        assert_eq!(nan.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            writer.into_inner().to_str().unwrap().lines().collect::<Vec<_>>(),
+            vec![
+                "DTSTART;TZID=Europe/Paris:20250303T090000",
+                "dtend;VALUE=DATE:20250304",
+                r"SUMMARY;LANGUAGE=fr:Café\, then a walk",
+                "X-COUNT;VALUE=FLOAT:1.5",
+                "X-NOTE:x",
+                "GEO:37.386013;-122.1",
+                "GEO:37.39;-122.1",
+                "X-COUNT;VALUE=FLOAT:2",
+            ]
+        );
+    }
+    #[test]
+    fn newline_escapes() {
+        let description = PropertyValue::from("Agenda:\n1. Budget".to_string());
+        let mut writer = ContentWriter::new(Vec::new()).line_ending(LineEnding::Lf);
+        writer.write_value("DESCRIPTION", &[], &description).unwrap();
+        let mut writer = writer.newline_escape(NewlineEscape::Uppercase);
+        writer.write_value("DESCRIPTION", &[], &description).unwrap();
+        assert_eq!(
+            writer.into_inner().to_str().unwrap(),
+            "DESCRIPTION:Agenda:\\n1. Budget\nDESCRIPTION:Agenda:\\N1. Budget\n"
+        );
+    }
+    #[test]
+    fn round_trips_through_the_reader() {
+        use crate::unfolded::BufReadContent;
+        let line = format!("SUMMARY:{}", "日本語".repeat(30));
+        let folded = written(line.as_bytes(), LineEnding::Crlf);
+        let read: Vec<_> = (io::Cursor::new(folded.as_bytes()).content_lines().strict_crlf())
+            .map(|line| line.unwrap().1)
+            .collect();
+        assert_eq!(read, vec![line]);
+    }
+}
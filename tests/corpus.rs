@@ -0,0 +1,137 @@
+//! Golden-file tests: anonymized calendars in the style of real-world exporters, each with
+//! the quirks that exporter is known for.
+#![cfg(feature = "cautious")]
+use bstr::BString;
+use rcal::preparse::{Prop, cautious_preparse};
+use rcal::rrule::parse_rrule;
+use rcal::unfolded::BufReadContent;
+use winnow::Parser;
+
+struct Corpus {
+    lines: Vec<(usize, BString)>,
+}
+impl Corpus {
+    fn load(name: &str) -> Self {
+        let path = format!("{}/assets/corpus/{name}", env!("CARGO_MANIFEST_DIR"));
+        let input = std::fs::read(&path).unwrap();
+        let lines = std::io::Cursor::new(input).content_lines().map(Result::unwrap).collect();
+        Corpus { lines }
+    }
+    // Preparse every content line, panicking with the line number if any fails
+    fn props(&self) -> Vec<Prop<'_>> {
+        self.lines
+            .iter()
+            .map(|(n, line)| match cautious_preparse(line) {
+                Ok(prop) => prop,
+                Err(err) => panic!("line {n}: {err}"),
+            })
+            .collect()
+    }
+    fn values_of<'a>(props: &[Prop<'a>], name: &str) -> Vec<&'a str> {
+        props.iter().filter(|p| p.name.val() == name).map(|p| p.value().val()).collect()
+    }
+    fn count(props: &[Prop], name: &str, value: &str) -> usize {
+        Self::values_of(props, name).iter().filter(|v| **v == value).count()
+    }
+    fn param<'a>(prop: &Prop<'a>, name: &str) -> Vec<&'a str> {
+        prop.parameters()
+            .iter()
+            .filter(|p| p.name().val() == name)
+            .flat_map(|p| p.values().iter().map(|v| v.val()))
+            .collect()
+    }
+    // Every RRULE in the file must parse
+    fn check_rrules(props: &[Prop]) -> usize {
+        let rules = Self::values_of(props, "RRULE");
+        for rule in &rules {
+            let line = format!("{rule}\r\n");
+            assert!(parse_rrule.parse(line.as_bytes()).is_ok(), "RRULE:{rule}");
+        }
+        rules.len()
+    }
+}
+
+#[test]
+fn google() {
+    let corpus = Corpus::load("google.ics");
+    let props = corpus.props();
+    assert_eq!(Corpus::count(&props, "BEGIN", "VEVENT"), 2);
+    assert_eq!(Corpus::check_rrules(&props), 3);
+    assert_eq!(Corpus::values_of(&props, "SUMMARY"), ["Standup", "Offsite — planning day"]);
+    let description = Corpus::values_of(&props, "DESCRIPTION")[0];
+    assert!(description.ends_with("https://support.example.com/a/users/answer/9282720"));
+
+    // An attendee whose CN was folded in the middle of the parameter value
+    let bob = props.iter().find(|p| p.value().val() == "mailto:bob@example.com").unwrap();
+    assert_eq!(Corpus::param(bob, "CN"), ["Bob Example"]);
+    assert_eq!(Corpus::param(bob, "X-NUM-GUESTS"), ["0"]);
+    let start = props.iter().find(|p| p.name.val() == "DTSTART" && p.parameters().len() == 1);
+    assert_eq!(Corpus::param(start.unwrap(), "TZID"), ["America/Los_Angeles"]);
+}
+
+#[test]
+fn outlook() {
+    let corpus = Corpus::load("outlook.ics");
+    let props = corpus.props();
+    assert_eq!(Corpus::count(&props, "BEGIN", "VEVENT"), 1);
+    assert_eq!(Corpus::count(&props, "BEGIN", "VALARM"), 1);
+    assert_eq!(Corpus::check_rrules(&props), 2);
+
+    // Windows zone names, with spaces and no quotes
+    let start = props.iter().find(|p| p.name.val() == "DTSTART" && !p.parameters().is_empty());
+    assert_eq!(Corpus::param(start.unwrap(), "TZID"), ["Pacific Standard Time"]);
+    // Quoted CNs containing commas, one folded inside the quotes
+    let organizer = props.iter().find(|p| p.name.val() == "ORGANIZER").unwrap();
+    assert_eq!(Corpus::param(organizer, "CN"), ["Example, Dana"]);
+    let erin = props.iter().find(|p| p.value().val() == "mailto:erin@example.com").unwrap();
+    assert_eq!(Corpus::param(erin, "CN"), ["Example, Erin"]);
+    // A property value folded in the middle of "mailto"
+    assert!(props.iter().any(|p| p.value().val() == "mailto:frank@example.com"));
+    let uid = "040000008200E00074C5B7101A82E00800000000D0E1F2A3B4C5D60100000000000000001000000\
+               12345678901234567890ABCDEF";
+    assert_eq!(Corpus::values_of(&props, "UID"), [uid]);
+}
+
+#[test]
+fn apple() {
+    let corpus = Corpus::load("apple.ics");
+    let props = corpus.props();
+    assert_eq!(Corpus::count(&props, "BEGIN", "VEVENT"), 1);
+    assert_eq!(Corpus::check_rrules(&props), 3);
+    // Folded in the middle of a two-byte UTF-8 sequence
+    assert_eq!(Corpus::values_of(&props, "LOCATION"), [r"Müllerstraße 1\n80469 München\nGermany"]);
+    // Folded with a tab, inside a quoted parameter value
+    let structured = props.iter().find(|p| p.name.val() == "X-APPLE-STRUCTURED-LOCATION").unwrap();
+    assert_eq!(
+        Corpus::param(structured, "X-ADDRESS"),
+        [r"Müllerstraße 1\\n80469 München\\nGermany"]
+    );
+    assert_eq!(structured.value().val(), "geo:48.131811,11.569873");
+    assert_eq!(Corpus::values_of(&props, "SUMMARY"), ["Abendessen 🍽"]);
+}
+
+#[test]
+fn meetup() {
+    let corpus = Corpus::load("meetup.ics");
+    let props = corpus.props();
+    // Bare LF line endings
+    assert_eq!(corpus.lines.len(), 56);
+    assert_eq!(Corpus::count(&props, "BEGIN", "VEVENT"), 2);
+    assert_eq!(Corpus::check_rrules(&props), 2);
+    assert_eq!(Corpus::values_of(&props, "GEO"), ["40.741895;-73.989308"; 2]);
+    assert_eq!(Corpus::values_of(&props, "STATUS"), ["CONFIRMED", "TENTATIVE"]);
+    let description = Corpus::values_of(&props, "DESCRIPTION")[0];
+    assert!(description.contains(r"Join us for talks on async Rust\, followed by pizza."));
+}
+
+#[test]
+fn parse_whole_calendars() {
+    for name in ["apple.ics", "google.ics", "meetup.ics", "outlook.ics"] {
+        let path = format!("{}/assets/corpus/{name}", env!("CARGO_MANIFEST_DIR"));
+        let input = std::fs::read(&path).unwrap();
+        let calendar = rcal::parse_calendar(&input[..]).unwrap();
+        assert_eq!(calendar.component.name, "VCALENDAR", "{name}");
+        let vevents = calendar.component.components.iter().filter(|c| c.name == "VEVENT");
+        assert_eq!(calendar.events.len(), vevents.count(), "{name}");
+    }
+}
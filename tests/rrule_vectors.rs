@@ -0,0 +1,115 @@
+//! Load the recurrence test vectors in `assets/rrule_vectors.kdl` and check them against the
+//! rules they're for, and against the rules' expansions
+use anyhow::{Context, Result, anyhow, bail};
+use jiff::civil::{Date, DateTime};
+use kdl::{KdlDocument, KdlNode};
+use rcal::event::EventTime;
+use rcal::rrule::{RRule, parse_rrule};
+use winnow::Parser;
+
+const KDL: &str = include_str!("../assets/rrule_vectors.kdl");
+
+struct Vector {
+    name: String,
+    dtstart: EventTime,
+    rule: RRule,
+    occurrences: Vec<EventTime>,
+}
+
+fn load(kdl: &str) -> Result<Vec<Vector>> {
+    let kdl: KdlDocument = kdl.parse()?;
+    kdl.nodes().iter().map(|node| vector(node).with_context(|| format!("{node}"))).collect()
+}
+fn vector(node: &KdlNode) -> Result<Vector> {
+    if node.name().value() != "vector" {
+        bail!("Expected a `vector` node");
+    }
+    let name = node.get(0).and_then(|v| v.as_string()).context("The vector has no name")?;
+    let children = node.children().context("The vector has no children")?;
+    let child = |name: &str| children.get(name).with_context(|| format!("No `{name}` node"));
+
+    let dtstart = child("dtstart")?;
+    let tzid = dtstart.get("tzid").and_then(|v| v.as_string());
+    let [start] = strings(dtstart)?[..] else { bail!("Expected one DTSTART value") };
+    let [rule] = strings(child("rrule")?)?[..] else { bail!("Expected one RRULE value") };
+    let rule = parse_rrule
+        .parse(format!("{rule}\r\n").as_bytes())
+        .map_err(|e| anyhow!("Bad RRULE {rule}: {e:?}"))?;
+    let occurrences = strings(child("occurrences")?)?;
+    Ok(Vector {
+        name: name.to_string(),
+        dtstart: event_time(start, tzid)?,
+        rule,
+        occurrences: occurrences.iter().map(|o| event_time(o, tzid)).collect::<Result<_>>()?,
+    })
+}
+// The node's arguments, which must be strings
+fn strings(node: &KdlNode) -> Result<Vec<&str>> {
+    (node.entries().iter().filter(|e| e.name().is_none()))
+        .map(|e| e.value().as_string().context("Expected a string"))
+        .collect()
+}
+fn event_time(text: &str, tzid: Option<&str>) -> Result<EventTime> {
+    Ok(match (text.len(), tzid) {
+        (8, None) => EventTime::Date(Date::strptime("%Y%m%d", text)?),
+        (15, None) => EventTime::Floating(DateTime::strptime("%Y%m%dT%H%M%S", text)?),
+        (15, Some(tzid)) => {
+            EventTime::Zoned(DateTime::strptime("%Y%m%dT%H%M%S", text)?.in_tz(tzid)?)
+        }
+        _ => bail!("Expected yyyymmdd, or yyyymmddThhmmss with an optional TZID: {text}"),
+    })
+}
+fn civil(time: &EventTime) -> DateTime {
+    match time {
+        EventTime::Date(date) => date.to_datetime(jiff::civil::Time::midnight()),
+        EventTime::Floating(dt) => *dt,
+        EventTime::Zoned(zoned) => zoned.datetime(),
+    }
+}
+
+#[test]
+fn vectors_are_consistent() {
+    let vectors = load(KDL).unwrap();
+    assert!(vectors.len() >= 20);
+    for Vector { name, dtstart, rule, occurrences } in vectors {
+        assert_eq!(occurrences.first(), Some(&dtstart), "{name}");
+        assert!(occurrences.windows(2).all(|w| civil(&w[0]) < civil(&w[1])), "{name}");
+        assert_eq!(rule.check_until(&dtstart), Ok(()), "{name}");
+        let written = format!("{rule}\r\n");
+        assert_eq!(parse_rrule.parse(written.as_bytes()).ok(), Some(rule.clone()), "{name}");
+        for occurrence in &occurrences {
+            assert!(rule.matches(occurrence), "{name}: {occurrence:?}");
+        }
+    }
+}
+#[test]
+fn expansions_match_vectors() {
+    for Vector { name, dtstart, rule, occurrences } in load(KDL).unwrap() {
+        // Floating and all-day starts expand in UTC, which leaves their civil times unchanged
+        let start = match &dtstart {
+            EventTime::Zoned(zoned) => zoned.clone(),
+            _ => civil(&dtstart).in_tz("UTC").unwrap(),
+        };
+        let expected: Vec<_> = occurrences.iter().map(civil).collect();
+        let expansion = rule.iter(&start).map(|zoned| zoned.datetime());
+        // For a rule without COUNT or UNTIL, the vector lists only the first few
+        let actual: Vec<_> = if rule.is_finite() {
+            expansion.collect()
+        } else {
+            expansion.take(expected.len()).collect()
+        };
+        assert_eq!(actual, expected, "{name}");
+    }
+}
+#[test]
+fn bad_vectors() {
+    for bad in [
+        r#"vector { dtstart "20250101"; rrule "FREQ=DAILY"; occurrences "20250101"; }"#,
+        r#"vector "No rule" { dtstart "20250101"; occurrences "20250101"; }"#,
+        r#"vector "Bad rule" { dtstart "20250101"; rrule "FREQ=DAILY;BYDAY=XX"; occurrences; }"#,
+        r#"vector "Bad date" { dtstart "2025-01-01"; rrule "FREQ=DAILY"; occurrences; }"#,
+        r#"vector "Zoned date" { dtstart "20250101" tzid="UTC"; rrule "FREQ=DAILY"; occurrences; }"#,
+    ] {
+        assert!(load(bad).is_err(), "{bad}");
+    }
+}
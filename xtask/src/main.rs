@@ -0,0 +1,139 @@
+//! Development tasks, run with `cargo xtask <task>`:
+//!  * `codegen` generates Rust code from the KDL files in `assets`; and
+//!  * `codegen --check` fails if the generated code in `src` is out of date (a test runs this,
+//!    so CI catches a KDL change without regenerated code).
+use anyhow::{Context, Result, anyhow, bail};
+use kdl::{KdlDocument, KdlNode, KdlValue, NodeKey};
+use litemap::LiteMap;
+use std::fs;
+use std::path::Path;
+
+mod parameters;
+mod properties;
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["codegen"] => codegen(false),
+        ["codegen", "--check"] => codegen(true),
+        _ => bail!("Usage: cargo xtask codegen [--check]"),
+    }
+}
+
+// A code generator: it reads a KDL file and a template, and writes Rust code
+type Generator = fn(&mut Vec<u8>, &str, &str) -> Result<()>;
+// Each generator, with its KDL file, template, and output file
+const GENERATORS: [(Generator, &str, &str, &str); 2] = [
+    (parameters::generate, parameters::KDL, parameters::TEMPLATE, parameters::OUTPUT),
+    (properties::generate, properties::KDL, properties::TEMPLATE, properties::OUTPUT),
+];
+
+// Run each generator, and write its output to the output file — or, if `check` is true,
+// fail if the output file's contents are different.
+fn codegen(check: bool) -> Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().context("No workspace root")?;
+    let read = |path: &str| {
+        fs::read_to_string(root.join(path)).with_context(|| format!("Can't read {path}"))
+    };
+    for (generate, kdl, template, output) in GENERATORS {
+        let mut content = Vec::new();
+        generate(&mut content, &read(kdl)?, &read(template)?)
+            .with_context(|| format!("Can't generate {output} from {kdl}"))?;
+        let content = String::from_utf8(content)?;
+        let content = codegenrs::rustfmt(content, Some(&root.join("rustfmt.toml")))
+            .map_err(|e| anyhow!("Can't format {output}: {e}"))?;
+        codegenrs::write_str(&content, &root.join(output), check).map_err(|e| anyhow!("{e}"))?;
+    }
+    Ok(())
+}
+
+pub(crate) trait GetStr {
+    fn get(&self, key: impl Into<NodeKey>) -> Option<&KdlValue>;
+    fn maybe_get_str(&self, key: &'static str) -> Result<Option<String>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(KdlValue::String(s)) => Ok(Some(s.clone())),
+            Some(KdlValue::Integer(n)) => Ok(Some(format!("{n}"))),
+            _ => {
+                if key == "RFC" {
+                    Err(anyhow!("The RFC value must be number"))
+                } else {
+                    Err(anyhow!("The {key} value must be an integer"))
+                }
+            }
+        }
+    }
+    fn get_bool(&self, key: &'static str) -> Result<bool> {
+        match self.get(key) {
+            None => Ok(false),
+            Some(KdlValue::Bool(b)) => Ok(*b),
+            _ => Err(anyhow!("The {key} value must be #true or #false")),
+        }
+    }
+    fn get_str(&self, key: &'static str) -> Result<String> {
+        match self.maybe_get_str(key) {
+            Ok(None) => Err(anyhow!("Expected {key} value")),
+            Ok(Some(str)) => Ok(str),
+            Err(e) => Err(e),
+        }
+    }
+}
+impl GetStr for KdlNode {
+    fn get(&self, key: impl Into<NodeKey>) -> Option<&KdlValue> {
+        KdlNode::get(self, key)
+    }
+}
+// Write `index_of`, which finds a name's index in `NAMES` with a `match` on the name's length
+// and first byte, leaving at most a few names to compare with the name itself
+pub(crate) fn write_index_of<W: std::io::Write>(out: &mut W, names: &[String]) -> Result<()> {
+    let mut candidates = LiteMap::<(usize, u8), Vec<usize>>::new();
+    for (n, name) in names.iter().enumerate() {
+        let key = (name.len(), *name.as_bytes().first().context("Empty name")?);
+        match candidates.get_mut(&key) {
+            Some(indexes) => indexes.push(n),
+            None => drop(candidates.insert(key, vec![n])),
+        }
+    }
+    writeln!(out, "/// The index in `NAMES` of `name`, if it's there (names are all uppercase)")?;
+    writeln!(out, "pub(crate) fn index_of(name: &str) -> Option<usize> {{")?;
+    writeln!(
+        out,
+        "    let candidates: &[usize] = match (name.len(), *name.as_bytes().first()?) {{"
+    )?;
+    for ((len, first), indexes) in candidates.iter() {
+        let first = char::from(*first);
+        let names: Vec<_> = indexes.iter().map(|&n| names[n].as_str()).collect();
+        let indexes: Vec<_> = indexes.iter().map(usize::to_string).collect();
+        let (indexes, names) = (indexes.join(", "), names.join(" "));
+        writeln!(out, "        ({len}, b'{first}') => &[{indexes}], // {names}")?;
+    }
+    writeln!(out, "        _ => return None,")?;
+    writeln!(out, "    }};")?;
+    writeln!(out, "    candidates.iter().copied().find(|&n| NAMES[n] == name)")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+pub(crate) fn dash_nodes<'a>(kdl: &'a KdlDocument, name: &str) -> Result<&'a [KdlNode]> {
+    let node = kdl.get(name).with_context(|| "Can't find {name} node")?;
+    let Some(children) = node.children() else {
+        bail!("{name} node is empty");
+    };
+    let nodes = children.nodes();
+    for node in nodes {
+        let name = node.name().value();
+        if name != "-" {
+            bail!(r#"Expected "-", found "{name}""#);
+        }
+    }
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    #[cfg_attr(miri, ignore)] // Takes too long, and if we die generating code it will be obvious
+    fn generated_code_is_current() {
+        super::codegen(true).unwrap();
+    }
+}
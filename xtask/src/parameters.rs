@@ -1,31 +1,27 @@
-use anyhow::{Context, Result, anyhow, bail};
-use heck::{ToShoutyKebabCase, ToShoutySnakeCase};
-use kdl::{KdlDocument, KdlNode, KdlValue, NodeKey};
+// Generate `src/parameter/parameter_value.rs` from `assets/parameters.kdl`
+use crate::{GetStr, dash_nodes, write_index_of};
+use anyhow::{Context, Result};
+use heck::{ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase};
+use kdl::KdlDocument;
 use litemap::LiteMap;
 
-const KDL: &str = include_str!("../assets/parameters.kdl");
-const TEMPLATE: &str = include_str!("../assets/parameter_template.rs");
+pub(crate) const KDL: &str = "assets/parameters.kdl";
+pub(crate) const TEMPLATE: &str = "assets/parameter_template.rs";
+pub(crate) const OUTPUT: &str = "src/parameter/parameter_value.rs";
 
-#[test]
-#[cfg_attr(miri, ignore)] // Takes too long, and if we die generating code it will be obvious
-fn codegen() {
-    let mut content: Vec<u8> = vec![];
-    generate(&mut content).unwrap();
-    let content = String::from_utf8(content).unwrap();
-    let content = codegenrs::rustfmt(content, None).unwrap();
-    snapbox::assert_data_eq!(content, snapbox::file!["../src/parameter/parameter_value.rs"].raw());
-}
-
-fn generate<W: std::io::Write>(out: &mut W) -> Result<()> {
-    let kdl: KdlDocument = KDL.parse()?;
+pub(crate) fn generate(out: &mut Vec<u8>, kdl: &str, template: &str) -> Result<()> {
+    use std::io::Write;
+    let kdl: KdlDocument = kdl.parse()?;
     let param_info = param_info(&kdl)?;
     let type_info = type_info(&kdl)?;
 
-    for line in TEMPLATE.lines() {
+    for line in template.lines() {
         if line.starts_with("    // ParameterValue") {
             type_info.write_param_values(&mut *out)?;
         } else if line.starts_with("// const") {
             write_constants(out, &param_info)?;
+        } else if line.starts_with("// fn parse_known") {
+            write_parse_known(out, &param_info, &type_info)?;
         } else if line.starts_with("    // Parameters") {
             for param in &param_info {
                 param.write_methods(out, &type_info)?;
@@ -36,35 +32,6 @@ fn generate<W: std::io::Write>(out: &mut W) -> Result<()> {
     }
     Ok(())
 }
-trait GetStr {
-    fn get(&self, key: impl Into<NodeKey>) -> Option<&KdlValue>;
-    fn maybe_get_str(&self, key: &'static str) -> Result<Option<String>> {
-        match self.get(key) {
-            None => Ok(None),
-            Some(KdlValue::String(s)) => Ok(Some(s.to_string())),
-            Some(KdlValue::Integer(n)) => Ok(Some(format!("{n}"))),
-            _ => {
-                if key == "RFC" {
-                    Err(anyhow!("The RFC value must be number"))
-                } else {
-                    Err(anyhow!("The {key} value must be an integer"))
-                }
-            }
-        }
-    }
-    fn get_str(&self, key: &'static str) -> Result<String> {
-        match self.maybe_get_str(key) {
-            Ok(None) => Err(anyhow!("Expected {key} value")),
-            Ok(Some(str)) => Ok(str),
-            Err(e) => Err(e),
-        }
-    }
-}
-impl GetStr for KdlNode {
-    fn get(&self, key: impl Into<NodeKey>) -> Option<&KdlValue> {
-        KdlNode::get(self, key)
-    }
-}
 #[derive(Debug)]
 struct TypeInfo {
     variants: Vec<String>,
@@ -82,7 +49,7 @@ impl TypeInfo {
         self.kind.get(variant).is_some()
     }
     fn is_single_valued(&self, variant: &str) -> bool {
-        self.kind.get(variant).map(|s| s == "single_valued") == Some(true)
+        self.kind.get(variant).is_some_and(|s| s == "single_valued")
     }
     fn type_of(&self, variant: &str) -> Result<String> {
         Ok(self
@@ -119,6 +86,8 @@ struct ParamInfo {
     section: String,
     method: String,
     variant: String,
+    properties: Option<Vec<String>>,
+    multi: bool,
     doc: Option<String>,
 }
 fn write_constants<W: std::io::Write>(out: &mut W, param_info: &Vec<ParamInfo>) -> Result<()> {
@@ -130,6 +99,65 @@ fn write_constants<W: std::io::Write>(out: &mut W, param_info: &Vec<ParamInfo>)
         writeln!(out, "    {},", info.literal())?;
     }
     writeln!(out, "];")?;
+    let names: Vec<_> = param_info.iter().map(|info| info.method.to_shouty_kebab_case()).collect();
+    write_index_of(out, &names)?;
+    writeln!(
+        out,
+        "/// The properties each parameter may appear on, or `None` if any property may have it"
+    )?;
+    writeln!(out, "pub(crate) const APPLIES_TO: [Option<&[&str]>; {}] = [", param_info.len())?;
+    for info in param_info {
+        match &info.properties {
+            None => writeln!(out, "    None, // {}", info.literal())?,
+            Some(names) => {
+                let names: Vec<_> = names.iter().map(|name| format!(r#""{name}""#)).collect();
+                writeln!(out, "    Some(&[{}]), // {}", names.join(", "), info.literal())?;
+            }
+        }
+    }
+    writeln!(out, "];")?;
+    writeln!(out, "/// Whether each parameter may have more than one value")?;
+    writeln!(out, "pub(crate) const MULTI_VALUED: [bool; {}] = [", param_info.len())?;
+    for info in param_info {
+        writeln!(out, "    {}, // {}", info.multi, info.literal())?;
+    }
+    writeln!(out, "];")?;
+    Ok(())
+}
+// Dispatch on the parameter's index to the parser for its variant: the types with a `kind` have
+// a `parse` function named after the variant (each may reject a value), and the others convert
+// `From<&str>`, the first value or, for a multi-valued parameter, each of them
+fn write_parse_known<W: std::io::Write>(
+    out: &mut W,
+    param_info: &[ParamInfo],
+    type_info: &TypeInfo,
+) -> Result<()> {
+    writeln!(
+        out,
+        "/// The value of the parameter at index `n` in `NAMES`, parsed from its `values` as \
+         written, or `None` if they aren't valid"
+    )?;
+    writeln!(
+        out,
+        "pub(super) fn parse_known(n: usize, values: &[&str]) -> Option<ParameterValue> {{"
+    )?;
+    writeln!(out, "    Some(match n {{")?;
+    for variant in &type_info.variants {
+        let params: Vec<_> = param_info.iter().filter(|info| &info.variant == variant).collect();
+        let Some(first) = params.first() else { continue };
+        let konsts: Vec<_> = params.iter().map(|info| info.konst()).collect();
+        let value = if type_info.is_copy(variant) {
+            format!("parse::{}(values)?", variant.to_snake_case())
+        } else if first.multi {
+            "parse::all(values)".to_string()
+        } else {
+            "parse::one(values).into()".to_string()
+        };
+        writeln!(out, "        {} => ParameterValue::{variant}({value}),", konsts.join(" | "))?;
+    }
+    writeln!(out, "        _ => return None,")?;
+    writeln!(out, "    }})")?;
+    writeln!(out, "}}")?;
     Ok(())
 }
 impl ParamInfo {
@@ -163,7 +191,7 @@ impl ParamInfo {
             out,
             r#"#[must_use]
             pub fn {method}(&self) -> Option<{amphersand}{typ}> {{
-                match self.0.get(&{konst}) {{
+                match self.0.get({konst}) {{
                 None => None,
                 Some(ParameterValue::{variant}(value)) => Some({star}value),
                 _ => panic!("Unexpected type for {{}}", {literal}),
@@ -177,7 +205,7 @@ impl ParamInfo {
                 out,
                 "pub fn set_{method}(&mut self, value: Option<{typ}>) {{
                     match value {{
-                        None => self.0.remove(&{konst}),
+                        None => self.0.remove({konst}),
                         Some(v) => self.0.insert({konst}, ParameterValue::{variant}(v)),
                     }};
                 }}"
@@ -195,20 +223,6 @@ impl ParamInfo {
     }
 }
 
-fn dash_nodes<'a>(kdl: &'a KdlDocument, name: &str) -> Result<&'a [KdlNode]> {
-    let node = kdl.get(name).with_context(|| "Can't find {name} node")?;
-    let Some(children) = node.children() else {
-        bail!("{name} node is empty");
-    };
-    let nodes = children.nodes();
-    for node in nodes {
-        let name = node.name().value();
-        if name != "-" {
-            bail!(r#"Expected "-", found "{name}""#);
-        }
-    }
-    Ok(nodes)
-}
 fn param_info(kdl: &KdlDocument) -> Result<Vec<ParamInfo>> {
     let nodes = dash_nodes(kdl, "parameters")?;
     let mut result = Vec::new();
@@ -217,8 +231,12 @@ fn param_info(kdl: &KdlDocument) -> Result<Vec<ParamInfo>> {
         let section = node.get_str("Section")?;
         let method = node.get_str("method")?;
         let variant = node.get_str("variant")?;
+        let properties = node
+            .maybe_get_str("properties")?
+            .map(|names| names.split_whitespace().map(String::from).collect());
+        let multi = node.get_bool("multi")?;
         let doc = node.maybe_get_str("doc")?;
-        result.push(ParamInfo { rfc, section, method, variant, doc });
+        result.push(ParamInfo { rfc, section, method, variant, properties, multi, doc });
     }
     Ok(result)
 }
@@ -0,0 +1,165 @@
+// Generate `src/property/property_table.rs` from `assets/properties.kdl`
+use crate::{GetStr, dash_nodes, write_index_of};
+use anyhow::{Result, bail};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use kdl::KdlDocument;
+
+pub(crate) const KDL: &str = "assets/properties.kdl";
+pub(crate) const TEMPLATE: &str = "assets/property_template.rs";
+pub(crate) const OUTPUT: &str = "src/property/property_table.rs";
+
+pub(crate) fn generate(out: &mut Vec<u8>, kdl: &str, template: &str) -> Result<()> {
+    use std::io::Write;
+    let kdl: KdlDocument = kdl.parse()?;
+    let properties = property_info(&kdl)?;
+    for line in template.lines() {
+        if line.starts_with("    // PropertyName") {
+            for p in &properties {
+                writeln!(out, "    {}, // RFC {}, § {}", p.variant(), p.rfc, p.section)?;
+            }
+        } else if line.starts_with("// const") {
+            write_tables(out, &properties)?;
+        } else if line.starts_with("    // Properties") {
+            for p in &properties {
+                p.write_methods(out)?;
+            }
+        } else {
+            writeln!(out, "{line}")?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct PropertyInfo {
+    rfc: String,
+    section: String,
+    name: String,
+    value: String,
+    also: Vec<String>,
+    multiple: bool,
+    parser: String,
+}
+impl PropertyInfo {
+    fn variant(&self) -> String {
+        self.name.to_upper_camel_case()
+    }
+    fn write_methods(&self, out: &mut Vec<u8>) -> Result<()> {
+        use std::io::Write;
+        let (rfc, section, name) = (&self.rfc, &self.section, &self.name);
+        let link = format!(
+            "[RFC {rfc}, § {section}](https://datatracker.ietf.org/doc/html/rfc{rfc}#section-{section})"
+        );
+        let (method, variant) = (self.name.to_snake_case(), self.variant());
+        if self.multiple {
+            writeln!(
+                out,
+                "/// Get the `{name}` properties ({link}).
+                #[must_use]
+                pub fn {method}(&self) -> &[PropertyValue] {{
+                    self.get(PropertyName::{variant})
+                }}
+                /// Add a value of the `{name}` property ({link}).
+                pub fn add_{method}(&mut self, value: PropertyValue) {{
+                    self.add(PropertyName::{variant}, value);
+                }}
+                "
+            )?;
+        } else {
+            writeln!(
+                out,
+                "/// Get the `{name}` property ({link}).
+                #[must_use]
+                pub fn {method}(&self) -> Option<&PropertyValue> {{
+                    self.first(PropertyName::{variant})
+                }}
+                /// Set the `{name}` property ({link}).
+                pub fn set_{method}(&mut self, value: PropertyValue) {{
+                    self.set(PropertyName::{variant}, value);
+                }}
+                "
+            )?;
+        }
+        Ok(())
+    }
+}
+fn property_info(kdl: &KdlDocument) -> Result<Vec<PropertyInfo>> {
+    let mut result: Vec<PropertyInfo> = Vec::new();
+    for node in dash_nodes(kdl, "properties")? {
+        let name = node.get_str("name")?;
+        if let Some(previous) = result.last()
+            && previous.name >= name
+        {
+            bail!("Properties must be in order by name, but {name} follows {}", previous.name);
+        }
+        let also = node.maybe_get_str("also")?.unwrap_or_default();
+        let parser = node.maybe_get_str("parser")?.unwrap_or_else(|| "single".to_string());
+        if !["single", "list", "geo"].contains(&parser.as_str()) {
+            bail!("{name} has parser={parser}, which isn't single, list, or geo");
+        }
+        result.push(PropertyInfo {
+            rfc: node.get_str("RFC")?,
+            section: node.get_str("Section")?,
+            name,
+            value: node.get_str("value")?,
+            also: also.split_whitespace().map(String::from).collect(),
+            multiple: node.get_bool("multiple")?,
+            parser: parser.to_upper_camel_case(),
+        });
+    }
+    Ok(result)
+}
+
+fn write_tables(out: &mut Vec<u8>, properties: &[PropertyInfo]) -> Result<()> {
+    use std::io::Write;
+    let n = properties.len();
+    writeln!(
+        out,
+        "/// The names of the properties we know, in order by name; a name's index is its"
+    )?;
+    writeln!(out, "/// `PropertyId`.")?;
+    writeln!(out, "pub(crate) const NAMES: [&str; {n}] = [")?;
+    for p in properties {
+        writeln!(out, r#"    "{}", // RFC {}, § {}"#, p.name, p.rfc, p.section)?;
+    }
+    writeln!(out, "];")?;
+    let names: Vec<_> = properties.iter().map(|p| p.name.clone()).collect();
+    write_index_of(out, &names)?;
+    writeln!(out, "/// The value type of each property when it has no `VALUE` parameter")?;
+    writeln!(out, "pub(crate) const DEFAULT_VALUES: [Value; {n}] = [")?;
+    for p in properties {
+        writeln!(out, "    Value::{}, // {}", p.value, p.name)?;
+    }
+    writeln!(out, "];")?;
+    writeln!(
+        out,
+        "/// The value types each property may have: its default, and the others a `VALUE`"
+    )?;
+    writeln!(out, "/// parameter may choose")?;
+    writeln!(out, "pub(crate) const ALLOWED_VALUES: [&[Value]; {n}] = [")?;
+    for p in properties {
+        let values: Vec<_> =
+            std::iter::once(&p.value).chain(&p.also).map(|v| format!("Value::{v}")).collect();
+        writeln!(out, "    &[{}], // {}", values.join(", "), p.name)?;
+    }
+    writeln!(out, "];")?;
+    writeln!(out, "/// Every `PropertyName`, in order")?;
+    writeln!(out, "const ALL: [PropertyName; {n}] = [")?;
+    for p in properties {
+        writeln!(out, "    PropertyName::{},", p.variant())?;
+    }
+    writeln!(out, "];")?;
+    writeln!(out, "/// Whether a component may have more than one of each property")?;
+    writeln!(out, "const MULTIPLE: [bool; {n}] = [")?;
+    for p in properties {
+        writeln!(out, "    {}, // {}", p.multiple, p.name)?;
+    }
+    writeln!(out, "];")?;
+    writeln!(out, "/// How `parse_value` reads each property's value")?;
+    writeln!(out, "pub(crate) const VALUE_PARSERS: [ValueParser; {n}] = [")?;
+    for p in properties {
+        writeln!(out, "    ValueParser::{}, // {}", p.parser, p.name)?;
+    }
+    writeln!(out, "];")?;
+    Ok(())
+}